@@ -39,6 +39,11 @@ pub enum Error<T> {
     /// Invalid match discriminant or enumeration.
     #[error("invalid discriminant {0:#?}")]
     InvalidDiscriminant(T),
+    /// Error parsing a [Path] selecting within Ipld, e.g. an awaited result.
+    ///
+    /// [Path]: crate::pointer::Path
+    #[error("invalid path: {0}")]
+    InvalidPath(String),
     /// Error related to a missing a field in a structure or key
     /// in a map.
     #[error("no {0} field set")]
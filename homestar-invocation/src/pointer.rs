@@ -8,7 +8,7 @@
 //! [Instructions]: crate::task::Instruction
 //! [Receipts]: super::Receipt
 
-use crate::{ensure, Error, Unit};
+use crate::{ensure, task, Error, Unit};
 use const_format::formatcp;
 #[cfg(feature = "diesel")]
 use diesel::{
@@ -27,9 +27,7 @@ use schemars::{
     JsonSchema,
 };
 use serde::{Deserialize, Serialize};
-#[cfg(feature = "diesel")]
-use std::str::FromStr;
-use std::{borrow::Cow, collections::btree_map::BTreeMap, fmt, module_path};
+use std::{borrow::Cow, collections::btree_map::BTreeMap, fmt, module_path, str::FromStr};
 
 /// `await/ok` branch for instruction result.
 pub const OK_BRANCH: &str = "await/ok";
@@ -37,6 +35,8 @@ pub const OK_BRANCH: &str = "await/ok";
 pub const ERR_BRANCH: &str = "await/error";
 /// `await/*` branch for instruction result.
 pub const PTR_BRANCH: &str = "await/*";
+/// Key for the [Path] selecting within an awaited instruction result.
+pub const PATH_KEY: &str = "path";
 
 /// Enumerated wrapper around resulting branches of a promise
 /// that's being awaited on.
@@ -70,6 +70,20 @@ pub enum AwaitResult {
     Ptr,
 }
 
+impl AwaitResult {
+    /// Whether a [task::Result] can be consumed by this branch.
+    ///
+    /// `await/ok` accepts successful (or direct) results, `await/error`
+    /// only accepts failed results, and `await/*` accepts any result.
+    pub fn matches<T>(&self, result: &task::Result<T>) -> bool {
+        match self {
+            AwaitResult::Ok => !matches!(result, task::Result::Error(_)),
+            AwaitResult::Error => matches!(result, task::Result::Error(_)),
+            AwaitResult::Ptr => true,
+        }
+    }
+}
+
 impl fmt::Display for AwaitResult {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -100,39 +114,30 @@ impl JsonSchema for AwaitResult {
             ..Default::default()
         };
 
-        let await_ok = SchemaObject {
-            instance_type: Some(SingleOrVec::Single(InstanceType::Object.into())),
-            object: Some(Box::new(ObjectValidation {
-                properties: BTreeMap::from([(
-                    OK_BRANCH.to_string(),
-                    gen.subschema_for::<Pointer>(),
-                )]),
-                ..Default::default()
-            })),
-            ..Default::default()
-        };
-        let await_err = SchemaObject {
-            instance_type: Some(SingleOrVec::Single(InstanceType::Object.into())),
-            object: Some(Box::new(ObjectValidation {
-                properties: BTreeMap::from([(
-                    ERR_BRANCH.to_string(),
-                    gen.subschema_for::<Pointer>(),
-                )]),
+        let path = SchemaObject {
+            instance_type: Some(SingleOrVec::Single(InstanceType::String.into())),
+            metadata: Some(Box::new(Metadata {
+                description: Some(
+                    "Path selecting within the awaited result, e.g. .outputs[2].width".to_string(),
+                ),
                 ..Default::default()
             })),
             ..Default::default()
         };
-        let await_ptr = SchemaObject {
+        let mut await_branch = |branch: &str| SchemaObject {
             instance_type: Some(SingleOrVec::Single(InstanceType::Object.into())),
             object: Some(Box::new(ObjectValidation {
-                properties: BTreeMap::from([(
-                    PTR_BRANCH.to_string(),
-                    gen.subschema_for::<Pointer>(),
-                )]),
+                properties: BTreeMap::from([
+                    (branch.to_string(), gen.subschema_for::<Pointer>()),
+                    (PATH_KEY.to_string(), Schema::Object(path.clone())),
+                ]),
                 ..Default::default()
             })),
             ..Default::default()
         };
+        let await_ok = await_branch(OK_BRANCH);
+        let await_err = await_branch(ERR_BRANCH);
+        let await_ptr = await_branch(PTR_BRANCH);
 
         schema.subschemas().one_of = Some(vec![
             Schema::Object(await_ok),
@@ -143,15 +148,137 @@ impl JsonSchema for AwaitResult {
     }
 }
 
+/// Segment of a [Path] into a structured result.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum PathSegment {
+    /// Field of a map.
+    Key(String),
+    /// Index into a list.
+    Index(usize),
+}
+
+/// Path selecting a subfield of an awaited, structured result, written as a
+/// sequence of `.field` and `[index]` segments, e.g. `.outputs[2].width`.
+///
+/// # Example
+///
+/// ```
+/// use homestar_invocation::pointer::Path;
+/// use libipld::ipld;
+///
+/// let path: Path = ".outputs[1].width".parse().unwrap();
+/// let output = ipld!({"outputs": [{"width": 10}, {"width": 20}]});
+///
+/// assert_eq!(path.select(output), Some(ipld!(20)));
+/// assert_eq!(path.to_string(), ".outputs[1].width");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Path(Vec<PathSegment>);
+
+impl Path {
+    /// Create a [Path] from its [segments].
+    ///
+    /// [segments]: PathSegment
+    pub fn new(segments: Vec<PathSegment>) -> Self {
+        Self(segments)
+    }
+
+    /// Return the [segments] of the [Path].
+    ///
+    /// [segments]: PathSegment
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.0
+    }
+
+    /// Select the value at the [Path] within Ipld, if there is one.
+    pub fn select(&self, ipld: Ipld) -> Option<Ipld> {
+        self.0
+            .iter()
+            .try_fold(ipld, |ipld, segment| match (segment, ipld) {
+                (PathSegment::Key(key), Ipld::Map(mut map)) => map.remove(key),
+                (PathSegment::Index(idx), Ipld::List(mut list)) if *idx < list.len() => {
+                    Some(list.swap_remove(*idx))
+                }
+                _ => None,
+            })
+    }
+}
+
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for segment in self.0.iter() {
+            match segment {
+                PathSegment::Key(key) => write!(f, ".{key}")?,
+                PathSegment::Index(idx) => write!(f, "[{idx}]")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Path {
+    type Err = Error<Unit>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::InvalidPath(s.to_string());
+
+        let mut segments = vec![];
+        let mut chars = s.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '.' => {
+                    let mut key = String::new();
+                    while let Some(c) = chars.next_if(|c| *c != '.' && *c != '[') {
+                        key.push(c);
+                    }
+                    ensure!(!key.is_empty(), invalid());
+                    segments.push(PathSegment::Key(key));
+                }
+                '[' => {
+                    let mut idx = String::new();
+                    while let Some(c) = chars.next_if(|c| *c != ']') {
+                        idx.push(c);
+                    }
+                    ensure!(chars.next() == Some(']'), invalid());
+                    let idx = idx.parse().map_err(|_| invalid())?;
+                    segments.push(PathSegment::Index(idx));
+                }
+                _ => return Err(invalid()),
+            }
+        }
+
+        ensure!(!segments.is_empty(), invalid());
+        Ok(Path(segments))
+    }
+}
+
+impl From<Path> for Ipld {
+    fn from(path: Path) -> Self {
+        Ipld::String(path.to_string())
+    }
+}
+
+impl TryFrom<&Ipld> for Path {
+    type Error = Error<Unit>;
+
+    fn try_from(ipld: &Ipld) -> Result<Self, Self::Error> {
+        from_ipld::<String>(ipld.to_owned())?.parse()
+    }
+}
+
 /// Describes the eventual output of the referenced [Instruction] as a
 /// [Pointer], either resolving to a tagged [OK_BRANCH], [ERR_BRANCH], or direct
-/// result of a [PTR_BRANCH].
+/// result of a [PTR_BRANCH], optionally narrowed down by a [Path] into the
+/// result:
+///
+/// `{"await/ok": {"/": "bafy..."}, "path": ".outputs[2].width"}`
 ///
 /// [Instruction]: crate::task::Instruction
 #[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub struct Await {
     instruction: Pointer,
     result: AwaitResult,
+    path: Option<Path>,
 }
 
 impl Await {
@@ -161,9 +288,16 @@ impl Await {
         Self {
             instruction,
             result,
+            path: None,
         }
     }
 
+    /// Narrow down the [Await]'ed result by a [Path].
+    pub fn with_path(mut self, path: Path) -> Self {
+        self.path = Some(path);
+        self
+    }
+
     /// Return Cid to [Instruction] being [Await]'ed on.
     ///
     /// [Instruction]: crate::task::Instruction
@@ -175,14 +309,57 @@ impl Await {
     pub fn result(&self) -> &AwaitResult {
         &self.result
     }
+
+    /// Return [Path] into the result, if any.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_ref()
+    }
+
+    /// Select the value [Await]'ed on from the referenced [Instruction]'s
+    /// result, if the result matches the awaited branch and the [Path], if
+    /// any, leads to a value.
+    ///
+    /// `await/ok` and `await/error` unwrap the result, whereas `await/*`
+    /// gives the whole result, e.g. `["ok", 1]`. The [Path] selects within
+    /// the result's value, before it's wrapped.
+    ///
+    /// [Instruction]: crate::task::Instruction
+    pub fn select<T>(&self, result: task::Result<T>) -> Option<Ipld>
+    where
+        Ipld: From<T>,
+    {
+        if !self.result.matches(&result) {
+            return None;
+        }
+
+        let value = |inner: T| match &self.path {
+            Some(path) => path.select(inner.into()),
+            None => Some(inner.into()),
+        };
+        let selected = match result {
+            task::Result::Ok(inner) => task::Result::Ok(value(inner)?),
+            task::Result::Error(inner) => task::Result::Error(value(inner)?),
+            task::Result::Just(inner) => task::Result::Just(value(inner)?),
+        };
+
+        match self.result {
+            AwaitResult::Ptr => Some(selected.into()),
+            _ => Some(selected.into_inner()),
+        }
+    }
 }
 
 impl From<Await> for Ipld {
     fn from(await_promise: Await) -> Self {
-        Ipld::Map(BTreeMap::from([(
+        let mut map = BTreeMap::from([(
             await_promise.result.branch().to_string(),
             await_promise.instruction.into(),
-        )]))
+        )]);
+        if let Some(path) = await_promise.path {
+            map.insert(PATH_KEY.to_string(), path.into());
+        }
+
+        Ipld::Map(map)
     }
 }
 
@@ -196,11 +373,15 @@ impl TryFrom<Ipld> for Await {
     type Error = Error<Unit>;
 
     fn try_from(ipld: Ipld) -> Result<Self, Self::Error> {
-        let map = from_ipld::<BTreeMap<String, Ipld>>(ipld)?;
+        let mut map = from_ipld::<BTreeMap<String, Ipld>>(ipld)?;
+        let path = map
+            .remove(PATH_KEY)
+            .map(|path| Path::try_from(&path))
+            .transpose()?;
         ensure!(
             map.len() == 1,
             Error::ConditionNotMet(
-                "await promise must have only a single key in a map".to_string()
+                "await promise must have only a single key in a map, besides a path".to_string()
             )
         );
 
@@ -216,6 +397,7 @@ impl TryFrom<Ipld> for Await {
         Ok(Await {
             instruction,
             result,
+            path,
         })
     }
 }
@@ -401,4 +583,81 @@ mod test {
 
         assert_eq!(awaited, de);
     }
+
+    #[test]
+    fn await_with_path_ipld_roundtrip() {
+        let awaited = Await::new(
+            Pointer::new(generate_cid(&mut thread_rng())),
+            AwaitResult::Ok,
+        )
+        .with_path(".outputs[2].width".parse().unwrap());
+        let ipld = Ipld::from(awaited.clone());
+
+        assert_eq!(
+            ipld,
+            Ipld::Map(BTreeMap::from([
+                (OK_BRANCH.into(), Ipld::Link(awaited.instruction_cid())),
+                (PATH_KEY.into(), Ipld::String(".outputs[2].width".into())),
+            ]))
+        );
+        assert_eq!(Await::try_from(ipld).unwrap(), awaited);
+    }
+
+    #[test]
+    fn parse_paths() {
+        assert_eq!(
+            "[0].name".parse::<Path>().unwrap(),
+            Path::new(vec![PathSegment::Index(0), PathSegment::Key("name".into())])
+        );
+        assert!("".parse::<Path>().is_err());
+        assert!(matches!(
+            "outputs".parse::<Path>(),
+            Err(Error::InvalidPath(path)) if path == "outputs"
+        ));
+        assert!(".outputs[two]".parse::<Path>().is_err());
+        assert!(".outputs..width".parse::<Path>().is_err());
+        assert!(".outputs[2".parse::<Path>().is_err());
+    }
+
+    #[test]
+    fn await_selects_from_result() {
+        let cid = generate_cid(&mut thread_rng());
+        let output = task::Result::Ok(Ipld::Map(BTreeMap::from([(
+            "outputs".into(),
+            Ipld::List(vec![Ipld::Integer(1), Ipld::Integer(2)]),
+        )])));
+        let path: Path = ".outputs[1]".parse().unwrap();
+
+        let ok = Await::new(Pointer::new(cid), AwaitResult::Ok).with_path(path.clone());
+        assert_eq!(ok.select(output.clone()), Some(Ipld::Integer(2)));
+
+        let ptr = Await::new(Pointer::new(cid), AwaitResult::Ptr);
+        assert_eq!(ptr.select(output.clone()), Some(Ipld::from(output.clone())));
+        assert_eq!(
+            ptr.with_path(path).select(output.clone()),
+            Some(Ipld::List(vec!["ok".into(), Ipld::Integer(2)]))
+        );
+
+        let missing = Await::new(Pointer::new(cid), AwaitResult::Ok)
+            .with_path(".outputs[5]".parse().unwrap());
+        assert_eq!(missing.select(output.clone()), None);
+
+        let err = Await::new(Pointer::new(cid), AwaitResult::Error);
+        assert_eq!(err.select(output), None);
+    }
+
+    #[test]
+    fn await_result_matches_branch() {
+        let ok = task::Result::Ok(Ipld::Bool(true));
+        let err = task::Result::Error(Ipld::String("failed".into()));
+        let just = task::Result::Just(Ipld::Bool(true));
+
+        assert!(AwaitResult::Ok.matches(&ok));
+        assert!(AwaitResult::Ok.matches(&just));
+        assert!(!AwaitResult::Ok.matches(&err));
+        assert!(AwaitResult::Error.matches(&err));
+        assert!(!AwaitResult::Error.matches(&ok));
+        assert!(AwaitResult::Ptr.matches(&ok));
+        assert!(AwaitResult::Ptr.matches(&err));
+    }
 }
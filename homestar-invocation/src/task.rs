@@ -10,13 +10,17 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
+mod condition;
 pub mod config;
 pub mod instruction;
+mod map;
 mod result;
 
-pub use config::Resources;
+pub use condition::{Condition, Predicate};
+pub use config::{Resources, RetryPolicy};
 pub use instruction::Instruction;
 use instruction::RunInstruction;
+pub use map::Map;
 pub use result::Result;
 
 const RUN_KEY: &str = "run";
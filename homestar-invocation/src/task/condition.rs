@@ -0,0 +1,383 @@
+//! Conditions gating whether a [Task] runs, based on the output of an
+//! awaited [Instruction].
+//!
+//! [Task]: crate::Task
+//! [Instruction]: crate::task::Instruction
+
+use crate::{
+    pointer::{Await, AwaitResult, Path, ERR_BRANCH, OK_BRANCH, PATH_KEY, PTR_BRANCH},
+    task, Error, Pointer, Unit,
+};
+use libipld::{serde::from_ipld, Ipld};
+use std::{cmp::Ordering, collections::BTreeMap};
+
+const CONDITION_KEY: &str = "if";
+const EQ_KEY: &str = "eq";
+const NE_KEY: &str = "ne";
+const GT_KEY: &str = "gt";
+const GTE_KEY: &str = "gte";
+const LT_KEY: &str = "lt";
+const LTE_KEY: &str = "lte";
+const IN_KEY: &str = "in";
+
+/// Predicate over the value of an awaited [Instruction]'s output.
+///
+/// Ordering predicates compare numbers with numbers and strings with
+/// strings, never holding for values of any other type.
+///
+/// [Instruction]: crate::task::Instruction
+#[derive(Clone, Debug, PartialEq)]
+pub enum Predicate {
+    /// Value is equal to the given value.
+    Eq(Ipld),
+    /// Value is not equal to the given value.
+    Ne(Ipld),
+    /// Value is greater than the given value.
+    Gt(Ipld),
+    /// Value is greater than or equal to the given value.
+    Gte(Ipld),
+    /// Value is less than the given value.
+    Lt(Ipld),
+    /// Value is less than or equal to the given value.
+    Lte(Ipld),
+    /// Value is one of the given values.
+    In(Vec<Ipld>),
+}
+
+impl Predicate {
+    /// Whether the predicate holds for the given value.
+    pub fn holds(&self, value: &Ipld) -> bool {
+        match self {
+            Predicate::Eq(other) => value == other,
+            Predicate::Ne(other) => value != other,
+            Predicate::Gt(other) => compare(value, other) == Some(Ordering::Greater),
+            Predicate::Gte(other) => {
+                matches!(
+                    compare(value, other),
+                    Some(Ordering::Greater | Ordering::Equal)
+                )
+            }
+            Predicate::Lt(other) => compare(value, other) == Some(Ordering::Less),
+            Predicate::Lte(other) => {
+                matches!(
+                    compare(value, other),
+                    Some(Ordering::Less | Ordering::Equal)
+                )
+            }
+            Predicate::In(values) => values.contains(value),
+        }
+    }
+
+    fn key(&self) -> &'static str {
+        match self {
+            Predicate::Eq(_) => EQ_KEY,
+            Predicate::Ne(_) => NE_KEY,
+            Predicate::Gt(_) => GT_KEY,
+            Predicate::Gte(_) => GTE_KEY,
+            Predicate::Lt(_) => LT_KEY,
+            Predicate::Lte(_) => LTE_KEY,
+            Predicate::In(_) => IN_KEY,
+        }
+    }
+}
+
+/// Compare numbers with numbers, and strings with strings.
+fn compare(value: &Ipld, other: &Ipld) -> Option<Ordering> {
+    match (value, other) {
+        (Ipld::Integer(a), Ipld::Integer(b)) => Some(a.cmp(b)),
+        (Ipld::Integer(a), Ipld::Float(b)) => (*a as f64).partial_cmp(b),
+        (Ipld::Float(a), Ipld::Integer(b)) => a.partial_cmp(&(*b as f64)),
+        (Ipld::Float(a), Ipld::Float(b)) => a.partial_cmp(b),
+        (Ipld::String(a), Ipld::String(b)) => Some(a.cmp(b)),
+        _ => None,
+    }
+}
+
+/// Condition on a [Task] running, expressed in task metadata under an `if`
+/// key, next to [Resources]:
+///
+/// `{"fuel": .., "memory": .., "time": .., "if": {"await/ok": {"/": "bafy..."}, "gt": 10}}`
+///
+/// The condition awaits an [Instruction]'s output, through any of the
+/// `await/ok`, `await/error`, or `await/*` branches, and holds if the
+/// output matches the awaited branch and its value matches the
+/// [Predicate]. A `path` narrows the tested value down to a subfield of the
+/// output, as for any [Await]'ed promise. Branching between tasks is expressed through tasks with
+/// complementary conditions, e.g. one with `gt` and another with `lte`.
+///
+/// A [Task] whose condition doesn't hold is skipped, rather than run.
+///
+/// [Task]: crate::Task
+/// [Resources]: crate::task::Resources
+/// [Instruction]: crate::task::Instruction
+#[derive(Clone, Debug, PartialEq)]
+pub struct Condition {
+    promise: Await,
+    predicate: Predicate,
+}
+
+impl Condition {
+    /// Create a new [Condition] over an awaited [Instruction]'s output.
+    ///
+    /// [Instruction]: crate::task::Instruction
+    pub fn new(promise: Await, predicate: Predicate) -> Self {
+        Self { promise, predicate }
+    }
+
+    /// Return the [Await]'ed promise the [Condition] is evaluated over.
+    pub fn promise(&self) -> &Await {
+        &self.promise
+    }
+
+    /// Return the [Predicate] of the [Condition].
+    pub fn predicate(&self) -> &Predicate {
+        &self.predicate
+    }
+
+    /// Whether the [Condition] holds for the awaited [Instruction]'s
+    /// output.
+    ///
+    /// The value tested is [selected] from the output, so `await/ok` and
+    /// `await/error` branches test the unwrapped value of the output,
+    /// whereas `await/*` tests the output as a whole, e.g. `["ok", 1]`.
+    ///
+    /// [Instruction]: crate::task::Instruction
+    /// [selected]: Await::select
+    pub fn holds<T>(&self, result: &task::Result<T>) -> bool
+    where
+        T: Clone,
+        Ipld: From<T>,
+    {
+        self.promise
+            .select(result.to_owned())
+            .is_some_and(|value| self.predicate.holds(&value))
+    }
+
+    /// Parse a [Condition] from task metadata, if the metadata gives one.
+    pub fn from_meta(meta: &Ipld) -> Result<Option<Self>, Error<Unit>> {
+        let Ok(map) = from_ipld::<BTreeMap<String, Ipld>>(meta.to_owned()) else {
+            return Ok(None);
+        };
+        map.get(CONDITION_KEY)
+            .map(|ipld| Condition::try_from(ipld.to_owned()))
+            .transpose()
+    }
+
+    /// Add [Condition] to task metadata, e.g. built from [Resources].
+    ///
+    /// [Resources]: crate::task::Resources
+    pub fn add_to_meta(self, meta: Ipld) -> Ipld {
+        let mut map = from_ipld::<BTreeMap<String, Ipld>>(meta).unwrap_or_default();
+        map.insert(CONDITION_KEY.into(), self.into());
+        Ipld::Map(map)
+    }
+}
+
+impl From<Condition> for Ipld {
+    fn from(condition: Condition) -> Ipld {
+        let key = condition.predicate.key();
+        let value = match condition.predicate {
+            Predicate::Eq(v)
+            | Predicate::Ne(v)
+            | Predicate::Gt(v)
+            | Predicate::Gte(v)
+            | Predicate::Lt(v)
+            | Predicate::Lte(v) => v,
+            Predicate::In(values) => Ipld::List(values),
+        };
+
+        let mut map = BTreeMap::from([
+            (
+                condition.promise.result().branch().to_string(),
+                Pointer::new(condition.promise.instruction_cid()).into(),
+            ),
+            (key.to_string(), value),
+        ]);
+        if let Some(path) = condition.promise.path() {
+            map.insert(PATH_KEY.to_string(), path.to_owned().into());
+        }
+
+        Ipld::Map(map)
+    }
+}
+
+impl TryFrom<Ipld> for Condition {
+    type Error = Error<Unit>;
+
+    /// Parse a [Condition], which must await exactly one branch and give
+    /// exactly one [Predicate].
+    fn try_from(ipld: Ipld) -> Result<Self, Self::Error> {
+        let map = from_ipld::<BTreeMap<String, Ipld>>(ipld)?;
+        if let Some(key) = map.keys().find(|key| {
+            ![
+                OK_BRANCH, ERR_BRANCH, PTR_BRANCH, PATH_KEY, EQ_KEY, NE_KEY, GT_KEY, GTE_KEY,
+                LT_KEY, LTE_KEY, IN_KEY,
+            ]
+            .contains(&key.as_str())
+        }) {
+            return Err(Error::ConditionNotMet(format!(
+                "unknown condition key: {key}"
+            )));
+        }
+
+        let mut promises = [OK_BRANCH, ERR_BRANCH, PTR_BRANCH]
+            .into_iter()
+            .filter_map(|branch| map.get_key_value(branch));
+        let promise = match (promises.next(), promises.next()) {
+            (Some((branch, ipld)), None) => {
+                let promise = Await::new(
+                    Pointer::try_from(ipld)?,
+                    AwaitResult::result(branch).ok_or_else(|| Error::InvalidDiscriminant(Unit))?,
+                );
+                match map.get(PATH_KEY) {
+                    Some(path) => promise.with_path(Path::try_from(path)?),
+                    None => promise,
+                }
+            }
+            (None, _) => {
+                return Err(Error::ConditionNotMet(
+                    "condition must await an instruction".to_string(),
+                ))
+            }
+            (Some(_), Some(_)) => {
+                return Err(Error::ConditionNotMet(
+                    "condition must await a single instruction".to_string(),
+                ))
+            }
+        };
+
+        let mut predicates = map.iter().filter_map(|(key, value)| {
+            let predicate = match key.as_str() {
+                EQ_KEY => Predicate::Eq(value.to_owned()),
+                NE_KEY => Predicate::Ne(value.to_owned()),
+                GT_KEY => Predicate::Gt(value.to_owned()),
+                GTE_KEY => Predicate::Gte(value.to_owned()),
+                LT_KEY => Predicate::Lt(value.to_owned()),
+                LTE_KEY => Predicate::Lte(value.to_owned()),
+                IN_KEY => match value {
+                    Ipld::List(values) => Predicate::In(values.to_owned()),
+                    _ => return Some(Err(Error::<Unit>::unexpected_ipld(value.to_owned()))),
+                },
+                _ => return None,
+            };
+            Some(Ok(predicate))
+        });
+        let predicate = match (predicates.next(), predicates.next()) {
+            (Some(predicate), None) => predicate?,
+            _ => {
+                return Err(Error::ConditionNotMet(
+                    "condition must give a single predicate".to_string(),
+                ))
+            }
+        };
+
+        Ok(Condition { promise, predicate })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{task::Resources, test_utils::cid::generate_cid};
+    use rand::thread_rng;
+
+    fn condition(result: AwaitResult, predicate: Predicate) -> Condition {
+        Condition::new(
+            Await::new(Pointer::new(generate_cid(&mut thread_rng())), result),
+            predicate,
+        )
+    }
+
+    #[test]
+    fn ipld_roundtrip_through_meta() {
+        let condition = condition(
+            AwaitResult::Ok,
+            Predicate::In(vec![Ipld::Integer(1), Ipld::Integer(2)]),
+        );
+        let meta = condition.clone().add_to_meta(Resources::default().into());
+
+        assert_eq!(Condition::from_meta(&meta).unwrap(), Some(condition));
+        assert_eq!(
+            Resources::try_from(&meta).unwrap(),
+            Resources::default(),
+            "resources are unaffected by a condition"
+        );
+        assert_eq!(
+            Condition::from_meta(&Resources::default().into()).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn invalid_conditions_error() {
+        let cid = generate_cid(&mut thread_rng());
+        let no_predicate = Ipld::Map(BTreeMap::from([(OK_BRANCH.into(), Ipld::Link(cid))]));
+        assert!(Condition::try_from(no_predicate).is_err());
+
+        let two_predicates = Ipld::Map(BTreeMap::from([
+            (OK_BRANCH.into(), Ipld::Link(cid)),
+            (GT_KEY.into(), Ipld::Integer(1)),
+            (LT_KEY.into(), Ipld::Integer(5)),
+        ]));
+        assert!(Condition::try_from(two_predicates).is_err());
+
+        let no_promise = Ipld::Map(BTreeMap::from([(EQ_KEY.into(), Ipld::Integer(1))]));
+        assert!(Condition::try_from(no_promise).is_err());
+
+        let misspelled_path = Ipld::Map(BTreeMap::from([
+            (OK_BRANCH.into(), Ipld::Link(cid)),
+            ("pth".into(), Ipld::String(".value".into())),
+            (EQ_KEY.into(), Ipld::Integer(1)),
+        ]));
+        assert!(Condition::try_from(misspelled_path).is_err());
+    }
+
+    #[test]
+    fn holds_for_matching_branch_and_value() {
+        let gt = condition(AwaitResult::Ok, Predicate::Gt(Ipld::Integer(2)));
+        assert!(gt.holds(&task::Result::Ok(Ipld::Integer(3))));
+        assert!(gt.holds(&task::Result::Ok(Ipld::Float(2.5))));
+        assert!(!gt.holds(&task::Result::Ok(Ipld::Integer(2))));
+        assert!(!gt.holds(&task::Result::Ok(Ipld::String("3".into()))));
+        assert!(!gt.holds(&task::Result::Error(Ipld::Integer(3))));
+
+        let lte = condition(AwaitResult::Ok, Predicate::Lte(Ipld::Integer(2)));
+        assert!(lte.holds(&task::Result::Ok(Ipld::Integer(2))));
+        assert!(!lte.holds(&task::Result::Ok(Ipld::Integer(3))));
+
+        let failed = condition(
+            AwaitResult::Error,
+            Predicate::Eq(Ipld::String("boom".into())),
+        );
+        assert!(failed.holds(&task::Result::Error(Ipld::String("boom".into()))));
+        assert!(!failed.holds(&task::Result::Ok(Ipld::String("boom".into()))));
+
+        let any = condition(
+            AwaitResult::Ptr,
+            Predicate::Eq(Ipld::List(vec!["ok".into(), Ipld::Bool(true)])),
+        );
+        assert!(any.holds(&task::Result::Ok(Ipld::Bool(true))));
+        assert!(!any.holds(&task::Result::Error(Ipld::Bool(true))));
+
+        let width = Condition::new(
+            Await::new(
+                Pointer::new(generate_cid(&mut thread_rng())),
+                AwaitResult::Ok,
+            )
+            .with_path(".width".parse().unwrap()),
+            Predicate::Gte(Ipld::Integer(100)),
+        );
+        let output = |width| {
+            task::Result::Ok(Ipld::Map(BTreeMap::from([(
+                "width".into(),
+                Ipld::Integer(width),
+            )])))
+        };
+        assert!(width.holds(&output(120)));
+        assert!(!width.holds(&output(80)));
+        assert_eq!(
+            Condition::try_from(Ipld::from(width.clone())).unwrap(),
+            width
+        );
+    }
+}
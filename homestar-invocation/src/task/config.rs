@@ -5,12 +5,16 @@
 use crate::{consts, Error, Unit};
 use libipld::{serde::from_ipld, Ipld};
 use schemars::JsonSchema;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{collections::BTreeMap, time::Duration};
 
 const FUEL_KEY: &str = "fuel";
 const MEMORY_KEY: &str = "memory";
 const TIMEOUT_KEY: &str = "time";
+const RETRY_KEY: &str = "retry";
+const RETRIES_KEY: &str = "retries";
+const INITIAL_DELAY_KEY: &str = "initial_delay";
+const MAX_DELAY_KEY: &str = "max_delay";
 
 /// Resource configuration for defining fuel quota, timeout, etc.
 #[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
@@ -99,7 +103,7 @@ impl From<Resources> for Ipld {
     }
 }
 
-impl<'a> TryFrom<&'a Ipld> for Resources {
+impl TryFrom<&Ipld> for Resources {
     type Error = Error<Unit>;
 
     fn try_from(ipld: &Ipld) -> Result<Self, Self::Error> {
@@ -113,25 +117,155 @@ impl TryFrom<Ipld> for Resources {
     fn try_from(ipld: Ipld) -> Result<Self, Self::Error> {
         let map = from_ipld::<BTreeMap<String, Ipld>>(ipld)?;
 
-        let fuel = map.get(FUEL_KEY).and_then(|ipld| match ipld {
-            Ipld::Null => None,
-            ipld => from_ipld(ipld.to_owned()).ok(),
-        });
+        let fuel = limit(&map, FUEL_KEY)?;
+        let memory = limit(&map, MEMORY_KEY)?;
+        let time = limit(&map, TIMEOUT_KEY)?.map(Duration::from_millis);
 
-        let memory = map.get(MEMORY_KEY).and_then(|ipld| match ipld {
-            Ipld::Null => None,
-            ipld => from_ipld(ipld.to_owned()).ok(),
-        });
+        Ok(Resources { fuel, memory, time })
+    }
+}
 
-        let time = map.get(TIMEOUT_KEY).and_then(|ipld| match ipld {
-            Ipld::Null => None,
-            ipld => {
-                let time = from_ipld(ipld.to_owned()).unwrap_or(100_000);
-                Some(Duration::from_millis(time))
-            }
-        });
+/// Get an optional resource limit from task metadata, like [optional], but
+/// also accepting whole, non-negative floats, saturating at [u64::MAX], as
+/// written by clients without 64-bit integers, e.g. JavaScript.
+fn limit(map: &BTreeMap<String, Ipld>, key: &str) -> Result<Option<u64>, Error<Unit>> {
+    match map.get(key) {
+        Some(Ipld::Float(limit)) if *limit >= 0.0 && limit.fract() == 0.0 => {
+            Ok(Some(*limit as u64))
+        }
+        _ => optional(map, key),
+    }
+}
 
-        Ok(Resources { fuel, memory, time })
+/// Get an optional, possibly null, field of task metadata, erroring if the
+/// field is given but malformed.
+fn optional<T: DeserializeOwned>(
+    map: &BTreeMap<String, Ipld>,
+    key: &str,
+) -> Result<Option<T>, Error<Unit>> {
+    match map.get(key) {
+        None | Some(Ipld::Null) => Ok(None),
+        Some(ipld) => Ok(Some(from_ipld(ipld.to_owned())?)),
+    }
+}
+
+/// Retry policy for re-running failed executions of a task, with
+/// exponential backoff.
+///
+/// Expressed in task metadata under a `retry` key, next to [Resources]:
+///
+/// `{"fuel": .., "memory": .., "time": .., "retry": {"retries": 3, "initial_delay": 500, "max_delay": 10000}}`
+///
+/// Retries are opt-in, so a task without a `retry` entry is never retried.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[schemars(
+    rename = "retry",
+    description = "Retry policy for failed executions, with exponential backoff"
+)]
+pub struct RetryPolicy {
+    retries: u32,
+    #[schemars(
+        with = "u64",
+        description = "Initial delay between retries in milliseconds"
+    )]
+    initial_delay: Duration,
+    #[schemars(
+        with = "u64",
+        description = "Maximum delay between retries in milliseconds"
+    )]
+    max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            retries: 0,
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Create new [RetryPolicy] configuration.
+    pub fn new(retries: u32, initial_delay: Duration, max_delay: Duration) -> Self {
+        Self {
+            retries,
+            initial_delay,
+            max_delay,
+        }
+    }
+
+    /// Get number of retries.
+    pub fn retries(&self) -> u32 {
+        self.retries
+    }
+
+    /// Get initial delay between retries.
+    pub fn initial_delay(&self) -> Duration {
+        self.initial_delay
+    }
+
+    /// Get maximum delay between retries.
+    pub fn max_delay(&self) -> Duration {
+        self.max_delay
+    }
+
+    /// Add [RetryPolicy] to task metadata, e.g. built from [Resources].
+    pub fn add_to_meta(self, meta: Ipld) -> Ipld {
+        let mut map = from_ipld::<BTreeMap<String, Ipld>>(meta).unwrap_or_default();
+        map.insert(RETRY_KEY.into(), self.into());
+        Ipld::Map(map)
+    }
+}
+
+impl From<RetryPolicy> for Ipld {
+    fn from(policy: RetryPolicy) -> Ipld {
+        Ipld::Map(BTreeMap::from([
+            (RETRIES_KEY.into(), Ipld::from(policy.retries)),
+            (
+                INITIAL_DELAY_KEY.into(),
+                Ipld::from(policy.initial_delay.as_millis() as i128),
+            ),
+            (
+                MAX_DELAY_KEY.into(),
+                Ipld::from(policy.max_delay.as_millis() as i128),
+            ),
+        ]))
+    }
+}
+
+impl TryFrom<&Ipld> for RetryPolicy {
+    type Error = Error<Unit>;
+
+    fn try_from(ipld: &Ipld) -> Result<Self, Self::Error> {
+        RetryPolicy::try_from(ipld.to_owned())
+    }
+}
+
+impl TryFrom<Ipld> for RetryPolicy {
+    type Error = Error<Unit>;
+
+    /// Parse a [RetryPolicy] from task metadata, falling back to the default
+    /// (no retries) if no policy is given, and erroring if it's malformed.
+    fn try_from(ipld: Ipld) -> Result<Self, Self::Error> {
+        let map = from_ipld::<BTreeMap<String, Ipld>>(ipld)?;
+        let Some(retry) = optional::<BTreeMap<String, Ipld>>(&map, RETRY_KEY)? else {
+            return Ok(RetryPolicy::default());
+        };
+
+        let default = RetryPolicy::default();
+        let retries = optional(&retry, RETRIES_KEY)?.unwrap_or(default.retries);
+        let initial_delay = optional(&retry, INITIAL_DELAY_KEY)?
+            .map_or(default.initial_delay, Duration::from_millis);
+        let max_delay =
+            optional(&retry, MAX_DELAY_KEY)?.map_or(default.max_delay, Duration::from_millis);
+
+        Ok(RetryPolicy {
+            retries,
+            initial_delay,
+            max_delay,
+        })
     }
 }
 
@@ -158,6 +292,60 @@ mod test {
         assert_eq!(config, ipld.try_into().unwrap())
     }
 
+    #[test]
+    fn malformed_resources() {
+        let meta = Ipld::Map(BTreeMap::from([
+            (FUEL_KEY.into(), Ipld::String("lots".into())),
+            (TIMEOUT_KEY.into(), Ipld::Integer(10)),
+        ]));
+        assert!(Resources::try_from(&meta).is_err());
+
+        let meta = Ipld::Map(BTreeMap::from([
+            (FUEL_KEY.into(), Ipld::Null),
+            (TIMEOUT_KEY.into(), Ipld::Integer(10)),
+        ]));
+        let resources = Resources::try_from(&meta).unwrap();
+        assert_eq!(resources.fuel(), None);
+        assert_eq!(resources.time(), Some(Duration::from_millis(10)));
+
+        let meta = Ipld::Map(BTreeMap::from([
+            (FUEL_KEY.into(), Ipld::Float(18446744073709552000.0)),
+            (MEMORY_KEY.into(), Ipld::Float(4294967296.0)),
+        ]));
+        let resources = Resources::try_from(&meta).unwrap();
+        assert_eq!(resources.fuel(), Some(u64::MAX));
+        assert_eq!(resources.memory(), Some(4294967296));
+
+        let meta = Ipld::Map(BTreeMap::from([(MEMORY_KEY.into(), Ipld::Float(1.5))]));
+        assert!(Resources::try_from(&meta).is_err());
+    }
+
+    #[test]
+    fn retry_policy_from_meta() {
+        let policy = RetryPolicy::new(3, Duration::from_millis(10), Duration::from_secs(1));
+        let meta = policy.clone().add_to_meta(Resources::default().into());
+
+        assert_eq!(RetryPolicy::try_from(&meta).unwrap(), policy);
+        assert_eq!(
+            Resources::try_from(&meta).unwrap(),
+            Resources::default(),
+            "resources are unaffected by a retry policy"
+        );
+        assert_eq!(
+            RetryPolicy::try_from(Ipld::from(Resources::default())).unwrap(),
+            RetryPolicy::default()
+        );
+
+        let meta = Ipld::Map(BTreeMap::from([(
+            RETRY_KEY.into(),
+            Ipld::Map(BTreeMap::from([(RETRIES_KEY.into(), Ipld::Integer(-1))])),
+        )]));
+        assert!(RetryPolicy::try_from(&meta).is_err());
+
+        let meta = Ipld::Map(BTreeMap::from([(RETRY_KEY.into(), Ipld::Integer(3))]));
+        assert!(RetryPolicy::try_from(&meta).is_err());
+    }
+
     #[test]
     fn ser_de() {
         let config = Resources::default();
@@ -7,7 +7,7 @@
 
 use crate::{
     error::ResolveError,
-    pointer::{Await, AwaitResult, ERR_BRANCH, OK_BRANCH, PTR_BRANCH},
+    pointer::{Await, AwaitResult, Path, ERR_BRANCH, OK_BRANCH, PATH_KEY, PTR_BRANCH},
     task, Error, Pointer,
 };
 use async_recursion::async_recursion;
@@ -46,14 +46,21 @@ where
         &self.0
     }
 
-    /// Return *only* deferred/awaited inputs.
+    /// Return *only* deferred/awaited inputs, including promises nested
+    /// within Ipld maps and lists.
     pub fn deferreds(&self) -> impl Iterator<Item = Cid> + '_ {
-        self.0.iter().filter_map(|input| {
-            if let Input::Deferred(awaited_promise) = input {
-                Some(awaited_promise.instruction_cid())
-            } else {
-                None
-            }
+        self.promises().map(|promise| promise.instruction_cid())
+    }
+
+    /// Return the [awaited promises] of deferred inputs, including promises
+    /// nested within Ipld maps and lists.
+    ///
+    /// [awaited promises]: Await
+    pub fn promises(&self) -> impl Iterator<Item = Await> + '_ {
+        self.0.iter().flat_map(|input| match input {
+            Input::Deferred(awaited_promise) => vec![awaited_promise.to_owned()],
+            Input::Ipld(ipld) => nested_awaits(ipld),
+            Input::Arg(_) => vec![],
         })
     }
 
@@ -73,6 +80,9 @@ where
     /// for unresolved promises, or just return [Input::Ipld],
     /// [resolving Ipld links] if the lookup function expected Ipld input data.
     ///
+    /// Promises nested within Ipld maps and lists are replaced by their
+    /// resolved values, and any left unresolved fail resolution.
+    ///
     /// [awaited promises]: Await
     /// [inputs]: Input
     /// [resolving Ipld links]: resolve_links
@@ -83,7 +93,13 @@ where
     {
         let inputs = resolve_args(self.0, lookup_fn).await;
         for input in inputs.iter() {
-            if let Input::Deferred(awaiting) = input {
+            let unresolved = match input {
+                Input::Deferred(awaiting) => Some(awaiting.to_owned()),
+                Input::Ipld(ipld) => nested_awaits(ipld).into_iter().next(),
+                Input::Arg(_) => None,
+            };
+
+            if let Some(awaiting) = unresolved {
                 return Err(ResolveError::UnresolvedCid(
                     awaiting.instruction_cid().to_string(),
                 ));
@@ -152,12 +168,30 @@ pub enum Input<T> {
 }
 
 impl<T> Input<T> {
+    /// Whether the [Input] awaits another [Instruction]'s output, directly
+    /// or through promises nested within Ipld maps and lists.
+    ///
+    /// [Instruction]: super::Instruction
+    pub fn is_deferred(&self) -> bool {
+        match self {
+            Input::Deferred(_) => true,
+            Input::Ipld(ipld) => !nested_awaits(ipld).is_empty(),
+            Input::Arg(_) => false,
+        }
+    }
+
     /// Resolve [awaited promise] of an [Input] into a task-specific
-    /// [Input::Arg], given a successful lookup function; otherwise, return
-    /// [Input::Deferred] for an unresolved promise, or just return
+    /// [Input::Arg], given a successful lookup function whose result matches
+    /// the awaited branch; otherwise, return [Input::Deferred] for an
+    /// unresolved promise, or just return
     /// [Input::Ipld], [resolving Ipld links] if the lookup function expected
     /// Ipld input data.
     ///
+    /// Promises awaiting the whole result, through `await/*`, or narrowed
+    /// down by a [Path] resolve into the [selected] [Input::Ipld] instead.
+    ///
+    /// [Path]: crate::pointer::Path
+    /// [selected]: Await::select
     /// [awaited promises]: Await
     /// [inputs]: Input
     /// [resolving Ipld links]: resolve_links
@@ -169,10 +203,9 @@ impl<T> Input<T> {
         match self {
             Input::Ipld(ipld) => {
                 if let Ok(await_promise) = Await::try_from(&ipld) {
-                    if let Ok(func_ret) = lookup_fn(await_promise.instruction_cid()).await {
-                        Input::Arg(func_ret)
-                    } else {
-                        Input::Deferred(await_promise)
+                    match lookup_fn(await_promise.instruction_cid()).await {
+                        Ok(func_ret) => resolve_await(await_promise, func_ret),
+                        Err(_) => Input::Deferred(await_promise),
                     }
                 } else {
                     Input::Ipld(resolve_links(ipld, lookup_fn.into()).await)
@@ -180,16 +213,37 @@ impl<T> Input<T> {
             }
             Input::Arg(ref _arg) => self,
             Input::Deferred(await_promise) => {
-                if let Ok(func_ret) = lookup_fn(await_promise.instruction_cid()).await {
-                    Input::Arg(func_ret)
-                } else {
-                    Input::Deferred(await_promise)
+                match lookup_fn(await_promise.instruction_cid()).await {
+                    Ok(func_ret) => resolve_await(await_promise, func_ret),
+                    Err(_) => Input::Deferred(await_promise),
                 }
             }
         }
     }
 }
 
+/// Resolve an [Await]'ed promise, given the awaited [Instruction]'s result,
+/// keeping it [Input::Deferred] if the result can't be [selected].
+///
+/// [Instruction]: super::Instruction
+/// [selected]: Await::select
+fn resolve_await<T>(await_promise: Await, func_ret: task::Result<T>) -> Input<T>
+where
+    Ipld: From<T>,
+{
+    if !await_promise.result().matches(&func_ret) {
+        return Input::Deferred(await_promise);
+    }
+
+    match (await_promise.result(), await_promise.path()) {
+        (AwaitResult::Ok | AwaitResult::Error, None) => Input::Arg(func_ret),
+        _ => match await_promise.select(func_ret) {
+            Some(selected) => Input::Ipld(selected),
+            None => Input::Deferred(await_promise),
+        },
+    }
+}
+
 impl<T> From<Input<T>> for Ipld
 where
     Ipld: From<T>,
@@ -220,6 +274,7 @@ where
             if let Ok(invocation_result) = ipld.to_owned().try_into() {
                 return Ok(Input::Arg(invocation_result));
             } else {
+                check_nested_awaits(&ipld)?;
                 return Ok(Input::Ipld(ipld));
             }
         };
@@ -231,15 +286,21 @@ where
                 if let Ok(invocation_result) = task::Result::try_from(ipld.to_owned()) {
                     Ok(Input::Arg(invocation_result))
                 } else {
-                    Ok(Input::Ipld(ipld))
+                    check_nested_awaits(&ipld).map(|()| Input::Ipld(ipld))
                 },
                 |(branch, ipld)| {
                     let instruction = Pointer::try_from(ipld)?;
-                    Ok(Input::Deferred(Await::new(
+                    let await_promise = Await::new(
                         instruction,
                         AwaitResult::result(branch)
                             .ok_or_else(|| Error::InvalidDiscriminant(branch.to_string()))?,
-                    )))
+                    );
+                    match map.get(PATH_KEY) {
+                        Some(path) => {
+                            Ok(Input::Deferred(await_promise.with_path(parse_path(path)?)))
+                        }
+                        None => Ok(Input::Deferred(await_promise)),
+                    }
                 },
             )
     }
@@ -256,6 +317,13 @@ where
 
 /// Resolve [awaited promises] for *only* Ipld data, given a lookup function.
 ///
+/// Promises nested anywhere within Ipld maps and lists are replaced by
+/// their [selected] values, and left as-is if they can't be resolved or
+/// selected. Other links are replaced by the unwrapped output they resolve
+/// to.
+///
+/// [selected]: Await::select
+///
 /// [awaited promises]: Await
 #[async_recursion]
 pub async fn resolve_links<'a, T, F>(ipld: Ipld, lookup_fn: Arc<F>) -> Ipld
@@ -263,33 +331,18 @@ where
     F: Fn(Cid) -> BoxFuture<'a, Result<task::Result<T>, ResolveError>> + Clone + Sync + Send,
     Ipld: From<T>,
 {
+    if let Ok(Some(await_promise)) = as_await(&ipld) {
+        return match (*lookup_fn)(await_promise.instruction_cid()).await {
+            Ok(func_ret) => await_promise.select(func_ret).unwrap_or(ipld),
+            Err(_) => ipld,
+        };
+    }
+
     match ipld {
         Ipld::Map(m) => {
-            let futures = m.into_iter().map(|(k, v)| async {
-                match v {
-                    Ipld::Link(cid) => {
-                        let mut f = Arc::clone(&lookup_fn);
-                        if let Ok(func_ret) = Arc::make_mut(&mut f)(cid).await {
-                            if k.eq(PTR_BRANCH) {
-                                (k, func_ret.into())
-                            } else {
-                                (k, func_ret.into_inner().into())
-                            }
-                        } else {
-                            (k, v)
-                        }
-                    }
-                    Ipld::Map(ref m) => {
-                        let resolved = resolve_links(Ipld::Map(m.clone()), lookup_fn.clone()).await;
-                        (k, resolved)
-                    }
-                    Ipld::List(ref l) => {
-                        let resolved =
-                            resolve_links(Ipld::List(l.clone()), lookup_fn.clone()).await;
-                        (k, resolved)
-                    }
-                    _ => (k, v),
-                }
+            let futures = m.into_iter().map(|(k, v)| {
+                let lookup_fn = lookup_fn.clone();
+                async move { (k, resolve_links(v, lookup_fn).await) }
             });
             let resolved_results = future::join_all(futures).await;
             Ipld::Map(
@@ -299,31 +352,12 @@ where
             )
         }
         Ipld::List(l) => {
-            let futures = l.into_iter().map(|v| async {
-                match v {
-                    Ipld::Link(cid) => {
-                        let mut f = Arc::clone(&lookup_fn);
-                        if let Ok(func_ret) = Arc::make_mut(&mut f)(cid).await {
-                            func_ret.into_inner().into()
-                        } else {
-                            v
-                        }
-                    }
-                    Ipld::Map(ref m) => {
-                        resolve_links(Ipld::Map(m.clone()), lookup_fn.clone()).await
-                    }
-                    Ipld::List(ref l) => {
-                        resolve_links(Ipld::List(l.clone()), lookup_fn.clone()).await
-                    }
-                    _ => v,
-                }
-            });
+            let futures = l.into_iter().map(|v| resolve_links(v, lookup_fn.clone()));
             let resolved_results = future::join_all(futures).await;
             Ipld::List(resolved_results)
         }
         Ipld::Link(link) => {
-            let mut f = Arc::clone(&lookup_fn);
-            if let Ok(func_ret) = Arc::make_mut(&mut f)(link).await {
+            if let Ok(func_ret) = (*lookup_fn)(link).await {
                 func_ret.into_inner().into()
             } else {
                 Ipld::Link(link)
@@ -333,10 +367,74 @@ where
     }
 }
 
+/// Return the [Await] promise making up an Ipld map, if the map is a single
+/// `await/ok`, `await/error`, or `await/*` branch to a link, along with an
+/// optional [Path].
+///
+/// Errors if the promise's [Path] is invalid.
+fn as_await(ipld: &Ipld) -> Result<Option<Await>, Error<String>> {
+    let Ipld::Map(map) = ipld else {
+        return Ok(None);
+    };
+
+    let mut entries = map.iter().filter(|(key, _)| key.as_str() != PATH_KEY);
+    let Some(await_promise) = (match (entries.next(), entries.next()) {
+        (Some((branch, Ipld::Link(cid))), None) => {
+            AwaitResult::result(branch).map(|result| Await::new(Pointer::new(*cid), result))
+        }
+        _ => None,
+    }) else {
+        return Ok(None);
+    };
+
+    match map.get(PATH_KEY) {
+        Some(path) => Ok(Some(await_promise.with_path(parse_path(path)?))),
+        None => Ok(Some(await_promise)),
+    }
+}
+
+/// Check that all [Await] promises nested within Ipld maps and lists have
+/// valid [paths].
+///
+/// [paths]: Path
+fn check_nested_awaits(ipld: &Ipld) -> Result<(), Error<String>> {
+    if as_await(ipld)?.is_some() {
+        return Ok(());
+    }
+
+    match ipld {
+        Ipld::Map(m) => m.values().try_for_each(check_nested_awaits),
+        Ipld::List(l) => l.iter().try_for_each(check_nested_awaits),
+        _ => Ok(()),
+    }
+}
+
+/// Parse a [Path] given alongside an [Await] promise.
+fn parse_path(ipld: &Ipld) -> Result<Path, Error<String>> {
+    Path::try_from(ipld).map_err(|err| match err {
+        Error::InvalidPath(path) => Error::InvalidPath(path),
+        _ => Error::InvalidPath(format!("{ipld:?}")),
+    })
+}
+
+/// Return all [Await] promises nested within Ipld maps and lists, in order.
+fn nested_awaits(ipld: &Ipld) -> Vec<Await> {
+    if let Ok(Some(await_promise)) = as_await(ipld) {
+        return vec![await_promise];
+    }
+
+    match ipld {
+        Ipld::Map(m) => m.values().flat_map(nested_awaits).collect(),
+        Ipld::List(l) => l.iter().flat_map(nested_awaits).collect(),
+        _ => vec![],
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::{test_utils, Unit};
+    use futures::FutureExt;
 
     #[test]
     fn input_ipld_ipld_rountrip() {
@@ -383,6 +481,119 @@ mod test {
         assert_eq!(args, ipld.try_into().unwrap());
     }
 
+    #[test]
+    fn nested_awaits_are_deferred_and_resolved() {
+        let instruction = test_utils::instruction::<Unit>();
+        let ptr: Pointer = instruction.try_into().unwrap();
+        let awaited =
+            |branch: &str| Ipld::Map(BTreeMap::from([(branch.into(), Ipld::Link(ptr.cid()))]));
+
+        let args: Args<Ipld> = Args::new(vec![
+            Input::Ipld(Ipld::Map(BTreeMap::from([
+                ("value".into(), awaited(OK_BRANCH)),
+                ("sigma".into(), Ipld::Float(1.5)),
+            ]))),
+            Input::Ipld(Ipld::List(vec![Ipld::Integer(1), awaited(PTR_BRANCH)])),
+        ]);
+        assert_eq!(
+            args.deferreds().collect::<Vec<_>>(),
+            vec![ptr.cid(), ptr.cid()]
+        );
+        assert!(args.inner().iter().all(Input::is_deferred));
+        assert!(!Input::<Ipld>::Ipld(Ipld::Integer(1)).is_deferred());
+
+        let lookup_fn =
+            |_cid: Cid| async { Ok::<_, ResolveError>(task::Result::Ok(Ipld::Integer(2))) }.boxed();
+        let resolved = futures::executor::block_on(args.clone().resolve(lookup_fn)).unwrap();
+        assert_eq!(
+            resolved,
+            Args::new(vec![
+                Input::Ipld(Ipld::Map(BTreeMap::from([
+                    ("value".into(), Ipld::Integer(2)),
+                    ("sigma".into(), Ipld::Float(1.5)),
+                ]))),
+                Input::Ipld(Ipld::List(vec![
+                    Ipld::Integer(1),
+                    Ipld::List(vec![Ipld::String("ok".into()), Ipld::Integer(2)]),
+                ])),
+            ])
+        );
+        assert_eq!(resolved.deferreds().count(), 0);
+
+        // The `ok` branch can't be taken by a failed output.
+        let lookup_fn = |_cid: Cid| {
+            async { Ok::<_, ResolveError>(task::Result::Error(Ipld::Integer(2))) }.boxed()
+        };
+        assert!(futures::executor::block_on(args.resolve(lookup_fn)).is_err());
+    }
+
+    #[test]
+    fn awaits_with_paths_resolve_to_selected_values() {
+        let instruction = test_utils::instruction::<Unit>();
+        let ptr: Pointer = instruction.try_into().unwrap();
+        let width = Await::new(ptr.clone(), AwaitResult::Ok)
+            .with_path(".outputs[1].width".parse().unwrap());
+
+        let args: Args<Ipld> = Args::new(vec![
+            Input::Deferred(width.clone()),
+            Input::Ipld(Ipld::Map(BTreeMap::from([(
+                "size".into(),
+                Ipld::from(width),
+            )]))),
+            Input::Deferred(Await::new(ptr, AwaitResult::Ptr)),
+        ]);
+
+        let lookup_fn = |_cid: Cid| {
+            async {
+                Ok::<_, ResolveError>(task::Result::Ok(Ipld::Map(BTreeMap::from([(
+                    "outputs".into(),
+                    Ipld::List(vec![
+                        Ipld::Map(BTreeMap::from([("width".into(), Ipld::Integer(10))])),
+                        Ipld::Map(BTreeMap::from([("width".into(), Ipld::Integer(20))])),
+                    ]),
+                )]))))
+            }
+            .boxed()
+        };
+        let resolved = futures::executor::block_on(args.resolve(lookup_fn))
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(resolved[0], Input::Ipld(Ipld::Integer(20)));
+        assert_eq!(
+            resolved[1],
+            Input::Ipld(Ipld::Map(BTreeMap::from([(
+                "size".into(),
+                Ipld::Integer(20)
+            )])))
+        );
+        let Input::Ipld(Ipld::List(envelope)) = &resolved[2] else {
+            panic!("expected the whole result")
+        };
+        assert_eq!(envelope[0], Ipld::String("ok".into()));
+    }
+
+    #[test]
+    fn invalid_await_paths_error() {
+        let instruction = test_utils::instruction::<Unit>();
+        let ptr: Pointer = instruction.try_into().unwrap();
+        let awaited = Ipld::Map(BTreeMap::from([
+            (OK_BRANCH.into(), Ipld::Link(ptr.cid())),
+            (PATH_KEY.into(), Ipld::String("outputs".into())),
+        ]));
+
+        for ipld in [
+            awaited.clone(),
+            Ipld::List(vec![Ipld::Integer(1), awaited.clone()]),
+            Ipld::Map(BTreeMap::from([("size".into(), awaited)])),
+        ] {
+            assert!(matches!(
+                Input::<Ipld>::try_from(ipld),
+                Err(Error::InvalidPath(path)) if path == "outputs"
+            ));
+        }
+    }
+
     #[test]
     fn ser_de_ipld() {
         let input: Input<Unit> = Input::Ipld(Ipld::Bool(true));
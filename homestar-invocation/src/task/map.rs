@@ -0,0 +1,241 @@
+//! Maps fanning a [Task] out over the elements of an awaited
+//! [Instruction]'s list output.
+//!
+//! [Task]: crate::Task
+//! [Instruction]: crate::task::Instruction
+
+use crate::{
+    ipld::DagCbor,
+    pointer::Await,
+    task::{
+        self,
+        instruction::{IncomingTyp, Input, Nonce},
+        Instruction,
+    },
+    Error, Unit,
+};
+use generic_array::GenericArray;
+use libipld::{
+    cid::Cid,
+    multihash::{Code, MultihashDigest},
+    serde::from_ipld,
+    Ipld,
+};
+use std::collections::BTreeMap;
+
+const MAP_KEY: &str = "map";
+
+/// Map of a [Task] over an awaited [Instruction]'s list output, expressed in
+/// task metadata under a `map` key, next to [Resources]:
+///
+/// `{"fuel": .., "memory": .., "time": .., "map": {"await/ok": {"/": "bafy..."}}}`
+///
+/// Once the awaited output is available, the [Task]'s [Instruction] is
+/// expanded into one [Instruction] per element of the list, with every
+/// argument awaiting the same promise replaced by the element. Expanded
+/// instructions are given a [Nonce] derived from the mapped [Instruction]
+/// and the element's index, so their Cids are deterministic across runs.
+///
+/// The mapped [Instruction] itself gathers the outputs of its expanded
+/// instructions into a list, in order, which is what any [Instruction]
+/// awaiting it is given.
+///
+/// [Task]: crate::Task
+/// [Resources]: crate::task::Resources
+#[derive(Clone, Debug, PartialEq)]
+pub struct Map {
+    promise: Await,
+}
+
+impl Map {
+    /// Create a new [Map] over an awaited [Instruction]'s output.
+    pub fn new(promise: Await) -> Self {
+        Self { promise }
+    }
+
+    /// Return the [Await]'ed promise whose list output is mapped over.
+    pub fn promise(&self) -> &Await {
+        &self.promise
+    }
+
+    /// Return the elements of the awaited [Instruction]'s output, if the
+    /// [selected] output is a list.
+    ///
+    /// [selected]: Await::select
+    pub fn elements<T>(&self, result: &task::Result<T>) -> Option<Vec<Ipld>>
+    where
+        T: Clone,
+        Ipld: From<T>,
+    {
+        match self.promise.select(result.to_owned()) {
+            Some(Ipld::List(elements)) => Some(elements),
+            _ => None,
+        }
+    }
+
+    /// Expand a mapped [Instruction] into one [Instruction] per element,
+    /// replacing every argument awaiting the mapped promise with the
+    /// element.
+    pub fn expand<'a, T>(
+        &self,
+        instruction: &Instruction<'a, T>,
+        elements: Vec<Ipld>,
+    ) -> Result<Vec<Instruction<'a, T>>, Error<Unit>>
+    where
+        T: Clone,
+        Ipld: From<T>,
+    {
+        let mapped_cid = instruction.clone().to_cid()?;
+        let promise = <Ipld as From<Await>>::from(self.promise.clone());
+        let input = <Ipld as From<Input<T>>>::from(instruction.input().to_owned());
+
+        Ok(elements
+            .into_iter()
+            .enumerate()
+            .map(|(index, element)| {
+                Instruction::new_with_nonce(
+                    instruction.resource().to_owned(),
+                    instruction.op().to_owned(),
+                    Input::Ipld(substitute(input.clone(), &promise, &element)),
+                    nonce(mapped_cid, index),
+                )
+            })
+            .collect())
+    }
+
+    /// Parse a [Map] from task metadata, if the metadata gives one.
+    pub fn from_meta(meta: &Ipld) -> Result<Option<Self>, Error<Unit>> {
+        let Ok(map) = from_ipld::<BTreeMap<String, Ipld>>(meta.to_owned()) else {
+            return Ok(None);
+        };
+        map.get(MAP_KEY)
+            .map(|ipld| Map::try_from(ipld.to_owned()))
+            .transpose()
+    }
+
+    /// Add [Map] to task metadata, e.g. built from [Resources].
+    ///
+    /// [Resources]: crate::task::Resources
+    pub fn add_to_meta(self, meta: Ipld) -> Ipld {
+        let mut map = from_ipld::<BTreeMap<String, Ipld>>(meta).unwrap_or_default();
+        map.insert(MAP_KEY.into(), self.into());
+        Ipld::Map(map)
+    }
+}
+
+/// Replace every occurrence of the promise in the Ipld with the element.
+fn substitute(ipld: Ipld, promise: &Ipld, element: &Ipld) -> Ipld {
+    if &ipld == promise {
+        return element.to_owned();
+    }
+
+    match ipld {
+        Ipld::List(list) => Ipld::List(
+            list.into_iter()
+                .map(|ipld| substitute(ipld, promise, element))
+                .collect(),
+        ),
+        Ipld::Map(map) => Ipld::Map(
+            map.into_iter()
+                .map(|(key, ipld)| (key, substitute(ipld, promise, element)))
+                .collect(),
+        ),
+        ipld => ipld,
+    }
+}
+
+/// Derive a 128-bit [Nonce] from the mapped [Instruction]'s Cid and the
+/// element's index.
+fn nonce(mapped_cid: Cid, index: usize) -> Nonce {
+    let mut bytes = mapped_cid.to_bytes();
+    bytes.extend_from_slice(&(index as u64).to_be_bytes());
+    let hash = Code::Sha3_256.digest(&bytes);
+
+    Nonce::Nonce128(
+        *GenericArray::from_slice(&hash.digest()[..16]),
+        IncomingTyp::Bytes,
+    )
+}
+
+impl From<Map> for Ipld {
+    fn from(map: Map) -> Ipld {
+        map.promise.into()
+    }
+}
+
+impl TryFrom<Ipld> for Map {
+    type Error = Error<Unit>;
+
+    /// Parse a [Map], which must await exactly one branch.
+    fn try_from(ipld: Ipld) -> Result<Self, Self::Error> {
+        let promise = Await::try_from(&ipld)?;
+        let map = from_ipld::<BTreeMap<String, Ipld>>(ipld)?;
+        if !map.contains_key(promise.result().branch()) {
+            return Err(Error::ConditionNotMet(
+                "map must await an instruction".to_string(),
+            ));
+        }
+
+        Ok(Map::new(promise))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{pointer::AwaitResult, task::Resources, test_utils, Pointer};
+
+    #[test]
+    fn ipld_roundtrip_through_meta() {
+        let (instruction, _, _) = test_utils::related_wasm_instructions::<Unit>();
+        let map = Map::new(Await::new(
+            Pointer::try_from(instruction).unwrap(),
+            AwaitResult::Ok,
+        ));
+        let meta = map.clone().add_to_meta(Resources::default().into());
+
+        assert_eq!(Map::from_meta(&meta).unwrap(), Some(map));
+        assert_eq!(
+            Resources::try_from(&meta).unwrap(),
+            Resources::default(),
+            "resources are unaffected by a map"
+        );
+        assert_eq!(Map::from_meta(&Resources::default().into()).unwrap(), None);
+    }
+
+    #[test]
+    fn expands_deterministically_per_element() {
+        let (instruction, mapped, _) = test_utils::related_wasm_instructions::<Unit>();
+        let map = Map::new(Await::new(
+            Pointer::try_from(instruction).unwrap(),
+            AwaitResult::Ok,
+        ));
+
+        let elements = map
+            .elements(&task::Result::Ok(Ipld::List(vec![
+                Ipld::Integer(1),
+                Ipld::Integer(2),
+            ])))
+            .unwrap();
+        assert!(map.elements(&task::Result::Ok(Ipld::Integer(1))).is_none());
+
+        let expanded = map.expand(&mapped, elements.clone()).unwrap();
+        assert_eq!(expanded.len(), 2);
+        assert_eq!(expanded, map.expand(&mapped, elements).unwrap());
+        assert_ne!(
+            expanded[0].clone().to_cid().unwrap(),
+            expanded[1].clone().to_cid().unwrap()
+        );
+
+        for (instruction, element) in expanded.iter().zip([1, 2]) {
+            let Ipld::Map(input) = Ipld::from(instruction.input().to_owned()) else {
+                panic!("expected an input map")
+            };
+            assert_eq!(
+                input.get("args"),
+                Some(&Ipld::List(vec![Ipld::Integer(element)])),
+                "awaited argument is replaced by the element"
+            );
+        }
+    }
+}
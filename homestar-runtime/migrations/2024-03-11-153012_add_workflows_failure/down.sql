@@ -0,0 +1,21 @@
+CREATE TABLE workflows_old (
+  cid           TEXT NOT NULL PRIMARY KEY,
+  name          TEXT,
+  num_tasks     INTEGER NOT NULL,
+  resources     BLOB NOT NULL,
+  created_at    TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  completed_at  TIMESTAMP,
+  status        TEXT CHECK(
+      status IN ('pending', 'completed', 'running', 'stuck')) NOT NULL DEFAULT
+              'pending',
+  retries       INTEGER NOT NULL DEFAULT 0
+);
+
+INSERT INTO workflows_old (cid, name, num_tasks, resources, created_at, completed_at, status, retries)
+  SELECT cid, name, num_tasks, resources, created_at, completed_at,
+    CASE WHEN status IN ('failed', 'cancelled') THEN 'stuck' ELSE status END,
+    retries
+  FROM workflows;
+
+DROP TABLE workflows;
+ALTER TABLE workflows_old RENAME TO workflows;
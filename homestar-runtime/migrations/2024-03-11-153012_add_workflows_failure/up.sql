@@ -0,0 +1,23 @@
+-- SQLite cannot alter a CHECK constraint in place, so the table is rebuilt
+-- to allow the `failed` and `cancelled` statuses.
+CREATE TABLE workflows_new (
+  cid                 TEXT NOT NULL PRIMARY KEY,
+  name                TEXT,
+  num_tasks           INTEGER NOT NULL,
+  resources           BLOB NOT NULL,
+  created_at          TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  completed_at        TIMESTAMP,
+  status              TEXT CHECK(
+      status IN ('pending', 'completed', 'running', 'stuck', 'failed', 'cancelled')) NOT NULL DEFAULT
+              'pending',
+  retries             INTEGER NOT NULL DEFAULT 0,
+  failure_reason      TEXT,
+  failed_instruction  TEXT,
+  failed_at           TIMESTAMP
+);
+
+INSERT INTO workflows_new (cid, name, num_tasks, resources, created_at, completed_at, status, retries)
+  SELECT cid, name, num_tasks, resources, created_at, completed_at, status, retries FROM workflows;
+
+DROP TABLE workflows;
+ALTER TABLE workflows_new RENAME TO workflows;
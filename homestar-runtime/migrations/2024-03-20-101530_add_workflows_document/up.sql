@@ -0,0 +1,3 @@
+-- Persisted DAG-CBOR copy of the workflow, used to resume the workflow after
+-- a node restart.
+ALTER TABLE workflows ADD COLUMN document BLOB;
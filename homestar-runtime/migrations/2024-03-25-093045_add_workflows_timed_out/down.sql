@@ -0,0 +1,25 @@
+CREATE TABLE workflows_old (
+  cid                 TEXT NOT NULL PRIMARY KEY,
+  name                TEXT,
+  num_tasks           INTEGER NOT NULL,
+  resources           BLOB NOT NULL,
+  created_at          TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  completed_at        TIMESTAMP,
+  status              TEXT CHECK(
+      status IN ('pending', 'completed', 'running', 'stuck', 'failed', 'cancelled')) NOT NULL DEFAULT
+              'pending',
+  retries             INTEGER NOT NULL DEFAULT 0,
+  failure_reason      TEXT,
+  failed_instruction  TEXT,
+  failed_at           TIMESTAMP,
+  document            BLOB
+);
+
+INSERT INTO workflows_old (cid, name, num_tasks, resources, created_at, completed_at, status, retries, failure_reason, failed_instruction, failed_at, document)
+  SELECT cid, name, num_tasks, resources, created_at, completed_at,
+    CASE WHEN status = 'timed_out' THEN 'failed' ELSE status END,
+    retries, failure_reason, failed_instruction, failed_at, document
+  FROM workflows;
+
+DROP TABLE workflows;
+ALTER TABLE workflows_old RENAME TO workflows;
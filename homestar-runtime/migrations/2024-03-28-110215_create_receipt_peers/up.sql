@@ -0,0 +1,7 @@
+-- Peers that receipts were received from, or `local` for receipts issued by
+-- this node, used to decide whether to trust them.
+CREATE TABLE receipt_peers (
+  receipt_cid TEXT NOT NULL REFERENCES receipts(cid),
+  peer_id TEXT NOT NULL,
+  PRIMARY KEY(receipt_cid, peer_id)
+);
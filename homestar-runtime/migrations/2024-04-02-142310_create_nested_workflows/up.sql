@@ -0,0 +1,8 @@
+-- Workflows run as tasks of other workflows, through `workflow/run`
+-- instructions, linked back to their parent workflows.
+CREATE TABLE nested_workflows (
+  parent_cid       TEXT NOT NULL REFERENCES workflows(cid),
+  workflow_cid     TEXT NOT NULL REFERENCES workflows(cid),
+  instruction_cid  TEXT NOT NULL,
+  PRIMARY KEY(parent_cid, workflow_cid)
+);
@@ -0,0 +1,10 @@
+-- Instructions of stored workflow documents, indexed by instruction, used to
+-- find the workflow an instruction belongs to without decoding every
+-- document.
+CREATE TABLE workflows_instructions (
+  workflow_cid     TEXT NOT NULL REFERENCES workflows(cid),
+  instruction_cid  TEXT NOT NULL,
+  PRIMARY KEY(workflow_cid, instruction_cid)
+);
+
+CREATE INDEX workflows_instructions_index ON workflows_instructions (instruction_cid);
@@ -0,0 +1,5 @@
+-- Receipts whose re-executed output didn't match, kept but never reused in
+-- place of running their instructions.
+CREATE TABLE flagged_receipts (
+  receipt_cid TEXT NOT NULL PRIMARY KEY
+);
@@ -0,0 +1,8 @@
+-- Blocks bundled with workflows, e.g. Wasm modules within CAR files, used in
+-- place of fetching the same resources when workflows are resumed.
+CREATE TABLE workflows_blocks (
+  workflow_cid  TEXT NOT NULL REFERENCES workflows(cid),
+  block_cid     TEXT NOT NULL,
+  data          BLOB NOT NULL,
+  PRIMARY KEY(workflow_cid, block_cid)
+);
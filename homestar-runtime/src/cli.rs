@@ -1,11 +1,13 @@
 //! CLI commands/arguments.
 
 use crate::{
+    db::utils::{WorkflowFilter, DEFAULT_WORKFLOWS_LIMIT},
     network::rpc::Client,
-    runner::{file, response},
-    KeyType,
+    runner::{file, response, ReceiptLookup},
+    workflow, KeyType,
 };
 use anyhow::anyhow;
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
 use clap::{ArgGroup, Args, Parser, Subcommand};
 use serde::{Deserialize, Serialize};
 use std::{
@@ -21,6 +23,10 @@ mod init;
 pub use init::{handle_init_command, KeyArg, OutputMode};
 pub(crate) mod show;
 pub use show::ConsoleTable;
+mod plan;
+pub use plan::handle_plan_command;
+mod validate;
+pub use validate::handle_validate_command;
 
 const DEFAULT_DB_PATH: &str = "homestar.db";
 const TMP_DIR: &str = "/tmp";
@@ -115,6 +121,93 @@ pub struct InitArgs {
     pub key_seed: Option<Option<String>>,
 }
 
+/// Arguments for `validate` command.
+#[derive(Debug, Clone, PartialEq, Args)]
+pub struct ValidateArgs {
+    /// IPVM-configured workflow file to validate.
+    #[arg(
+        value_hint = clap::ValueHint::FilePath,
+        value_name = "FILE",
+        value_parser = clap::value_parser!(file::ReadWorkflow),
+        index = 1,
+        required = true,
+        help = r#"IPVM-configured workflow file to validate.
+Supported:
+  - JSON (.json)
+  - DAG-CBOR (.cbor)
+  - CARv1, bundling the workflow with its Wasm modules (.car)
+  - YAML (.yaml, .yml)"#
+    )]
+    pub workflow: file::ReadWorkflow,
+    /// Directories of cached Wasm modules, named by Cid.
+    #[arg(
+        short = 'm',
+        long = "modules",
+        value_hint = clap::ValueHint::DirPath,
+        value_name = "DIR",
+        help = "Directory of cached Wasm modules, each named by its Cid, e.g. <cid> or <cid>.wasm. Can be given more than once [optional]"
+    )]
+    pub modules: Vec<PathBuf>,
+}
+
+/// Arguments for `plan` command.
+#[derive(Debug, Clone, PartialEq, Args)]
+pub struct PlanArgs {
+    /// IPVM-configured workflow file to plan.
+    #[arg(
+        value_hint = clap::ValueHint::FilePath,
+        value_name = "FILE",
+        value_parser = clap::value_parser!(file::ReadWorkflow),
+        index = 1,
+        required = true,
+        help = r#"IPVM-configured workflow file to plan.
+Supported:
+  - JSON (.json)
+  - DAG-CBOR (.cbor)
+  - CARv1, bundling the workflow with its Wasm modules (.car)
+  - YAML (.yaml, .yml)"#
+    )]
+    pub workflow: file::ReadWorkflow,
+    /// Database URL, defaults to homestar.db.
+    #[arg(
+        long = "db",
+        env = "DATABASE_PATH",
+        value_hint = clap::ValueHint::AnyPath,
+        value_name = "DATABASE_PATH",
+        default_value = DEFAULT_DB_PATH,
+        help = "Database path (SQLite), checked for receipts to replay [optional]"
+    )]
+    pub database_url: Option<String>,
+    /// Runtime configuration file (.toml).
+    #[arg(
+        short = 'c',
+        long = "config",
+        value_hint = clap::ValueHint::FilePath,
+        value_name = "CONFIG",
+        help = "Runtime configuration file (.toml) [optional]"
+    )]
+    pub runtime_config: Option<PathBuf>,
+    /// Write the workflow's DAG as a Graphviz DOT file.
+    #[arg(
+        long = "dot",
+        value_hint = clap::ValueHint::FilePath,
+        value_name = "FILE",
+        help = "Write the workflow's DAG to a Graphviz DOT file [optional]"
+    )]
+    pub dot: Option<PathBuf>,
+    /// Write the workflow's DAG as a Mermaid flowchart.
+    #[arg(
+        long = "mermaid",
+        value_hint = clap::ValueHint::FilePath,
+        value_name = "FILE",
+        help = "Write the workflow's DAG to a Mermaid flowchart file [optional]"
+    )]
+    pub mermaid: Option<PathBuf>,
+    /// Output as JSON instead of a table.
+    #[arg(long = "json", default_value = "false")]
+    pub json: bool,
+}
+
 /// General RPC arguments for [Client] commands.
 ///
 /// [Client]: crate::network::rpc::Client
@@ -210,6 +303,9 @@ pub enum Command {
         /// IPVM-configured workflow file to run.
         /// Supported:
         ///   - JSON (.json).
+        ///   - DAG-CBOR (.cbor).
+        ///   - CARv1, bundling the workflow with its Wasm modules (.car).
+        ///   - YAML (.yaml, .yml).
         #[arg(
             value_hint = clap::ValueHint::FilePath,
             value_name = "FILE",
@@ -218,10 +314,57 @@ pub enum Command {
             required = true,
             help = r#"IPVM-configured workflow file to run.
 Supported:
-  - JSON (.json)"#
+  - JSON (.json)
+  - DAG-CBOR (.cbor)
+  - CARv1, bundling the workflow with its Wasm modules (.car)
+  - YAML (.yaml, .yml)"#
         )]
         workflow: file::ReadWorkflow,
     },
+    /// Validate an IPVM-configured workflow file offline, without running it.
+    Validate(ValidateArgs),
+    /// Print the execution plan of an IPVM-configured workflow file, without
+    /// running it.
+    Plan(PlanArgs),
+    /// Cancel a running workflow on the Homestar runtime.
+    Cancel {
+        /// RPC host / port arguments.
+        #[clap(flatten)]
+        args: RpcArgs,
+        /// Cid or local name of the workflow to cancel.
+        #[arg(
+            value_name = "WORKFLOW",
+            index = 1,
+            required = true,
+            help = "Cid or local name of the workflow to cancel"
+        )]
+        workflow: String,
+    },
+    /// List or show workflows stored on the Homestar runtime.
+    Workflows {
+        /// Workflows subcommand.
+        #[clap(subcommand)]
+        command: WorkflowsCommand,
+    },
+    /// Get receipts stored on, or discoverable by, the Homestar runtime.
+    Receipt {
+        /// Receipt subcommand.
+        #[clap(subcommand)]
+        command: ReceiptCommand,
+    },
+    /// Verify a receipt stored on the Homestar runtime by re-executing the
+    /// instruction it was issued for and comparing outputs.
+    Verify {
+        /// RPC host / port arguments.
+        #[clap(flatten)]
+        args: RpcArgs,
+        /// Receipt Cid.
+        #[arg(value_name = "CID", index = 1, required = true, help = "Receipt Cid")]
+        cid: String,
+        /// Output the verification result as JSON instead of a table.
+        #[arg(long = "json", default_value = "false")]
+        json: bool,
+    },
     /// Get node identity / information.
     Node {
         /// RPC host / port arguments.
@@ -232,6 +375,159 @@ Supported:
     Info,
 }
 
+/// Workflows subcommands.
+#[derive(Debug, Subcommand)]
+pub enum WorkflowsCommand {
+    /// List stored workflows, most recent first.
+    List {
+        /// RPC host / port arguments.
+        #[clap(flatten)]
+        args: RpcArgs,
+        /// Only list workflows with the given status.
+        #[arg(
+            long = "status",
+            value_name = "STATUS",
+            help = "Only list workflows with the given status: pending, running, completed, stuck, failed, cancelled, or timed_out [optional]"
+        )]
+        status: Option<workflow::Status>,
+        /// Only list workflows with a local name containing the given text.
+        #[arg(
+            short = 'n',
+            long = "name",
+            value_name = "NAME",
+            help = "Only list workflows with a local name containing the given text [optional]"
+        )]
+        name: Option<String>,
+        /// Only list workflows created at or after the given date/time.
+        #[arg(
+            long = "since",
+            value_name = "DATETIME",
+            value_parser = parse_datetime,
+            help = "Only list workflows created at or after the given date/time (UTC), e.g. 2024-01-31 or 2024-01-31T12:00:00 [optional]"
+        )]
+        since: Option<NaiveDateTime>,
+        /// Only list workflows created at or before the given date/time.
+        #[arg(
+            long = "until",
+            value_name = "DATETIME",
+            value_parser = parse_until,
+            help = "Only list workflows created at or before the given date/time (UTC), e.g. 2024-01-31 (through the end of that day) or 2024-01-31T12:00:00 [optional]"
+        )]
+        until: Option<NaiveDateTime>,
+        /// Maximum number of workflows to list.
+        #[arg(
+            long = "limit",
+            default_value_t = DEFAULT_WORKFLOWS_LIMIT,
+            value_parser = clap::value_parser!(i64).range(0..)
+        )]
+        limit: i64,
+        /// Number of workflows to skip, for pagination.
+        #[arg(
+            long = "offset",
+            default_value_t = 0,
+            value_parser = clap::value_parser!(i64).range(0..)
+        )]
+        offset: i64,
+        /// Output as JSON instead of a table.
+        #[arg(long = "json", default_value = "false")]
+        json: bool,
+    },
+    /// Show a stored workflow's tasks, progress, receipts, resources, and
+    /// timings.
+    Show {
+        /// RPC host / port arguments.
+        #[clap(flatten)]
+        args: RpcArgs,
+        /// Cid or local name of the workflow to show.
+        #[arg(
+            value_name = "WORKFLOW",
+            index = 1,
+            required = true,
+            help = "Cid or local name of the workflow to show"
+        )]
+        workflow: String,
+        /// Output as JSON instead of a table.
+        #[arg(long = "json", default_value = "false")]
+        json: bool,
+    },
+    /// Get a stored workflow's document, as it was submitted.
+    Get {
+        /// RPC host / port arguments.
+        #[clap(flatten)]
+        args: RpcArgs,
+        /// Cid or local name of the workflow to get.
+        #[arg(
+            value_name = "WORKFLOW",
+            index = 1,
+            required = true,
+            help = "Cid or local name of the workflow to get"
+        )]
+        workflow: String,
+        /// Workflow output format.
+        #[clap(flatten)]
+        format: WorkflowFormat,
+    },
+}
+
+/// Receipt subcommands.
+#[derive(Debug, Subcommand)]
+pub enum ReceiptCommand {
+    /// Get a receipt by its Cid.
+    Get {
+        /// RPC host / port arguments.
+        #[clap(flatten)]
+        args: RpcArgs,
+        /// Receipt Cid.
+        #[arg(value_name = "CID", index = 1, required = true, help = "Receipt Cid")]
+        cid: String,
+        /// Receipt output format.
+        #[clap(flatten)]
+        format: ReceiptFormat,
+    },
+    /// Get a receipt by the Cid of the instruction it was issued for, falling
+    /// back to the DHT if it isn't stored locally.
+    ForInstruction {
+        /// RPC host / port arguments.
+        #[clap(flatten)]
+        args: RpcArgs,
+        /// Instruction Cid.
+        #[arg(
+            value_name = "CID",
+            index = 1,
+            required = true,
+            help = "Instruction Cid"
+        )]
+        cid: String,
+        /// Receipt output format.
+        #[clap(flatten)]
+        format: ReceiptFormat,
+    },
+}
+
+/// Output format arguments for [ReceiptCommand]s.
+#[derive(Debug, Clone, PartialEq, Args)]
+#[group(multiple = false)]
+pub struct ReceiptFormat {
+    /// Output the receipt as DAG-JSON instead of a table.
+    #[arg(long = "json", default_value = "false")]
+    json: bool,
+    /// Output the receipt as raw DAG-CBOR bytes instead of a table.
+    #[arg(long = "cbor", default_value = "false")]
+    cbor: bool,
+}
+
+/// Output format arguments for [WorkflowsCommand::Get].
+#[derive(Debug, Clone, PartialEq, Args)]
+#[group(multiple = false)]
+pub struct WorkflowFormat {
+    /// Output the workflow as DAG-JSON instead of a table.
+    #[arg(long = "json", default_value = "false")]
+    json: bool,
+    /// Output the workflow as raw DAG-CBOR bytes instead of a table.
+    #[arg(long = "cbor", default_value = "false")]
+    cbor: bool,
+}
+
 impl Command {
     fn name(&self) -> &'static str {
         match self {
@@ -240,6 +536,12 @@ impl Command {
             Command::Stop { .. } => "stop",
             Command::Ping { .. } => "ping",
             Command::Run { .. } => "run",
+            Command::Validate { .. } => "validate",
+            Command::Plan { .. } => "plan",
+            Command::Cancel { .. } => "cancel",
+            Command::Workflows { .. } => "workflows",
+            Command::Receipt { .. } => "receipt",
+            Command::Verify { .. } => "verify",
             Command::Node { .. } => "node",
             Command::Info => "info",
         }
@@ -283,6 +585,147 @@ impl Command {
                 response.echo_table()?;
                 Ok(())
             }
+            Command::Cancel { args, workflow } => {
+                let response = rt.block_on(async {
+                    let client = args.client().await?;
+                    let response = client.cancel(workflow.into()).await??;
+                    Ok::<response::AckCancel, Error>(response)
+                })?;
+
+                response.echo_table()?;
+                Ok(())
+            }
+            Command::Workflows {
+                command:
+                    WorkflowsCommand::List {
+                        args,
+                        status,
+                        name,
+                        since,
+                        until,
+                        limit,
+                        offset,
+                        json,
+                    },
+            } => {
+                let filter = WorkflowFilter {
+                    status,
+                    name,
+                    since,
+                    until,
+                    limit,
+                    offset,
+                };
+                let response = rt.block_on(async {
+                    let client = args.client().await?;
+                    let response = client.list_workflows(filter).await??;
+                    Ok::<response::AckWorkflowList, Error>(response)
+                })?;
+
+                if json {
+                    show::echo_json(&response)?;
+                } else {
+                    response.echo_table()?;
+                }
+                Ok(())
+            }
+            Command::Workflows {
+                command:
+                    WorkflowsCommand::Show {
+                        args,
+                        workflow,
+                        json,
+                    },
+            } => {
+                let response = rt.block_on(async {
+                    let client = args.client().await?;
+                    let response = client.show_workflow(workflow.into()).await??;
+                    Ok::<Box<response::AckWorkflowDetail>, Error>(response)
+                })?;
+
+                if json {
+                    show::echo_json(&response)?;
+                } else {
+                    response.echo_table()?;
+                }
+                Ok(())
+            }
+            Command::Workflows {
+                command:
+                    WorkflowsCommand::Get {
+                        args,
+                        workflow,
+                        format,
+                    },
+            } => {
+                let response = rt.block_on(async {
+                    let client = args.client().await?;
+                    let response = client.get_workflow(workflow.into(), format.cbor).await??;
+                    Ok::<Box<response::AckWorkflowDocument>, Error>(response)
+                })?;
+
+                if format.cbor {
+                    let bytes = response
+                        .dag_cbor_bytes()?
+                        .ok_or_else(|| anyhow!("workflow DAG-CBOR was not returned"))?;
+                    show::echo_bytes(&bytes)?;
+                } else if format.json {
+                    show::echo_json(&response.workflow)?;
+                } else {
+                    response.echo_table()?;
+                }
+                Ok(())
+            }
+            Command::Receipt { command } => {
+                let (args, cid, lookup, format) = match command {
+                    ReceiptCommand::Get { args, cid, format } => {
+                        (args, cid, ReceiptLookup::Receipt, format)
+                    }
+                    ReceiptCommand::ForInstruction { args, cid, format } => {
+                        (args, cid, ReceiptLookup::Instruction, format)
+                    }
+                };
+
+                let response = rt.block_on(async {
+                    let client = args.client().await?;
+                    let response = client
+                        .get_receipt(cid.into(), lookup, format.cbor)
+                        .await??;
+                    Ok::<Box<response::AckReceipt>, Error>(response)
+                })?;
+
+                if format.cbor {
+                    let bytes = response
+                        .dag_cbor_bytes()?
+                        .ok_or_else(|| anyhow!("receipt DAG-CBOR was not returned"))?;
+                    show::echo_bytes(&bytes)?;
+                } else if format.json {
+                    show::echo_json(&response.receipt)?;
+                } else {
+                    response.echo_table()?;
+                }
+                Ok(())
+            }
+            Command::Verify { args, cid, json } => {
+                let response = rt.block_on(async {
+                    let client = args.client().await?;
+                    let response = client.verify_receipt(cid.clone().into()).await??;
+                    Ok::<Box<response::AckVerify>, Error>(response)
+                })?;
+
+                if json {
+                    show::echo_json(&response)?;
+                } else {
+                    response.echo_table()?;
+                }
+
+                if response.is_mismatch() {
+                    return Err(
+                        anyhow!("receipt output does not match re-executed output: {cid}").into(),
+                    );
+                }
+                Ok(())
+            }
             Command::Node { args } => {
                 let response = rt.block_on(async {
                     let client = args.client().await?;
@@ -307,3 +750,36 @@ impl RpcArgs {
         Ok(client)
     }
 }
+
+/// Parse a date or date/time, in UTC, for filtering workflows.
+///
+/// A bare date means the start of that day.
+fn parse_datetime(s: &str) -> Result<NaiveDateTime, String> {
+    parse_datetime_or_date(s, |date| date.and_hms_opt(0, 0, 0))
+}
+
+/// Parse a date or date/time, in UTC, as an inclusive upper bound for
+/// filtering workflows.
+///
+/// A bare date means the end of that day, so that workflows created on
+/// that day are included.
+fn parse_until(s: &str) -> Result<NaiveDateTime, String> {
+    parse_datetime_or_date(s, |date| date.and_hms_nano_opt(23, 59, 59, 999_999_999))
+}
+
+fn parse_datetime_or_date(
+    s: &str,
+    on_date: impl FnOnce(NaiveDate) -> Option<NaiveDateTime>,
+) -> Result<NaiveDateTime, String> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|datetime| datetime.naive_utc())
+        .or_else(|_| NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S"))
+        .or_else(|_| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S"))
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .ok()
+                .and_then(on_date)
+        })
+        .ok_or_else(|| format!("invalid date/time: {s}"))
+}
@@ -0,0 +1,197 @@
+//! Execution plans for [Workflow]s, reviewed ahead of running them.
+//!
+//! [Workflow]: homestar_workflow::Workflow
+
+use super::{show, ConsoleTable, PlanArgs};
+use crate::{
+    db::{Database, Db},
+    runner::response::{AckPlan, PlannedTask},
+    workflow::{self, AOTContext},
+    Settings,
+};
+use homestar_invocation::{ipld::DagCbor, Pointer};
+use homestar_wasm::io::Arg;
+use homestar_workflow::Workflow;
+use miette::{miette, IntoDiagnostic, Result};
+use std::collections::HashMap;
+
+/// Handle the `plan` command, printing the batches a workflow would run in,
+/// the resources each instruction needs, and the receipts stored locally
+/// that would be replayed.
+///
+/// The workflow's DAG can also be written out as a Graphviz DOT file or a
+/// Mermaid flowchart.
+///
+/// The database is only ever read: if there's none yet, no receipts are
+/// replayed.
+pub fn handle_plan_command(args: PlanArgs) -> Result<()> {
+    let settings = if let Some(file) = args.runtime_config {
+        Settings::load_from_file(file)
+    } else {
+        Settings::load()
+    }
+    .into_diagnostic()?;
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .into_diagnostic()?;
+
+    let (workflow, _settings, _bundled) = rt
+        .block_on(args.workflow.validate_and_parse())
+        .map_err(|e| miette!("failed to parse workflow {}: {e}", args.workflow))?;
+
+    let db = Db::open_read_only(settings.node(), args.database_url)
+        .map_err(|e| miette!("failed to open database: {e}"))?;
+
+    let (response, aot) = plan(workflow, db.as_ref()).map_err(|e| miette!(e.to_string()))?;
+
+    if let Some(path) = args.dot {
+        aot.dot(&response.cid, &path)
+            .map_err(|e| miette!("failed to write DOT file: {e}"))?;
+    }
+
+    if let Some(path) = args.mermaid {
+        std::fs::write(path, aot.mermaid()).into_diagnostic()?;
+    }
+
+    if args.json {
+        show::echo_json(&response).into_diagnostic()
+    } else {
+        response.echo_table().into_diagnostic()
+    }
+}
+
+/// Plan a [Workflow] without running it, returning its tasks batch by batch,
+/// along with the [AOTContext] its execution graph is built from.
+///
+/// Instructions with receipts already stored in the database, if given, are
+/// marked with their receipt, as running the workflow would replay them.
+pub(crate) fn plan<DB: Database>(
+    workflow: Workflow<'static, Arg>,
+    db: Option<&DB>,
+) -> anyhow::Result<(AckPlan, AOTContext<'static>)> {
+    let cid = workflow.clone().to_cid()?;
+    let builder = workflow::Builder::new(workflow);
+    let aot = builder.clone().aot()?;
+    let graph = builder.graph()?;
+
+    let pointers = graph
+        .indexed_resources
+        .inner()
+        .keys()
+        .map(|cid| Pointer::new(*cid))
+        .collect::<Vec<_>>();
+    let receipts = match db {
+        Some(db) => DB::find_instruction_pointers(&pointers, &mut db.conn()?)?,
+        None => vec![],
+    }
+    .into_iter()
+    .map(|receipt| (receipt.instruction().cid(), receipt.cid()))
+    .collect::<HashMap<_, _>>();
+
+    let tasks = graph
+        .schedule
+        .iter()
+        .enumerate()
+        .flat_map(|(batch, nodes)| nodes.iter().map(move |node| (batch, node)))
+        .map(|(batch, node)| {
+            let vertex = node.inner();
+            let instruction = vertex.instruction.clone().to_cid()?;
+
+            Ok(PlannedTask {
+                batch,
+                instruction: instruction.to_string(),
+                function: vertex.parsed.fun().unwrap_or_default(),
+                resources: graph
+                    .indexed_resources
+                    .get(&instruction)
+                    .map(|rscs| {
+                        rscs.iter()
+                            .map(|rsc| rsc.to_string())
+                            .collect::<Vec<String>>()
+                            .join(", ")
+                    })
+                    .unwrap_or_default(),
+                receipt: receipts.get(&instruction).map(|cid| cid.to_string()),
+            })
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    Ok((AckPlan::new(cid, tasks), aot))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{test_utils::db::MemoryDb, Receipt};
+    use homestar_invocation::{
+        authority::UcanPrf,
+        receipt::Receipt as InvocationReceipt,
+        task::{self, instruction::RunInstruction, Resources},
+        test_utils, Task,
+    };
+    use libipld::Ipld;
+
+    #[homestar_runtime_proc_macro::db_async_test]
+    fn plans_batches_and_replayed_receipts() {
+        let settings = TestSettings::load();
+        let db = MemoryDb::setup_connection_pool(settings.node(), None).unwrap();
+
+        let (instruction1, instruction2, _) = test_utils::related_wasm_instructions::<Arg>();
+        let task1 = Task::new(
+            RunInstruction::Expanded(instruction1.clone()),
+            Resources::default().into(),
+            UcanPrf::default(),
+        );
+        let task2 = Task::new(
+            RunInstruction::Expanded(instruction2.clone()),
+            Resources::default().into(),
+            UcanPrf::default(),
+        );
+        let workflow = Workflow::new(vec![task1, task2]);
+
+        let invocation_receipt = InvocationReceipt::new(
+            Pointer::new(instruction1.clone().to_cid().unwrap()),
+            task::Result::Ok(Ipld::Integer(2)),
+            Ipld::Null,
+            None,
+            UcanPrf::default(),
+        );
+        let receipt = Receipt::try_with(
+            Pointer::new(instruction1.clone().to_cid().unwrap()),
+            &invocation_receipt,
+        )
+        .unwrap();
+        MemoryDb::store_receipt(receipt.clone(), &mut db.conn().unwrap()).unwrap();
+
+        let (response, aot) = plan(workflow.clone(), Some(&db)).unwrap();
+        assert_eq!(response.cid, workflow.clone().to_cid().unwrap().to_string());
+        assert_eq!(response.num_batches, 2);
+        assert_eq!(response.num_replayed(), 1);
+
+        let first = &response.tasks[0];
+        assert_eq!(first.batch, 0);
+        assert_eq!(
+            first.instruction,
+            instruction1.to_cid().unwrap().to_string()
+        );
+        assert_eq!(first.function, "add_one");
+        assert_eq!(first.receipt, Some(receipt.cid().to_string()));
+        assert!(first.resources.starts_with("ipfs://"));
+
+        let second = &response.tasks[1];
+        assert_eq!(second.batch, 1);
+        assert_eq!(
+            second.instruction,
+            instruction2.to_cid().unwrap().to_string()
+        );
+        assert_eq!(second.receipt, None);
+
+        assert_eq!(aot.mermaid().matches("-->").count(), 1);
+
+        // Without a database, nothing is replayed.
+        let (response, _aot) = plan::<MemoryDb>(workflow, None).unwrap();
+        assert_eq!(response.num_replayed(), 0);
+    }
+}
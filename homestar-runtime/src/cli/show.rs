@@ -1,5 +1,6 @@
 //! Styled, output response for console table.
 
+use serde::Serialize;
 use std::{
     fmt,
     io::{self, Write},
@@ -54,6 +55,23 @@ pub trait ConsoleTable {
     fn echo_table(&self) -> Result<(), io::Error>;
 }
 
+/// Print a response to console via [io::stdout] as pretty-printed JSON,
+/// in place of its [ConsoleTable] output.
+pub(crate) fn echo_json<T: Serialize>(response: &T) -> Result<(), io::Error> {
+    let json = serde_json::to_string_pretty(response)?;
+    let stdout = io::stdout();
+    let mut handle = io::BufWriter::new(stdout);
+    writeln!(handle, "{json}")
+}
+
+/// Write raw bytes, e.g. DAG-CBOR, to console via [io::stdout].
+pub(crate) fn echo_bytes(bytes: &[u8]) -> Result<(), io::Error> {
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    handle.write_all(bytes)?;
+    handle.flush()
+}
+
 /// Style trait for console table output responses.
 #[allow(dead_code)]
 pub(crate) trait ApplyStyle {
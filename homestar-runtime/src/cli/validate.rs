@@ -0,0 +1,498 @@
+//! Offline validation of [Workflow]s, ahead of running them.
+
+use super::ValidateArgs;
+use crate::{
+    runner::file::Bundled,
+    tasks::{FileLoad, Registry, WasmContext, WASM_OP},
+    workflow,
+};
+use fnv::FnvHashSet;
+use homestar_invocation::task::{
+    instruction::{Args, Parse, RunInstruction},
+    Condition, Map, Resources, RetryPolicy,
+};
+use homestar_wasm::{io::Arg, wasmtime::Error as WasmRuntimeError};
+use homestar_workflow::Workflow;
+use indexmap::IndexMap;
+use itertools::Itertools;
+use libipld::Cid;
+use miette::{miette, Diagnostic, IntoDiagnostic, Report, Result, Severity};
+use std::path::PathBuf;
+use url::Url;
+
+/// A problem found while validating a [Workflow], reported by task index.
+#[derive(thiserror::Error, Debug, Diagnostic)]
+pub(crate) enum Problem {
+    /// Task given by pointer, rather than as an expanded instruction.
+    #[error("task {index}: instruction is not expanded")]
+    #[diagnostic(
+        code(homestar::validate::unexpanded),
+        help("workflow tasks/instructions must be expanded / inlined")
+    )]
+    Unexpanded {
+        /// Index of the task in the workflow.
+        index: usize,
+    },
+    /// Task awaiting an instruction outside of the workflow.
+    #[error("task {index}: awaited instruction {cid} is not part of the workflow")]
+    #[diagnostic(code(homestar::validate::missing_await))]
+    MissingAwait {
+        /// Index of the task in the workflow.
+        index: usize,
+        /// Cid of the awaited instruction.
+        cid: Cid,
+    },
+    /// Task that can't be parsed, e.g. malformed input or metadata.
+    #[error("task {index}: {reason}")]
+    #[diagnostic(code(homestar::validate::malformed))]
+    Malformed {
+        /// Index of the task in the workflow.
+        index: usize,
+        /// Reason the task can't be parsed.
+        reason: String,
+    },
+    /// Tasks that can't be scheduled, e.g. duplicate or cyclic tasks.
+    #[error("{0}")]
+    #[diagnostic(code(homestar::validate::schedule))]
+    Schedule(String),
+    /// Module not found locally, so calls to it were not type-checked.
+    #[error("task {index}: module {resource} not found locally, so {fun} was not type-checked")]
+    #[diagnostic(
+        code(homestar::validate::module_not_found),
+        severity(Warning),
+        help("pass a directory of cached modules, named by Cid, with --modules")
+    )]
+    ModuleNotFound {
+        /// Index of the task in the workflow.
+        index: usize,
+        /// Module resource.
+        resource: Url,
+        /// Function called.
+        fun: String,
+    },
+    /// Module that can't be instantiated as a Wasm component.
+    #[error("task {index}: module {resource} is invalid: {reason}")]
+    #[diagnostic(code(homestar::validate::invalid_module))]
+    InvalidModule {
+        /// Index of the task in the workflow.
+        index: usize,
+        /// Module resource.
+        resource: Url,
+        /// Reason the module can't be instantiated.
+        reason: String,
+    },
+    /// Function called that the module doesn't export.
+    #[error("task {index}: function {fun} is not exported by module {resource}")]
+    #[diagnostic(code(homestar::validate::function_not_found))]
+    FunctionNotFound {
+        /// Index of the task in the workflow.
+        index: usize,
+        /// Module resource.
+        resource: Url,
+        /// Function called.
+        fun: String,
+    },
+    /// Arguments not matching the exported function's signature.
+    #[error("task {index}: arguments do not match the signature of {fun}: {reason}")]
+    #[diagnostic(code(homestar::validate::type_mismatch))]
+    TypeMismatch {
+        /// Index of the task in the workflow.
+        index: usize,
+        /// Function called.
+        fun: String,
+        /// Reason the arguments don't match.
+        reason: String,
+    },
+}
+
+/// Every error found while validating a [Workflow].
+#[derive(thiserror::Error, Debug, Diagnostic)]
+#[error("workflow {workflow} is invalid, with {} error(s)", .problems.len())]
+#[diagnostic(code(homestar::validate))]
+struct Invalid {
+    workflow: String,
+    #[related]
+    problems: Vec<Problem>,
+}
+
+/// A call to a Wasm function, to type-check once its module is loaded.
+#[derive(Debug)]
+struct Call {
+    index: usize,
+    resource: Url,
+    fun: String,
+    args: Args<Arg>,
+    resources: Resources,
+}
+
+/// Handle the `validate` command, validating a workflow file offline and
+/// printing every problem found.
+///
+/// Warnings, e.g. modules that couldn't be loaded locally, are printed
+/// without failing validation.
+///
+/// Only the built-in task-types of the default [Registry] are known to the
+/// command, so tasks of abilities that an embedder registers executors for
+/// are reported as unsupported operations.
+pub fn handle_validate_command(args: ValidateArgs) -> Result<()> {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .into_diagnostic()?;
+
+    let problems = rt.block_on(async {
+        let (workflow, _settings, bundled) = args
+            .workflow
+            .validate_and_parse()
+            .await
+            .map_err(|e| miette!("failed to parse workflow {}: {e}", args.workflow))?;
+
+        Ok::<_, Report>(validate(workflow, &bundled, &args.modules, &Registry::default()).await)
+    })?;
+
+    let (warnings, errors): (Vec<_>, Vec<_>) = problems
+        .into_iter()
+        .partition(|problem| problem.severity() == Some(Severity::Warning));
+
+    for warning in warnings {
+        eprintln!("{:?}", Report::new(warning));
+    }
+
+    if !errors.is_empty() {
+        return Err(Invalid {
+            workflow: args.workflow.to_string(),
+            problems: errors,
+        }
+        .into());
+    }
+
+    println!("workflow {} is valid", args.workflow);
+    Ok(())
+}
+
+/// Validate a [Workflow] without running it, returning every [Problem]
+/// found.
+///
+/// The [ExecutionGraph] is built to catch tasks that can't be scheduled,
+/// and each Wasm call is type-checked against its module's exports, loading
+/// modules from local `file://` paths, blocks bundled with the workflow, or
+/// directories of cached modules named by Cid.
+///
+/// Tasks of abilities without an [Executor] in the given [Registry] are
+/// reported as unsupported operations.
+///
+/// [ExecutionGraph]: crate::scheduler::ExecutionGraph
+/// [Executor]: crate::Executor
+pub(crate) async fn validate(
+    workflow: Workflow<'_, Arg>,
+    bundled: &Bundled,
+    module_dirs: &[PathBuf],
+    executors: &Registry,
+) -> Vec<Problem> {
+    let mut problems = vec![];
+    let mut schedulable = true;
+    let mut calls = vec![];
+
+    // Unexpanded tasks only point at instructions, which aren't run.
+    let instruction_cids = workflow
+        .tasks_ref()
+        .iter()
+        .filter(|task| matches!(task.run(), RunInstruction::Expanded(_)))
+        .filter_map(|task| task.instruction_cid().ok())
+        .collect::<FnvHashSet<_>>();
+
+    for (index, task) in workflow.tasks_ref().iter().enumerate() {
+        let RunInstruction::Expanded(instruction) = task.run() else {
+            problems.push(Problem::Unexpanded { index });
+            schedulable = false;
+            continue;
+        };
+
+        // Malformed resource limits or retry policies would otherwise leave
+        // the task unbounded, or fail the workflow once run.
+        let parsed = Condition::from_meta(task.meta())
+            .and_then(|condition| Ok((condition, Map::from_meta(task.meta())?)))
+            .and_then(|(condition, map)| {
+                let resources = Resources::try_from(task.meta())?;
+                RetryPolicy::try_from(task.meta())?;
+                Ok((condition, map, resources))
+            })
+            .map_err(|e| e.to_string())
+            .and_then(|(condition, map, resources)| {
+                let parsed = instruction.input().parse().map_err(|e| e.to_string())?;
+                Ok((condition, map, resources, parsed))
+            });
+
+        let (condition, map, resources, parsed) = match parsed {
+            Ok(parsed) => parsed,
+            Err(reason) => {
+                problems.push(Problem::Malformed { index, reason });
+                schedulable = false;
+                continue;
+            }
+        };
+
+        problems.extend(
+            workflow::awaits(&parsed, condition.as_ref(), map.as_ref())
+                .filter(|cid| !instruction_cids.contains(cid))
+                .unique()
+                .map(|cid| Problem::MissingAwait { index, cid }),
+        );
+
+        let op = instruction.op().to_string();
+        let Some(executor) = executors.get(instruction.op()) else {
+            problems.push(Problem::Malformed {
+                index,
+                reason: format!("unsupported operation {op}"),
+            });
+            continue;
+        };
+
+        // Nested workflows are validated when they're run.
+        if executor.runs_workflow() {
+            continue;
+        }
+
+        let fun = parsed.fun();
+        if executor.requires_fun() && fun.is_none() {
+            problems.push(Problem::Malformed {
+                index,
+                reason: "no function defined".to_string(),
+            });
+            continue;
+        }
+
+        // Other task-types don't run a module to type-check.
+        let Some(fun) = fun.filter(|_| op == WASM_OP) else {
+            continue;
+        };
+
+        calls.push(Call {
+            index,
+            resource: instruction.resource().to_owned(),
+            fun,
+            args: parsed.into_args(),
+            resources,
+        });
+    }
+
+    if schedulable {
+        if let Err(err) = workflow::Builder::new(workflow).graph() {
+            problems.push(Problem::Schedule(err.to_string()));
+        }
+    }
+
+    let mut modules: IndexMap<Url, Option<Vec<u8>>> = IndexMap::new();
+    for call in calls {
+        if !modules.contains_key(&call.resource) {
+            let module = load_module(&call.resource, bundled, module_dirs).await;
+            modules.insert(call.resource.clone(), module);
+        }
+
+        let Some(Some(bytes)) = modules.get(&call.resource).cloned() else {
+            problems.push(Problem::ModuleNotFound {
+                index: call.index,
+                resource: call.resource,
+                fun: call.fun,
+            });
+            continue;
+        };
+
+        let checked = match WasmContext::new(call.resources) {
+            Ok(mut ctx) => ctx.typecheck(bytes, &call.fun, &call.args).await,
+            Err(err) => Err(err),
+        };
+
+        match checked {
+            Ok(()) => {}
+            Err(WasmRuntimeError::WasmFunctionNotFound(_)) => {
+                problems.push(Problem::FunctionNotFound {
+                    index: call.index,
+                    resource: call.resource,
+                    fun: call.fun,
+                })
+            }
+            Err(
+                err @ (WasmRuntimeError::WasmArgumentCount { .. }
+                | WasmRuntimeError::InterpreterError(_)),
+            ) => problems.push(Problem::TypeMismatch {
+                index: call.index,
+                fun: call.fun,
+                reason: err.to_string(),
+            }),
+            Err(err) => problems.push(Problem::InvalidModule {
+                index: call.index,
+                resource: call.resource,
+                reason: err.to_string(),
+            }),
+        }
+    }
+
+    problems
+}
+
+/// Load a Wasm module from a local `file://` path, from blocks bundled with
+/// the workflow, or from directories of cached modules named by Cid, e.g.
+/// `<cid>` or `<cid>.wasm`.
+async fn load_module(
+    resource: &Url,
+    bundled: &Bundled,
+    module_dirs: &[PathBuf],
+) -> Option<Vec<u8>> {
+    if resource.scheme() == "file" {
+        return WasmContext::load(resource.to_file_path().ok()?).await.ok();
+    }
+
+    let cid = match resource.scheme() {
+        "ipfs" => resource.domain().and_then(|cid| Cid::try_from(cid).ok()),
+        _ => None,
+    }?;
+
+    if let Some(bytes) = bundled.get(&cid) {
+        return Some(bytes.to_owned());
+    }
+
+    for dir in module_dirs {
+        for file in [cid.to_string(), format!("{cid}.wasm")] {
+            if let Ok(bytes) = WasmContext::load(dir.join(file)).await {
+                return Some(bytes);
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use homestar_invocation::{
+        authority::UcanPrf,
+        pointer::{Await, AwaitResult},
+        task::{
+            instruction::{Ability, Input},
+            Instruction,
+        },
+        Pointer, Task,
+    };
+    use libipld::Ipld;
+    use std::collections::BTreeMap;
+
+    fn wasm_task<'a>(rsc: Url, fun: &str, args: Vec<Ipld>) -> Task<'a, Arg> {
+        Task::new(
+            RunInstruction::Expanded(Instruction::new(
+                rsc,
+                Ability::from("wasm/run"),
+                Input::Ipld(Ipld::Map(BTreeMap::from([
+                    ("func".into(), Ipld::String(fun.to_string())),
+                    ("args".into(), Ipld::List(args)),
+                ]))),
+            )),
+            Resources::default().into(),
+            UcanPrf::default(),
+        )
+    }
+
+    fn local_module() -> Url {
+        Url::from_file_path(format!(
+            "{}/../homestar-wasm/fixtures/example_test.wasm",
+            env!("CARGO_MANIFEST_DIR")
+        ))
+        .unwrap()
+    }
+
+    fn awaiting(task: &Task<'_, Arg>) -> Ipld {
+        Await::new(
+            Pointer::new(task.instruction_cid().unwrap()),
+            AwaitResult::Ok,
+        )
+        .into()
+    }
+
+    #[tokio::test]
+    async fn validates_workflow_with_local_module() {
+        let task1 = wasm_task(local_module(), "add_one", vec![Ipld::Integer(1)]);
+        let task2 = wasm_task(local_module(), "add_one", vec![awaiting(&task1)]);
+        let workflow = Workflow::new(vec![task1, task2]);
+
+        let problems = validate(workflow, &Bundled::default(), &[], &Registry::default()).await;
+        assert!(problems.is_empty(), "unexpected problems: {problems:?}");
+    }
+
+    #[tokio::test]
+    async fn reports_every_problem() {
+        let outside = wasm_task(local_module(), "add_one", vec![Ipld::Integer(2)]);
+        let pointed = Task::new(
+            RunInstruction::Ptr(Pointer::new(outside.instruction_cid().unwrap())),
+            Resources::default().into(),
+            UcanPrf::default(),
+        );
+        let workflow = Workflow::new(vec![
+            wasm_task(local_module(), "add_onez", vec![Ipld::Integer(1)]),
+            wasm_task(local_module(), "add_one", vec![Ipld::String("one".into())]),
+            wasm_task(local_module(), "add_one", vec![]),
+            wasm_task(local_module(), "add_one", vec![awaiting(&outside)]),
+            wasm_task(
+                Url::parse("ipfs://bafybeibk42jwhq7w2zcpe6q3wgtleugp3ymfs3pa5gerjmnakqihhqx4zq")
+                    .unwrap(),
+                "add_one",
+                vec![Ipld::Integer(1)],
+            ),
+            pointed,
+        ]);
+
+        let problems = validate(workflow, &Bundled::default(), &[], &Registry::default()).await;
+        assert_eq!(problems.len(), 6, "unexpected problems: {problems:?}");
+        assert!(problems.iter().any(|p| matches!(
+            p,
+            Problem::FunctionNotFound { index: 0, fun, .. } if fun == "add_onez"
+        )));
+        assert!(problems
+            .iter()
+            .any(|p| matches!(p, Problem::TypeMismatch { index: 1, .. })));
+        assert!(problems
+            .iter()
+            .any(|p| matches!(p, Problem::TypeMismatch { index: 2, .. })));
+        assert!(problems.iter().any(|p| matches!(
+            p,
+            Problem::MissingAwait { index: 3, cid } if *cid == outside.instruction_cid().unwrap()
+        )));
+        assert!(problems
+            .iter()
+            .any(|p| matches!(p, Problem::ModuleNotFound { index: 4, .. })
+                && p.severity() == Some(Severity::Warning)));
+        assert!(problems
+            .iter()
+            .any(|p| matches!(p, Problem::Unexpanded { index: 5 })));
+    }
+
+    #[tokio::test]
+    async fn reports_malformed_task_meta() {
+        let with_meta = |meta: Ipld| {
+            let task = wasm_task(local_module(), "add_one", vec![Ipld::Integer(1)]);
+            Task::new(task.into_instruction(), meta, UcanPrf::default())
+        };
+        let workflow = Workflow::new(vec![
+            with_meta(Ipld::Map(BTreeMap::from([(
+                "fuel".into(),
+                Ipld::String("lots".into()),
+            )]))),
+            with_meta(Ipld::Map(BTreeMap::from([(
+                "retry".into(),
+                Ipld::Map(BTreeMap::from([(
+                    "retries".into(),
+                    Ipld::String("twice".into()),
+                )])),
+            )]))),
+        ]);
+
+        let problems = validate(workflow, &Bundled::default(), &[], &Registry::default()).await;
+        assert_eq!(problems.len(), 2, "unexpected problems: {problems:?}");
+        assert!(problems
+            .iter()
+            .any(|p| matches!(p, Problem::Malformed { index: 0, .. })));
+        assert!(problems
+            .iter()
+            .any(|p| matches!(p, Problem::Malformed { index: 1, .. })));
+    }
+}
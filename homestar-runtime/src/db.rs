@@ -1,18 +1,22 @@
 //! (Default) sqlite database integration and setup.
 
 use crate::{
-    db::utils::Health,
+    db::utils::{Health, WorkflowFilter},
+    runner::file::Bundled,
     settings,
-    workflow::{self, StoredReceipt},
+    workflow::{self, InstructionCids, StoredReceipt},
     Receipt,
 };
 use anyhow::Result;
 use byte_unit::{AdjustedByte, Byte, ByteUnit};
+use chrono::Utc;
 use diesel::{
     dsl::now,
+    expression_methods::EscapeExpressionMethods,
     r2d2::{self, CustomizeConnection, ManageConnection},
-    BelongingToDsl, Connection as SingleConnection, ExpressionMethods, OptionalExtension, QueryDsl,
-    RunQueryDsl, SelectableHelper, SqliteConnection,
+    BelongingToDsl, Connection as SingleConnection, ExpressionMethods, NullableExpressionMethods,
+    OptionalExtension, QueryDsl, RunQueryDsl, SelectableHelper, SqliteConnection,
+    TextExpressionMethods,
 };
 use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
 use dotenvy::dotenv;
@@ -77,6 +81,46 @@ impl Db {
         let byte_unit = byte.get_adjusted_unit(ByteUnit::MB);
         Ok(byte_unit)
     }
+
+    /// Open an existing Sqlite database read-only, e.g. for commands that
+    /// only inspect it, returning `None` if there's no database file.
+    ///
+    /// Unlike [Database::setup_connection_pool], neither is the database
+    /// file created nor are migrations run.
+    pub(crate) fn open_read_only(
+        settings: &settings::Node,
+        database_url: Option<String>,
+    ) -> Result<Option<Self>> {
+        let database_url = Self::resolve_url(settings, database_url);
+        if !std::path::Path::new(&database_url).exists() {
+            return Ok(None);
+        }
+
+        let manager = r2d2::ConnectionManager::<SqliteConnection>::new(format!(
+            "file:{database_url}?mode=ro"
+        ));
+        let pool = r2d2::Pool::builder()
+            .max_size(1)
+            .min_idle(Some(0))
+            .build(manager)?;
+
+        Ok(Some(Db {
+            pool: Arc::new(pool),
+            url: database_url,
+        }))
+    }
+
+    /// Resolve the database url from the given url, the environment or
+    /// settings, in that order, defaulting to `homestar.db`.
+    fn resolve_url(settings: &settings::Node, database_url: Option<String>) -> String {
+        Self::set_url(database_url).unwrap_or_else(|| {
+            settings
+                .db
+                .url
+                .as_ref()
+                .map_or_else(|| "homestar.db".to_string(), |url| url.to_string())
+        })
+    }
 }
 
 /// Database trait for working with different Sqlite connection pool and
@@ -185,26 +229,54 @@ pub trait Database: Send + Sync + Clone {
 
     /// Find receipts given a set of [Instruction] [Pointer]s, which is indexed.
     ///
+    /// Receipts of skipped tasks, which are local to the workflow that skipped
+    /// them, are excluded, as are [flagged] receipts. Receipts of failed
+    /// tasks are excluded too, so that their tasks are run again rather
+    /// than their errors reused.
+    ///
+    /// [flagged]: Self::store_flagged_receipt
     /// [Instruction]: homestar_invocation::task::Instruction
     fn find_instruction_pointers(
         pointers: &Vec<Pointer>,
         conn: &mut Connection,
     ) -> Result<Vec<Receipt>, diesel::result::Error> {
-        schema::receipts::dsl::receipts
+        let receipts: Vec<Receipt> = schema::receipts::dsl::receipts
             .filter(schema::receipts::instruction.eq_any(pointers))
-            .load(conn)
+            .filter(schema::receipts::cid.ne_all(
+                schema::flagged_receipts::table.select(schema::flagged_receipts::receipt_cid),
+            ))
+            .load(conn)?;
+
+        Ok(receipts
+            .into_iter()
+            .filter(|receipt| !receipt.skipped() && !receipt.failed())
+            .collect())
     }
 
     /// Find receipt for a given [Instruction] Cid, which is indexed.
     ///
+    /// Receipts of skipped tasks, which are local to the workflow that skipped
+    /// them, are excluded, as are [flagged] receipts. Receipts of failed
+    /// tasks are excluded too, so that their tasks are run again rather
+    /// than their errors reused.
+    ///
+    /// [flagged]: Self::store_flagged_receipt
     /// [Instruction]: homestar_invocation::task::Instruction
     fn find_instruction_by_cid(
         cid: Cid,
         conn: &mut Connection,
     ) -> Result<Receipt, diesel::result::Error> {
-        schema::receipts::dsl::receipts
+        let receipts: Vec<Receipt> = schema::receipts::dsl::receipts
             .filter(schema::receipts::instruction.eq(Pointer::new(cid)))
-            .first(conn)
+            .filter(schema::receipts::cid.ne_all(
+                schema::flagged_receipts::table.select(schema::flagged_receipts::receipt_cid),
+            ))
+            .load(conn)?;
+
+        receipts
+            .into_iter()
+            .find(|receipt| !receipt.skipped() && !receipt.failed())
+            .ok_or(diesel::result::Error::NotFound)
     }
 
     /// Find a receipt for a given Cid.
@@ -228,6 +300,89 @@ pub trait Database: Send + Sync + Clone {
             .load(conn)
     }
 
+    /// Record a peer that a [Receipt] was received from, or this node, as
+    /// [LOCAL_PEER], if it issued the receipt itself.
+    ///
+    /// [LOCAL_PEER]: crate::receipt::trust::LOCAL_PEER
+    ///
+    /// On conflicts, do nothing.
+    fn store_receipt_peer(
+        receipt_cid: Cid,
+        peer_id: &str,
+        conn: &mut Connection,
+    ) -> Result<usize, diesel::result::Error> {
+        diesel::insert_into(schema::receipt_peers::table)
+            .values((
+                schema::receipt_peers::receipt_cid.eq(Pointer::new(receipt_cid)),
+                schema::receipt_peers::peer_id.eq(peer_id),
+            ))
+            .on_conflict((
+                schema::receipt_peers::receipt_cid,
+                schema::receipt_peers::peer_id,
+            ))
+            .do_nothing()
+            .execute(conn)
+    }
+
+    /// Find the peers a [Receipt] was received from, or [LOCAL_PEER] for
+    /// receipts issued by this node.
+    ///
+    /// [LOCAL_PEER]: crate::receipt::trust::LOCAL_PEER
+    fn find_receipt_peers(
+        receipt_cid: Cid,
+        conn: &mut Connection,
+    ) -> Result<Vec<String>, diesel::result::Error> {
+        schema::receipt_peers::dsl::receipt_peers
+            .filter(schema::receipt_peers::receipt_cid.eq(Pointer::new(receipt_cid)))
+            .select(schema::receipt_peers::peer_id)
+            .order(schema::receipt_peers::peer_id.asc())
+            .load(conn)
+    }
+
+    /// Forget the peers a [Receipt] was received from, e.g. once this node
+    /// has issued the same receipt itself.
+    fn delete_receipt_peers(
+        receipt_cid: Cid,
+        conn: &mut Connection,
+    ) -> Result<usize, diesel::result::Error> {
+        diesel::delete(
+            schema::receipt_peers::dsl::receipt_peers
+                .filter(schema::receipt_peers::receipt_cid.eq(Pointer::new(receipt_cid))),
+        )
+        .execute(conn)
+    }
+
+    /// Flag a [Receipt] whose re-executed output didn't match its own, so
+    /// that it's never reused in place of running its [Instruction].
+    ///
+    /// On conflicts, do nothing.
+    ///
+    /// [Instruction]: homestar_invocation::task::Instruction
+    fn store_flagged_receipt(
+        receipt_cid: Cid,
+        conn: &mut Connection,
+    ) -> Result<usize, diesel::result::Error> {
+        diesel::insert_into(schema::flagged_receipts::table)
+            .values(schema::flagged_receipts::receipt_cid.eq(Pointer::new(receipt_cid)))
+            .on_conflict(schema::flagged_receipts::receipt_cid)
+            .do_nothing()
+            .execute(conn)
+    }
+
+    /// Whether a [Receipt] was [flagged].
+    ///
+    /// [flagged]: Self::store_flagged_receipt
+    fn is_receipt_flagged(
+        receipt_cid: Cid,
+        conn: &mut Connection,
+    ) -> Result<bool, diesel::result::Error> {
+        diesel::select(diesel::dsl::exists(
+            schema::flagged_receipts::dsl::flagged_receipts
+                .filter(schema::flagged_receipts::receipt_cid.eq(Pointer::new(receipt_cid))),
+        ))
+        .get_result(conn)
+    }
+
     /// Store localized workflow cid and information, e.g. number of tasks.
     ///
     /// On conflicts, do nothing.
@@ -240,6 +395,7 @@ pub trait Database: Send + Sync + Clone {
             .values(&workflow)
             .on_conflict(schema::workflows::cid)
             .do_nothing()
+            .returning(workflow::Stored::as_returning())
             .get_result(conn)
             .optional()?
         {
@@ -250,19 +406,238 @@ pub trait Database: Send + Sync + Clone {
     }
 
     /// Update workflow status given a Cid to the workflow.
+    ///
+    /// Marking a workflow as completed also records its time of completion,
+    /// unless it was already completed.
     fn set_workflow_status(
         workflow_cid: Cid,
         status: workflow::Status,
         conn: &mut Connection,
     ) -> Result<(), diesel::result::Error> {
+        if status == workflow::Status::Completed {
+            diesel::update(schema::workflows::dsl::workflows)
+                .filter(schema::workflows::cid.eq(Pointer::new(workflow_cid)))
+                .filter(schema::workflows::status.ne(workflow::Status::Completed))
+                .set((
+                    schema::workflows::status.eq(status),
+                    schema::workflows::completed_at.eq(Utc::now().naive_utc()),
+                ))
+                .execute(conn)?;
+        } else {
+            diesel::update(schema::workflows::dsl::workflows)
+                .filter(schema::workflows::cid.eq(Pointer::new(workflow_cid)))
+                .set(schema::workflows::status.eq(status))
+                .execute(conn)?;
+        }
+
+        Ok(())
+    }
+
+    /// Mark a workflow as failed (or cancelled) given a Cid to the workflow,
+    /// recording the reason, the failing [Instruction] (if any), and the
+    /// time of failure.
+    ///
+    /// [Instruction]: homestar_invocation::task::Instruction
+    fn set_workflow_failure(
+        workflow_cid: Cid,
+        status: workflow::Status,
+        reason: &str,
+        failed_instruction: Option<Cid>,
+        conn: &mut Connection,
+    ) -> Result<(), diesel::result::Error> {
+        diesel::update(schema::workflows::dsl::workflows)
+            .filter(schema::workflows::cid.eq(Pointer::new(workflow_cid)))
+            .set((
+                schema::workflows::status.eq(status),
+                schema::workflows::failure_reason.eq(reason),
+                schema::workflows::failed_instruction.eq(failed_instruction.map(Pointer::new)),
+                schema::workflows::failed_at.eq(Utc::now().naive_utc()),
+            ))
+            .execute(conn)?;
+
+        Ok(())
+    }
+
+    /// Mark a workflow as timed out given a Cid to the workflow, recording
+    /// the reason, the [Instruction]s left unfinished, and the time it timed
+    /// out.
+    ///
+    /// [Instruction]: homestar_invocation::task::Instruction
+    fn set_workflow_timed_out(
+        workflow_cid: Cid,
+        reason: &str,
+        unfinished_instructions: &[Cid],
+        conn: &mut Connection,
+    ) -> Result<(), diesel::result::Error> {
+        let unfinished_instructions = InstructionCids::new(unfinished_instructions.to_vec());
+
         diesel::update(schema::workflows::dsl::workflows)
             .filter(schema::workflows::cid.eq(Pointer::new(workflow_cid)))
-            .set(schema::workflows::status.eq(status))
+            .set((
+                schema::workflows::status.eq(workflow::Status::TimedOut),
+                schema::workflows::failure_reason.eq(reason),
+                schema::workflows::unfinished_instructions.eq(unfinished_instructions),
+                schema::workflows::failed_at.eq(Utc::now().naive_utc()),
+            ))
             .execute(conn)?;
 
         Ok(())
     }
 
+    /// Store the DAG-CBOR encoded workflow document given a Cid to the
+    /// workflow, so that it can be resumed after a node restart.
+    fn store_workflow_document(
+        workflow_cid: Cid,
+        document: &[u8],
+        conn: &mut Connection,
+    ) -> Result<(), diesel::result::Error> {
+        diesel::update(schema::workflows::dsl::workflows)
+            .filter(schema::workflows::cid.eq(Pointer::new(workflow_cid)))
+            .set(schema::workflows::document.eq(document))
+            .execute(conn)?;
+
+        Ok(())
+    }
+
+    /// Select the DAG-CBOR encoded workflow document given a Cid to the
+    /// workflow, if one was stored.
+    fn select_workflow_document(
+        workflow_cid: Cid,
+        conn: &mut Connection,
+    ) -> Result<Option<Vec<u8>>, diesel::result::Error> {
+        schema::workflows::dsl::workflows
+            .filter(schema::workflows::cid.eq(Pointer::new(workflow_cid)))
+            .select(schema::workflows::document)
+            .get_result(conn)
+    }
+
+    /// Store the blocks bundled with a workflow, e.g. Wasm modules within a
+    /// CAR file, given a Cid to the workflow, so that they're used again
+    /// when the workflow is resumed.
+    ///
+    /// NOTE: We cannot do batch inserts with `on_conflict`, so we add
+    /// each one 1-by-1:
+    /// <https://github.com/diesel-rs/diesel/issues/3114>
+    fn store_workflow_blocks(
+        workflow_cid: Cid,
+        blocks: &Bundled,
+        conn: &mut Connection,
+    ) -> Result<usize, diesel::result::Error> {
+        blocks.iter().try_fold(0, |acc, (cid, data)| {
+            let res = diesel::insert_into(schema::workflows_blocks::table)
+                .values((
+                    schema::workflows_blocks::workflow_cid.eq(Pointer::new(workflow_cid)),
+                    schema::workflows_blocks::block_cid.eq(Pointer::new(*cid)),
+                    schema::workflows_blocks::data.eq(data),
+                ))
+                .on_conflict((
+                    schema::workflows_blocks::workflow_cid,
+                    schema::workflows_blocks::block_cid,
+                ))
+                .do_nothing()
+                .execute(conn)?;
+
+            Ok::<_, diesel::result::Error>(acc + res)
+        })
+    }
+
+    /// Select the blocks bundled with a workflow given a Cid to the
+    /// workflow.
+    fn select_workflow_blocks(
+        workflow_cid: Cid,
+        conn: &mut Connection,
+    ) -> Result<Bundled, diesel::result::Error> {
+        let blocks: Vec<(Pointer, Vec<u8>)> = schema::workflows_blocks::dsl::workflows_blocks
+            .filter(schema::workflows_blocks::workflow_cid.eq(Pointer::new(workflow_cid)))
+            .select((
+                schema::workflows_blocks::block_cid,
+                schema::workflows_blocks::data,
+            ))
+            .load(conn)?;
+
+        Ok(blocks
+            .into_iter()
+            .map(|(cid, data)| (cid.cid(), data))
+            .collect())
+    }
+
+    /// Index the [Instruction]s of a stored workflow document given a Cid to
+    /// the workflow.
+    ///
+    /// NOTE: We cannot do batch inserts with `on_conflict`, so we add
+    /// each one 1-by-1:
+    /// <https://github.com/diesel-rs/diesel/issues/3114>
+    ///
+    /// [Instruction]: homestar_invocation::task::Instruction
+    fn store_workflow_instructions(
+        workflow_cid: Cid,
+        instructions: &[Cid],
+        conn: &mut Connection,
+    ) -> Result<usize, diesel::result::Error> {
+        instructions.iter().try_fold(0, |acc, instruction| {
+            let res = diesel::insert_into(schema::workflows_instructions::table)
+                .values((
+                    schema::workflows_instructions::workflow_cid.eq(Pointer::new(workflow_cid)),
+                    schema::workflows_instructions::instruction_cid.eq(Pointer::new(*instruction)),
+                ))
+                .on_conflict((
+                    schema::workflows_instructions::workflow_cid,
+                    schema::workflows_instructions::instruction_cid,
+                ))
+                .do_nothing()
+                .execute(conn)?;
+
+            Ok::<_, diesel::result::Error>(acc + res)
+        })
+    }
+
+    /// Find the Cids of stored workflows including an [Instruction], given
+    /// its Cid, newest first.
+    ///
+    /// [Instruction]: homestar_invocation::task::Instruction
+    fn find_instruction_workflows(
+        instruction_cid: Cid,
+        conn: &mut Connection,
+    ) -> Result<Vec<Cid>, diesel::result::Error> {
+        let workflows: Vec<Pointer> = schema::workflows_instructions::dsl::workflows_instructions
+            .inner_join(schema::workflows::table)
+            .filter(
+                schema::workflows_instructions::instruction_cid.eq(Pointer::new(instruction_cid)),
+            )
+            .order(schema::workflows::created_at.desc())
+            .select(schema::workflows_instructions::workflow_cid)
+            .load(conn)?;
+
+        Ok(workflows.into_iter().map(|ptr| ptr.cid()).collect())
+    }
+
+    /// Select workflows left `running` or `pending`, e.g. by a node
+    /// restart, along with their stored DAG-CBOR encoded documents, oldest
+    /// first.
+    ///
+    /// Workflows without a stored document cannot be resumed and are
+    /// skipped, as are nested workflows, which are run again by their
+    /// parent workflows' `workflow/run` tasks.
+    fn find_resumable_workflows(
+        conn: &mut Connection,
+    ) -> Result<Vec<(workflow::Stored, Vec<u8>)>, diesel::result::Error> {
+        schema::workflows::dsl::workflows
+            .filter(
+                schema::workflows::status
+                    .eq_any([workflow::Status::Running, workflow::Status::Pending]),
+            )
+            .filter(schema::workflows::document.is_not_null())
+            .filter(schema::workflows::cid.ne_all(
+                schema::nested_workflows::table.select(schema::nested_workflows::workflow_cid),
+            ))
+            .order(schema::workflows::created_at.asc())
+            .select((
+                workflow::Stored::as_select(),
+                schema::workflows::document.assume_not_null(),
+            ))
+            .load(conn)
+    }
+
     /// Store workflow Cid and [Receipt] Cid in the database for inner join.
     fn store_workflow_receipt(
         workflow_cid: Cid,
@@ -301,6 +676,46 @@ pub trait Database: Send + Sync + Clone {
         })
     }
 
+    /// Link a workflow run by a `workflow/run` [Instruction] back to the
+    /// parent workflow the [Instruction] belongs to.
+    ///
+    /// On conflicts, do nothing.
+    ///
+    /// [Instruction]: homestar_invocation::task::Instruction
+    fn store_nested_workflow(
+        parent_cid: Cid,
+        workflow_cid: Cid,
+        instruction_cid: Cid,
+        conn: &mut Connection,
+    ) -> Result<usize, diesel::result::Error> {
+        diesel::insert_into(schema::nested_workflows::table)
+            .values((
+                schema::nested_workflows::parent_cid.eq(Pointer::new(parent_cid)),
+                schema::nested_workflows::workflow_cid.eq(Pointer::new(workflow_cid)),
+                schema::nested_workflows::instruction_cid.eq(Pointer::new(instruction_cid)),
+            ))
+            .on_conflict((
+                schema::nested_workflows::parent_cid,
+                schema::nested_workflows::workflow_cid,
+            ))
+            .do_nothing()
+            .execute(conn)
+    }
+
+    /// Find the Cids of workflows run as tasks of a parent workflow, given a
+    /// Cid to the parent workflow.
+    fn find_nested_workflows(
+        parent_cid: Cid,
+        conn: &mut Connection,
+    ) -> Result<Vec<Cid>, diesel::result::Error> {
+        let nested: Vec<Pointer> = schema::nested_workflows::dsl::nested_workflows
+            .filter(schema::nested_workflows::parent_cid.eq(Pointer::new(parent_cid)))
+            .select(schema::nested_workflows::workflow_cid)
+            .load(conn)?;
+
+        Ok(nested.into_iter().map(|ptr| ptr.cid()).collect())
+    }
+
     /// Select workflow given a Cid to the workflow.
     fn select_workflow(
         cid: Cid,
@@ -312,6 +727,78 @@ pub trait Database: Send + Sync + Clone {
             .get_result(conn)
     }
 
+    /// Select the most recently created workflow given its local name.
+    fn select_workflow_by_name(
+        name: &str,
+        conn: &mut Connection,
+    ) -> Result<workflow::Stored, diesel::result::Error> {
+        schema::workflows::dsl::workflows
+            .filter(schema::workflows::name.eq(name))
+            .order(schema::workflows::created_at.desc())
+            .select(workflow::Stored::as_select())
+            .first(conn)
+    }
+
+    /// Select workflows matching a [WorkflowFilter], most recently created
+    /// first.
+    fn list_workflows(
+        filter: &WorkflowFilter,
+        conn: &mut Connection,
+    ) -> Result<Vec<workflow::Stored>, diesel::result::Error> {
+        let mut query = schema::workflows::dsl::workflows.into_boxed();
+
+        if let Some(status) = &filter.status {
+            query = query.filter(schema::workflows::status.eq(status.to_owned()));
+        }
+        if let Some(name) = &filter.name {
+            let name = name
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            query = query.filter(
+                schema::workflows::name
+                    .like(format!("%{name}%"))
+                    .escape('\\'),
+            );
+        }
+        if let Some(since) = filter.since {
+            query = query.filter(schema::workflows::created_at.ge(since));
+        }
+        if let Some(until) = filter.until {
+            query = query.filter(schema::workflows::created_at.le(until));
+        }
+
+        query
+            .order(schema::workflows::created_at.desc())
+            .limit(filter.limit)
+            .offset(filter.offset)
+            .select(workflow::Stored::as_select())
+            .load(conn)
+    }
+
+    /// Find the receipts emitted for a workflow given a Cid to the workflow.
+    fn find_workflow_receipts(
+        workflow_cid: Cid,
+        conn: &mut Connection,
+    ) -> Result<Vec<Receipt>, diesel::result::Error> {
+        schema::workflows_receipts::dsl::workflows_receipts
+            .inner_join(schema::receipts::table)
+            .filter(schema::workflows_receipts::workflow_cid.eq(Pointer::new(workflow_cid)))
+            .select(Receipt::as_select())
+            .load(conn)
+    }
+
+    /// Count the receipts emitted for a workflow given a Cid to the workflow.
+    fn count_workflow_receipts(
+        workflow_cid: Cid,
+        conn: &mut Connection,
+    ) -> Result<i64, diesel::result::Error> {
+        schema::workflows_receipts::dsl::workflows_receipts
+            .filter(schema::workflows_receipts::workflow_cid.eq(Pointer::new(workflow_cid)))
+            .count()
+            .get_result(conn)
+    }
+
     /// Return workflow information with number of receipts emitted.
     fn get_workflow_info(
         workflow_cid: Cid,
@@ -349,13 +836,7 @@ impl Database for Db {
         settings: &settings::Node,
         database_url: Option<String>,
     ) -> Result<Self> {
-        let database_url = Self::set_url(database_url).unwrap_or_else(|| {
-            settings
-                .db
-                .url
-                .as_ref()
-                .map_or_else(|| "homestar.db".to_string(), |url| url.to_string())
-        });
+        let database_url = Self::resolve_url(settings, database_url);
 
         Self::setup(&database_url)?;
         let manager = r2d2::ConnectionManager::<SqliteConnection>::new(database_url.clone());
@@ -404,7 +885,10 @@ where
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::test_utils::db::MemoryDb;
+    use crate::{test_utils::db::MemoryDb, workflow::IndexedResources};
+    use chrono::NaiveDate;
+    use homestar_invocation::test_utils::cid::generate_cid;
+    use rand::thread_rng;
 
     #[homestar_runtime_proc_macro::db_async_test]
     fn check_pragmas_memory_db() {
@@ -431,4 +915,206 @@ mod test {
 
         assert_eq!(busy_timeout, vec!["1000".to_string()]);
     }
+
+    #[homestar_runtime_proc_macro::db_async_test]
+    fn list_workflows_with_filters() {
+        let settings = TestSettings::load();
+
+        let db = MemoryDb::setup_connection_pool(settings.node(), None).unwrap();
+        let mut conn = db.conn().unwrap();
+
+        let day = |d| {
+            NaiveDate::from_ymd_opt(2024, 1, d)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap()
+        };
+
+        let mut rng = thread_rng();
+        let stored = [
+            ("add-one", day(1)),
+            ("add-two", day(2)),
+            ("crop-image", day(3)),
+        ]
+        .map(|(name, created_at)| {
+            MemoryDb::store_workflow(
+                workflow::Stored::new(
+                    Pointer::new(generate_cid(&mut rng)),
+                    Some(name.to_string()),
+                    1,
+                    IndexedResources::default(),
+                    created_at,
+                ),
+                &mut conn,
+            )
+            .unwrap()
+        });
+
+        MemoryDb::set_workflow_status(stored[1].cid.cid(), workflow::Status::Completed, &mut conn)
+            .unwrap();
+
+        let all = MemoryDb::list_workflows(&WorkflowFilter::default(), &mut conn).unwrap();
+        assert_eq!(
+            all.iter()
+                .map(|w| w.name.clone().unwrap())
+                .collect::<Vec<_>>(),
+            vec!["crop-image", "add-two", "add-one"]
+        );
+
+        let completed = MemoryDb::list_workflows(
+            &WorkflowFilter {
+                status: Some(workflow::Status::Completed),
+                ..Default::default()
+            },
+            &mut conn,
+        )
+        .unwrap();
+        assert_eq!(completed.len(), 1);
+        assert_eq!(completed[0].cid, stored[1].cid);
+        assert!(completed[0].completed_at.is_some());
+
+        let named = MemoryDb::list_workflows(
+            &WorkflowFilter {
+                name: Some("add".to_string()),
+                since: Some(day(2)),
+                ..Default::default()
+            },
+            &mut conn,
+        )
+        .unwrap();
+        assert_eq!(named.len(), 1);
+        assert_eq!(named[0].cid, stored[1].cid);
+
+        // LIKE metacharacters in names are matched literally.
+        let underscored = MemoryDb::list_workflows(
+            &WorkflowFilter {
+                name: Some("_".to_string()),
+                ..Default::default()
+            },
+            &mut conn,
+        )
+        .unwrap();
+        assert!(underscored.is_empty());
+
+        // Completion time is kept through later status changes.
+        MemoryDb::set_workflow_status(stored[1].cid.cid(), workflow::Status::Completed, &mut conn)
+            .unwrap();
+        MemoryDb::set_workflow_status(stored[1].cid.cid(), workflow::Status::Running, &mut conn)
+            .unwrap();
+        let rerun = MemoryDb::select_workflow(stored[1].cid.cid(), &mut conn).unwrap();
+        assert_eq!(rerun.completed_at, completed[0].completed_at);
+
+        let paged = MemoryDb::list_workflows(
+            &WorkflowFilter {
+                until: Some(day(2)),
+                limit: 1,
+                offset: 1,
+                ..Default::default()
+            },
+            &mut conn,
+        )
+        .unwrap();
+        assert_eq!(paged.len(), 1);
+        assert_eq!(paged[0].cid, stored[0].cid);
+    }
+
+    #[homestar_runtime_proc_macro::db_async_test]
+    fn find_resumable_workflows_with_documents() {
+        let settings = TestSettings::load();
+
+        let db = MemoryDb::setup_connection_pool(settings.node(), None).unwrap();
+        let mut conn = db.conn().unwrap();
+
+        let mut rng = thread_rng();
+        let stored = [
+            workflow::Status::Running,
+            workflow::Status::Pending,
+            workflow::Status::Completed,
+            workflow::Status::Running,
+        ]
+        .map(|status| {
+            let stored = MemoryDb::store_workflow(
+                workflow::Stored::default(Pointer::new(generate_cid(&mut rng)), 1),
+                &mut conn,
+            )
+            .unwrap();
+            MemoryDb::set_workflow_status(stored.cid.cid(), status, &mut conn).unwrap();
+            stored
+        });
+
+        // The last workflow has no stored document to resume from.
+        for (i, workflow) in stored.iter().take(3).enumerate() {
+            MemoryDb::store_workflow_document(workflow.cid.cid(), &[i as u8], &mut conn).unwrap();
+        }
+
+        // Nested workflows are resumed by their parent workflows.
+        let nested = MemoryDb::store_workflow(
+            workflow::Stored::default(Pointer::new(generate_cid(&mut rng)), 1),
+            &mut conn,
+        )
+        .unwrap();
+        MemoryDb::set_workflow_status(nested.cid.cid(), workflow::Status::Running, &mut conn)
+            .unwrap();
+        MemoryDb::store_workflow_document(nested.cid.cid(), &[4], &mut conn).unwrap();
+        MemoryDb::store_nested_workflow(
+            stored[0].cid.cid(),
+            nested.cid.cid(),
+            generate_cid(&mut rng),
+            &mut conn,
+        )
+        .unwrap();
+
+        let mut resumable = MemoryDb::find_resumable_workflows(&mut conn)
+            .unwrap()
+            .into_iter()
+            .map(|(workflow, document)| (workflow.cid, document))
+            .collect::<Vec<_>>();
+        resumable.sort_by(|a, b| a.1.cmp(&b.1));
+        assert_eq!(
+            resumable,
+            vec![
+                (stored[0].cid.clone(), vec![0]),
+                (stored[1].cid.clone(), vec![1])
+            ]
+        );
+    }
+
+    #[homestar_runtime_proc_macro::db_async_test]
+    fn store_and_select_workflow_blocks() {
+        let settings = TestSettings::load();
+
+        let db = MemoryDb::setup_connection_pool(settings.node(), None).unwrap();
+        let mut conn = db.conn().unwrap();
+
+        let mut rng = thread_rng();
+        let stored = MemoryDb::store_workflow(
+            workflow::Stored::default(Pointer::new(generate_cid(&mut rng)), 1),
+            &mut conn,
+        )
+        .unwrap();
+        let workflow_cid = stored.cid.cid();
+        assert!(MemoryDb::select_workflow_blocks(workflow_cid, &mut conn)
+            .unwrap()
+            .is_empty());
+
+        let blocks = Bundled::from([
+            (generate_cid(&mut rng), b"\0asm".to_vec()),
+            (generate_cid(&mut rng), vec![1, 2, 3]),
+        ]);
+        assert_eq!(
+            MemoryDb::store_workflow_blocks(workflow_cid, &blocks, &mut conn).unwrap(),
+            2
+        );
+        // Storing the same blocks again is a no-op.
+        assert_eq!(
+            MemoryDb::store_workflow_blocks(workflow_cid, &blocks, &mut conn).unwrap(),
+            0
+        );
+
+        let mut selected = MemoryDb::select_workflow_blocks(workflow_cid, &mut conn).unwrap();
+        selected.sort_keys();
+        let mut expected = blocks.clone();
+        expected.sort_keys();
+        assert_eq!(selected, expected);
+    }
 }
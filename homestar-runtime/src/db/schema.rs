@@ -1,5 +1,19 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    flagged_receipts (receipt_cid) {
+        receipt_cid -> Text,
+    }
+}
+
+diesel::table! {
+    nested_workflows (parent_cid, workflow_cid) {
+        parent_cid -> Text,
+        workflow_cid -> Text,
+        instruction_cid -> Text,
+    }
+}
+
 diesel::table! {
     receipts (cid) {
         cid -> Text,
@@ -13,6 +27,13 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    receipt_peers (receipt_cid, peer_id) {
+        receipt_cid -> Text,
+        peer_id -> Text,
+    }
+}
+
 diesel::table! {
     workflows (cid) {
         cid -> Text,
@@ -23,6 +44,26 @@ diesel::table! {
         completed_at -> Nullable<Timestamp>,
         status -> crate::workflow::StatusMapping,
         retries -> Integer,
+        failure_reason -> Nullable<Text>,
+        failed_instruction -> Nullable<Text>,
+        failed_at -> Nullable<Timestamp>,
+        document -> Nullable<Binary>,
+        unfinished_instructions -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    workflows_blocks (workflow_cid, block_cid) {
+        workflow_cid -> Text,
+        block_cid -> Text,
+        data -> Binary,
+    }
+}
+
+diesel::table! {
+    workflows_instructions (workflow_cid, instruction_cid) {
+        workflow_cid -> Text,
+        instruction_cid -> Text,
     }
 }
 
@@ -33,11 +74,19 @@ diesel::table! {
     }
 }
 
+diesel::joinable!(receipt_peers -> receipts (receipt_cid));
+diesel::joinable!(workflows_blocks -> workflows (workflow_cid));
+diesel::joinable!(workflows_instructions -> workflows (workflow_cid));
 diesel::joinable!(workflows_receipts -> receipts (receipt_cid));
 diesel::joinable!(workflows_receipts -> workflows (workflow_cid));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    flagged_receipts,
+    nested_workflows,
+    receipt_peers,
     receipts,
     workflows,
+    workflows_blocks,
+    workflows_instructions,
     workflows_receipts,
 );
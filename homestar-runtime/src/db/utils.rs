@@ -1,9 +1,13 @@
 //! Utility functions Database interaction.
 
+use crate::workflow;
 use chrono::{DateTime, NaiveDateTime};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+/// Default number of workflows returned per page when listing workflows.
+pub(crate) const DEFAULT_WORKFLOWS_LIMIT: i64 = 20;
+
 /// Trait for converting nanoseconds to a timestamp.
 #[allow(dead_code)]
 pub(crate) trait Timestamp {
@@ -26,3 +30,33 @@ pub struct Health {
     /// Health status.
     pub healthy: bool,
 }
+
+/// Filter and pagination parameters for listing stored workflows.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkflowFilter {
+    /// Only include workflows with this status.
+    pub status: Option<workflow::Status>,
+    /// Only include workflows whose local name contains this string.
+    pub name: Option<String>,
+    /// Only include workflows created at or after this (UTC) time.
+    pub since: Option<NaiveDateTime>,
+    /// Only include workflows created at or before this (UTC) time.
+    pub until: Option<NaiveDateTime>,
+    /// Maximum number of workflows to return.
+    pub limit: i64,
+    /// Number of workflows to skip, most recent first.
+    pub offset: i64,
+}
+
+impl Default for WorkflowFilter {
+    fn default() -> Self {
+        Self {
+            status: None,
+            name: None,
+            since: None,
+            until: None,
+            limit: DEFAULT_WORKFLOWS_LIMIT,
+            offset: 0,
+        }
+    }
+}
@@ -8,7 +8,9 @@ use crate::{
     channel,
     db::Database,
     network::swarm::{ComposedBehaviour, PeerDiscoveryInfo, RequestResponseKey},
+    receipt::verify::Verifier,
     settings,
+    tasks::Registry,
 };
 use anyhow::Result;
 use fnv::FnvHashMap;
@@ -19,7 +21,7 @@ use libp2p::{
 use moka::future::Cache;
 use std::{sync::Arc, time::Duration};
 use swarm_event::ResponseEvent;
-use tokio::{runtime::Handle, select};
+use tokio::{runtime::Handle, select, sync::Semaphore};
 
 pub(crate) mod cache;
 pub(crate) mod error;
@@ -104,6 +106,8 @@ pub(crate) struct EventHandler<DB: Database> {
     poll_cache_interval: Duration,
     /// Bootstrap configuration.
     bootstrap: Bootstrap,
+    /// [Verifier] for receipts received from peers.
+    verifier: Verifier,
 }
 
 /// Event loop handler for libp2p network events and commands.
@@ -145,6 +149,8 @@ pub(crate) struct EventHandler<DB: Database> {
     poll_cache_interval: Duration,
     /// Bootstrap configuration.
     bootstrap: Bootstrap,
+    /// [Verifier] for receipts received from peers.
+    verifier: Verifier,
 }
 
 /// Rendezvous protocol configurations and state
@@ -164,7 +170,7 @@ struct Connections {
 
 impl<DB> EventHandler<DB>
 where
-    DB: Database,
+    DB: Database + 'static,
 {
     fn setup_channel(
         settings: &settings::Network,
@@ -175,13 +181,19 @@ where
         channel::AsyncChannel::with(settings.events_buffer_len)
     }
 
-    /// Create an [EventHandler] with channel sender/receiver defaults.
+    /// Create an [EventHandler] with channel sender/receiver defaults,
+    /// verifying receipts received from peers on the given [Executor]s,
+    /// within the node's task permits.
+    ///
+    /// [Executor]: crate::Executor
     #[cfg(feature = "websocket-notify")]
     #[cfg_attr(docsrs, doc(cfg(feature = "websocket-notify")))]
     pub(crate) fn new(
         swarm: Swarm<ComposedBehaviour>,
         db: DB,
         settings: &settings::Network,
+        executors: Arc<Registry>,
+        task_permits: Arc<Semaphore>,
         ws_evt_sender: webserver::Notifier<notifier::Message>,
         ws_workflow_sender: webserver::Notifier<notifier::Message>,
     ) -> Self {
@@ -222,15 +234,22 @@ where
             bootstrap: Bootstrap {
                 interval: settings.libp2p.bootstrap_interval,
             },
+            verifier: Verifier::new(settings, executors, task_permits),
         }
     }
 
-    /// Create an [EventHandler] with channel sender/receiver defaults.
+    /// Create an [EventHandler] with channel sender/receiver defaults,
+    /// verifying receipts received from peers on the given [Executor]s,
+    /// within the node's task permits.
+    ///
+    /// [Executor]: crate::Executor
     #[cfg(not(feature = "websocket-notify"))]
     pub(crate) fn new(
         swarm: Swarm<ComposedBehaviour>,
         db: DB,
         settings: &settings::Network,
+        executors: Arc<Registry>,
+        task_permits: Arc<Semaphore>,
     ) -> Self {
         let (sender, receiver) = Self::setup_channel(settings);
         let sender = Arc::new(sender);
@@ -267,6 +286,7 @@ where
             bootstrap: Bootstrap {
                 interval: settings.libp2p.bootstrap_interval,
             },
+            verifier: Verifier::new(settings, executors, task_permits),
         }
     }
 
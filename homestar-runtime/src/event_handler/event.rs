@@ -139,7 +139,7 @@ pub(crate) enum Event {
 impl Event {
     async fn handle_info<DB>(self, event_handler: &mut EventHandler<DB>) -> Result<()>
     where
-        DB: Database,
+        DB: Database + 'static,
     {
         match self {
             Event::CapturedReceipt(captured) => {
@@ -368,7 +368,7 @@ impl Captured {
         event_handler: &mut EventHandler<DB>,
     ) -> Result<(Cid, InvocationReceipt<Ipld>)>
     where
-        DB: Database,
+        DB: Database + 'static,
     {
         let receipt = Db::find_receipt_by_cid(self.receipt, &mut event_handler.db.conn()?)?;
         let invocation_receipt = InvocationReceipt::from(&receipt);
@@ -384,11 +384,12 @@ impl Captured {
             )
         }
 
-        // short-circuit if no peers
+        // short-circuit if no peers, or if the receipt is for a skipped task,
+        // which is local to its workflow
         //
         // - don't gossip receipt
         // - don't store receipt or workflow info on DHT
-        if event_handler.connections.peers.is_empty() {
+        if event_handler.connections.peers.is_empty() || receipt.skipped() {
             return Ok((self.receipt, invocation_receipt));
         }
 
@@ -559,7 +560,7 @@ impl Replay {
 
     fn notify<DB>(self, event_handler: &mut EventHandler<DB>) -> Result<()>
     where
-        DB: Database,
+        DB: Database + 'static,
     {
         let mut receipts =
             Db::find_instruction_pointers(&self.pointers, &mut event_handler.db.conn()?)?;
@@ -645,7 +646,7 @@ impl QueryRecord {
 
     async fn find<DB>(self, event_handler: &mut EventHandler<DB>)
     where
-        DB: Database,
+        DB: Database + 'static,
     {
         let id = event_handler
             .swarm
@@ -659,7 +660,7 @@ impl QueryRecord {
 
     async fn remove<DB>(self, event_handler: &mut EventHandler<DB>)
     where
-        DB: Database,
+        DB: Database + 'static,
     {
         event_handler
             .swarm
@@ -676,7 +677,7 @@ impl QueryRecord {
 
     async fn get_providers<DB>(self, event_handler: &mut EventHandler<DB>)
     where
-        DB: Database,
+        DB: Database + 'static,
     {
         let id = event_handler
             .swarm
@@ -702,7 +703,7 @@ impl PeerRequest {
 
 impl<DB> Handler<DB> for Event
 where
-    DB: Database,
+    DB: Database + 'static,
 {
     #[cfg(not(feature = "ipfs"))]
     async fn handle_event(self, event_handler: &mut EventHandler<DB>) {
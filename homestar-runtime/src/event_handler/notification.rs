@@ -9,11 +9,12 @@ use crate::{
     Receipt,
 };
 use homestar_invocation::{ipld::DagJson, Receipt as InvocationReceipt};
-use libipld::Ipld;
+use libipld::{Cid, Ipld};
 use tracing::{debug, warn};
 
 pub(crate) mod network;
 pub(crate) mod receipt;
+pub(crate) mod workflow;
 pub(crate) use network::{
     ConnectionClosed, ConnectionEstablished, DiscoverServedRendezvous, DiscoveredMdns,
     DiscoveredRendezvous, GotReceiptDht, GotWorkflowInfoDht, IncomingConnectionError,
@@ -24,6 +25,7 @@ pub(crate) use network::{
     WorkflowInfoQuorumSuccessDht, WorkflowInfoSource,
 };
 pub(crate) use receipt::ReceiptNotification;
+pub(crate) use workflow::WorkflowStatusNotification;
 
 /// Send receipt notification as bytes.
 pub(crate) fn emit_receipt(
@@ -67,6 +69,34 @@ pub(crate) fn emit_receipt(
     }
 }
 
+/// Send workflow status notification as bytes to subscribers of the
+/// given workflow.
+pub(crate) fn emit_workflow_status(
+    notifier: Notifier<notifier::Message>,
+    workflow_cid: Cid,
+    notification: WorkflowStatusNotification,
+) {
+    let header = Header::new(SubscriptionTyp::Cid(workflow_cid), None);
+
+    if let Ok(json) = notification.to_json() {
+        debug!(
+            subject = "notification.workflow",
+            category = "notification",
+            cid = workflow_cid.to_string(),
+            status = notification.status(),
+            "emitting workflow status to WebSocket"
+        );
+        let _ = notifier.notify(Message::new(header, json));
+    } else {
+        warn!(
+            subject = "notification.err",
+            category = "notification",
+            cid = workflow_cid.to_string(),
+            "unable to serialize workflow status notification as bytes"
+        );
+    }
+}
+
 /// Send network event notification as bytes.
 pub(crate) fn emit_network_event(
     notifier: Notifier<notifier::Message>,
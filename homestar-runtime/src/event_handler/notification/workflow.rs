@@ -0,0 +1,210 @@
+//! Notification types for changes in a workflow's status.
+
+use crate::workflow::Status;
+use anyhow::anyhow;
+use chrono::prelude::Utc;
+use derive_getters::Getters;
+use faststr::FastStr;
+use homestar_invocation::ipld::DagJson;
+use libipld::{serde::from_ipld, Cid, Ipld};
+use schemars::JsonSchema;
+use std::collections::BTreeMap;
+
+const CID_KEY: &str = "cid";
+const NAME_KEY: &str = "name";
+const REASON_KEY: &str = "reason";
+const STATUS_KEY: &str = "status";
+const TIMESTAMP_KEY: &str = "timestamp";
+const UNFINISHED_KEY: &str = "unfinished";
+
+/// Notification sent to workflow subscribers when a workflow's status
+/// changes outside of emitting a receipt, e.g. on cancellation or timeout.
+#[derive(Debug, Clone, Getters, JsonSchema)]
+#[schemars(rename = "workflow_status")]
+pub struct WorkflowStatusNotification {
+    timestamp: i64,
+    #[schemars(description = "Workflow CID")]
+    cid: String,
+    #[schemars(description = "Optional workflow name")]
+    name: Option<String>,
+    #[schemars(description = "Workflow status")]
+    status: String,
+    #[schemars(description = "Reason for the status change")]
+    reason: Option<String>,
+    #[schemars(description = "Instruction CIDs left unfinished, e.g. on timeout")]
+    unfinished: Vec<String>,
+}
+
+impl WorkflowStatusNotification {
+    pub(crate) fn new(
+        cid: Cid,
+        name: Option<FastStr>,
+        status: Status,
+        reason: Option<String>,
+    ) -> WorkflowStatusNotification {
+        WorkflowStatusNotification {
+            timestamp: Utc::now().timestamp_millis(),
+            cid: cid.to_string(),
+            name: name.map(|n| n.into()),
+            status: status.to_string(),
+            reason,
+            unfinished: vec![],
+        }
+    }
+
+    /// Set the [Instruction]s left unfinished by the status change.
+    ///
+    /// [Instruction]: homestar_invocation::task::Instruction
+    pub(crate) fn with_unfinished(mut self, unfinished: &[Cid]) -> Self {
+        self.unfinished = unfinished.iter().map(|cid| cid.to_string()).collect();
+        self
+    }
+}
+
+impl DagJson for WorkflowStatusNotification {}
+
+impl From<WorkflowStatusNotification> for Ipld {
+    fn from(notification: WorkflowStatusNotification) -> Self {
+        let map: BTreeMap<String, Ipld> = BTreeMap::from([
+            (TIMESTAMP_KEY.into(), notification.timestamp.into()),
+            (CID_KEY.into(), notification.cid.into()),
+            (
+                NAME_KEY.into(),
+                notification
+                    .name
+                    .map(|name| name.into())
+                    .unwrap_or(Ipld::Null),
+            ),
+            (STATUS_KEY.into(), notification.status.into()),
+            (
+                REASON_KEY.into(),
+                notification
+                    .reason
+                    .map(|reason| reason.into())
+                    .unwrap_or(Ipld::Null),
+            ),
+            (
+                UNFINISHED_KEY.into(),
+                Ipld::List(
+                    notification
+                        .unfinished
+                        .into_iter()
+                        .map(Ipld::from)
+                        .collect(),
+                ),
+            ),
+        ]);
+
+        Ipld::Map(map)
+    }
+}
+
+impl TryFrom<Ipld> for WorkflowStatusNotification {
+    type Error = anyhow::Error;
+
+    fn try_from(ipld: Ipld) -> Result<Self, Self::Error> {
+        let map = from_ipld::<BTreeMap<String, Ipld>>(ipld)?;
+
+        let timestamp = from_ipld(
+            map.get(TIMESTAMP_KEY)
+                .ok_or_else(|| anyhow!("missing {TIMESTAMP_KEY}"))?
+                .to_owned(),
+        )?;
+
+        let cid = from_ipld(
+            map.get(CID_KEY)
+                .ok_or_else(|| anyhow!("missing {CID_KEY}"))?
+                .to_owned(),
+        )?;
+
+        let name = map
+            .get(NAME_KEY)
+            .and_then(|ipld| match ipld {
+                Ipld::Null => None,
+                ipld => Some(ipld),
+            })
+            .and_then(|ipld| from_ipld(ipld.to_owned()).ok());
+
+        let status = from_ipld(
+            map.get(STATUS_KEY)
+                .ok_or_else(|| anyhow!("missing {STATUS_KEY}"))?
+                .to_owned(),
+        )?;
+
+        let reason = map
+            .get(REASON_KEY)
+            .and_then(|ipld| match ipld {
+                Ipld::Null => None,
+                ipld => Some(ipld),
+            })
+            .and_then(|ipld| from_ipld(ipld.to_owned()).ok());
+
+        let unfinished = map
+            .get(UNFINISHED_KEY)
+            .and_then(|ipld| from_ipld(ipld.to_owned()).ok())
+            .unwrap_or_default();
+
+        Ok(WorkflowStatusNotification {
+            timestamp,
+            cid,
+            name,
+            status,
+            reason,
+            unfinished,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use homestar_invocation::test_utils::cid::generate_cid;
+    use rand::thread_rng;
+
+    #[test]
+    fn workflow_status_notification_bytes_rountrip() {
+        let cid = generate_cid(&mut thread_rng());
+        let notification = WorkflowStatusNotification::new(
+            cid,
+            Some("test".into()),
+            Status::Cancelled,
+            Some("workflow cancelled".to_string()),
+        );
+
+        let bytes = notification.to_json().unwrap();
+        let ipld = Ipld::from(notification.clone());
+        let parsed = WorkflowStatusNotification::try_from(ipld).unwrap();
+
+        assert_eq!(parsed.cid(), &cid.to_string());
+        assert_eq!(parsed.name(), &Some("test".to_string()));
+        assert_eq!(parsed.status(), "cancelled");
+        assert_eq!(parsed.reason(), &Some("workflow cancelled".to_string()));
+        assert_eq!(parsed.timestamp(), notification.timestamp());
+        assert_eq!(
+            WorkflowStatusNotification::from_json(&bytes)
+                .unwrap()
+                .status(),
+            "cancelled"
+        );
+        assert!(parsed.unfinished().is_empty());
+    }
+
+    #[test]
+    fn workflow_status_notification_with_unfinished_rountrip() {
+        let mut rng = thread_rng();
+        let cid = generate_cid(&mut rng);
+        let unfinished = [generate_cid(&mut rng), generate_cid(&mut rng)];
+        let notification = WorkflowStatusNotification::new(cid, None, Status::TimedOut, None)
+            .with_unfinished(&unfinished);
+
+        let parsed = WorkflowStatusNotification::try_from(Ipld::from(notification)).unwrap();
+        assert_eq!(parsed.status(), "timed_out");
+        assert_eq!(
+            parsed.unfinished(),
+            &unfinished
+                .iter()
+                .map(|cid| cid.to_string())
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -18,9 +18,10 @@ use crate::{
             CapsuleTag, ComposedEvent, PeerDiscoveryInfo, RequestResponseKey, HOMESTAR_PROTOCOL_VER,
         },
     },
+    receipt::trust,
     workflow,
     workflow::WORKFLOW_TAG,
-    Db, Receipt,
+    Receipt,
 };
 use anyhow::{anyhow, Result};
 use libipld::Cid;
@@ -40,6 +41,7 @@ use libp2p::{
 #[cfg(feature = "websocket-notify")]
 use std::collections::BTreeMap;
 use std::collections::{HashMap, HashSet};
+use tokio::runtime::Handle;
 use tracing::{debug, error, info, warn};
 
 pub(crate) mod record;
@@ -88,7 +90,7 @@ pub(crate) struct WorkflowInfoEvent {
 
 impl<DB> Handler<DB> for SwarmEvent<ComposedEvent>
 where
-    DB: Database + Sync,
+    DB: Database + Sync + 'static,
 {
     #[cfg(feature = "ipfs")]
     #[cfg_attr(docsrs, doc(cfg(feature = "ipfs")))]
@@ -102,7 +104,7 @@ where
     }
 }
 
-async fn handle_swarm_event<DB: Database>(
+async fn handle_swarm_event<DB: Database + 'static>(
     event: SwarmEvent<ComposedEvent>,
     event_handler: &mut EventHandler<DB>,
 ) {
@@ -616,6 +618,11 @@ async fn handle_swarm_event<DB: Database>(
                 propagation_source,
                 message_id,
             } => {
+                // Publishing peer, authenticated by its signature under
+                // `ValidationMode::Strict`, rather than the forwarding one.
+                let source = message
+                    .source
+                    .map_or(trust::UNKNOWN_PEER.to_string(), |source| source.to_string());
                 let bytes: Vec<u8> = message.data;
                 match pubsub::Message::<Receipt>::try_from(bytes) {
                     Ok(msg) => {
@@ -629,12 +636,23 @@ async fn handle_swarm_event<DB: Database>(
                             receipt.cid()
                         );
 
-                        // Store gossiped receipt.
-                        let _ = event_handler
-                            .db
-                            .conn()
-                            .as_mut()
-                            .map(|conn| Db::store_receipt(receipt.clone(), conn));
+                        // Store gossiped receipt, re-executing it first if
+                        // it's sampled for verification.
+                        let db = event_handler.db.clone();
+                        if event_handler.verifier.sample() {
+                            let verifier = event_handler.verifier.clone();
+                            let receipt = receipt.clone();
+                            Handle::current().spawn(async move {
+                                if verifier.admit(&receipt, db.clone()).await {
+                                    let _ = db
+                                        .conn()
+                                        .as_mut()
+                                        .map(|conn| trust::store_from_peer(receipt, &source, conn));
+                                }
+                            });
+                        } else if let Ok(mut conn) = db.conn() {
+                            let _ = trust::store_from_peer(receipt.clone(), &source, &mut conn);
+                        }
 
                         #[cfg(feature = "websocket-notify")]
                         notification::emit_network_event(
@@ -745,7 +763,28 @@ async fn handle_swarm_event<DB: Database>(
                                         }),
                                     ));
 
-                                    if let Some(sender) = sender {
+                                    // Re-execute the receipt before responding
+                                    // if it's sampled for verification.
+                                    if event_handler.verifier.sample() {
+                                        let verifier = event_handler.verifier.clone();
+                                        let db = event_handler.db.clone();
+                                        let receipt = receipt.clone();
+                                        Handle::current().spawn(async move {
+                                            let response_event =
+                                                if verifier.admit(&receipt, db).await {
+                                                    response_event
+                                                } else {
+                                                    ResponseEvent::Found(Err(anyhow!(
+                                                        "receipt failed verification: {}",
+                                                        receipt.cid()
+                                                    )))
+                                                };
+
+                                            if let Some(sender) = sender {
+                                                let _ = sender.send_async(response_event).await;
+                                            }
+                                        });
+                                    } else if let Some(sender) = sender {
                                         let _ = sender.send_async(response_event).await;
                                     }
 
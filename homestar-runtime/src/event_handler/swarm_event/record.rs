@@ -21,7 +21,9 @@ impl FoundRecord for PeerRecord {
     fn found_record(&self) -> Result<DecodedRecord> {
         let key_cid = Cid::try_from(self.record.key.as_ref())?;
 
-        decode_capsule(key_cid, self.record.publisher, &self.record.value)
+        // The record's publisher is set by whoever stored it, so isn't
+        // authenticated, unlike the peer it was retrieved from.
+        decode_capsule(key_cid, self.peer, &self.record.value)
     }
 }
 
@@ -69,12 +69,18 @@ pub mod test_utils;
 mod worker;
 pub mod workflow;
 
-pub use db::{utils::Health, Db};
+pub use db::{
+    utils::{Health, WorkflowFilter},
+    Db,
+};
 pub(crate) mod libp2p;
 pub use logger::*;
 pub(crate) mod metrics;
 #[cfg(feature = "websocket-notify")]
-pub use event_handler::notification::{network::NetworkNotification, receipt::ReceiptNotification};
+pub use event_handler::notification::{
+    network::NetworkNotification, receipt::ReceiptNotification,
+    workflow::WorkflowStatusNotification,
+};
 #[allow(unused_imports)]
 pub(crate) use event_handler::EventHandler;
 pub use network::webserver::{listener, PrometheusData};
@@ -85,8 +91,11 @@ pub(crate) use scheduler::TaskScheduler;
 pub use settings::IpfsBuilder;
 pub use settings::{
     Autonat, DatabaseBuilder, Dht, ExistingKeyPath, KeyType, Libp2p, Mdns, MetricsBuilder,
-    MonitoringBuilder, NetworkBuilder, NodeBuilder, PubkeyConfig, Pubsub, RNGSeed, Rendezvous,
-    RpcBuilder, Settings, SettingsBuilder, WebserverBuilder,
+    MonitoringBuilder, NetworkBuilder, NodeBuilder, OnMismatch, PubkeyConfig, Pubsub, RNGSeed,
+    ReceiptTrust, Rendezvous, RpcBuilder, SchedulerBuilder, Settings, SettingsBuilder,
+    VerificationBuilder, WebserverBuilder,
 };
+pub use tasks::{Executor, Registry, TaskInput};
 pub(crate) use worker::Worker;
+pub use worker::{TaskError, TaskErrorKind};
 pub use workflow::WORKFLOW_TAG;
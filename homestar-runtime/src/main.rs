@@ -1,6 +1,9 @@
 use clap::Parser;
 use homestar_runtime::{
-    cli::{handle_init_command, Cli, Command, ConsoleTable},
+    cli::{
+        handle_init_command, handle_plan_command, handle_validate_command, Cli, Command,
+        ConsoleTable,
+    },
     daemon,
     db::Database,
     runner::response,
@@ -57,6 +60,8 @@ fn main() -> Result<()> {
             info!("starting Homestar runtime...");
             Runner::start(settings, db).expect("Failed to start runtime")
         }
+        Command::Validate(validate_args) => handle_validate_command(validate_args)?,
+        Command::Plan(plan_args) => handle_plan_command(plan_args)?,
         Command::Info => {
             let response = response::Info::default();
             response
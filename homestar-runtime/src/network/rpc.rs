@@ -2,7 +2,8 @@
 
 use crate::{
     channel::{AsyncChannel, AsyncChannelReceiver, AsyncChannelSender},
-    runner::{self, file::ReadWorkflow, response, RpcSender},
+    db::utils::WorkflowFilter,
+    runner::{self, file::ReadWorkflow, response, ReceiptLookup, RpcSender},
     settings,
 };
 use faststr::FastStr;
@@ -53,6 +54,67 @@ pub(crate) enum ServerMessage {
     NodeInfo,
     /// Acknowledgement of the node's identity/info.
     NodeInfoAck(response::AckNodeInfo),
+    /// Message sent to the [Runner] to cancel a running [Workflow], given
+    /// its Cid or local name.
+    ///
+    /// [Runner]: crate::Runner
+    /// [Workflow]: homestar_workflow::Workflow
+    Cancel(FastStr),
+    /// Acknowledgement of a cancelled [Workflow].
+    ///
+    /// [Workflow]: homestar_workflow::Workflow
+    CancelAck(response::AckCancel),
+    /// Message sent to the [Runner] to list stored [Workflow]s matching a
+    /// [WorkflowFilter].
+    ///
+    /// [Runner]: crate::Runner
+    /// [Workflow]: homestar_workflow::Workflow
+    ListWorkflows(WorkflowFilter),
+    /// Acknowledgement of listed [Workflow]s.
+    ///
+    /// [Workflow]: homestar_workflow::Workflow
+    ListWorkflowsAck(response::AckWorkflowList),
+    /// Message sent to the [Runner] to show a stored [Workflow], given its
+    /// Cid or local name.
+    ///
+    /// [Runner]: crate::Runner
+    /// [Workflow]: homestar_workflow::Workflow
+    ShowWorkflow(FastStr),
+    /// Acknowledgement of a shown [Workflow].
+    ///
+    /// [Workflow]: homestar_workflow::Workflow
+    ShowWorkflowAck(Box<response::AckWorkflowDetail>),
+    /// Message sent to the [Runner] to get a [Receipt], given a Cid, how to
+    /// look it up, and whether to include its raw DAG-CBOR bytes.
+    ///
+    /// [Runner]: crate::Runner
+    /// [Receipt]: crate::Receipt
+    GetReceipt((FastStr, ReceiptLookup, bool)),
+    /// Acknowledgement of a found [Receipt].
+    ///
+    /// [Receipt]: crate::Receipt
+    GetReceiptAck(Box<response::AckReceipt>),
+    /// Message sent to the [Runner] to get a stored [Workflow]'s document,
+    /// given its Cid or local name, and whether to include its raw DAG-CBOR
+    /// bytes.
+    ///
+    /// [Runner]: crate::Runner
+    /// [Workflow]: homestar_workflow::Workflow
+    GetWorkflow((FastStr, bool)),
+    /// Acknowledgement of a found [Workflow] document.
+    ///
+    /// [Workflow]: homestar_workflow::Workflow
+    GetWorkflowAck(Box<response::AckWorkflowDocument>),
+    /// Message sent to the [Runner] to verify a [Receipt], given its Cid, by
+    /// re-executing the instruction it was issued for.
+    ///
+    /// [Runner]: crate::Runner
+    /// [Receipt]: crate::Receipt
+    VerifyReceipt(FastStr),
+    /// Acknowledgement of a verified [Receipt].
+    ///
+    /// [Receipt]: crate::Receipt
+    VerifyReceiptAck(Box<response::AckVerify>),
     /// For skipping server messages.
     Skip,
 }
@@ -71,6 +133,27 @@ pub(crate) trait Interface {
     async fn stop() -> Result<(), Error>;
     /// Identify the node.
     async fn node_info() -> Result<response::AckNodeInfo, Error>;
+    /// Cancel a running workflow, given its Cid or local name.
+    async fn cancel(workflow: FastStr) -> Result<response::AckCancel, Error>;
+    /// List stored workflows matching a filter.
+    async fn list_workflows(filter: WorkflowFilter) -> Result<response::AckWorkflowList, Error>;
+    /// Show a stored workflow, given its Cid or local name.
+    async fn show_workflow(workflow: FastStr) -> Result<Box<response::AckWorkflowDetail>, Error>;
+    /// Get a receipt, given its Cid or the Cid of the instruction it was
+    /// issued for.
+    async fn get_receipt(
+        cid: FastStr,
+        lookup: ReceiptLookup,
+        cbor: bool,
+    ) -> Result<Box<response::AckReceipt>, Error>;
+    /// Get a stored workflow's document, given its Cid or local name.
+    async fn get_workflow(
+        workflow: FastStr,
+        cbor: bool,
+    ) -> Result<Box<response::AckWorkflowDocument>, Error>;
+    /// Verify a stored receipt, given its Cid, by re-executing the
+    /// instruction it was issued for and comparing outputs.
+    async fn verify_receipt(cid: FastStr) -> Result<Box<response::AckVerify>, Error>;
 }
 
 /// RPC server state information.
@@ -190,6 +273,183 @@ impl Interface for ServerHandler {
             }
         }
     }
+    async fn cancel(
+        self,
+        _: context::Context,
+        workflow: FastStr,
+    ) -> Result<response::AckCancel, Error> {
+        let (tx, rx) = AsyncChannel::oneshot();
+        self.runner_sender
+            .send_async((ServerMessage::Cancel(workflow), Some(tx)))
+            .await
+            .map_err(|e| Error::FailureToSendOnChannel(e.to_string()))?;
+
+        let now = time::Instant::now();
+        select! {
+            Ok(msg) = rx.recv_async() => {
+                match msg {
+                    ServerMessage::CancelAck(response) => Ok(response),
+                    ServerMessage::RunErr(err) => Err(Error::FromRunner(err.to_string())),
+                    _ => Err(Error::FailureToSendOnChannel("unexpected message".into())),
+                }
+            },
+            _ = time::sleep_until(now + self.timeout) => {
+                let s = format!("server timeout of {} ms reached", self.timeout.as_millis());
+                info!(subject = "rpc.timeout",
+                      category = "rpc",
+                      "{s}");
+                Err(Error::FailureToReceiveOnChannel(s))
+            }
+        }
+    }
+    async fn list_workflows(
+        self,
+        _: context::Context,
+        filter: WorkflowFilter,
+    ) -> Result<response::AckWorkflowList, Error> {
+        let (tx, rx) = AsyncChannel::oneshot();
+        self.runner_sender
+            .send_async((ServerMessage::ListWorkflows(filter), Some(tx)))
+            .await
+            .map_err(|e| Error::FailureToSendOnChannel(e.to_string()))?;
+
+        let now = time::Instant::now();
+        select! {
+            Ok(msg) = rx.recv_async() => {
+                match msg {
+                    ServerMessage::ListWorkflowsAck(response) => Ok(response),
+                    ServerMessage::RunErr(err) => Err(Error::FromRunner(err.to_string())),
+                    _ => Err(Error::FailureToSendOnChannel("unexpected message".into())),
+                }
+            },
+            _ = time::sleep_until(now + self.timeout) => {
+                let s = format!("server timeout of {} ms reached", self.timeout.as_millis());
+                info!(subject = "rpc.timeout",
+                      category = "rpc",
+                      "{s}");
+                Err(Error::FailureToReceiveOnChannel(s))
+            }
+        }
+    }
+    async fn show_workflow(
+        self,
+        _: context::Context,
+        workflow: FastStr,
+    ) -> Result<Box<response::AckWorkflowDetail>, Error> {
+        let (tx, rx) = AsyncChannel::oneshot();
+        self.runner_sender
+            .send_async((ServerMessage::ShowWorkflow(workflow), Some(tx)))
+            .await
+            .map_err(|e| Error::FailureToSendOnChannel(e.to_string()))?;
+
+        let now = time::Instant::now();
+        select! {
+            Ok(msg) = rx.recv_async() => {
+                match msg {
+                    ServerMessage::ShowWorkflowAck(response) => Ok(response),
+                    ServerMessage::RunErr(err) => Err(Error::FromRunner(err.to_string())),
+                    _ => Err(Error::FailureToSendOnChannel("unexpected message".into())),
+                }
+            },
+            _ = time::sleep_until(now + self.timeout) => {
+                let s = format!("server timeout of {} ms reached", self.timeout.as_millis());
+                info!(subject = "rpc.timeout",
+                      category = "rpc",
+                      "{s}");
+                Err(Error::FailureToReceiveOnChannel(s))
+            }
+        }
+    }
+    async fn get_receipt(
+        self,
+        _: context::Context,
+        cid: FastStr,
+        lookup: ReceiptLookup,
+        cbor: bool,
+    ) -> Result<Box<response::AckReceipt>, Error> {
+        let (tx, rx) = AsyncChannel::oneshot();
+        self.runner_sender
+            .send_async((ServerMessage::GetReceipt((cid, lookup, cbor)), Some(tx)))
+            .await
+            .map_err(|e| Error::FailureToSendOnChannel(e.to_string()))?;
+
+        let now = time::Instant::now();
+        select! {
+            Ok(msg) = rx.recv_async() => {
+                match msg {
+                    ServerMessage::GetReceiptAck(response) => Ok(response),
+                    ServerMessage::RunErr(err) => Err(Error::FromRunner(err.to_string())),
+                    _ => Err(Error::FailureToSendOnChannel("unexpected message".into())),
+                }
+            },
+            _ = time::sleep_until(now + self.timeout) => {
+                let s = format!("server timeout of {} ms reached", self.timeout.as_millis());
+                info!(subject = "rpc.timeout",
+                      category = "rpc",
+                      "{s}");
+                Err(Error::FailureToReceiveOnChannel(s))
+            }
+        }
+    }
+    async fn get_workflow(
+        self,
+        _: context::Context,
+        workflow: FastStr,
+        cbor: bool,
+    ) -> Result<Box<response::AckWorkflowDocument>, Error> {
+        let (tx, rx) = AsyncChannel::oneshot();
+        self.runner_sender
+            .send_async((ServerMessage::GetWorkflow((workflow, cbor)), Some(tx)))
+            .await
+            .map_err(|e| Error::FailureToSendOnChannel(e.to_string()))?;
+
+        let now = time::Instant::now();
+        select! {
+            Ok(msg) = rx.recv_async() => {
+                match msg {
+                    ServerMessage::GetWorkflowAck(response) => Ok(response),
+                    ServerMessage::RunErr(err) => Err(Error::FromRunner(err.to_string())),
+                    _ => Err(Error::FailureToSendOnChannel("unexpected message".into())),
+                }
+            },
+            _ = time::sleep_until(now + self.timeout) => {
+                let s = format!("server timeout of {} ms reached", self.timeout.as_millis());
+                info!(subject = "rpc.timeout",
+                      category = "rpc",
+                      "{s}");
+                Err(Error::FailureToReceiveOnChannel(s))
+            }
+        }
+    }
+    async fn verify_receipt(
+        self,
+        _: context::Context,
+        cid: FastStr,
+    ) -> Result<Box<response::AckVerify>, Error> {
+        let (tx, rx) = AsyncChannel::oneshot();
+        self.runner_sender
+            .send_async((ServerMessage::VerifyReceipt(cid), Some(tx)))
+            .await
+            .map_err(|e| Error::FailureToSendOnChannel(e.to_string()))?;
+
+        let now = time::Instant::now();
+        select! {
+            Ok(msg) = rx.recv_async() => {
+                match msg {
+                    ServerMessage::VerifyReceiptAck(response) => Ok(response),
+                    ServerMessage::RunErr(err) => Err(Error::FromRunner(err.to_string())),
+                    _ => Err(Error::FailureToSendOnChannel("unexpected message".into())),
+                }
+            },
+            _ = time::sleep_until(now + self.timeout) => {
+                let s = format!("server timeout of {} ms reached", self.timeout.as_millis());
+                info!(subject = "rpc.timeout",
+                      category = "rpc",
+                      "{s}");
+                Err(Error::FailureToReceiveOnChannel(s))
+            }
+        }
+    }
 }
 
 impl Server {
@@ -297,6 +557,74 @@ impl Client {
         self.cli.node_info(self.ctx).await
     }
 
+    /// Cancel a running [Workflow], given its Cid or local name.
+    ///
+    /// [Workflow]: homestar_workflow::Workflow
+    pub async fn cancel(
+        &self,
+        workflow: FastStr,
+    ) -> Result<Result<response::AckCancel, Error>, RpcError> {
+        self.cli.cancel(self.ctx, workflow).await
+    }
+
+    /// List stored [Workflow]s matching a [WorkflowFilter].
+    ///
+    /// [Workflow]: homestar_workflow::Workflow
+    pub async fn list_workflows(
+        &self,
+        filter: WorkflowFilter,
+    ) -> Result<Result<response::AckWorkflowList, Error>, RpcError> {
+        self.cli.list_workflows(self.ctx, filter).await
+    }
+
+    /// Show a stored [Workflow], given its Cid or local name.
+    ///
+    /// [Workflow]: homestar_workflow::Workflow
+    pub async fn show_workflow(
+        &self,
+        workflow: FastStr,
+    ) -> Result<Result<Box<response::AckWorkflowDetail>, Error>, RpcError> {
+        self.cli.show_workflow(self.ctx, workflow).await
+    }
+
+    /// Get a [Receipt], given its Cid or the Cid of the [Instruction] it was
+    /// issued for, optionally including its raw DAG-CBOR bytes.
+    ///
+    /// [Receipt]: crate::Receipt
+    /// [Instruction]: homestar_invocation::task::Instruction
+    pub async fn get_receipt(
+        &self,
+        cid: FastStr,
+        lookup: ReceiptLookup,
+        cbor: bool,
+    ) -> Result<Result<Box<response::AckReceipt>, Error>, RpcError> {
+        self.cli.get_receipt(self.ctx, cid, lookup, cbor).await
+    }
+
+    /// Get a stored [Workflow]'s document, given its Cid or local name,
+    /// optionally including its raw DAG-CBOR bytes.
+    ///
+    /// [Workflow]: homestar_workflow::Workflow
+    pub async fn get_workflow(
+        &self,
+        workflow: FastStr,
+        cbor: bool,
+    ) -> Result<Result<Box<response::AckWorkflowDocument>, Error>, RpcError> {
+        self.cli.get_workflow(self.ctx, workflow, cbor).await
+    }
+
+    /// Verify a stored [Receipt], given its Cid, by re-executing the
+    /// [Instruction] it was issued for and comparing outputs.
+    ///
+    /// [Receipt]: crate::Receipt
+    /// [Instruction]: homestar_invocation::task::Instruction
+    pub async fn verify_receipt(
+        &self,
+        cid: FastStr,
+    ) -> Result<Result<Box<response::AckVerify>, Error>, RpcError> {
+        self.cli.verify_receipt(self.ctx, cid).await
+    }
+
     /// Run a [Workflow].
     ///
     /// [Workflow]: homestar_workflow::Workflow
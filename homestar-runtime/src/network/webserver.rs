@@ -59,8 +59,9 @@ pub(crate) enum Message {
     RunErr(runner::Error),
     /// Run a workflow, given a tuple of name, and [Workflow].
     RunWorkflow((FastStr, Workflow<'static, Arg>)),
-    /// Acknowledgement of a [Workflow] run.
-    AckWorkflow((Cid, FastStr)),
+    /// Acknowledgement of a [Workflow] run, with its position in the run
+    /// queue if it's pending.
+    AckWorkflow((Cid, FastStr, Option<usize>)),
     /// Message sent to the [Runner] to gather node information from the [EventHandler].
     ///
     /// [Runner]: crate::Runner
@@ -69,6 +70,24 @@ pub(crate) enum Message {
     /// Acknowledgement of a [Message::GetNodeInfo] request, receiving static and dynamic
     /// node information.
     AckNodeInfo((StaticNodeInfo, DynamicNodeInfo)),
+    /// Cancel a running [Workflow], given its Cid or local name.
+    CancelWorkflow(FastStr),
+    /// Acknowledgement of a [Message::CancelWorkflow] request.
+    AckCancelWorkflow(runner::response::AckCancel),
+    /// Get a [Receipt], given a Cid, how to look it up, and whether to
+    /// include its raw DAG-CBOR bytes.
+    ///
+    /// [Receipt]: crate::Receipt
+    GetReceipt((FastStr, runner::ReceiptLookup, bool)),
+    /// Acknowledgement of a [Message::GetReceipt] request.
+    AckReceipt(Box<runner::response::AckReceipt>),
+    /// Get all [Receipt]s for a stored [Workflow], given its Cid or local
+    /// name and whether to include raw DAG-CBOR bytes.
+    ///
+    /// [Receipt]: crate::Receipt
+    GetWorkflowReceipts((FastStr, bool)),
+    /// Acknowledgement of a [Message::GetWorkflowReceipts] request.
+    AckWorkflowReceipts(Vec<runner::response::AckReceipt>),
 }
 
 /// Server fields.
@@ -356,8 +375,12 @@ mod test {
     use crate::{channel::AsyncChannel, test_utils::db::MemoryDb};
     #[cfg(feature = "websocket-notify")]
     use crate::{event_handler::notification::ReceiptNotification, test_utils};
-    #[cfg(feature = "websocket-notify")]
-    use homestar_invocation::ipld::DagJson;
+    use homestar_invocation::{
+        authority::UcanPrf,
+        ipld::{DagCbor, DagJson},
+        task::{instruction::RunInstruction, Resources},
+        Task,
+    };
     #[cfg(feature = "websocket-notify")]
     use jsonrpsee::core::client::{error::Error as ClientError, Subscription, SubscriptionClientT};
     #[cfg(feature = "websocket-notify")]
@@ -546,4 +569,58 @@ mod test {
             }
         });
     }
+
+    #[homestar_runtime_proc_macro::runner_test]
+    fn ws_run_workflow_inline() {
+        let TestRunner { runner, settings } = TestRunner::start();
+        runner.runtime.block_on(async {
+            let server = Server::new(settings.node().network().webserver()).unwrap();
+            let db = MemoryDb::setup_connection_pool(settings.node(), None).unwrap();
+            let metrics_hdl = metrics_handle().await;
+            let (runner_tx, runner_rx) = AsyncChannel::oneshot();
+            let _ws_hdl = server.start(runner_tx, metrics_hdl, db).await.unwrap();
+
+            // Stand in for the runner, acknowledging the workflow run as
+            // queued behind another.
+            tokio::spawn(async move {
+                if let Ok((Message::RunWorkflow((name, workflow)), Some(tx))) =
+                    runner_rx.recv_async().await
+                {
+                    let cid = workflow.clone().to_cid().unwrap();
+                    let _ = tx
+                        .send_async(Message::AckWorkflow((cid, name, Some(2))))
+                        .await;
+                }
+            });
+
+            let task = Task::new(
+                RunInstruction::Expanded(homestar_invocation::test_utils::instruction::<Arg>()),
+                Resources::default().into(),
+                UcanPrf::default(),
+            );
+            let workflow = Workflow::new(vec![task]);
+            let workflow_json: serde_json::Value =
+                serde_json::from_str(&workflow.clone().to_json_string().unwrap()).unwrap();
+
+            let ws_url = format!("ws://{}", server.v4_addr);
+            let client = WsClientBuilder::default().build(ws_url).await.unwrap();
+            let ws_resp: serde_json::Value = client
+                .request(
+                    rpc::RUN_WORKFLOW_ENDPOINT,
+                    rpc_params![serde_json::json!({"name": "inline", "workflow": workflow_json})],
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(
+                ws_resp,
+                serde_json::json!({
+                    "cid": workflow.clone().to_cid().unwrap().to_string(),
+                    "name": "inline",
+                    "status": "pending",
+                    "queue_position": 2
+                })
+            );
+        });
+    }
 }
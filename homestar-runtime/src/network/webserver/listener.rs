@@ -9,6 +9,7 @@ use libipld::{serde::from_ipld, Ipld};
 use names::{Generator, Name};
 use serde::{de, Deserialize, Deserializer, Serialize};
 use serde_json::value::RawValue;
+use serde_with::{base64::Base64, serde_as};
 use std::collections::BTreeMap;
 
 const NAME_KEY: &str = "name";
@@ -95,12 +96,62 @@ impl<'a, 'de> Deserialize<'de> for CborRun<'a> {
     }
 }
 
+/// A [Workflow] run command via JSON-RPC, without a subscription, given the
+/// workflow as a DAG-JSON object or as a base64-encoded DAG-CBOR string.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct InlineRun<'a> {
+    #[serde(default = "default_name")]
+    pub(crate) name: FastStr,
+    #[serde(deserialize_with = "from_inline_value")]
+    pub(crate) workflow: Workflow<'a, Arg>,
+}
+
+/// Base64-encoded DAG-CBOR bytes.
+#[serde_as]
+#[derive(Serialize, Deserialize)]
+#[serde(transparent)]
+struct Base64Bytes(#[serde_as(as = "Base64")] Vec<u8>);
+
+fn from_inline_value<'a, 'de, D>(deserializer: D) -> Result<Workflow<'a, Arg>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw_value: &RawValue = Deserialize::deserialize(deserializer)?;
+    if raw_value.get().starts_with('"') {
+        let Base64Bytes(bytes) =
+            serde_json::from_str(raw_value.get()).map_err(de::Error::custom)?;
+        let ipld: Ipld = serde_ipld_dagcbor::from_slice(&bytes).map_err(de::Error::custom)?;
+        Workflow::try_from(ipld).map_err(de::Error::custom)
+    } else {
+        Workflow::from_json(raw_value.get().as_bytes()).map_err(de::Error::custom)
+    }
+}
+
 /// Filter metrics by prefix.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub(crate) struct MetricsPrefix {
     pub(crate) prefix: String,
 }
 
+/// Get a receipt by its Cid or, if `instruction` is set, by the Cid of the
+/// instruction it was issued for.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct GetReceipt {
+    pub(crate) cid: String,
+    #[serde(default)]
+    pub(crate) instruction: bool,
+    #[serde(default)]
+    pub(crate) cbor: bool,
+}
+
+/// Get the receipts for a workflow by its Cid or local name.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct GetWorkflowReceipts {
+    pub(crate) workflow: String,
+    #[serde(default)]
+    pub(crate) cbor: bool,
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -162,4 +213,33 @@ mod test {
         let run2: CborRun<'_> = DagCbor::from_cbor(&cbor_file).unwrap();
         assert_eq!(run1, run2);
     }
+
+    #[test]
+    fn run_inline_json_and_cbor() {
+        let config = Resources::default();
+        let instruction = test_utils::instruction::<Arg>();
+        let task = Task::new(
+            RunInstruction::Expanded(instruction),
+            config.into(),
+            UcanPrf::default(),
+        );
+        let workflow = Workflow::new(vec![task]);
+
+        let json_str = format!(
+            r#"{{"name": "test", "workflow": {}}}"#,
+            workflow.clone().to_json_string().unwrap()
+        );
+        let json_run: InlineRun<'_> = serde_json::from_str(&json_str).unwrap();
+        assert_eq!(json_run.name, "test");
+        assert_eq!(json_run.workflow, workflow);
+
+        let encoded =
+            serde_json::to_string(&Base64Bytes(workflow.clone().to_cbor().unwrap())).unwrap();
+        let cbor_run: InlineRun<'_> =
+            serde_json::from_str(&format!(r#"{{"workflow": {encoded}}}"#)).unwrap();
+        assert_eq!(cbor_run.workflow, workflow);
+        assert!(!cbor_run.name.is_empty());
+
+        assert!(serde_json::from_str::<InlineRun<'_>>(r#"{"workflow": "not base64!"}"#).is_err());
+    }
 }
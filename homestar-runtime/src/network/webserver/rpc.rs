@@ -8,7 +8,7 @@ use super::{listener, prom::PrometheusData, Message};
 use crate::channel::{AsyncChannel, AsyncChannelReceiver};
 use crate::{
     db::Database,
-    runner::{NodeInfo, WsSender},
+    runner::{response::AckRunWorkflow, NodeInfo, ReceiptLookup, WsSender},
 };
 #[cfg(feature = "websocket-notify")]
 use anyhow::anyhow;
@@ -62,6 +62,16 @@ pub(crate) const HEALTH_ENDPOINT: &str = "health";
 pub(crate) const METRICS_ENDPOINT: &str = "metrics";
 /// Node information endpoint.
 pub(crate) const NODE_INFO_ENDPOINT: &str = "node";
+/// Cancel a running workflow, given its Cid or local name.
+pub(crate) const CANCEL_WORKFLOW_ENDPOINT: &str = "cancel_workflow";
+/// Run a workflow, given inline as DAG-JSON or base64-encoded DAG-CBOR,
+/// without subscribing to its events.
+pub(crate) const RUN_WORKFLOW_ENDPOINT: &str = "run_workflow";
+/// Get a receipt, given its Cid or the Cid of the instruction it was issued
+/// for.
+pub(crate) const GET_RECEIPT_ENDPOINT: &str = "get_receipt";
+/// Get the receipts for a workflow, given its Cid or local name.
+pub(crate) const GET_RECEIPTS_BY_WORKFLOW_ENDPOINT: &str = "get_receipts_by_workflow";
 /// Run a workflow and subscribe to that workflow's events.
 #[cfg(feature = "websocket-notify")]
 pub(crate) const SUBSCRIBE_RUN_WORKFLOW_ENDPOINT: &str = "subscribe_run_workflow";
@@ -219,6 +229,118 @@ where
             }
         })?;
 
+        module.register_async_method(CANCEL_WORKFLOW_ENDPOINT, |params, ctx| async move {
+            let workflow = params.one::<String>()?;
+            let (tx, rx) = crate::channel::AsyncChannel::oneshot();
+            ctx.runner_sender
+                .send_async((Message::CancelWorkflow(workflow.into()), Some(tx)))
+                .await
+                .map_err(|err| internal_err(err.to_string()))?;
+
+            match rx.recv_async().await {
+                Ok(Message::AckCancelWorkflow(ack)) => Ok(serde_json::json!(ack)),
+                Ok(Message::RunErr(err)) => Err(internal_err(err.to_string())),
+                _ => {
+                    error!(
+                        subject = "call.cancel_workflow",
+                        category = "jsonrpc.call",
+                        sub = CANCEL_WORKFLOW_ENDPOINT,
+                        "did not acknowledge message in time"
+                    );
+                    Err(internal_err("failed to cancel workflow".to_string()))
+                }
+            }
+        })?;
+
+        module.register_async_method(RUN_WORKFLOW_ENDPOINT, |params, ctx| async move {
+            let listener::InlineRun { name, workflow } = params
+                .one::<listener::InlineRun<'_>>()
+                .or_else(|_| params.parse::<listener::InlineRun<'_>>())?;
+            let (tx, rx) = crate::channel::AsyncChannel::oneshot();
+            ctx.runner_sender
+                .send_async((Message::RunWorkflow((name, workflow)), Some(tx)))
+                .await
+                .map_err(|err| internal_err(err.to_string()))?;
+
+            match rx.recv_async().await {
+                Ok(Message::AckWorkflow((cid, name, queue_position))) => Ok(serde_json::json!(
+                    AckRunWorkflow::new(cid, name, queue_position)
+                )),
+                Ok(Message::RunErr(err)) => Err(internal_err(err.to_string())),
+                _ => {
+                    error!(
+                        subject = "call.run_workflow",
+                        category = "jsonrpc.call",
+                        sub = RUN_WORKFLOW_ENDPOINT,
+                        "did not acknowledge message in time"
+                    );
+                    Err(internal_err("failed to run workflow".to_string()))
+                }
+            }
+        })?;
+
+        module.register_async_method(GET_RECEIPT_ENDPOINT, |params, ctx| async move {
+            let listener::GetReceipt {
+                cid,
+                instruction,
+                cbor,
+            } = params.parse::<listener::GetReceipt>()?;
+            let lookup = if instruction {
+                ReceiptLookup::Instruction
+            } else {
+                ReceiptLookup::Receipt
+            };
+            let (tx, rx) = crate::channel::AsyncChannel::oneshot();
+            ctx.runner_sender
+                .send_async((Message::GetReceipt((cid.into(), lookup, cbor)), Some(tx)))
+                .await
+                .map_err(|err| internal_err(err.to_string()))?;
+
+            match rx.recv_async().await {
+                Ok(Message::AckReceipt(ack)) => Ok(serde_json::json!(ack)),
+                Ok(Message::RunErr(err)) => Err(internal_err(err.to_string())),
+                _ => {
+                    error!(
+                        subject = "call.get_receipt",
+                        category = "jsonrpc.call",
+                        sub = GET_RECEIPT_ENDPOINT,
+                        "did not acknowledge message in time"
+                    );
+                    Err(internal_err("failed to get receipt".to_string()))
+                }
+            }
+        })?;
+
+        module.register_async_method(
+            GET_RECEIPTS_BY_WORKFLOW_ENDPOINT,
+            |params, ctx| async move {
+                let listener::GetWorkflowReceipts { workflow, cbor } =
+                    params.parse::<listener::GetWorkflowReceipts>()?;
+                let (tx, rx) = crate::channel::AsyncChannel::oneshot();
+                ctx.runner_sender
+                    .send_async((
+                        Message::GetWorkflowReceipts((workflow.into(), cbor)),
+                        Some(tx),
+                    ))
+                    .await
+                    .map_err(|err| internal_err(err.to_string()))?;
+
+                match rx.recv_async().await {
+                    Ok(Message::AckWorkflowReceipts(acks)) => Ok(serde_json::json!(acks)),
+                    Ok(Message::RunErr(err)) => Err(internal_err(err.to_string())),
+                    _ => {
+                        error!(
+                            subject = "call.get_receipts_by_workflow",
+                            category = "jsonrpc.call",
+                            sub = GET_RECEIPTS_BY_WORKFLOW_ENDPOINT,
+                            "did not acknowledge message in time"
+                        );
+                        Err(internal_err("failed to get workflow receipts".to_string()))
+                    }
+                }
+            },
+        )?;
+
         #[cfg(feature = "websocket-notify")]
         module.register_subscription(
             SUBSCRIBE_NETWORK_EVENTS_ENDPOINT,
@@ -294,7 +416,7 @@ where
         ctx: Arc<Context<DB>>,
         pending: PendingSubscriptionSink,
     ) -> Result<()> {
-        if let Ok(Message::AckWorkflow((cid, name))) = rx.recv_async().await {
+        if let Ok(Message::AckWorkflow((cid, name, _))) = rx.recv_async().await {
             let sink = pending.accept().await?;
             ctx.workflow_listeners
                 .insert(sink.subscription_id(), (cid, name));
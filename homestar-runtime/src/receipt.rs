@@ -25,6 +25,8 @@ use serde::{Deserialize, Serialize};
 use std::{collections::BTreeMap, fmt};
 
 pub(crate) mod metadata;
+pub(crate) mod trust;
+pub(crate) mod verify;
 
 /// General version key for receipts.
 pub const VERSION_KEY: &str = "version";
@@ -130,6 +132,21 @@ impl Receipt {
         self.meta = LocalIpld(meta)
     }
 
+    /// Whether the [Receipt] is for a task that was skipped, rather than
+    /// run, which is local to the workflow that skipped it.
+    pub(crate) fn skipped(&self) -> bool {
+        matches!(
+            self.meta(),
+            Ipld::Map(meta) if meta.get(metadata::SKIPPED_KEY) == Some(&Ipld::Bool(true))
+        )
+    }
+
+    /// Whether the [Receipt] is for a task that failed, with its error
+    /// captured on the `error` branch of its output.
+    pub(crate) fn failed(&self) -> bool {
+        matches!(self.output(), task::Result::Error(_))
+    }
+
     /// Get unique identifier of receipt.
     pub fn cid(&self) -> Cid {
         self.cid.cid()
@@ -162,6 +179,11 @@ impl Receipt {
         self.ran.to_string()
     }
 
+    /// Get the [Issuer] of the [Receipt], if any.
+    pub fn issuer(&self) -> &Option<Issuer> {
+        &self.issuer
+    }
+
     /// Get executed result/value in [Receipt] as Ipld.
     pub fn output(&self) -> &task::Result<Ipld> {
         &self.out
@@ -12,3 +12,29 @@ pub(crate) const WORKFLOW_KEY: &str = "workflow";
 /// Associated metadata key for a workflow name, which
 /// will either be some identifier, or the Cid of the workflow.
 pub(crate) const WORKFLOW_NAME_KEY: &str = "name";
+
+/// Metadata key for the number of attempts taken to execute a task, which is
+/// only recorded for tasks with a retry policy.
+pub(crate) const ATTEMPTS_KEY: &str = "attempts";
+
+/// Metadata key marking a receipt for a task that was skipped, rather than
+/// run, as its condition didn't hold.
+///
+/// Skipped receipts are local to the workflow that skipped the task, so are
+/// neither published nor reused for the same instruction elsewhere.
+pub(crate) const SKIPPED_KEY: &str = "skipped";
+
+/// Metadata key for the mapped instruction an instruction was expanded from,
+/// one per element of the list it was mapped over.
+pub(crate) const MAPPED_FROM_KEY: &str = "mapped_from";
+
+/// Metadata key for the decision to trust, or not, a receipt received from
+/// peers in place of running its instruction.
+pub(crate) const TRUST_KEY: &str = "trust";
+
+/// Metadata key for the nested workflow Cid run by a `workflow/run` task.
+pub(crate) const NESTED_WORKFLOW_KEY: &str = "nested_workflow";
+
+/// Metadata key for the parent workflow Cid of a workflow run by a
+/// `workflow/run` task.
+pub(crate) const PARENT_WORKFLOW_KEY: &str = "parent_workflow";
@@ -0,0 +1,322 @@
+//! Decisions on whether to reuse [Receipt]s received from peers, per a node's
+//! [ReceiptTrust] policy.
+
+use crate::{
+    db::{Connection, Database},
+    settings::ReceiptTrust,
+    Db, Receipt,
+};
+use anyhow::Result;
+use libipld::{multibase, Cid, Ipld};
+use libp2p::identity::{ed25519, secp256k1, PublicKey};
+use std::collections::BTreeMap;
+
+/// Peer recorded for receipts whose publisher isn't authenticated, e.g.
+/// receipts found on the DHT, which never matches an allowlist or counts
+/// toward a quorum.
+pub(crate) const UNKNOWN_PEER: &str = "unknown";
+
+/// Peer recorded for receipts issued by this node, which are always reused.
+pub(crate) const LOCAL_PEER: &str = "local";
+
+const POLICY_KEY: &str = "policy";
+const TRUSTED_KEY: &str = "trusted";
+const RECEIPT_KEY: &str = "receipt";
+const PEERS_KEY: &str = "peers";
+const DID_KEY_PREFIX: &str = "did:key:";
+
+/// Decision on whether to reuse a [Receipt].
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Decision {
+    /// Receipt was issued by this node, and is always reused.
+    Local,
+    /// Receipt was received from the given peers, and is trusted.
+    Trusted(Vec<String>),
+    /// Receipt was received from the given peers, but isn't trusted.
+    Untrusted(Vec<String>),
+}
+
+impl Decision {
+    /// Decide whether to reuse a [Receipt] received from the given peers.
+    ///
+    /// Receipts recorded as issued by this node, via [LOCAL_PEER], are always
+    /// reused, while receipts without any recorded peers, e.g. ones stored
+    /// before peers were recorded, are only reused if any receipt is.
+    pub(crate) fn new(policy: &ReceiptTrust, mut peers: Vec<String>) -> Self {
+        if peers.iter().any(|peer| peer == LOCAL_PEER) {
+            return Decision::Local;
+        }
+
+        peers.sort();
+        peers.dedup();
+
+        let trusted = match policy {
+            ReceiptTrust::Any => true,
+            ReceiptTrust::Allowlist { peers: allowed } => {
+                let allowed = allowed.iter().map(|peer| peer_id(peer)).collect::<Vec<_>>();
+                peers
+                    .iter()
+                    .any(|peer| peer != UNKNOWN_PEER && allowed.contains(peer))
+            }
+            ReceiptTrust::Quorum { quorum } => {
+                peers.iter().filter(|peer| *peer != UNKNOWN_PEER).count() >= *quorum
+            }
+            ReceiptTrust::Never => false,
+        };
+
+        if trusted {
+            Decision::Trusted(peers)
+        } else {
+            Decision::Untrusted(peers)
+        }
+    }
+
+    /// Decide whether to reuse a stored [Receipt], given the peers it was
+    /// recorded as received from.
+    pub(crate) fn for_stored(
+        policy: &ReceiptTrust,
+        receipt: &Receipt,
+        conn: &mut Connection,
+    ) -> Result<Self> {
+        let peers = Db::find_receipt_peers(receipt.cid(), conn)?;
+        Ok(Self::new(policy, peers))
+    }
+
+    /// Whether the [Receipt] can be reused.
+    pub(crate) fn is_trusted(&self) -> bool {
+        !matches!(self, Decision::Untrusted(_))
+    }
+
+    /// Metadata recording the decision for a [Receipt] received from peers,
+    /// or `None` for receipts issued by this node.
+    pub(crate) fn to_meta(&self, policy: &ReceiptTrust, receipt: &Receipt) -> Option<Ipld> {
+        let (trusted, peers) = match self {
+            Decision::Local => return None,
+            Decision::Trusted(peers) => (true, peers),
+            Decision::Untrusted(peers) => (false, peers),
+        };
+
+        Some(Ipld::Map(BTreeMap::from([
+            (POLICY_KEY.into(), policy.to_string().into()),
+            (TRUSTED_KEY.into(), trusted.into()),
+            (RECEIPT_KEY.into(), receipt.cid().into()),
+            (
+                PEERS_KEY.into(),
+                Ipld::List(peers.iter().map(|peer| peer.as_str().into()).collect()),
+            ),
+        ])))
+    }
+}
+
+/// Peer ID of an allowlisted peer, given either as a peer ID or as a
+/// `did:key` DID of the peer's Ed25519 or secp256k1 public key.
+fn peer_id(peer: &str) -> String {
+    let public_key = peer
+        .strip_prefix(DID_KEY_PREFIX)
+        .and_then(|key| multibase::decode(key).ok())
+        .and_then(|(_, bytes)| match bytes.as_slice() {
+            // Multicodec-prefixed Ed25519 or secp256k1 public keys.
+            [0xed, 0x01, key @ ..] => ed25519::PublicKey::try_from_bytes(key)
+                .ok()
+                .map(PublicKey::from),
+            [0xe7, 0x01, key @ ..] => secp256k1::PublicKey::try_from_bytes(key)
+                .ok()
+                .map(PublicKey::from),
+            _ => None,
+        });
+
+    public_key.map_or_else(|| peer.to_string(), |key| key.to_peer_id().to_string())
+}
+
+/// Peers a [Receipt] was received from so far, or `None` if this node issued
+/// the receipt itself.
+pub(crate) fn remote_peers(receipt_cid: Cid, conn: &mut Connection) -> Result<Option<Vec<String>>> {
+    let peers = Db::find_receipt_peers(receipt_cid, conn)?;
+    if peers.iter().any(|peer| peer == LOCAL_PEER) {
+        Ok(None)
+    } else {
+        Ok(Some(peers))
+    }
+}
+
+/// Record a [Receipt] as issued by this node, forgetting the peers it may
+/// have been received from before.
+pub(crate) fn mark_local(receipt_cid: Cid, conn: &mut Connection) -> Result<()> {
+    Db::delete_receipt_peers(receipt_cid, conn)?;
+    Db::store_receipt_peer(receipt_cid, LOCAL_PEER, conn)?;
+    Ok(())
+}
+
+/// Store a [Receipt] received from a peer, recording the peer unless this
+/// node issued the receipt itself.
+///
+/// The peer must be authenticated, e.g. the signed source of a gossipsub
+/// message, or [UNKNOWN_PEER] otherwise.
+pub(crate) fn store_from_peer(receipt: Receipt, peer: &str, conn: &mut Connection) -> Result<()> {
+    let receipt_cid = receipt.cid();
+    let remote = remote_peers(receipt_cid, conn)?.is_some();
+    Db::store_receipt(receipt, conn)?;
+    if remote {
+        Db::store_receipt_peer(receipt_cid, peer, conn)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::{self, db::MemoryDb};
+
+    fn receipt() -> Receipt {
+        let (_, receipt) = test_utils::receipt::receipts();
+        receipt
+    }
+
+    #[test]
+    fn receipts_issued_locally_are_always_trusted() {
+        let receipt = receipt();
+        let decision = Decision::new(&ReceiptTrust::Never, vec![LOCAL_PEER.to_string()]);
+
+        assert_eq!(decision, Decision::Local);
+        assert!(decision.is_trusted());
+        assert_eq!(decision.to_meta(&ReceiptTrust::Never, &receipt), None);
+    }
+
+    #[test]
+    fn receipts_without_peers_are_not_local() {
+        let allowlist = ReceiptTrust::Allowlist {
+            peers: vec!["peer-a".to_string()],
+        };
+
+        assert_eq!(
+            Decision::new(&allowlist, vec![]),
+            Decision::Untrusted(vec![])
+        );
+        assert!(!Decision::new(&ReceiptTrust::Quorum { quorum: 1 }, vec![]).is_trusted());
+        assert!(Decision::new(&ReceiptTrust::Any, vec![]).is_trusted());
+    }
+
+    #[homestar_runtime_proc_macro::db_async_test]
+    fn only_receipts_marked_local_are_local() {
+        let settings = TestSettings::load();
+        let db = MemoryDb::setup_connection_pool(&settings.node, None).unwrap();
+        let mut conn = db.conn().unwrap();
+        let receipt = receipt();
+
+        // Stored without any recorded peers, e.g. before peers were recorded.
+        MemoryDb::store_receipt(receipt.clone(), &mut conn).unwrap();
+        assert_eq!(
+            remote_peers(receipt.cid(), &mut conn).unwrap(),
+            Some(vec![])
+        );
+
+        store_from_peer(receipt.clone(), "peer-a", &mut conn).unwrap();
+        mark_local(receipt.cid(), &mut conn).unwrap();
+        assert_eq!(remote_peers(receipt.cid(), &mut conn).unwrap(), None);
+
+        store_from_peer(receipt.clone(), "peer-b", &mut conn).unwrap();
+        assert_eq!(
+            Decision::for_stored(&ReceiptTrust::Never, &receipt, &mut conn).unwrap(),
+            Decision::Local
+        );
+    }
+
+    #[test]
+    fn receipts_from_peers_follow_policy() {
+        let peers = vec!["peer-b".to_string(), "peer-a".to_string()];
+
+        assert!(Decision::new(&ReceiptTrust::Any, peers.clone()).is_trusted());
+        assert!(!Decision::new(&ReceiptTrust::Never, peers.clone()).is_trusted());
+
+        let allowlist = ReceiptTrust::Allowlist {
+            peers: vec!["peer-a".to_string()],
+        };
+        assert!(Decision::new(&allowlist, peers.clone()).is_trusted());
+        assert!(!Decision::new(&allowlist, vec!["peer-c".to_string()]).is_trusted());
+
+        let quorum = ReceiptTrust::Quorum { quorum: 2 };
+        assert_eq!(
+            Decision::new(&quorum, peers.clone()),
+            Decision::Trusted(vec!["peer-a".to_string(), "peer-b".to_string()])
+        );
+        assert!(!Decision::new(
+            &quorum,
+            vec![
+                "peer-a".to_string(),
+                "peer-a".to_string(),
+                UNKNOWN_PEER.to_string()
+            ]
+        )
+        .is_trusted());
+    }
+
+    #[test]
+    fn allowlisted_dids_resolve_to_peer_ids() {
+        let ed25519 = libp2p::identity::Keypair::generate_ed25519();
+        let secp256k1 = libp2p::identity::Keypair::generate_secp256k1();
+        let did = |codec: [u8; 2], key: Vec<u8>| {
+            let bytes = [codec.as_slice(), key.as_slice()].concat();
+            format!(
+                "{DID_KEY_PREFIX}{}",
+                multibase::encode(multibase::Base::Base58Btc, bytes)
+            )
+        };
+
+        let allowlist = ReceiptTrust::Allowlist {
+            peers: vec![
+                did(
+                    [0xed, 0x01],
+                    ed25519
+                        .public()
+                        .try_into_ed25519()
+                        .unwrap()
+                        .to_bytes()
+                        .to_vec(),
+                ),
+                did(
+                    [0xe7, 0x01],
+                    secp256k1
+                        .public()
+                        .try_into_secp256k1()
+                        .unwrap()
+                        .to_bytes()
+                        .to_vec(),
+                ),
+            ],
+        };
+        for keypair in [ed25519, secp256k1] {
+            let peer = keypair.public().to_peer_id().to_string();
+            assert!(Decision::new(&allowlist, vec![peer]).is_trusted());
+        }
+        assert!(!Decision::new(&allowlist, vec!["peer-a".to_string()]).is_trusted());
+    }
+
+    #[test]
+    fn unknown_peers_are_never_trusted() {
+        let allowlist = ReceiptTrust::Allowlist {
+            peers: vec![UNKNOWN_PEER.to_string()],
+        };
+        let peers = vec![UNKNOWN_PEER.to_string(), UNKNOWN_PEER.to_string()];
+
+        assert!(!Decision::new(&allowlist, peers.clone()).is_trusted());
+        assert!(!Decision::new(&ReceiptTrust::Quorum { quorum: 1 }, peers).is_trusted());
+    }
+
+    #[test]
+    fn decisions_as_metadata() {
+        let receipt = receipt();
+        let quorum = ReceiptTrust::Quorum { quorum: 2 };
+        let decision = Decision::new(&quorum, vec!["peer-a".to_string()]);
+
+        assert_eq!(
+            decision.to_meta(&quorum, &receipt),
+            Some(Ipld::Map(BTreeMap::from([
+                (POLICY_KEY.into(), "quorum".into()),
+                (TRUSTED_KEY.into(), false.into()),
+                (RECEIPT_KEY.into(), receipt.cid().into()),
+                (PEERS_KEY.into(), Ipld::List(vec!["peer-a".into()])),
+            ])))
+        );
+    }
+}
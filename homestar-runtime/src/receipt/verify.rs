@@ -0,0 +1,391 @@
+//! Verification of [Receipt]s, e.g. ones received from peers, by
+//! deterministically re-executing the [Instruction] they were issued for and
+//! comparing outputs.
+//!
+//! [Instruction]: homestar_invocation::task::Instruction
+
+#[cfg(feature = "ipfs")]
+use crate::network::IpfsCli;
+use crate::{
+    db::Database,
+    settings::{self, OnMismatch, ReceiptTrust},
+    tasks::{Fetch, Registry, TaskInput},
+    worker::{Resolver, KIND_KEY},
+    workflow::{self, Resource},
+    Db, Receipt,
+};
+use anyhow::{anyhow, Result};
+use fnv::FnvHashSet;
+use futures::FutureExt;
+use homestar_invocation::{
+    ipld::DagCbor,
+    task::{
+        self,
+        instruction::{Parse, RunInstruction},
+        Map, Resources,
+    },
+    Task,
+};
+use homestar_wasm::io::Arg;
+use homestar_workflow::{LinkMap, Workflow};
+use indexmap::IndexMap;
+use libipld::{Cid, Ipld};
+use std::{fmt, sync::Arc};
+use tokio::sync::{RwLock, Semaphore};
+use tracing::{debug, warn};
+
+/// Outcome of re-executing a [Receipt]'s [Instruction].
+///
+/// [Instruction]: homestar_invocation::task::Instruction
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Verdict {
+    /// Re-executed output matches the receipt's output.
+    Match,
+    /// Re-executed output differs from the receipt's output.
+    Mismatch {
+        /// Output recorded in the receipt.
+        expected: task::Result<Ipld>,
+        /// Output of re-executing the instruction.
+        actual: task::Result<Ipld>,
+    },
+    /// Receipt couldn't be re-executed, e.g. its instruction isn't part of
+    /// any locally stored workflow, or one of its inputs can't be resolved.
+    Unverifiable(String),
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Match => write!(f, "match"),
+            Verdict::Mismatch { .. } => write!(f, "mismatch"),
+            Verdict::Unverifiable(_) => write!(f, "unverifiable"),
+        }
+    }
+}
+
+/// Verifier for [Receipt]s, applying the node's [settings::Verification]
+/// policy to receipts received from peers.
+#[derive(Debug, Clone)]
+pub(crate) struct Verifier {
+    settings: settings::Verification,
+    trust: Arc<ReceiptTrust>,
+    /// Registered [Executor]s to re-execute instructions on.
+    ///
+    /// [Executor]: crate::Executor
+    executors: Arc<Registry>,
+    /// Node-wide permits bounding concurrently running tasks, which
+    /// re-executions are subject to as well.
+    task_permits: Arc<Semaphore>,
+    #[cfg(feature = "ipfs")]
+    ipfs: settings::Ipfs,
+}
+
+impl Verifier {
+    /// Create a new [Verifier] from network settings, re-executing
+    /// instructions on the node's registered [Executor]s, within the node's
+    /// task permits.
+    ///
+    /// [Executor]: crate::Executor
+    pub(crate) fn new(
+        settings: &settings::Network,
+        executors: Arc<Registry>,
+        task_permits: Arc<Semaphore>,
+    ) -> Self {
+        Self {
+            settings: settings.verification().to_owned(),
+            trust: Arc::new(settings.libp2p().dht.receipt_trust.clone()),
+            executors,
+            task_permits,
+            #[cfg(feature = "ipfs")]
+            ipfs: settings.ipfs().to_owned(),
+        }
+    }
+
+    /// Whether a receipt received from a peer is sampled for verification,
+    /// given the configured sample rate.
+    pub(crate) fn sample(&self) -> bool {
+        self.settings.sample_rate > 0.0 && rand::random::<f64>() < self.settings.sample_rate
+    }
+
+    /// Re-execute the [Instruction] a [Receipt] was issued for, with the
+    /// same module and inputs, and compare outputs.
+    ///
+    /// The instruction is looked up in locally stored workflow documents, and
+    /// its inputs are resolved from locally stored receipts, trusted under the
+    /// node's [ReceiptTrust] policy. Errors are compared by kind only, as
+    /// their messages may differ across nodes, and re-executions failing
+    /// with a transient error, e.g. a timeout, are unverifiable.
+    ///
+    /// [Instruction]: homestar_invocation::task::Instruction
+    pub(crate) async fn verify(&self, receipt: &Receipt, db: impl Database) -> Result<Verdict> {
+        let instruction_cid = receipt.instruction().cid();
+        if let task::Result::Just(_) = receipt.output() {
+            return Ok(Verdict::Unverifiable(
+                "receipt output was not computed".to_string(),
+            ));
+        }
+
+        let Some(task) = find_task(instruction_cid, &db)? else {
+            return Ok(Verdict::Unverifiable(format!(
+                "no stored workflow includes instruction: {instruction_cid}"
+            )));
+        };
+
+        if Map::from_meta(task.meta())?.is_some() {
+            return Ok(Verdict::Unverifiable(
+                "mapped instructions are not re-executed".to_string(),
+            ));
+        }
+
+        let resources = Resources::try_from(task.meta())?;
+        let RunInstruction::Expanded(instruction) = task.into_instruction() else {
+            return Ok(Verdict::Unverifiable(
+                "instruction is not expanded".to_string(),
+            ));
+        };
+
+        let Some(executor) = self.executors.get(instruction.op()) else {
+            return Ok(Verdict::Unverifiable(format!(
+                "unsupported operation: {}",
+                instruction.op()
+            )));
+        };
+
+        let parsed = instruction
+            .input()
+            .parse()
+            .map_err(|err| anyhow!(err.to_string()))?;
+        let fun = parsed.fun();
+        if executor.requires_fun() && fun.is_none() {
+            return Ok(Verdict::Unverifiable("no function defined".to_string()));
+        }
+
+        let linkmap = Arc::new(RwLock::new(LinkMap::<task::Result<Arg>>::default()));
+        let found = Arc::new(RwLock::new(IndexMap::default()));
+        let trust = self.trust.clone();
+        let lookup_fn = move |cid: Cid| {
+            cid.resolve(linkmap.clone(), found.clone(), trust.clone(), db.clone())
+                .boxed()
+        };
+        let args = match parsed.into_args().resolve(lookup_fn).await {
+            Ok(args) => args,
+            Err(err) => {
+                return Ok(Verdict::Unverifiable(format!(
+                    "cannot resolve input: {err}"
+                )))
+            }
+        };
+
+        let rsc = Resource::Url(instruction.resource().to_owned());
+        let bytes = if executor.loads_resource() {
+            let Some(bytes) = self.fetch(rsc.clone()).await?.swap_remove(&rsc) else {
+                return Ok(Verdict::Unverifiable(format!(
+                    "resource not available: {rsc}"
+                )));
+            };
+            Some(bytes)
+        } else {
+            None
+        };
+
+        let input = TaskInput {
+            resource: instruction.resource().to_owned(),
+            fun,
+            args,
+            resources,
+            bytes,
+        };
+        let _permit = self.task_permits.clone().acquire_owned().await?;
+        let actual = match executor.execute(input).await {
+            Ok(output) => task::Result::Ok(output),
+            Err(err) if err.kind().is_retryable() => {
+                return Ok(Verdict::Unverifiable(format!(
+                    "re-execution failed transiently: {err}"
+                )))
+            }
+            Err(err) => task::Result::Error(Ipld::from(err)),
+        };
+
+        Ok(compare(receipt.output(), actual))
+    }
+
+    /// Verify a [Receipt] received from a peer, returning whether it should
+    /// be kept.
+    ///
+    /// Only mismatched receipts are dropped, and only if the node's policy is
+    /// to [reject] them. Otherwise, mismatched receipts are [flagged], so
+    /// that they're never reused in place of running their instructions.
+    /// Receipts that can't be verified are kept.
+    ///
+    /// [reject]: OnMismatch::Reject
+    /// [flagged]: crate::db::Database::store_flagged_receipt
+    pub(crate) async fn admit(&self, receipt: &Receipt, db: impl Database) -> bool {
+        match self.verify(receipt, db.clone()).await {
+            Ok(Verdict::Match) => {
+                debug!(
+                    subject = "receipt.verify",
+                    category = "receipt",
+                    cid = receipt.cid().to_string(),
+                    "re-executed receipt output matches"
+                );
+                true
+            }
+            Ok(Verdict::Mismatch { expected, actual }) => {
+                warn!(
+                    subject = "receipt.verify.mismatch",
+                    category = "receipt",
+                    cid = receipt.cid().to_string(),
+                    instruction_cid = receipt.instruction().cid().to_string(),
+                    expected = ?expected,
+                    actual = ?actual,
+                    on_mismatch = ?self.settings.on_mismatch,
+                    "re-executed receipt output does not match"
+                );
+
+                match self.settings.on_mismatch {
+                    OnMismatch::Reject => false,
+                    OnMismatch::Flag => {
+                        if let Err(err) = db.conn().and_then(|mut conn| {
+                            Ok(Db::store_flagged_receipt(receipt.cid(), &mut conn)?)
+                        }) {
+                            warn!(
+                                subject = "receipt.verify.err",
+                                category = "receipt",
+                                cid = receipt.cid().to_string(),
+                                err = ?err,
+                                "failed to flag mismatched receipt"
+                            );
+                        }
+                        true
+                    }
+                }
+            }
+            Ok(Verdict::Unverifiable(reason)) => {
+                debug!(
+                    subject = "receipt.verify",
+                    category = "receipt",
+                    cid = receipt.cid().to_string(),
+                    reason = reason,
+                    "receipt could not be re-executed"
+                );
+                true
+            }
+            Err(err) => {
+                warn!(
+                    subject = "receipt.verify.err",
+                    category = "receipt",
+                    cid = receipt.cid().to_string(),
+                    err = ?err,
+                    "failed to re-execute receipt"
+                );
+                true
+            }
+        }
+    }
+
+    #[cfg(feature = "ipfs")]
+    async fn fetch(&self, rsc: Resource) -> Result<IndexMap<Resource, Vec<u8>>> {
+        let ipfs = IpfsCli::new(&self.ipfs)?;
+        Fetch::get_resources(
+            FnvHashSet::from_iter([rsc]),
+            Arc::new(workflow::Settings::default()),
+            ipfs,
+        )
+        .await
+    }
+
+    #[cfg(not(feature = "ipfs"))]
+    async fn fetch(&self, rsc: Resource) -> Result<IndexMap<Resource, Vec<u8>>> {
+        Fetch::get_resources(
+            FnvHashSet::from_iter([rsc]),
+            Arc::new(workflow::Settings::default()),
+        )
+        .await
+    }
+}
+
+/// Find the [Task] for an [Instruction] in locally stored workflow
+/// documents, decoding only the documents of workflows indexed as including
+/// the instruction.
+///
+/// [Instruction]: homestar_invocation::task::Instruction
+fn find_task(instruction_cid: Cid, db: &impl Database) -> Result<Option<Task<'static, Arg>>> {
+    let mut conn = db.conn()?;
+    for workflow_cid in Db::find_instruction_workflows(instruction_cid, &mut conn)? {
+        let Some(document) = Db::select_workflow_document(workflow_cid, &mut conn)? else {
+            continue;
+        };
+        let Ok(workflow) = Workflow::<'static, Arg>::from_cbor(&document) else {
+            continue;
+        };
+
+        let found = workflow.tasks().into_iter().find(|task| {
+            task.instruction_cid()
+                .is_ok_and(|cid| cid == instruction_cid)
+        });
+        if found.is_some() {
+            return Ok(found);
+        }
+    }
+
+    Ok(None)
+}
+
+/// Compare a receipt's output with its re-executed output.
+fn compare(expected: &task::Result<Ipld>, actual: task::Result<Ipld>) -> Verdict {
+    match (expected, &actual) {
+        (task::Result::Ok(expected), task::Result::Ok(actual)) if expected == actual => {
+            Verdict::Match
+        }
+        (task::Result::Error(expected), task::Result::Error(actual))
+            if error_kind(expected) == error_kind(actual) =>
+        {
+            Verdict::Match
+        }
+        _ => Verdict::Mismatch {
+            expected: expected.to_owned(),
+            actual,
+        },
+    }
+}
+
+/// Return the kind of an error output, if given.
+fn error_kind(error: &Ipld) -> Option<&Ipld> {
+    match error {
+        Ipld::Map(map) => map.get(KIND_KEY),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{TaskError, TaskErrorKind};
+
+    #[test]
+    fn errors_compare_by_kind() {
+        let error = |kind, message| task::Result::Error(Ipld::from(TaskError::new(kind, message)));
+
+        assert_eq!(
+            compare(
+                &error(TaskErrorKind::Trap, "unreachable"),
+                error(TaskErrorKind::Trap, "wasm trap: unreachable")
+            ),
+            Verdict::Match
+        );
+        assert!(matches!(
+            compare(
+                &error(TaskErrorKind::Trap, "unreachable"),
+                error(TaskErrorKind::OutOfFuel, "all fuel consumed")
+            ),
+            Verdict::Mismatch { .. }
+        ));
+        assert!(matches!(
+            compare(
+                &task::Result::Ok(Ipld::Integer(1)),
+                error(TaskErrorKind::Trap, "unreachable")
+            ),
+            Verdict::Mismatch { .. }
+        ));
+    }
+}
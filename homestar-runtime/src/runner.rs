@@ -1,16 +1,23 @@
 //! General [Runner] interface for working across multiple workers
 //! and executing workflows.
 
+#[cfg(feature = "websocket-notify")]
+use crate::event_handler::notification::{self, WorkflowStatusNotification};
 #[cfg(feature = "ipfs")]
 use crate::network::IpfsCli;
 use crate::{
     channel::{AsyncChannel, AsyncChannelReceiver, AsyncChannelSender},
-    db::Database,
-    event_handler::{Event, EventHandler},
-    network::{rpc, swarm, webserver},
+    db::{Connection, Database},
+    event_handler::{
+        event::QueryRecord,
+        swarm_event::{FoundEvent, ResponseEvent},
+        Event, EventHandler,
+    },
+    network::{rpc, swarm, swarm::CapsuleTag, webserver},
+    receipt::verify::Verifier,
     settings,
-    tasks::Fetch,
-    worker::WorkerMessage,
+    tasks::{Fetch, Registry},
+    worker::{FetchFn, WorkerMessage},
     workflow::{self, Resource},
     Db, Receipt, Settings, Worker,
 };
@@ -20,36 +27,46 @@ use chrono::NaiveDateTime;
 use dashmap::DashMap;
 use faststr::FastStr;
 use fnv::FnvHashSet;
-use futures::{future::poll_fn, FutureExt};
-use homestar_invocation::Pointer;
+use futures::{future::poll_fn, Future, FutureExt};
+use homestar_invocation::{ipld::DagCbor, Pointer};
 use homestar_wasm::io::Arg;
 use homestar_workflow::Workflow;
 use jsonrpsee::server::ServerHandle;
 use libipld::Cid;
 use metrics_exporter_prometheus::PrometheusHandle;
+use serde::{Deserialize, Serialize};
 #[cfg(not(test))]
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::{collections::HashMap, ops::ControlFlow, rc::Rc, sync::Arc, task::Poll};
+use std::{collections::HashMap, fmt, ops::ControlFlow, rc::Rc, sync::Arc, task::Poll};
 #[cfg(not(windows))]
 use tokio::signal::unix::{signal, SignalKind};
 #[cfg(windows)]
 use tokio::signal::windows;
 use tokio::{
     runtime, select,
+    sync::Semaphore,
     task::{AbortHandle, JoinHandle},
     time,
 };
 use tokio_util::time::{delay_queue, DelayQueue};
 use tracing::{debug, error, info, info_span, instrument, warn, Instrument};
 
+mod admission;
 mod error;
 pub(crate) mod file;
 mod nodeinfo;
 pub mod response;
+use admission::{AdmissionQueue, Queued};
 pub(crate) use error::Error;
 pub use nodeinfo::NodeInfo;
 pub(crate) use nodeinfo::{DynamicNodeInfo, StaticNodeInfo};
 
+/// Reason recorded for a workflow cancelled by request.
+const CANCELLED_REASON: &str = "workflow cancelled";
+
+/// Reason recorded for a workflow that timed out.
+const TIMED_OUT_REASON: &str = "workflow timed out";
+
 /// Name of the thread used for the [Runner] / runtime.
 #[cfg(not(test))]
 const HOMESTAR_THREAD: &str = "homestar-runtime";
@@ -93,6 +110,27 @@ pub(crate) type WsReceiver = AsyncChannelReceiver<(
     Option<AsyncChannelSender<webserver::Message>>,
 )>;
 
+/// How to look up a [Receipt] for a client request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReceiptLookup {
+    /// Look up a [Receipt] by its own Cid.
+    Receipt,
+    /// Look up a [Receipt] by the Cid of the [Instruction] it was issued
+    /// for.
+    ///
+    /// [Instruction]: homestar_invocation::task::Instruction
+    Instruction,
+}
+
+impl fmt::Display for ReceiptLookup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReceiptLookup::Receipt => write!(f, "receipt"),
+            ReceiptLookup::Instruction => write!(f, "instruction"),
+        }
+    }
+}
+
 impl ModifiedSet for RunningTaskSet {
     fn append_or_insert(&self, cid: Cid, mut handles: Vec<AbortHandle>) {
         self.entry(cid)
@@ -109,13 +147,16 @@ impl ModifiedSet for RunningTaskSet {
 /// [Workflows]: homestar_workflow::Workflow
 #[derive(Debug)]
 pub struct Runner {
+    admission_queue: AtomicRefCell<AdmissionQueue>,
     event_sender: Arc<AsyncChannelSender<Event>>,
+    executors: Arc<Registry>,
     expiration_queue: Rc<AtomicRefCell<DelayQueue<Cid>>>,
     node_info: StaticNodeInfo,
     running_tasks: Arc<RunningTaskSet>,
     running_workers: RunningWorkerSet,
     pub(crate) runtime: tokio::runtime::Runtime,
     pub(crate) settings: Arc<Settings>,
+    task_permits: Arc<Semaphore>,
     webserver: Arc<webserver::Server>,
 }
 
@@ -144,6 +185,19 @@ impl Runner {
     /// Initialize and start the Homestar [Runner] / runtime.
     #[cfg(not(test))]
     pub fn start(settings: Settings, db: impl Database + 'static) -> Result<()> {
+        Self::start_with_registry(settings, Registry::default(), db)
+    }
+
+    /// Initialize and start the Homestar [Runner] / runtime, running tasks
+    /// on the [Executor]s of the given [Registry].
+    ///
+    /// [Executor]: crate::Executor
+    #[cfg(not(test))]
+    pub fn start_with_registry(
+        settings: Settings,
+        executors: Registry,
+        db: impl Database + 'static,
+    ) -> Result<()> {
         let runtime = runtime::Builder::new_multi_thread()
             .enable_all()
             .thread_name_fn(|| {
@@ -153,22 +207,36 @@ impl Runner {
             })
             .build()?;
 
-        Self::init(settings, db.clone(), runtime)?.serve(db)
+        Self::init(settings, executors, db.clone(), runtime)?.serve(db)
     }
 
     /// Initialize and start the Homestar [Runner] / runtime.
     #[cfg(test)]
     pub fn start(settings: Settings, db: impl Database + 'static) -> Result<Self> {
+        Self::start_with_registry(settings, Registry::default(), db)
+    }
+
+    /// Initialize and start the Homestar [Runner] / runtime, running tasks
+    /// on the [Executor]s of the given [Registry].
+    ///
+    /// [Executor]: crate::Executor
+    #[cfg(test)]
+    pub fn start_with_registry(
+        settings: Settings,
+        executors: Registry,
+        db: impl Database + 'static,
+    ) -> Result<Self> {
         let runtime = runtime::Builder::new_current_thread()
             .enable_all()
             .build()?;
 
-        let runner = Self::init(settings, db, runtime)?;
+        let runner = Self::init(settings, executors, db, runtime)?;
         Ok(runner)
     }
 
     fn init(
         settings: Settings,
+        executors: Registry,
         db: impl Database + 'static,
         runtime: tokio::runtime::Runtime,
     ) -> Result<Self> {
@@ -185,11 +253,29 @@ impl Runner {
             (ws_msg_tx, ws_evt_tx)
         };
 
+        let executors = Arc::new(executors);
+        let task_permits = Arc::new(Semaphore::new(
+            settings.node.scheduler.max_concurrent_tasks.max(1),
+        ));
+
         #[cfg(feature = "websocket-notify")]
-        let event_handler =
-            EventHandler::new(swarm, db, settings.node().network(), ws_evt_tx, ws_msg_tx);
+        let event_handler = EventHandler::new(
+            swarm,
+            db,
+            settings.node().network(),
+            executors.clone(),
+            task_permits.clone(),
+            ws_evt_tx,
+            ws_msg_tx,
+        );
         #[cfg(not(feature = "websocket-notify"))]
-        let event_handler = EventHandler::new(swarm, db, settings.node().network());
+        let event_handler = EventHandler::new(
+            swarm,
+            db,
+            settings.node().network(),
+            executors.clone(),
+            task_permits.clone(),
+        );
 
         let event_sender = event_handler.sender();
 
@@ -203,13 +289,16 @@ impl Runner {
         let _event_handler_hdl = runtime.spawn(event_handler.start());
 
         Ok(Self {
+            admission_queue: AtomicRefCell::new(AdmissionQueue::default()),
             event_sender,
+            executors,
             expiration_queue: Rc::new(AtomicRefCell::new(DelayQueue::new())),
             node_info: StaticNodeInfo::new(peer_id),
             running_tasks: DashMap::new().into(),
             running_workers: DashMap::new(),
             runtime,
             settings: settings.into(),
+            task_permits,
             webserver: webserver.into(),
         })
     }
@@ -246,6 +335,20 @@ impl Runner {
         let rpc_sender = rpc_server.sender();
         self.runtime.block_on(rpc_server.spawn())?;
 
+        if self.settings.node.scheduler.resume_on_startup {
+            if let Err(err) = self
+                .runtime
+                .block_on(self.resume_workflows(runner_worker_tx.clone(), db.clone()))
+            {
+                error!(
+                    subject = "workflow.resume.err",
+                    category = "workflow",
+                    err=?err,
+                    "failed to find workflows to resume"
+                );
+            }
+        }
+
         let shutdown_time_left = self.runtime.block_on(async {
             let mut gc_interval = tokio::time::interval(self.settings.node.gc_interval);
             loop {
@@ -258,6 +361,7 @@ impl Runner {
                             Channels {
                                 rpc: rpc_sender.clone(),
                                 runner: runner_worker_tx.clone(),
+                                reply: oneshot_tx.clone(),
                             },
                             ws_hdl.clone(),
                             db.clone(),
@@ -281,6 +385,30 @@ impl Runner {
                                        "sending workflow_run message to rpc server");
                                 let _ = oneshot_tx.send_async(msg).await;
                             },
+                            Ok(ControlFlow::Continue(msg @ rpc::ServerMessage::CancelAck(_))) => {
+                                debug!(subject = "rpc.ack",
+                                       category = "rpc",
+                                       "sending workflow_cancel message to rpc server");
+                                let _ = oneshot_tx.send_async(msg).await;
+                            },
+                            Ok(ControlFlow::Continue(msg @ rpc::ServerMessage::ListWorkflowsAck(_))) => {
+                                debug!(subject = "rpc.ack",
+                                       category = "rpc",
+                                       "sending workflows_list message to rpc server");
+                                let _ = oneshot_tx.send_async(msg).await;
+                            },
+                            Ok(ControlFlow::Continue(msg @ rpc::ServerMessage::ShowWorkflowAck(_))) => {
+                                debug!(subject = "rpc.ack",
+                                       category = "rpc",
+                                       "sending workflows_show message to rpc server");
+                                let _ = oneshot_tx.send_async(msg).await;
+                            },
+                            Ok(ControlFlow::Continue(msg @ rpc::ServerMessage::GetWorkflowAck(_))) => {
+                                debug!(subject = "rpc.ack",
+                                       category = "rpc",
+                                       "sending workflows_get message to rpc server");
+                                let _ = oneshot_tx.send_async(msg).await;
+                            },
                             Err(err) => {
                                 error!(subject = "rpc.err",
                                        category = "rpc",
@@ -297,21 +425,24 @@ impl Runner {
                                 info!(subject = "workflow",
                                       category = "workflow.run",
                                       "running workflow: {}", name);
-                                // TODO: Parse this from the workflow data itself.
-                                let workflow_settings = workflow::Settings::default();
-                                match self.run_worker(
-                                    workflow,
-                                    workflow_settings,
-                                    self.settings.node.network().libp2p().dht(),
-                                    Some(name),
-                                    runner_worker_tx.clone(),
-                                    db.clone(),
-                                ).await {
+                                let run = async {
+                                    let workflow_settings = workflow::Settings::try_from(workflow.meta())?;
+                                    self.run_worker(
+                                        workflow,
+                                        workflow_settings,
+                                        file::Bundled::default(),
+                                        self.settings.node.network().libp2p().dht(),
+                                        Some(name),
+                                        runner_worker_tx.clone(),
+                                        db.clone(),
+                                    ).await
+                                };
+                                match run.await {
                                     Ok(data) => {
                                         debug!(subject = "jsonrpc.ack",
                                                category = "jsonrpc",
                                                "sending message to jsonrpc server");
-                                        let _ = oneshot_tx.send_async(webserver::Message::AckWorkflow((data.info.cid, data.name))).await;
+                                        let _ = oneshot_tx.send_async(webserver::Message::AckWorkflow((data.info.cid, data.name, data.queue_position))).await;
                                     }
                                     Err(err) => {
                                         error!(subject = "jsonrpc.err",
@@ -323,6 +454,69 @@ impl Runner {
                                 }
 
                             }
+                            (webserver::Message::CancelWorkflow(workflow), Some(oneshot_tx)) => {
+                                info!(subject = "workflow",
+                                      category = "workflow.cancel",
+                                      "cancelling workflow: {}", workflow);
+                                match self.cancel_worker(&workflow, db.clone()) {
+                                    Ok(stored) => {
+                                        debug!(subject = "jsonrpc.ack",
+                                               category = "jsonrpc",
+                                               "sending message to jsonrpc server");
+                                        let _ = oneshot_tx.send_async(webserver::Message::AckCancelWorkflow(response::AckCancel::new(stored))).await;
+                                    }
+                                    Err(err) => {
+                                        error!(subject = "jsonrpc.err",
+                                               category = "jsonrpc",
+                                               err=?err,
+                                               "error handling ws message");
+                                        let _ = oneshot_tx.send_async(webserver::Message::RunErr(err.into())).await;
+                                    }
+                                }
+                            }
+                            (webserver::Message::GetReceipt((cid, lookup, cbor)), Some(oneshot_tx)) => {
+                                debug!(subject = "jsonrpc.receipt",
+                                       category = "jsonrpc",
+                                       "getting receipt: {}", cid);
+                                let receipt = self.get_receipt(
+                                    &cid,
+                                    lookup,
+                                    cbor,
+                                    db.clone(),
+                                    self.settings.node.network().libp2p().dht().p2p_receipt_timeout,
+                                );
+                                self.runtime.spawn(async move {
+                                    match receipt.await {
+                                        Ok(receipt) => {
+                                            let _ = oneshot_tx.send_async(webserver::Message::AckReceipt(Box::new(receipt))).await;
+                                        }
+                                        Err(err) => {
+                                            error!(subject = "jsonrpc.err",
+                                                   category = "jsonrpc",
+                                                   err=?err,
+                                                   "error handling ws message");
+                                            let _ = oneshot_tx.send_async(webserver::Message::RunErr(err.into())).await;
+                                        }
+                                    }
+                                });
+                            }
+                            (webserver::Message::GetWorkflowReceipts((workflow, cbor)), Some(oneshot_tx)) => {
+                                debug!(subject = "jsonrpc.receipt",
+                                       category = "jsonrpc",
+                                       "getting receipts for workflow: {}", workflow);
+                                match self.get_workflow_receipts(&workflow, cbor, db.clone()) {
+                                    Ok(receipts) => {
+                                        let _ = oneshot_tx.send_async(webserver::Message::AckWorkflowReceipts(receipts)).await;
+                                    }
+                                    Err(err) => {
+                                        error!(subject = "jsonrpc.err",
+                                               category = "jsonrpc",
+                                               err=?err,
+                                               "error handling ws message");
+                                        let _ = oneshot_tx.send_async(webserver::Message::RunErr(err.into())).await;
+                                    }
+                                }
+                            }
                             (webserver::Message::GetNodeInfo, Some(oneshot_tx)) => {
                                 debug!(subject = "jsonrpc.nodeinfo",
                                        category = "jsonrpc",
@@ -345,6 +539,7 @@ impl Runner {
                         match msg {
                             WorkerMessage::Dropped(cid) => {
                                 let _ = self.abort_worker(cid);
+                                let _ = self.admit_workers();
                             },
                         }
                     }
@@ -361,8 +556,9 @@ impl Runner {
                     ) => {
                         info!(subject = "worker.expired",
                               category = "worker",
+                              workflow_cid = expired.get_ref().to_string(),
                               "worker expired, aborting");
-                        let _ = self.abort_worker(*expired.get_ref());
+                        let _ = self.expire_worker(*expired.get_ref(), db.clone());
                     },
                     // Handle shutdown signal.
                     _ = Self::shutdown_signal() => {
@@ -440,7 +636,8 @@ impl Runner {
         self.running_workers
             .retain(|_cid, (handle, _delay_key)| !handle.is_finished());
 
-        Ok(())
+        drop(expiration_q);
+        self.admit_workers()
     }
 
     /// Abort and gc/cleanup all workers and tasks.
@@ -462,10 +659,14 @@ impl Runner {
         self.abort_tasks();
     }
 
-    /// Cleanup all workers, tasks, and the expiration queue.
+    /// Cleanup all workers, tasks, and the expiration and admission queues.
     #[allow(dead_code)]
     fn cleanup_workers(&self) -> Result<()> {
         self.running_workers.clear();
+        self.admission_queue
+            .try_borrow_mut()
+            .map_err(|e| anyhow!("failed to borrow admission queue: {e}"))?
+            .clear();
         self.expiration_queue
             .try_borrow_mut()
             .map_err(|e| anyhow!("failed to borrow expiration queue: {e}"))?
@@ -509,6 +710,269 @@ impl Runner {
         Ok(())
     }
 
+    /// Removes a specific worker from the admission queue given a Cid,
+    /// along with its started timeout, returning whether it was queued.
+    fn dequeue_worker(&self, cid: Cid) -> Result<bool> {
+        let Some(queued) = self
+            .admission_queue
+            .try_borrow_mut()
+            .map_err(|e| anyhow!("failed to borrow admission queue: {e}"))?
+            .remove(cid)
+        else {
+            return Ok(false);
+        };
+
+        if let Some(delay_key) = queued.expiration {
+            let _ = self
+                .expiration_queue
+                .try_borrow_mut()
+                .map_err(|e| anyhow!("failed to borrow expiration queue: {e}"))?
+                .try_remove(&delay_key);
+        }
+
+        Ok(true)
+    }
+
+    /// Aborts a specific worker whose workflow timed out, along with its
+    /// outstanding tasks, or removes it from the admission queue if it was
+    /// still awaiting a free slot.
+    ///
+    /// If the workflow hadn't finished, whether running or pending, it's
+    /// marked as timed out, recording the instructions left without a
+    /// receipt, and the workflow's subscribers are notified.
+    fn expire_worker(&self, cid: Cid, db: impl Database) -> Result<()> {
+        self.dequeue_worker(cid)?;
+        self.abort_worker(cid)?;
+
+        let mut conn = db.conn()?;
+        let stored = Db::select_workflow(cid, &mut conn)?;
+        if stored.status.is_terminal() {
+            return Ok(());
+        }
+
+        let finished = Db::find_workflow_receipts(cid, &mut conn)?
+            .iter()
+            .map(|receipt| receipt.instruction().cid())
+            .collect::<FnvHashSet<Cid>>();
+        let unfinished = stored
+            .resources
+            .inner()
+            .keys()
+            .filter(|instruction| !finished.contains(instruction))
+            .copied()
+            .collect::<Vec<Cid>>();
+
+        Db::set_workflow_timed_out(cid, TIMED_OUT_REASON, &unfinished, &mut conn)?;
+
+        warn!(
+            subject = "worker.expire",
+            category = "worker",
+            workflow_cid = cid.to_string(),
+            unfinished = unfinished.len(),
+            "workflow timed out"
+        );
+
+        #[cfg(feature = "websocket-notify")]
+        notification::emit_workflow_status(
+            self.webserver.workflow_msg_notifier(),
+            cid,
+            WorkflowStatusNotification::new(
+                cid,
+                stored.name.map(|name| name.into()),
+                workflow::Status::TimedOut,
+                Some(TIMED_OUT_REASON.to_string()),
+            )
+            .with_unfinished(&unfinished),
+        );
+
+        Ok(())
+    }
+
+    /// Get a [Receipt] by its Cid, or by the Cid of the [Instruction] it
+    /// was issued for.
+    ///
+    /// Receipts for instructions that aren't in the local database are
+    /// looked up on the DHT, where receipt records are keyed by instruction,
+    /// so the lookup is returned as a future to be run off the runner's
+    /// event loop.
+    ///
+    /// [Instruction]: homestar_invocation::task::Instruction
+    fn get_receipt(
+        &self,
+        cid: &str,
+        lookup: ReceiptLookup,
+        cbor: bool,
+        db: impl Database + 'static,
+        p2p_receipt_timeout: time::Duration,
+    ) -> impl Future<Output = Result<response::AckReceipt>> + Send + 'static {
+        let cid = Cid::try_from(cid).map_err(|_| anyhow!("invalid Cid: {cid}"));
+        let event_sender = self.event_sender();
+
+        async move {
+            let cid = cid?;
+            let local = {
+                let mut conn = db.conn()?;
+                match lookup {
+                    ReceiptLookup::Receipt => Db::find_receipt_by_cid(cid, &mut conn),
+                    ReceiptLookup::Instruction => Db::find_instruction_by_cid(cid, &mut conn),
+                }
+            };
+
+            match local {
+                Ok(receipt) => {
+                    response::AckReceipt::new(receipt, response::ReceiptSource::Local, cbor)
+                }
+                Err(_)
+                    if lookup == ReceiptLookup::Instruction
+                        && p2p_receipt_timeout.as_millis() > 0 =>
+                {
+                    let (tx, rx) = AsyncChannel::oneshot();
+                    let _ = event_sender
+                        .send_async(Event::FindRecord(QueryRecord::with(
+                            cid,
+                            CapsuleTag::Receipt,
+                            Some(tx),
+                        )))
+                        .await;
+
+                    match time::timeout(p2p_receipt_timeout, rx.recv_async()).await {
+                        Ok(Ok(ResponseEvent::Found(Ok(FoundEvent::Receipt(found))))) => {
+                            debug!(
+                                subject = "receipt.get",
+                                category = "receipt",
+                                instruction_cid = cid.to_string(),
+                                receipt_cid = found.receipt.cid().to_string(),
+                                "found receipt on the DHT"
+                            );
+                            response::AckReceipt::new(
+                                found.receipt,
+                                response::ReceiptSource::Dht,
+                                cbor,
+                            )
+                        }
+                        _ => Err(anyhow!("no receipt found for {lookup} Cid: {cid}")),
+                    }
+                }
+                Err(_) => Err(anyhow!("no receipt found for {lookup} Cid: {cid}")),
+            }
+        }
+    }
+
+    /// Get all [Receipt]s emitted for a stored [Workflow], given its Cid or
+    /// local name.
+    ///
+    /// [Workflow]: homestar_workflow::Workflow
+    fn get_workflow_receipts(
+        &self,
+        workflow: &str,
+        cbor: bool,
+        db: impl Database,
+    ) -> Result<Vec<response::AckReceipt>> {
+        let mut conn = db.conn()?;
+        let stored = find_workflow(workflow, &mut conn)?;
+        Db::find_workflow_receipts(stored.cid.cid(), &mut conn)?
+            .into_iter()
+            .map(|receipt| response::AckReceipt::new(receipt, response::ReceiptSource::Local, cbor))
+            .collect()
+    }
+
+    /// Get the document of a stored [Workflow], given its Cid or local name.
+    ///
+    /// [Workflow]: homestar_workflow::Workflow
+    fn get_workflow(
+        &self,
+        workflow: &str,
+        cbor: bool,
+        db: impl Database,
+    ) -> Result<response::AckWorkflowDocument> {
+        let mut conn = db.conn()?;
+        let stored = find_workflow(workflow, &mut conn)?;
+        let cid = stored.cid.cid();
+        let document = Db::select_workflow_document(cid, &mut conn)?
+            .ok_or_else(|| anyhow!("no document stored for workflow: {cid}"))?;
+
+        response::AckWorkflowDocument::new(stored, document, cbor)
+    }
+
+    /// Verify a stored [Receipt], given its Cid, by re-executing the
+    /// [Instruction] it was issued for and comparing outputs.
+    ///
+    /// Re-execution may fetch the instruction's resource and run it, so the
+    /// verification is returned as a future to be run off the runner's event
+    /// loop.
+    ///
+    /// [Instruction]: homestar_invocation::task::Instruction
+    fn verify_receipt(
+        &self,
+        cid: &str,
+        db: impl Database + 'static,
+    ) -> impl Future<Output = Result<response::AckVerify>> + Send + 'static {
+        let cid = Cid::try_from(cid).map_err(|_| anyhow!("invalid Cid: {cid}"));
+        let verifier = Verifier::new(
+            self.settings.node.network(),
+            self.executors.clone(),
+            self.task_permits.clone(),
+        );
+
+        async move {
+            let cid = cid?;
+            let receipt = Db::find_receipt_by_cid(cid, &mut db.conn()?)
+                .map_err(|_| anyhow!("no receipt found for receipt Cid: {cid}"))?;
+
+            let verdict = verifier.verify(&receipt, db).await?;
+            response::AckVerify::new(&receipt, verdict)
+        }
+    }
+
+    /// Cancels a running or queued worker given a workflow Cid or local
+    /// name, aborting the worker and its tasks, marking the workflow as
+    /// cancelled, and notifying the workflow's subscribers.
+    fn cancel_worker(&self, workflow: &str, db: impl Database) -> Result<workflow::Stored> {
+        let mut conn = db.conn()?;
+        let cid = find_workflow(workflow, &mut conn)?.cid.cid();
+
+        let still_running = self
+            .running_workers
+            .get(&cid)
+            .is_some_and(|worker| !worker.value().0.is_finished());
+        let queued = self.dequeue_worker(cid)?;
+
+        if !still_running && !queued {
+            return Err(anyhow!("workflow {cid} is not running"));
+        }
+
+        self.abort_worker(cid)?;
+        Db::set_workflow_failure(
+            cid,
+            workflow::Status::Cancelled,
+            CANCELLED_REASON,
+            None,
+            &mut conn,
+        )?;
+        let stored = Db::select_workflow(cid, &mut conn)?;
+
+        info!(
+            subject = "worker.cancel",
+            category = "worker",
+            workflow_cid = cid.to_string(),
+            "workflow cancelled"
+        );
+
+        #[cfg(feature = "websocket-notify")]
+        notification::emit_workflow_status(
+            self.webserver.workflow_msg_notifier(),
+            cid,
+            WorkflowStatusNotification::new(
+                cid,
+                stored.name.clone().map(|name| name.into()),
+                workflow::Status::Cancelled,
+                Some(CANCELLED_REASON.to_string()),
+            ),
+        );
+
+        Ok(stored)
+    }
+
     /// Abort a specific worker's tasks given a Cid.
     fn abort_worker_tasks(&self, cid: Cid) {
         if let Some((_cid, handles)) = self.running_tasks.remove(&cid) {
@@ -659,13 +1123,129 @@ impl Runner {
                     }
                 }
             }
+            rpc::ServerMessage::Cancel(workflow) => {
+                info!(
+                    subject = "rpc.command",
+                    category = "rpc",
+                    "RPC cancel command received, cancelling workflow"
+                );
+                let stored = self.cancel_worker(&workflow, db)?;
+
+                Ok(ControlFlow::Continue(rpc::ServerMessage::CancelAck(
+                    response::AckCancel::new(stored),
+                )))
+            }
+            rpc::ServerMessage::ListWorkflows(filter) => {
+                info!(
+                    subject = "rpc.command",
+                    category = "rpc",
+                    "RPC list workflows command received"
+                );
+                let mut conn = db.conn()?;
+                let workflows = Db::list_workflows(&filter, &mut conn)?
+                    .iter()
+                    .map(|stored| {
+                        Db::count_workflow_receipts(stored.cid.cid(), &mut conn)
+                            .map(|count| response::WorkflowSummary::new(stored, count))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                Ok(ControlFlow::Continue(rpc::ServerMessage::ListWorkflowsAck(
+                    response::AckWorkflowList::new(workflows),
+                )))
+            }
+            rpc::ServerMessage::ShowWorkflow(workflow) => {
+                info!(
+                    subject = "rpc.command",
+                    category = "rpc",
+                    "RPC show workflow command received"
+                );
+                let mut conn = db.conn()?;
+                let stored = find_workflow(&workflow, &mut conn)?;
+                let receipts = Db::find_workflow_receipts(stored.cid.cid(), &mut conn)?;
+
+                Ok(ControlFlow::Continue(rpc::ServerMessage::ShowWorkflowAck(
+                    Box::new(response::AckWorkflowDetail::new(stored, receipts)),
+                )))
+            }
+            rpc::ServerMessage::GetReceipt((cid, lookup, cbor)) => {
+                info!(
+                    subject = "rpc.command",
+                    category = "rpc",
+                    "RPC get receipt command received"
+                );
+                let receipt =
+                    self.get_receipt(&cid, lookup, cbor, db, network_settings.p2p_receipt_timeout);
+                let reply = channels.reply;
+                self.runtime.spawn(async move {
+                    let msg = match receipt.await {
+                        Ok(receipt) => rpc::ServerMessage::GetReceiptAck(Box::new(receipt)),
+                        Err(err) => {
+                            error!(subject = "rpc.err",
+                                   category = "rpc",
+                                   err=?err,
+                                   "error handling rpc message");
+                            rpc::ServerMessage::RunErr(err.into())
+                        }
+                    };
+                    debug!(
+                        subject = "rpc.ack",
+                        category = "rpc",
+                        "sending receipt_get message to rpc server"
+                    );
+                    let _ = reply.send_async(msg).await;
+                });
+
+                Ok(ControlFlow::Continue(rpc::ServerMessage::Skip))
+            }
+            rpc::ServerMessage::GetWorkflow((workflow, cbor)) => {
+                info!(
+                    subject = "rpc.command",
+                    category = "rpc",
+                    "RPC get workflow command received"
+                );
+                let document = self.get_workflow(&workflow, cbor, db)?;
+
+                Ok(ControlFlow::Continue(rpc::ServerMessage::GetWorkflowAck(
+                    Box::new(document),
+                )))
+            }
+            rpc::ServerMessage::VerifyReceipt(cid) => {
+                info!(
+                    subject = "rpc.command",
+                    category = "rpc",
+                    "RPC verify receipt command received"
+                );
+                let verified = self.verify_receipt(&cid, db);
+                let reply = channels.reply;
+                self.runtime.spawn(async move {
+                    let msg = match verified.await {
+                        Ok(verified) => rpc::ServerMessage::VerifyReceiptAck(Box::new(verified)),
+                        Err(err) => {
+                            error!(subject = "rpc.err",
+                                   category = "rpc",
+                                   err=?err,
+                                   "error handling rpc message");
+                            rpc::ServerMessage::RunErr(err.into())
+                        }
+                    };
+                    debug!(
+                        subject = "rpc.ack",
+                        category = "rpc",
+                        "sending verify message to rpc server"
+                    );
+                    let _ = reply.send_async(msg).await;
+                });
+
+                Ok(ControlFlow::Continue(rpc::ServerMessage::Skip))
+            }
             rpc::ServerMessage::Run((name, workflow_file)) => {
                 info!(
                     subject = "rpc.command",
                     category = "rpc",
                     "RPC run command received, running workflow"
                 );
-                let (workflow, workflow_settings) =
+                let (workflow, workflow_settings, bundled) =
                     workflow_file.validate_and_parse().await.with_context(|| {
                         format!("failed to validate/parse workflow @ path: {workflow_file}",)
                     })?;
@@ -674,6 +1254,7 @@ impl Runner {
                     .run_worker(
                         workflow,
                         workflow_settings,
+                        bundled,
                         network_settings,
                         name,
                         channels.runner,
@@ -687,6 +1268,7 @@ impl Runner {
                         data.replayed_receipt_info,
                         data.name,
                         data.timestamp,
+                        data.queue_position,
                     ),
                 ))))
             }
@@ -702,17 +1284,102 @@ impl Runner {
         }
     }
 
+    /// Resume workflows left `running` or `pending` in the database, e.g.
+    /// by a crash or restart, rebuilding each from its stored document.
+    ///
+    /// Resumed workflows are admitted like newly run ones, by priority and
+    /// then by age, up to the maximum number of concurrent workflows, with
+    /// the rest queued as pending.
+    ///
+    /// Receipts already stored for a resumed workflow are replayed by its
+    /// scheduler, which picks up from the last completed step, and blocks
+    /// bundled with it, e.g. within a CAR file, are used again.
+    ///
+    /// Returns the Cids of the workflows resumed.
+    async fn resume_workflows(
+        &self,
+        runner_sender: AsyncChannelSender<WorkerMessage>,
+        db: impl Database + 'static,
+    ) -> Result<Vec<Cid>> {
+        let mut resumable = Vec::new();
+        for (stored, document) in Db::find_resumable_workflows(&mut db.conn()?)? {
+            let cid = stored.cid.cid();
+            let resume = decode_workflow(&document).and_then(|(workflow, workflow_settings)| {
+                let bundled = Db::select_workflow_blocks(cid, &mut db.conn()?)?;
+                Ok((workflow, workflow_settings, bundled))
+            });
+            match resume {
+                Ok((workflow, workflow_settings, bundled)) => {
+                    resumable.push((stored, workflow, workflow_settings, bundled))
+                }
+                Err(err) => {
+                    error!(
+                        subject = "workflow.resume.err",
+                        category = "workflow",
+                        cid = cid.to_string(),
+                        err=?err,
+                        "failed to resume workflow"
+                    );
+                }
+            }
+        }
+
+        // Stable, so workflows of the same priority stay oldest first.
+        resumable.sort_by_key(|(_, _, workflow_settings, _)| -workflow_settings.priority);
+
+        let mut resumed = Vec::with_capacity(resumable.len());
+        for (stored, workflow, workflow_settings, bundled) in resumable {
+            let cid = stored.cid.cid();
+            let resume = self.run_worker(
+                workflow,
+                workflow_settings,
+                bundled,
+                self.settings.node.network().libp2p().dht(),
+                stored.name,
+                runner_sender.clone(),
+                db.clone(),
+            );
+
+            match resume.await {
+                Ok(data) => {
+                    info!(
+                        subject = "workflow.resume",
+                        category = "workflow",
+                        cid = cid.to_string(),
+                        progress = data.info.progress_count,
+                        "resumed workflow: {}",
+                        data.name
+                    );
+                    resumed.push(cid);
+                }
+                Err(err) => {
+                    error!(
+                        subject = "workflow.resume.err",
+                        category = "workflow",
+                        cid = cid.to_string(),
+                        err=?err,
+                        "failed to resume workflow"
+                    );
+                }
+            }
+        }
+
+        Ok(resumed)
+    }
+
+    #[allow(clippy::too_many_arguments)]
     #[instrument(skip_all)]
     async fn run_worker<S: Into<FastStr>>(
         &self,
         workflow: Workflow<'static, Arg>,
         workflow_settings: workflow::Settings,
+        bundled: file::Bundled,
         network_settings: &settings::Dht,
         name: Option<S>,
         runner_sender: AsyncChannelSender<WorkerMessage>,
         db: impl Database + 'static,
     ) -> Result<WorkflowData> {
-        let worker = {
+        let mut worker = {
             Worker::new(
                 workflow,
                 workflow_settings,
@@ -725,10 +1392,20 @@ impl Runner {
             .await?
         };
 
+        // Share task permits across workers to limit the number of tasks
+        // running concurrently on the node.
+        worker.task_permits = self.task_permits.clone();
+        worker.executors = self.executors.clone();
+
+        if !bundled.is_empty() {
+            Db::store_workflow_blocks(worker.workflow_info.cid, &bundled, &mut db.conn()?)?;
+        }
+
         // Deliberate use of Arc::clone for readability, could just be
         // `clone`, as the underlying type is an `Arc`.
         let initial_info = Arc::clone(&worker.workflow_info);
         let workflow_timeout = worker.workflow_settings.timeout;
+        let workflow_priority = worker.workflow_settings.priority;
         let workflow_name = worker.workflow_name.clone();
         let workflow_settings = worker.workflow_settings.clone();
         let timestamp = worker.workflow_started;
@@ -754,36 +1431,53 @@ impl Runner {
             ))
             .await?;
 
+        // Shared with nested workers, which run the workflows of
+        // `workflow/run` tasks.
         #[cfg(feature = "ipfs")]
-        let fetch_fn = {
+        let fetch: FetchFn = {
             let settings = Arc::clone(&self.settings);
             let ipfs = IpfsCli::new(settings.node.network.ipfs())?;
-            move |rscs: FnvHashSet<Resource>| {
-                async move { Fetch::get_resources(rscs, workflow_settings, ipfs).await }.boxed()
-            }
+            Arc::new(move |rscs: FnvHashSet<Resource>| {
+                let (rscs, mut found) = Fetch::bundled(rscs, &bundled);
+                let workflow_settings = workflow_settings.clone();
+                let ipfs = ipfs.clone();
+                async move {
+                    found.extend(Fetch::get_resources(rscs, workflow_settings, ipfs).await?);
+                    Ok(found)
+                }
+                .boxed()
+            })
         };
 
         #[cfg(not(feature = "ipfs"))]
-        let fetch_fn = |rscs: FnvHashSet<Resource>| {
-            async move { Fetch::get_resources(rscs, workflow_settings).await }.boxed()
-        };
-
-        let handle = self.runtime.spawn(
-            worker
-                .run(self.running_tasks(), fetch_fn)
-                .instrument(info_span!("run").or_current()),
-        );
-
-        // Add Cid to expirations timing wheel
-        let delay_key = self
-            .expiration_queue
-            .try_borrow_mut()
-            .map_err(|e| anyhow!("failed to borrow expiration queue: {e}"))?
-            .insert(initial_info.cid, workflow_timeout);
+        let fetch: FetchFn = Arc::new(move |rscs: FnvHashSet<Resource>| {
+            let (rscs, mut found) = Fetch::bundled(rscs, &bundled);
+            let workflow_settings = workflow_settings.clone();
+            async move {
+                found.extend(Fetch::get_resources(rscs, workflow_settings).await?);
+                Ok(found)
+            }
+            .boxed()
+        });
 
-        // Insert handle into running workers map
-        self.running_workers
-            .insert(initial_info.cid, (handle, delay_key));
+        worker.fetch = Some(fetch.clone());
+        let fetch_fn = move |rscs| fetch(rscs);
+
+        let run = worker
+            .run(self.running_tasks(), fetch_fn)
+            .instrument(info_span!("run").or_current())
+            .boxed();
+
+        let queue_position = self.admit_or_queue(
+            Queued {
+                cid: initial_info.cid,
+                priority: workflow_priority,
+                timeout: workflow_timeout,
+                expiration: None,
+                run,
+            },
+            db.clone(),
+        )?;
 
         // Gather receipt info
         let receipt_pointers = initial_info
@@ -812,8 +1506,104 @@ impl Runner {
             name: workflow_name,
             timestamp,
             replayed_receipt_info,
+            queue_position,
         })
     }
+
+    /// Spawn a worker's run if there's a free slot for it, or otherwise queue
+    /// it as pending by priority, returning its position in the queue.
+    fn admit_or_queue(&self, mut queued: Queued, db: impl Database) -> Result<Option<usize>> {
+        let mut admission_q = self
+            .admission_queue
+            .try_borrow_mut()
+            .map_err(|e| anyhow!("failed to borrow admission queue: {e}"))?;
+
+        if admission_q.is_empty() && self.has_free_worker_slot() {
+            self.spawn_worker(queued)?;
+            return Ok(None);
+        }
+
+        let cid = queued.cid;
+        Db::set_workflow_status(cid, workflow::Status::Pending, &mut db.conn()?)?;
+
+        // Queued workflows can time out before they're admitted.
+        queued.expiration = Some(
+            self.expiration_queue
+                .try_borrow_mut()
+                .map_err(|e| anyhow!("failed to borrow expiration queue: {e}"))?
+                .insert(cid, queued.timeout),
+        );
+        let position = admission_q.push(queued);
+
+        info!(
+            subject = "worker.queue",
+            category = "worker",
+            workflow_cid = cid.to_string(),
+            position,
+            "workflow queued, awaiting a free slot to run"
+        );
+
+        Ok(Some(position))
+    }
+
+    /// Spawn queued workers, by priority, while there are free slots for
+    /// them.
+    fn admit_workers(&self) -> Result<()> {
+        let mut admission_q = self
+            .admission_queue
+            .try_borrow_mut()
+            .map_err(|e| anyhow!("failed to borrow admission queue: {e}"))?;
+
+        while self.has_free_worker_slot() {
+            let Some(queued) = admission_q.pop() else {
+                break;
+            };
+
+            info!(
+                subject = "worker.admit",
+                category = "worker",
+                workflow_cid = queued.cid.to_string(),
+                "admitting queued workflow"
+            );
+
+            self.spawn_worker(queued)?;
+        }
+
+        Ok(())
+    }
+
+    /// Whether fewer workers are running than the maximum number of
+    /// concurrent workflows.
+    fn has_free_worker_slot(&self) -> bool {
+        let running = self
+            .running_workers
+            .iter()
+            .filter(|worker| !worker.value().0.is_finished())
+            .count();
+
+        running < self.settings.node.scheduler.max_concurrent_workflows.max(1)
+    }
+
+    /// Spawn a worker's run, starting its timeout unless it was started when
+    /// the worker was queued.
+    fn spawn_worker(&self, queued: Queued) -> Result<()> {
+        let handle = self.runtime.spawn(queued.run);
+
+        // Add Cid to expirations timing wheel
+        let delay_key = match queued.expiration {
+            Some(delay_key) => delay_key,
+            None => self
+                .expiration_queue
+                .try_borrow_mut()
+                .map_err(|e| anyhow!("failed to borrow expiration queue: {e}"))?
+                .insert(queued.cid, queued.timeout),
+        };
+
+        // Insert handle into running workers map
+        self.running_workers.insert(queued.cid, (handle, delay_key));
+
+        Ok(())
+    }
 }
 
 /// Find receipts given a batch of [Receipt] [Pointer]s, and return them as [WorkflowReceiptInfo]s.
@@ -840,12 +1630,34 @@ fn find_receipt_info_by_pointers(
     Ok(receipt_info)
 }
 
+/// Decode a stored DAG-CBOR [Workflow] document, along with the
+/// [workflow::Settings] given in its metadata.
+///
+/// [Workflow]: homestar_workflow::Workflow
+fn decode_workflow(document: &[u8]) -> Result<(Workflow<'static, Arg>, workflow::Settings)> {
+    let workflow: Workflow<'static, Arg> = DagCbor::from_cbor(document)?;
+    let workflow_settings = workflow::Settings::try_from(workflow.meta())?;
+    Ok((workflow, workflow_settings))
+}
+
+/// Find a stored [Workflow] by its Cid or, failing that, its local name.
+///
+/// [Workflow]: homestar_workflow::Workflow
+fn find_workflow(workflow: &str, conn: &mut Connection) -> Result<workflow::Stored> {
+    match Cid::try_from(workflow) {
+        Ok(cid) => Db::select_workflow(cid, conn),
+        Err(_) => Db::select_workflow_by_name(workflow, conn),
+    }
+    .map_err(|_| anyhow!("no workflow found with Cid or name: {workflow}"))
+}
+
 /// Internal Workflow data used for wrapper.
 struct WorkflowData {
     info: Arc<workflow::Info>,
     name: FastStr,
     timestamp: NaiveDateTime,
     replayed_receipt_info: Vec<WorkflowReceiptInfo>,
+    queue_position: Option<usize>,
 }
 
 /// Channels for sending messages to/from the RPC server and the runner.
@@ -853,6 +1665,9 @@ struct WorkflowData {
 struct Channels {
     rpc: Arc<AsyncChannelSender<rpc::ServerMessage>>,
     runner: AsyncChannelSender<WorkerMessage>,
+    /// Sender for replying to the RPC server on, for commands answered
+    /// off the runner's event loop.
+    reply: AsyncChannelSender<rpc::ServerMessage>,
 }
 
 #[cfg(test)]
@@ -862,6 +1677,13 @@ mod test {
         network::rpc::Client,
         test_utils::{db::MemoryDb, WorkerBuilder},
     };
+    use homestar_invocation::{
+        authority::UcanPrf,
+        task::{self, instruction::RunInstruction},
+        test_utils::cid::generate_cid,
+        Invocation, Receipt as InvocationReceipt, Task,
+    };
+    use libipld::Ipld;
     use metrics_exporter_prometheus::PrometheusBuilder;
     use rand::thread_rng;
     use std::net::{IpAddr, Ipv4Addr, SocketAddr};
@@ -982,6 +1804,495 @@ mod test {
         });
     }
 
+    #[homestar_runtime_proc_macro::runner_test]
+    fn expire_running_worker() {
+        let TestRunner { runner, settings } = TestRunner::start();
+
+        let builder = WorkerBuilder::new(settings.node);
+        let db = builder.db();
+        let workflow_cid = runner.runtime.block_on(async {
+            let fetch_fn = builder.fetch_fn();
+            let worker = builder.build().await;
+            let workflow_cid = worker.workflow_info.cid;
+            let workflow_timeout = worker.workflow_settings.timeout;
+            let handle = runner
+                .runtime
+                .spawn(worker.run(runner.running_tasks(), fetch_fn));
+            let delay_key = runner
+                .expiration_queue
+                .try_borrow_mut()
+                .unwrap()
+                .insert(workflow_cid, workflow_timeout);
+            runner
+                .running_workers
+                .insert(workflow_cid, (handle, delay_key));
+
+            workflow_cid
+        });
+
+        runner.expire_worker(workflow_cid, db.clone()).unwrap();
+        assert!(runner.running_workers.is_empty());
+        assert!(runner.expiration_queue.try_borrow_mut().unwrap().is_empty());
+
+        let stored = MemoryDb::select_workflow(workflow_cid, &mut db.conn().unwrap()).unwrap();
+        assert_eq!(stored.status, workflow::Status::TimedOut);
+        assert_eq!(stored.failure_reason, Some(TIMED_OUT_REASON.to_string()));
+        assert!(stored.failed_at.is_some());
+
+        // At least the last task of the workflow is left unfinished.
+        let unfinished = stored.unfinished_instructions.unwrap();
+        assert!(!unfinished.inner().is_empty());
+        assert!(unfinished
+            .inner()
+            .iter()
+            .all(|instruction| stored.resources.inner().contains_key(instruction)));
+    }
+
+    #[homestar_runtime_proc_macro::runner_test]
+    fn cancel_running_worker() {
+        let TestRunner { runner, settings } = TestRunner::start();
+
+        let builder = WorkerBuilder::new(settings.node);
+        let db = builder.db();
+        let (workflow_cid, workflow_name) = runner.runtime.block_on(async {
+            let fetch_fn = builder.fetch_fn();
+            let worker = builder.build().await;
+            let workflow_cid = worker.workflow_info.cid;
+            let workflow_name = worker.workflow_name.clone();
+            let workflow_timeout = worker.workflow_settings.timeout;
+            let handle = runner
+                .runtime
+                .spawn(worker.run(runner.running_tasks(), fetch_fn));
+            let delay_key = runner
+                .expiration_queue
+                .try_borrow_mut()
+                .unwrap()
+                .insert(workflow_cid, workflow_timeout);
+            runner
+                .running_workers
+                .insert(workflow_cid, (handle, delay_key));
+
+            (workflow_cid, workflow_name)
+        });
+
+        let stored = runner.cancel_worker(&workflow_name, db.clone()).unwrap();
+        assert_eq!(stored.cid.cid(), workflow_cid);
+        assert_eq!(stored.status, workflow::Status::Cancelled);
+        assert_eq!(stored.failure_reason, Some(CANCELLED_REASON.to_string()));
+        assert!(stored.failed_at.is_some());
+        assert!(runner.running_workers.is_empty());
+        assert!(!runner.running_tasks.contains_key(&workflow_cid));
+        assert!(runner.expiration_queue.try_borrow_mut().unwrap().is_empty());
+
+        // Cancelling a workflow that is no longer running is an error.
+        assert!(runner
+            .cancel_worker(&workflow_cid.to_string(), db.clone())
+            .is_err());
+        assert!(runner.cancel_worker("not-a-workflow", db).is_err());
+    }
+
+    #[homestar_runtime_proc_macro::runner_test]
+    fn resume_interrupted_workflows() {
+        let TestRunner { runner, settings } = TestRunner::start();
+        let _guard = runner.runtime.enter();
+
+        let builder = WorkerBuilder::new(settings.node);
+        let db = builder.db();
+        let workflow_cid = runner.runtime.block_on(async {
+            let worker = builder.build().await;
+            worker.workflow_info.cid
+        });
+
+        // Leave the workflow as if the node stopped mid-run.
+        MemoryDb::set_workflow_status(
+            workflow_cid,
+            workflow::Status::Running,
+            &mut db.conn().unwrap(),
+        )
+        .unwrap();
+
+        let (runner_tx, _runner_rx) = Runner::setup_worker_channel(1);
+        let resumed = runner
+            .runtime
+            .block_on(runner.resume_workflows(runner_tx, db.clone()))
+            .unwrap();
+
+        assert_eq!(resumed, vec![workflow_cid]);
+        assert!(runner.running_workers.contains_key(&workflow_cid));
+    }
+
+    #[homestar_runtime_proc_macro::runner_test]
+    fn resume_interrupted_workflows_up_to_limit() {
+        let TestRunner {
+            mut runner,
+            settings,
+        } = TestRunner::start();
+        Arc::make_mut(&mut runner.settings)
+            .node
+            .scheduler
+            .max_concurrent_workflows = 1;
+        let _guard = runner.runtime.enter();
+
+        let builder = WorkerBuilder::new(settings.node);
+        let db = builder.db();
+        let workflow_cid = runner.runtime.block_on(async {
+            let worker = builder.build().await;
+            worker.workflow_info.cid
+        });
+
+        let (instruction, _) =
+            homestar_invocation::test_utils::wasm_instruction_with_nonce::<Arg>();
+        let other_workflow = Workflow::new(vec![Task::new(
+            RunInstruction::Expanded(instruction),
+            task::Resources::default().into(),
+            UcanPrf::default(),
+        )]);
+        let other_workflow_cid = other_workflow.clone().to_cid().unwrap();
+
+        let mut conn = db.conn().unwrap();
+        MemoryDb::store_workflow(
+            workflow::Stored::default(Pointer::new(other_workflow_cid), 1),
+            &mut conn,
+        )
+        .unwrap();
+        MemoryDb::store_workflow_document(
+            other_workflow_cid,
+            &other_workflow.to_cbor().unwrap(),
+            &mut conn,
+        )
+        .unwrap();
+        for cid in [workflow_cid, other_workflow_cid] {
+            MemoryDb::set_workflow_status(cid, workflow::Status::Running, &mut conn).unwrap();
+        }
+
+        let (runner_tx, _runner_rx) = Runner::setup_worker_channel(1);
+        let resumed = runner
+            .runtime
+            .block_on(runner.resume_workflows(runner_tx, db.clone()))
+            .unwrap();
+
+        // Both are resumed, but only one runs, with the other queued.
+        assert_eq!(resumed.len(), 2);
+        assert_eq!(runner.running_workers.len(), 1);
+        assert!(!runner.admission_queue.try_borrow().unwrap().is_empty());
+
+        let statuses: Vec<workflow::Status> = resumed
+            .iter()
+            .map(|cid| MemoryDb::select_workflow(*cid, &mut conn).unwrap().status)
+            .collect();
+        assert!(statuses.contains(&workflow::Status::Pending));
+    }
+
+    #[homestar_runtime_proc_macro::runner_test]
+    fn queue_workers_by_priority() {
+        let TestRunner {
+            mut runner,
+            settings,
+        } = TestRunner::start();
+        Arc::make_mut(&mut runner.settings)
+            .node
+            .scheduler
+            .max_concurrent_workflows = 1;
+        let _guard = runner.runtime.enter();
+
+        let builder = WorkerBuilder::new(settings.node);
+        let db = builder.db();
+        let queued = |priority| Queued {
+            cid: generate_cid(&mut thread_rng()),
+            priority,
+            timeout: time::Duration::from_secs(60),
+            expiration: None,
+            run: futures::future::pending().boxed(),
+        };
+
+        // Take the only free slot.
+        let running = queued(0);
+        let running_cid = running.cid;
+        assert_eq!(runner.admit_or_queue(running, db.clone()).unwrap(), None);
+
+        let (workflow_cid, workflow_name) = runner.runtime.block_on(async {
+            let fetch_fn = builder.fetch_fn();
+            let worker = builder.build().await;
+            let workflow_cid = worker.workflow_info.cid;
+            let workflow_name = worker.workflow_name.clone();
+            let position = runner
+                .admit_or_queue(
+                    Queued {
+                        cid: workflow_cid,
+                        priority: 0,
+                        timeout: worker.workflow_settings.timeout,
+                        expiration: None,
+                        run: worker.run(runner.running_tasks(), fetch_fn).boxed(),
+                    },
+                    db.clone(),
+                )
+                .unwrap();
+            assert_eq!(position, Some(1));
+
+            (workflow_cid, workflow_name)
+        });
+
+        let stored = MemoryDb::select_workflow(workflow_cid, &mut db.conn().unwrap()).unwrap();
+        assert_eq!(stored.status, workflow::Status::Pending);
+
+        // Higher priority workflows jump the queue.
+        let urgent = queued(1);
+        let urgent_cid = urgent.cid;
+        assert_eq!(runner.admit_or_queue(urgent, db.clone()).unwrap(), Some(1));
+
+        runner.abort_worker(running_cid).unwrap();
+        runner.admit_workers().unwrap();
+        assert!(runner.running_workers.contains_key(&urgent_cid));
+        assert!(!runner.running_workers.contains_key(&workflow_cid));
+
+        // Queued workflows can be cancelled before they run.
+        let stored = runner.cancel_worker(&workflow_name, db.clone()).unwrap();
+        assert_eq!(stored.status, workflow::Status::Cancelled);
+        assert!(runner.admission_queue.try_borrow().unwrap().is_empty());
+    }
+
+    #[homestar_runtime_proc_macro::runner_test]
+    fn expire_queued_worker() {
+        let TestRunner {
+            mut runner,
+            settings,
+        } = TestRunner::start();
+        Arc::make_mut(&mut runner.settings)
+            .node
+            .scheduler
+            .max_concurrent_workflows = 1;
+        let _guard = runner.runtime.enter();
+
+        let builder = WorkerBuilder::new(settings.node);
+        let db = builder.db();
+
+        // Take the only free slot.
+        let running = Queued {
+            cid: generate_cid(&mut thread_rng()),
+            priority: 0,
+            timeout: time::Duration::from_secs(60),
+            expiration: None,
+            run: futures::future::pending().boxed(),
+        };
+        assert_eq!(runner.admit_or_queue(running, db.clone()).unwrap(), None);
+
+        let workflow_cid = runner.runtime.block_on(async {
+            let fetch_fn = builder.fetch_fn();
+            let worker = builder.build().await;
+            let workflow_cid = worker.workflow_info.cid;
+            let position = runner
+                .admit_or_queue(
+                    Queued {
+                        cid: workflow_cid,
+                        priority: 0,
+                        timeout: worker.workflow_settings.timeout,
+                        expiration: None,
+                        run: worker.run(runner.running_tasks(), fetch_fn).boxed(),
+                    },
+                    db.clone(),
+                )
+                .unwrap();
+            assert_eq!(position, Some(1));
+
+            workflow_cid
+        });
+
+        runner.expire_worker(workflow_cid, db.clone()).unwrap();
+        assert!(runner.admission_queue.try_borrow().unwrap().is_empty());
+        assert!(!runner.running_workers.contains_key(&workflow_cid));
+
+        let stored = MemoryDb::select_workflow(workflow_cid, &mut db.conn().unwrap()).unwrap();
+        assert_eq!(stored.status, workflow::Status::TimedOut);
+        assert_eq!(stored.failure_reason, Some(TIMED_OUT_REASON.to_string()));
+        assert_eq!(
+            stored.unfinished_instructions.unwrap().inner().len(),
+            stored.resources.inner().len()
+        );
+    }
+
+    #[homestar_runtime_proc_macro::runner_test]
+    fn get_receipt_by_cid_and_instruction() {
+        let TestRunner { runner, settings } = TestRunner::start();
+
+        let db = MemoryDb::setup_connection_pool(&settings.node, None).unwrap();
+        let (invocation_receipt, receipt) = crate::test_utils::receipt::receipts();
+        let _ = MemoryDb::store_receipt(receipt.clone(), &mut db.conn().unwrap()).unwrap();
+
+        runner.runtime.block_on(async {
+            let by_cid = runner
+                .get_receipt(
+                    &receipt.cid().to_string(),
+                    ReceiptLookup::Receipt,
+                    true,
+                    db.clone(),
+                    time::Duration::ZERO,
+                )
+                .await
+                .unwrap();
+            assert_eq!(by_cid.cid, receipt.cid().to_string());
+            assert_eq!(by_cid.source, "local");
+
+            let bytes = by_cid.dag_cbor_bytes().unwrap().unwrap();
+            let decoded = homestar_invocation::Receipt::try_from(bytes).unwrap();
+            assert_eq!(decoded, invocation_receipt);
+
+            let by_instruction = runner
+                .get_receipt(
+                    &receipt.instruction().to_string(),
+                    ReceiptLookup::Instruction,
+                    false,
+                    db.clone(),
+                    time::Duration::ZERO,
+                )
+                .await
+                .unwrap();
+            assert_eq!(by_instruction.cid, receipt.cid().to_string());
+            assert!(by_instruction.dag_cbor.is_none());
+
+            let missing = runner
+                .get_receipt(
+                    &receipt.cid().to_string(),
+                    ReceiptLookup::Instruction,
+                    false,
+                    db.clone(),
+                    time::Duration::ZERO,
+                )
+                .await;
+            assert_eq!(
+                missing.unwrap_err().to_string(),
+                format!("no receipt found for instruction Cid: {}", receipt.cid())
+            );
+
+            assert!(runner
+                .get_receipt(
+                    "not-a-cid",
+                    ReceiptLookup::Receipt,
+                    false,
+                    db,
+                    time::Duration::ZERO
+                )
+                .await
+                .is_err());
+        });
+    }
+
+    #[homestar_runtime_proc_macro::runner_test]
+    fn get_stored_workflow_document() {
+        let TestRunner { runner, settings } = TestRunner::start();
+
+        let builder = WorkerBuilder::new(settings.node);
+        let db = builder.db();
+        let workflow = builder.workflow();
+        let workflow_name = runner
+            .runtime
+            .block_on(async { builder.build().await.workflow_name.clone() });
+
+        let document = runner
+            .get_workflow(workflow_name.as_str(), true, db.clone())
+            .unwrap();
+        assert_eq!(document.cid, workflow.clone().to_cid().unwrap().to_string());
+        assert_eq!(document.num_tasks, workflow.len());
+        assert_eq!(
+            document.dag_cbor_bytes().unwrap(),
+            Some(workflow.to_cbor().unwrap())
+        );
+
+        // Workflows stored without a document, e.g. found on the network,
+        // can't be returned.
+        let stored = MemoryDb::store_workflow(
+            workflow::Stored::default(Pointer::new(generate_cid(&mut thread_rng())), 1),
+            &mut db.conn().unwrap(),
+        )
+        .unwrap();
+        assert!(runner
+            .get_workflow(&stored.cid.to_string(), false, db.clone())
+            .is_err());
+        assert!(runner.get_workflow("not-a-workflow", false, db).is_err());
+    }
+
+    #[homestar_runtime_proc_macro::runner_test]
+    fn verify_receipts_by_re_execution() {
+        let TestRunner { runner, settings } = TestRunner::start();
+
+        let builder = WorkerBuilder::new(settings.node);
+        let db = builder.db();
+        let tasks = builder.workflow().tasks();
+        runner.runtime.block_on(builder.build());
+
+        let (_, _, instruction3) =
+            homestar_invocation::test_utils::related_wasm_instructions::<Arg>();
+        let unknown_task = Task::new(
+            RunInstruction::Expanded(instruction3),
+            Ipld::Null,
+            UcanPrf::default(),
+        );
+
+        let receipt = |task: &Task<'_, Arg>, out: task::Result<Ipld>| {
+            let invocation_receipt = InvocationReceipt::new(
+                Invocation::new(task.clone()).try_into().unwrap(),
+                out,
+                Ipld::Null,
+                None,
+                UcanPrf::default(),
+            );
+            let receipt = Receipt::try_with(
+                Pointer::new(task.instruction_cid().unwrap()),
+                &invocation_receipt,
+            )
+            .unwrap();
+            MemoryDb::store_receipt(receipt.clone(), &mut db.conn().unwrap()).unwrap();
+            receipt
+        };
+
+        // `add_one(1)`, with its output as input to the second task.
+        let honest = receipt(&tasks[0], task::Result::Ok(Ipld::Integer(2)));
+        // `add_one(2)`, claimed to be 7.
+        let forged = receipt(&tasks[1], task::Result::Ok(Ipld::Integer(7)));
+        let unknown = receipt(&unknown_task, task::Result::Ok(Ipld::Integer(1)));
+
+        let verified = runner
+            .runtime
+            .block_on(runner.verify_receipt(&honest.cid_as_string(), db.clone()))
+            .unwrap();
+        assert_eq!(verified.verdict, "match");
+        assert!(!verified.is_mismatch());
+
+        let verified = runner
+            .runtime
+            .block_on(runner.verify_receipt(&forged.cid_as_string(), db.clone()))
+            .unwrap();
+        assert_eq!(verified.verdict, "mismatch");
+        assert!(verified.is_mismatch());
+        assert_eq!(verified.actual, Some(serde_json::json!(["ok", 3])));
+
+        // Mismatched receipts are flagged by default, and never reused.
+        let verifier = Verifier::new(
+            runner.settings.node.network(),
+            runner.executors.clone(),
+            runner.task_permits.clone(),
+        );
+        assert!(runner.runtime.block_on(verifier.admit(&forged, db.clone())));
+        let mut conn = db.conn().unwrap();
+        assert!(MemoryDb::is_receipt_flagged(forged.cid(), &mut conn).unwrap());
+        assert!(!MemoryDb::is_receipt_flagged(honest.cid(), &mut conn).unwrap());
+        assert!(
+            MemoryDb::find_instruction_by_cid(tasks[1].instruction_cid().unwrap(), &mut conn)
+                .is_err()
+        );
+
+        let verified = runner
+            .runtime
+            .block_on(runner.verify_receipt(&unknown.cid_as_string(), db.clone()))
+            .unwrap();
+        assert_eq!(verified.verdict, "unverifiable");
+        assert!(verified.reason.is_some());
+
+        assert!(runner
+            .runtime
+            .block_on(runner.verify_receipt(&generate_cid(&mut thread_rng()).to_string(), db))
+            .is_err());
+    }
+
     #[homestar_runtime_proc_macro::runner_test]
     fn abort_and_cleanup_all_workers() {
         let TestRunner { runner, settings } = TestRunner::start();
@@ -0,0 +1,118 @@
+//! Priority queue of workflows waiting on a free slot to run, used by the
+//! [Runner] to limit the number of workflows run concurrently.
+//!
+//! [Runner]: crate::Runner
+
+use anyhow::Result;
+use futures::future::BoxFuture;
+use libipld::Cid;
+use std::{fmt, time::Duration};
+use tokio_util::time::delay_queue;
+
+/// Worker run for a workflow, queued until it's admitted.
+pub(crate) struct Queued {
+    /// Cid of the workflow.
+    pub(crate) cid: Cid,
+    /// Priority of the workflow, where higher runs first.
+    pub(crate) priority: i32,
+    /// Timeout for the workflow, started once it's admitted or queued.
+    pub(crate) timeout: Duration,
+    /// Key of the workflow's started timeout, if it's been queued.
+    pub(crate) expiration: Option<delay_queue::Key>,
+    /// Worker run to spawn once admitted.
+    pub(crate) run: BoxFuture<'static, Result<()>>,
+}
+
+impl fmt::Debug for Queued {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Queued")
+            .field("cid", &self.cid)
+            .field("priority", &self.priority)
+            .field("timeout", &self.timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Queue of workflows ordered by priority, and then by submission order
+/// for workflows of the same priority.
+#[derive(Debug, Default)]
+pub(crate) struct AdmissionQueue {
+    queued: Vec<Queued>,
+}
+
+impl AdmissionQueue {
+    /// Queue a workflow run, returning its 1-based position in the queue.
+    pub(crate) fn push(&mut self, queued: Queued) -> usize {
+        let index = self
+            .queued
+            .iter()
+            .take_while(|other| other.priority >= queued.priority)
+            .count();
+        self.queued.insert(index, queued);
+        index + 1
+    }
+
+    /// Take the next workflow run to admit, if any.
+    pub(crate) fn pop(&mut self) -> Option<Queued> {
+        if self.queued.is_empty() {
+            None
+        } else {
+            Some(self.queued.remove(0))
+        }
+    }
+
+    /// Remove a queued workflow run given the workflow's Cid.
+    pub(crate) fn remove(&mut self, cid: Cid) -> Option<Queued> {
+        let index = self.queued.iter().position(|queued| queued.cid == cid)?;
+        Some(self.queued.remove(index))
+    }
+
+    /// Whether any workflow is queued.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.queued.is_empty()
+    }
+
+    /// Clear the queue, dropping any queued workflow runs.
+    pub(crate) fn clear(&mut self) {
+        self.queued.clear()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::FutureExt;
+    use homestar_invocation::test_utils::cid::generate_cid;
+    use rand::thread_rng;
+
+    fn queued(priority: i32) -> Queued {
+        Queued {
+            cid: generate_cid(&mut thread_rng()),
+            priority,
+            timeout: Duration::from_secs(60),
+            expiration: None,
+            run: async { Ok(()) }.boxed(),
+        }
+    }
+
+    #[test]
+    fn admits_by_priority_then_submission_order() {
+        let mut queue = AdmissionQueue::default();
+        let (low, first, second, high) = (queued(-1), queued(0), queued(0), queued(5));
+        let (low_cid, first_cid, second_cid, high_cid) = (low.cid, first.cid, second.cid, high.cid);
+
+        assert_eq!(queue.push(first), 1);
+        assert_eq!(queue.push(low), 2);
+        assert_eq!(queue.push(second), 2);
+        assert_eq!(queue.push(high), 1);
+
+        assert_eq!(queue.remove(low_cid).unwrap().priority, -1);
+        assert!(queue.remove(low_cid).is_none());
+
+        assert_eq!(queue.pop().unwrap().cid, high_cid);
+        assert_eq!(queue.pop().unwrap().cid, first_cid);
+        assert_eq!(queue.pop().unwrap().cid, second_cid);
+        assert!(queue.pop().is_none());
+        assert!(queue.is_empty());
+    }
+}
@@ -4,15 +4,24 @@
 
 use super::Error;
 use crate::workflow;
-use homestar_invocation::ipld::DagJson;
+use anyhow::anyhow;
+use homestar_invocation::ipld::{DagCbor, DagJson};
 use homestar_wasm::io::Arg;
 use homestar_workflow::Workflow;
+use indexmap::IndexMap;
+use libipld::Cid;
 use serde::{Deserialize, Serialize};
 use std::{ffi::OsStr, fmt, path::PathBuf, str::FromStr};
 use tokio::fs;
 
+mod car;
+
+/// Blocks bundled alongside a workflow, e.g. Wasm modules within a CAR
+/// file, keyed by [Cid].
+pub(crate) type Bundled = IndexMap<Cid, Vec<u8>>;
+
 /// Data structure for a workflow file path.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ReadWorkflow {
     /// Workflow file to run.
     file: PathBuf,
@@ -35,26 +44,93 @@ impl fmt::Display for ReadWorkflow {
 }
 
 impl ReadWorkflow {
-    /// Validate and parse the workflow file.
+    /// Validate and parse the workflow file, along with any blocks bundled
+    /// with it, and its workflow-level settings.
     ///
-    /// Validation is currently limited to checking the file extension,
-    /// or attempting to treat the file as JSON if no extension is provided.
+    /// The format is chosen by file extension:
+    ///   * `.json`, or no extension, for DAG-JSON;
+    ///   * `.cbor` for DAG-CBOR;
+    ///   * `.car` for a CARv1 file, whose root is the DAG-CBOR workflow and
+    ///     whose other blocks are bundled resources;
+    ///   * `.yaml`/`.yml` for YAML, mirroring the DAG-JSON format, but with
+    ///     links written as `!cid <cid>` and bytes as `!bytes <base64>`.
     pub(crate) async fn validate_and_parse<'a>(
         &self,
-    ) -> Result<(Workflow<'a, Arg>, workflow::Settings), Error> {
-        match self.file.extension().and_then(OsStr::to_str) {
-            None | Some("json") => {
-                let data = fs::read_to_string(&self.file.canonicalize()?).await?;
-                // TODO: Parse this from the workflow data/file itself.
-                let workflow_settings = workflow::Settings::default();
-                Ok((
-                    DagJson::from_json_string(data).map_err(anyhow::Error::new)?,
-                    workflow_settings,
-                ))
-            }
+    ) -> Result<(Workflow<'a, Arg>, workflow::Settings, Bundled), Error> {
+        let (workflow, bundled): (Workflow<'a, Arg>, _) =
+            match self.file.extension().and_then(OsStr::to_str) {
+                None | Some("json") => {
+                    let data = fs::read_to_string(&self.file.canonicalize()?).await?;
+                    (
+                        DagJson::from_json_string(data).map_err(anyhow::Error::new)?,
+                        Bundled::default(),
+                    )
+                }
+                Some("cbor") => {
+                    let data = fs::read(&self.file.canonicalize()?).await?;
+                    (
+                        DagCbor::from_cbor(&data).map_err(anyhow::Error::new)?,
+                        Bundled::default(),
+                    )
+                }
+                Some("car") => {
+                    let data = fs::read(&self.file.canonicalize()?).await?;
+                    let mut car = car::Car::decode(&data)?;
+                    let root = car
+                        .take_root()
+                        .ok_or_else(|| anyhow!("CAR root block not found: {}", car.root))?;
+                    (
+                        DagCbor::from_cbor(&root).map_err(anyhow::Error::new)?,
+                        car.blocks,
+                    )
+                }
+                Some("yaml" | "yml") => {
+                    let data = fs::read_to_string(&self.file.canonicalize()?).await?;
+                    let value = serde_yaml::from_str(&data).map_err(anyhow::Error::new)?;
+                    let value =
+                        serde_json::to_vec(&untag_yaml(value)?).map_err(anyhow::Error::new)?;
+                    (
+                        DagJson::from_json(&value).map_err(anyhow::Error::new)?,
+                        Bundled::default(),
+                    )
+                }
 
-            Some(ext) => Err(Error::UnsupportedWorkflow(ext.to_string())),
-        }
+                Some(ext) => return Err(Error::UnsupportedWorkflow(ext.to_string())),
+            };
+
+        let workflow_settings = workflow::Settings::try_from(workflow.meta())?;
+        Ok((workflow, workflow_settings, bundled))
+    }
+}
+
+/// Rewrite `!cid` and `!bytes` tagged YAML values into the DAG-JSON
+/// representations of links and bytes, i.e. `{"/": <cid>}` and
+/// `{"/": {"bytes": <base64>}}`.
+fn untag_yaml(value: serde_yaml::Value) -> anyhow::Result<serde_yaml::Value> {
+    use serde_yaml::{Mapping, Value};
+
+    let slash = |value| Value::Mapping(Mapping::from_iter([("/".into(), value)]));
+    match value {
+        Value::Tagged(tagged) => match (tagged.tag.to_string().as_str(), tagged.value) {
+            ("!cid", value @ Value::String(_)) => Ok(slash(value)),
+            ("!bytes", value @ Value::String(_)) => Ok(slash(Value::Mapping(Mapping::from_iter(
+                [("bytes".into(), value)],
+            )))),
+            (tag, _) => Err(anyhow!(
+                "unsupported YAML tag {tag}: expected a !cid or !bytes string"
+            )),
+        },
+        Value::Sequence(seq) => Ok(Value::Sequence(
+            seq.into_iter()
+                .map(untag_yaml)
+                .collect::<anyhow::Result<_>>()?,
+        )),
+        Value::Mapping(map) => Ok(Value::Mapping(
+            map.into_iter()
+                .map(|(key, value)| Ok((key, untag_yaml(value)?)))
+                .collect::<anyhow::Result<_>>()?,
+        )),
+        value => Ok(value),
     }
 }
 
@@ -66,6 +142,9 @@ mod test {
         task::{instruction::RunInstruction, Resources},
         test_utils, Task,
     };
+    use homestar_workflow::workflow::Meta;
+    use libipld::multihash::{Code, MultihashDigest};
+    use std::time::Duration;
 
     #[tokio::test]
     async fn validate_and_parse_workflow() {
@@ -90,7 +169,8 @@ mod test {
         workflow.to_file(path.display().to_string()).unwrap();
         let workflow_file = ReadWorkflow { file: path.clone() };
 
-        let (validated_workflow, _settings) = workflow_file.validate_and_parse().await.unwrap();
+        let (validated_workflow, _settings, _bundled) =
+            workflow_file.validate_and_parse().await.unwrap();
 
         assert_eq!(workflow, validated_workflow);
 
@@ -112,7 +192,7 @@ mod test {
         let workflow_file = ReadWorkflow {
             file: new_path.clone(),
         };
-        let (newly_validated_workflow, _settings) =
+        let (newly_validated_workflow, _settings, _bundled) =
             workflow_file.validate_and_parse().await.unwrap();
         assert_eq!(workflow, newly_validated_workflow);
     }
@@ -134,8 +214,141 @@ mod test {
         workflow.to_file(path.display().to_string()).unwrap();
         let workflow_file = ReadWorkflow { file: path.clone() };
 
-        let (validated_workflow, _settings) = workflow_file.validate_and_parse().await.unwrap();
+        let (validated_workflow, _settings, _bundled) =
+            workflow_file.validate_and_parse().await.unwrap();
 
         assert_eq!(workflow, validated_workflow);
     }
+
+    #[tokio::test]
+    async fn validate_and_parse_cbor_car_and_yaml_workflows() {
+        let config = Resources::default();
+        let (instruction, _) = test_utils::wasm_instruction_with_nonce::<Arg>();
+        let task = Task::new(
+            RunInstruction::Expanded(instruction),
+            config.into(),
+            UcanPrf::default(),
+        );
+        let workflow = Workflow::new(vec![task]);
+
+        let cbor = workflow.clone().to_cbor().unwrap();
+        let path = PathBuf::from("./fixtures/test_workflow.cbor");
+        fs::write(&path, &cbor).await.unwrap();
+        let (parsed, _settings, bundled) = ReadWorkflow { file: path.clone() }
+            .validate_and_parse()
+            .await
+            .unwrap();
+        assert_eq!(parsed, workflow);
+        assert!(bundled.is_empty());
+        fs::remove_file(path).await.unwrap();
+
+        let root = workflow.clone().to_cid().unwrap();
+        let wasm = b"\0asm".to_vec();
+        let wasm_cid = Cid::new_v1(0x55, Code::Sha2_256.digest(&wasm));
+        let path = PathBuf::from("./fixtures/test_workflow.car");
+        fs::write(
+            &path,
+            car::test_utils::encode(&[root], &[(root, cbor), (wasm_cid, wasm.clone())]),
+        )
+        .await
+        .unwrap();
+        let (parsed, _settings, bundled) = ReadWorkflow { file: path.clone() }
+            .validate_and_parse()
+            .await
+            .unwrap();
+        assert_eq!(parsed, workflow);
+        assert_eq!(bundled.get(&wasm_cid), Some(&wasm));
+        assert_eq!(bundled.len(), 1);
+        fs::remove_file(path).await.unwrap();
+
+        let mut meta = Meta::default();
+        meta.set_timeout(Duration::from_secs(30));
+        meta.set_priority(1);
+        let workflow = workflow.with_meta(meta);
+        let json: serde_json::Value =
+            serde_json::from_str(&workflow.clone().to_json_string().unwrap()).unwrap();
+        let path = PathBuf::from("./fixtures/test_workflow.yaml");
+        fs::write(&path, serde_yaml::to_string(&json).unwrap())
+            .await
+            .unwrap();
+        let (parsed, settings, _bundled) = ReadWorkflow { file: path.clone() }
+            .validate_and_parse()
+            .await
+            .unwrap();
+        assert_eq!(parsed, workflow);
+        assert_eq!(settings.timeout, Duration::from_secs(30));
+        assert_eq!(settings.priority, 1);
+        fs::remove_file(path).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn validate_and_parse_tagged_yaml_workflow() {
+        fn tag(value: serde_json::Value) -> serde_yaml::Value {
+            use serde_yaml::value::{Tag, TaggedValue};
+
+            let tagged = |tag, value: &String| {
+                serde_yaml::Value::Tagged(Box::new(TaggedValue {
+                    tag: Tag::new(tag),
+                    value: value.clone().into(),
+                }))
+            };
+            match value {
+                serde_json::Value::Object(map) if map.len() == 1 && map.contains_key("/") => {
+                    match &map["/"] {
+                        serde_json::Value::String(cid) => tagged("cid", cid),
+                        serde_json::Value::Object(bytes) => match &bytes["bytes"] {
+                            serde_json::Value::String(bytes) => tagged("bytes", bytes),
+                            _ => panic!("malformed DAG-JSON bytes"),
+                        },
+                        _ => panic!("malformed DAG-JSON link"),
+                    }
+                }
+                serde_json::Value::Object(map) => serde_yaml::Value::Mapping(
+                    map.into_iter().map(|(k, v)| (k.into(), tag(v))).collect(),
+                ),
+                serde_json::Value::Array(seq) => {
+                    serde_yaml::Value::Sequence(seq.into_iter().map(tag).collect())
+                }
+                value => serde_yaml::to_value(value).unwrap(),
+            }
+        }
+
+        let config = Resources::default();
+        let (instruction, _) = test_utils::wasm_instruction_with_nonce::<Arg>();
+        let (instruction1, instruction2, _) = test_utils::related_wasm_instructions::<Arg>();
+        let workflow = Workflow::new(
+            [instruction, instruction1, instruction2]
+                .into_iter()
+                .map(|instruction| {
+                    Task::new(
+                        RunInstruction::Expanded(instruction),
+                        config.clone().into(),
+                        UcanPrf::default(),
+                    )
+                })
+                .collect(),
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&workflow.clone().to_json_string().unwrap()).unwrap();
+        let yaml = serde_yaml::to_string(&tag(json)).unwrap();
+        assert!(yaml.contains("!cid "));
+        assert!(yaml.contains("!bytes "));
+        assert!(!yaml.contains("'/'"));
+
+        let path = PathBuf::from("./fixtures/test_tagged_workflow.yaml");
+        fs::write(&path, yaml).await.unwrap();
+        let (parsed, _settings, _bundled) = ReadWorkflow { file: path.clone() }
+            .validate_and_parse()
+            .await
+            .unwrap();
+        assert_eq!(parsed, workflow);
+
+        fs::write(&path, "tasks: !link bafy").await.unwrap();
+        assert!(ReadWorkflow { file: path.clone() }
+            .validate_and_parse()
+            .await
+            .is_err());
+        fs::remove_file(path).await.unwrap();
+    }
 }
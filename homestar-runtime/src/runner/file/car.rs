@@ -0,0 +1,352 @@
+//! Reader for [CARv1] archives bundling a [Workflow] with its resources.
+//!
+//! The archive's single root is the DAG-CBOR encoded workflow. Any other
+//! blocks, e.g. Wasm modules encoded as raw blocks, are kept around to be
+//! used in place of fetching the same resource over the network. Files
+//! chunked across [UnixFS] blocks are reassembled from their chunks.
+//!
+//! [CARv1]: <https://ipld.io/specs/transport/car/carv1/>
+//! [UnixFS]: <https://github.com/ipfs/specs/blob/main/UNIXFS.md>
+//! [Workflow]: homestar_workflow::Workflow
+
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use fnv::FnvHashSet;
+use indexmap::IndexMap;
+use libipld::{
+    multihash::{Code, MultihashDigest},
+    serde::from_ipld,
+    Cid, Ipld,
+};
+use serde::Deserialize;
+use std::io::Cursor;
+
+/// Supported CAR version.
+const CAR_VERSION: u64 = 1;
+
+/// Multicodec code of raw blocks, e.g. the chunks of a UnixFS file.
+const RAW: u64 = 0x55;
+/// Multicodec code of DAG-PB blocks, e.g. the nodes of a UnixFS file.
+const DAG_PB: u64 = 0x70;
+
+/// UnixFS data types of file nodes, as opposed to e.g. directories.
+const UNIXFS_RAW: u64 = 0;
+const UNIXFS_FILE: u64 = 2;
+
+/// Header of a CARv1 archive.
+#[derive(Debug, Deserialize)]
+struct Header {
+    version: u64,
+    roots: Vec<Cid>,
+}
+
+/// Decoded CARv1 archive.
+#[derive(Debug)]
+pub(crate) struct Car {
+    /// Root [Cid] of the archive.
+    pub(crate) root: Cid,
+    /// Blocks contained in the archive, keyed by [Cid].
+    pub(crate) blocks: IndexMap<Cid, Vec<u8>>,
+}
+
+impl Car {
+    /// Decode a CARv1 archive, verifying each block against its [Cid].
+    ///
+    /// The archive must have exactly one root, which must be present
+    /// in the archive itself.
+    pub(crate) fn decode(bytes: &[u8]) -> Result<Self> {
+        let (header, mut rest) = read_section(bytes).context("failed to read CAR header")?;
+        let header: Header = from_ipld(serde_ipld_dagcbor::from_slice::<Ipld>(header)?)
+            .map_err(|e| anyhow!("invalid CAR header: {e}"))?;
+
+        ensure!(
+            header.version == CAR_VERSION,
+            "unsupported CAR version: {}",
+            header.version
+        );
+        let root = match header.roots.as_slice() {
+            [root] => *root,
+            roots => bail!("expected a single CAR root, found {}", roots.len()),
+        };
+
+        let mut blocks = IndexMap::new();
+        while !rest.is_empty() {
+            let (section, next) = read_section(rest).context("failed to read CAR block")?;
+            let mut cursor = Cursor::new(section);
+            let cid = Cid::read_bytes(&mut cursor)?;
+            let data = &section[cursor.position() as usize..];
+
+            let code = Code::try_from(cid.hash().code())?;
+            ensure!(
+                code.digest(data) == *cid.hash(),
+                "CAR block does not match its Cid: {cid}"
+            );
+
+            blocks.insert(cid, data.to_vec());
+            rest = next;
+        }
+
+        ensure!(
+            blocks.contains_key(&root),
+            "CAR root block not found: {root}"
+        );
+
+        let mut car = Self { root, blocks };
+        car.reassemble_files()?;
+        Ok(car)
+    }
+
+    /// Replace the root nodes of UnixFS files chunked across DAG-PB blocks
+    /// with the files' contents, reassembled from their chunks, so that
+    /// they're bundled like files encoded as single raw blocks.
+    ///
+    /// Every chunk must be present in the archive.
+    fn reassemble_files(&mut self) -> Result<()> {
+        let mut files = IndexMap::new();
+        let mut chunks = FnvHashSet::default();
+        for (cid, block) in self.blocks.iter().filter(|(cid, _)| cid.codec() == DAG_PB) {
+            if let Some(node) = FileNode::decode(block)? {
+                chunks.extend(node.links.iter().copied());
+                files.insert(*cid, node);
+            }
+        }
+
+        let mut reassembled = vec![];
+        for (cid, node) in files.iter().filter(|(cid, _)| !chunks.contains(*cid)) {
+            let mut contents = node.data.to_vec();
+            for link in node.links.iter() {
+                self.read_chunk(cid, link, &mut contents)?;
+            }
+            reassembled.push((*cid, contents));
+        }
+
+        self.blocks.extend(reassembled);
+        Ok(())
+    }
+
+    /// Append the contents of a chunk of a UnixFS file to `contents`.
+    fn read_chunk(&self, file: &Cid, chunk: &Cid, contents: &mut Vec<u8>) -> Result<()> {
+        let block = self
+            .blocks
+            .get(chunk)
+            .ok_or_else(|| anyhow!("UnixFS file {file} is missing chunk {chunk} in CAR"))?;
+
+        match chunk.codec() {
+            RAW => contents.extend_from_slice(block),
+            DAG_PB => {
+                let node = FileNode::decode(block)?
+                    .ok_or_else(|| anyhow!("UnixFS file {file} chunk is not a file: {chunk}"))?;
+                contents.extend_from_slice(node.data);
+                for link in node.links.iter() {
+                    self.read_chunk(file, link, contents)?;
+                }
+            }
+            codec => bail!("UnixFS file {file} chunk has unsupported codec {codec:#x}: {chunk}"),
+        }
+
+        Ok(())
+    }
+
+    /// Remove and return the root block of the archive.
+    pub(crate) fn take_root(&mut self) -> Option<Vec<u8>> {
+        self.blocks.shift_remove(&self.root)
+    }
+}
+
+/// UnixFS file node, decoded from a DAG-PB block: the data it holds itself,
+/// followed by the chunks it links to, in order.
+#[derive(Debug)]
+struct FileNode<'a> {
+    data: &'a [u8],
+    links: Vec<Cid>,
+}
+
+impl<'a> FileNode<'a> {
+    /// Decode a DAG-PB block as a UnixFS file node, or `None` if it's
+    /// another type of UnixFS node, e.g. a directory.
+    fn decode(block: &'a [u8]) -> Result<Option<Self>> {
+        let mut unixfs = None;
+        let mut links = vec![];
+        for (number, field) in fields(block)? {
+            match (number, field) {
+                (1, Field::Bytes(data)) => unixfs = Some(data),
+                (2, Field::Bytes(link)) => {
+                    for (number, field) in fields(link)? {
+                        if let (1, Field::Bytes(hash)) = (number, field) {
+                            links.push(Cid::try_from(hash)?);
+                        }
+                    }
+                }
+                _ => bail!("invalid DAG-PB block"),
+            }
+        }
+
+        let mut file_type = None;
+        let mut data: &[u8] = &[];
+        for (number, field) in fields(unixfs.unwrap_or_default())? {
+            match (number, field) {
+                (1, Field::Varint(value)) => file_type = Some(value),
+                (2, Field::Bytes(bytes)) => data = bytes,
+                _ => {}
+            }
+        }
+
+        match file_type {
+            Some(UNIXFS_RAW | UNIXFS_FILE) => Ok(Some(Self { data, links })),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Value of a protobuf field.
+enum Field<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+}
+
+/// Decode the fields of a protobuf message, by field number, as used by
+/// DAG-PB and UnixFS.
+fn fields(mut bytes: &[u8]) -> Result<Vec<(u64, Field<'_>)>> {
+    let mut fields = vec![];
+    while !bytes.is_empty() {
+        let (key, rest) = unsigned_varint::decode::u64(bytes)?;
+        let (field, rest) = match key & 0x7 {
+            0 => {
+                let (value, rest) = unsigned_varint::decode::u64(rest)?;
+                (Field::Varint(value), rest)
+            }
+            2 => {
+                let (value, rest) = read_section(rest)?;
+                (Field::Bytes(value), rest)
+            }
+            wire_type => bail!("unsupported protobuf wire type: {wire_type}"),
+        };
+        fields.push((key >> 3, field));
+        bytes = rest;
+    }
+
+    Ok(fields)
+}
+
+/// Read a varint length-prefixed section, returning it and the remaining
+/// bytes.
+fn read_section(bytes: &[u8]) -> Result<(&[u8], &[u8])> {
+    let (len, rest) = unsigned_varint::decode::usize(bytes)?;
+    ensure!(rest.len() >= len, "unexpected end of CAR data");
+    Ok(rest.split_at(len))
+}
+
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use libipld::Cid;
+
+    /// Encode blocks into a CARv1 archive with the given roots.
+    pub(crate) fn encode(roots: &[Cid], blocks: &[(Cid, Vec<u8>)]) -> Vec<u8> {
+        let header = libipld::ipld!({
+            "roots": roots.iter().map(|cid| libipld::Ipld::Link(*cid)).collect::<Vec<_>>(),
+            "version": 1,
+        });
+        let header = serde_ipld_dagcbor::to_vec(&header).unwrap();
+
+        let mut car = vec![];
+        write_section(&mut car, &header);
+        for (cid, data) in blocks {
+            write_section(&mut car, &[cid.to_bytes(), data.to_vec()].concat());
+        }
+        car
+    }
+
+    fn write_section(buf: &mut Vec<u8>, data: &[u8]) {
+        let mut len = unsigned_varint::encode::usize_buffer();
+        buf.extend_from_slice(unsigned_varint::encode::usize(data.len(), &mut len));
+        buf.extend_from_slice(data);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use libipld::multihash::Code;
+
+    /// Encode a protobuf field, given its number and wire type.
+    fn field(number: u64, wire_type: u64, value: &[u8]) -> Vec<u8> {
+        let mut key = unsigned_varint::encode::u64_buffer();
+        let mut len = unsigned_varint::encode::usize_buffer();
+        let mut field = unsigned_varint::encode::u64((number << 3) | wire_type, &mut key).to_vec();
+        if wire_type == 2 {
+            field.extend_from_slice(unsigned_varint::encode::usize(value.len(), &mut len));
+        }
+        field.extend_from_slice(value);
+        field
+    }
+
+    /// Encode a UnixFS file node as a DAG-PB block, linking to its chunks.
+    fn file_node(data: &[u8], links: &[Cid]) -> (Cid, Vec<u8>) {
+        let mut file_type = unsigned_varint::encode::u64_buffer();
+        let unixfs = [
+            field(
+                1,
+                0,
+                unsigned_varint::encode::u64(UNIXFS_FILE, &mut file_type),
+            ),
+            field(2, 2, data),
+        ]
+        .concat();
+
+        let mut block: Vec<u8> = links
+            .iter()
+            .flat_map(|link| field(2, 2, &field(1, 2, &link.to_bytes())))
+            .collect();
+        block.extend(field(1, 2, &unixfs));
+        (Cid::new_v1(DAG_PB, Code::Sha2_256.digest(&block)), block)
+    }
+
+    #[test]
+    fn decode_car_and_verify_blocks() {
+        let data = b"wasm".to_vec();
+        let cid = Cid::new_v1(RAW, Code::Sha2_256.digest(&data));
+        let bytes = test_utils::encode(&[cid], &[(cid, data.clone())]);
+
+        let mut car = Car::decode(&bytes).unwrap();
+        assert_eq!(car.root, cid);
+        assert_eq!(car.take_root(), Some(data.clone()));
+        assert!(car.blocks.is_empty());
+
+        let tampered = test_utils::encode(&[cid], &[(cid, b"nope".to_vec())]);
+        assert!(Car::decode(&tampered)
+            .unwrap_err()
+            .to_string()
+            .contains("does not match its Cid"));
+
+        let missing_root = test_utils::encode(&[cid], &[]);
+        assert!(Car::decode(&missing_root).is_err());
+    }
+
+    #[test]
+    fn reassemble_chunked_unixfs_files() {
+        let root = Cid::new_v1(RAW, Code::Sha2_256.digest(b"workflow"));
+        let chunks = [b"\0asm".to_vec(), b"\x01\0\0\0".to_vec()];
+        let chunk_cids: Vec<Cid> = chunks
+            .iter()
+            .map(|chunk| Cid::new_v1(RAW, Code::Sha2_256.digest(chunk)))
+            .collect();
+        let (inner_cid, inner) = file_node(b"", &chunk_cids[1..]);
+        let (file_cid, file) = file_node(b"", &[chunk_cids[0], inner_cid]);
+
+        let mut blocks = vec![
+            (root, b"workflow".to_vec()),
+            (file_cid, file),
+            (inner_cid, inner),
+            (chunk_cids[0], chunks[0].clone()),
+            (chunk_cids[1], chunks[1].clone()),
+        ];
+        let car = Car::decode(&test_utils::encode(&[root], &blocks)).unwrap();
+        assert_eq!(car.blocks.get(&file_cid), Some(&chunks.concat()));
+        assert_eq!(car.blocks.get(&chunk_cids[0]), Some(&chunks[0]));
+
+        blocks.pop();
+        assert!(Car::decode(&test_utils::encode(&[root], &blocks))
+            .unwrap_err()
+            .to_string()
+            .contains(&format!("missing chunk {}", chunk_cids[1])));
+    }
+}
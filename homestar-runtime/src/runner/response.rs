@@ -3,14 +3,23 @@
 
 use crate::{
     cli::show::{self, ApplyStyle},
+    receipt::verify::Verdict,
     runner::WorkflowReceiptInfo,
     workflow::{self, IndexedResources},
+    Receipt,
 };
-use chrono::NaiveDateTime;
+use chrono::{NaiveDateTime, Utc};
 use faststr::FastStr;
-use libipld::Cid;
+use homestar_invocation::{
+    ipld::{DagCbor, DagJson},
+    task, Receipt as InvocationReceipt,
+};
+use homestar_wasm::io::Arg;
+use homestar_workflow::Workflow;
+use libipld::{codec::Codec, json::DagJsonCodec, Cid, Ipld};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::{fmt, net::SocketAddr, sync::Arc};
+use std::{collections::HashMap, fmt, net::SocketAddr, sync::Arc};
 use tabled::{
     builder::Builder,
     col,
@@ -28,6 +37,9 @@ pub struct AckWorkflow {
     pub(crate) name: FastStr,
     pub(crate) num_tasks: u32,
     pub(crate) progress_count: u32,
+    pub(crate) status: workflow::Status,
+    #[tabled(display_with = "display_option")]
+    pub(crate) queue_position: Option<usize>,
     #[tabled(skip)]
     pub(crate) resources: IndexedResources,
     #[tabled(skip)]
@@ -39,25 +51,37 @@ impl fmt::Display for AckWorkflow {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "cid: {}, progress: {}/{}, timestamp: {}",
-            self.cid, self.progress_count, self.num_tasks, self.timestamp
-        )
+            "cid: {}, status: {}, progress: {}/{}, timestamp: {}",
+            self.cid, self.status, self.progress_count, self.num_tasks, self.timestamp
+        )?;
+
+        if let Some(position) = self.queue_position {
+            write!(f, ", queue position: {position}")?;
+        }
+
+        Ok(())
     }
 }
 
 impl AckWorkflow {
     /// Workflow information for response / display.
+    ///
+    /// Workflows given a queue position are pending, waiting on a free
+    /// slot to run.
     pub(crate) fn new(
         workflow_info: Arc<workflow::Info>,
         replayed_receipt_info: Vec<WorkflowReceiptInfo>,
         name: FastStr,
         timestamp: NaiveDateTime,
+        queue_position: Option<usize>,
     ) -> Self {
         Self {
             cid: workflow_info.cid,
             name,
             num_tasks: workflow_info.num_tasks,
             progress_count: workflow_info.progress_count,
+            status: queued_status(queue_position),
+            queue_position,
             resources: workflow_info.resources.clone(),
             replayed_receipt_info,
             timestamp: timestamp.format("%Y-%m-%d %H:%M:%S").to_string(),
@@ -117,6 +141,604 @@ impl show::ConsoleTable for AckWorkflow {
     }
 }
 
+/// Workflow information specified for response / display upon
+/// acknowledgement of cancelling a workflow.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Tabled, JsonSchema)]
+#[schemars(rename = "cancel_workflow")]
+pub struct AckCancel {
+    #[schemars(description = "Workflow CID")]
+    pub(crate) cid: String,
+    #[schemars(description = "Local workflow name")]
+    pub(crate) name: String,
+    #[schemars(description = "Workflow status")]
+    pub(crate) status: String,
+    #[schemars(description = "Local time the workflow was cancelled")]
+    pub(crate) timestamp: String,
+}
+
+impl fmt::Display for AckCancel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "cid: {}, status: {}, timestamp: {}",
+            self.cid, self.status, self.timestamp
+        )
+    }
+}
+
+impl AckCancel {
+    /// Cancelled workflow information for response / display.
+    pub(crate) fn new(stored: workflow::Stored) -> Self {
+        let cid = stored.cid.cid().to_string();
+        let cancelled_at = stored.failed_at.unwrap_or(Utc::now().naive_utc());
+
+        Self {
+            name: stored.name.unwrap_or(cid.clone()),
+            cid,
+            status: stored.status.to_string(),
+            timestamp: cancelled_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+        }
+    }
+}
+
+impl show::ConsoleTable for AckCancel {
+    fn table(&self) -> show::Output {
+        Table::new(vec![&self]).default_with_title("cancel")
+    }
+
+    fn echo_table(&self) -> Result<(), std::io::Error> {
+        self.table().echo()
+    }
+}
+
+/// Workflow information specified for response upon acknowledgement of
+/// running a workflow submitted inline over JSON-RPC.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[schemars(rename = "run_workflow")]
+pub struct AckRunWorkflow {
+    #[schemars(description = "Workflow CID")]
+    pub(crate) cid: String,
+    #[schemars(description = "Local workflow name")]
+    pub(crate) name: String,
+    #[schemars(description = "Workflow status, either running or pending")]
+    pub(crate) status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Position of a pending workflow in the run queue")]
+    pub(crate) queue_position: Option<usize>,
+}
+
+impl AckRunWorkflow {
+    /// Create a new [AckRunWorkflow] response.
+    pub(crate) fn new(cid: Cid, name: FastStr, queue_position: Option<usize>) -> Self {
+        Self {
+            cid: cid.to_string(),
+            name: name.to_string(),
+            status: queued_status(queue_position).to_string(),
+            queue_position,
+        }
+    }
+}
+
+/// Workflow summary for response / display when listing workflows.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Tabled)]
+pub struct WorkflowSummary {
+    pub(crate) cid: String,
+    pub(crate) name: String,
+    pub(crate) status: String,
+    pub(crate) num_tasks: i32,
+    pub(crate) progress_count: i64,
+    pub(crate) created_at: String,
+    #[tabled(display_with = "display_option")]
+    pub(crate) completed_at: Option<String>,
+}
+
+impl WorkflowSummary {
+    /// Summarize a stored workflow, given the number of receipts it has
+    /// emitted.
+    pub(crate) fn new(stored: &workflow::Stored, progress_count: i64) -> Self {
+        let cid = stored.cid.cid().to_string();
+        Self {
+            name: stored.name.clone().unwrap_or(cid.clone()),
+            cid,
+            status: stored.status.to_string(),
+            num_tasks: stored.num_tasks,
+            progress_count,
+            created_at: format_timestamp(stored.created_at),
+            completed_at: stored.completed_at.map(format_timestamp),
+        }
+    }
+}
+
+/// Listed workflows for response / display.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AckWorkflowList {
+    pub(crate) workflows: Vec<WorkflowSummary>,
+}
+
+impl AckWorkflowList {
+    /// Create a new [AckWorkflowList] response.
+    pub(crate) fn new(workflows: Vec<WorkflowSummary>) -> Self {
+        Self { workflows }
+    }
+}
+
+impl show::ConsoleTable for AckWorkflowList {
+    fn table(&self) -> show::Output {
+        if self.workflows.is_empty() {
+            let mut builder = Builder::default();
+            builder.push_record(["Workflows".to_string()]);
+            builder.push_record(["<none>".to_string()]);
+            builder.build().default_with_title("workflows")
+        } else {
+            Table::new(&self.workflows).default_with_title("workflows")
+        }
+    }
+
+    fn echo_table(&self) -> Result<(), std::io::Error> {
+        self.table().echo()
+    }
+}
+
+/// Task information within a workflow for response / display.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Tabled)]
+pub struct TaskSummary {
+    pub(crate) instruction: String,
+    pub(crate) status: String,
+    #[tabled(display_with = "display_option")]
+    pub(crate) receipt: Option<String>,
+    pub(crate) resources: String,
+}
+
+/// Detailed workflow information for response / display, including its
+/// tasks, receipts, resources, and timings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AckWorkflowDetail {
+    pub(crate) workflow: WorkflowSummary,
+    pub(crate) failure: Option<WorkflowFailure>,
+    pub(crate) duration: Option<String>,
+    pub(crate) tasks: Vec<TaskSummary>,
+}
+
+/// Failure information for a failed, cancelled, or timed out workflow.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Tabled)]
+pub struct WorkflowFailure {
+    pub(crate) reason: String,
+    #[tabled(display_with = "display_option")]
+    pub(crate) instruction: Option<String>,
+    #[tabled(display_with = "display_option")]
+    pub(crate) failed_at: Option<String>,
+    #[tabled(skip)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) unfinished: Vec<String>,
+}
+
+impl AckWorkflowDetail {
+    /// Create a new [AckWorkflowDetail] response from a stored workflow and
+    /// the receipts it has emitted.
+    pub(crate) fn new(stored: workflow::Stored, receipts: Vec<Receipt>) -> Self {
+        let summary = WorkflowSummary::new(&stored, receipts.len() as i64);
+        let receipts: HashMap<Cid, Receipt> = receipts
+            .into_iter()
+            .map(|receipt| (receipt.instruction().cid(), receipt))
+            .collect();
+
+        let tasks = stored
+            .resources
+            .inner()
+            .iter()
+            .map(|(instruction, rscs)| {
+                let receipt = receipts.get(instruction);
+                TaskSummary {
+                    instruction: instruction.to_string(),
+                    status: receipt
+                        .map_or("pending", |receipt| output_status(receipt.output()))
+                        .to_string(),
+                    receipt: receipt.map(|receipt| receipt.cid().to_string()),
+                    resources: rscs
+                        .iter()
+                        .map(|rsc| rsc.to_string())
+                        .collect::<Vec<String>>()
+                        .join(", "),
+                }
+            })
+            .collect();
+
+        let failure = stored.failure_reason.map(|reason| WorkflowFailure {
+            reason,
+            instruction: stored
+                .failed_instruction
+                .map(|pointer| pointer.cid().to_string()),
+            failed_at: stored.failed_at.map(format_timestamp),
+            unfinished: stored
+                .unfinished_instructions
+                .map(|unfinished| unfinished.inner().iter().map(Cid::to_string).collect())
+                .unwrap_or_default(),
+        });
+
+        let duration = stored
+            .completed_at
+            .or(stored.failed_at)
+            .and_then(|finished_at| (finished_at - stored.created_at).to_std().ok())
+            .map(|duration| {
+                humantime::format_duration(std::time::Duration::from_secs(duration.as_secs()))
+                    .to_string()
+            });
+
+        Self {
+            workflow: summary,
+            failure,
+            duration,
+            tasks,
+        }
+    }
+}
+
+impl show::ConsoleTable for AckWorkflowDetail {
+    fn table(&self) -> show::Output {
+        let workflow_table = Table::new(vec![&self.workflow]);
+
+        let mut timings_builder = Builder::default();
+        timings_builder.push_record(["Duration".to_string()]);
+        timings_builder.push_record([self
+            .duration
+            .clone()
+            .unwrap_or_else(|| "<running>".to_string())]);
+        let timings_table = timings_builder.build();
+
+        let tasks_table = if self.tasks.is_empty() {
+            let mut builder = Builder::default();
+            builder.push_record(["Tasks".to_string()]);
+            builder.push_record(["<none>".to_string()]);
+            builder.build()
+        } else {
+            Table::new(&self.tasks)
+        };
+
+        if let Some(failure) = &self.failure {
+            col![
+                workflow_table,
+                timings_table,
+                Table::new(vec![failure]),
+                tasks_table
+            ]
+            .default_with_title("workflow")
+        } else {
+            col![workflow_table, timings_table, tasks_table].default_with_title("workflow")
+        }
+    }
+
+    fn echo_table(&self) -> Result<(), std::io::Error> {
+        self.table().echo()
+    }
+}
+
+/// Planned task within a workflow's execution plan, for response / display.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Tabled)]
+pub struct PlannedTask {
+    pub(crate) batch: usize,
+    pub(crate) instruction: String,
+    pub(crate) function: String,
+    pub(crate) resources: String,
+    /// Receipt already stored locally for the instruction, which running the
+    /// workflow would replay.
+    #[tabled(display_with = "display_option")]
+    pub(crate) receipt: Option<String>,
+}
+
+/// Execution plan of a workflow for response / display, listing its tasks
+/// batch by batch, ahead of running it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AckPlan {
+    pub(crate) cid: String,
+    pub(crate) num_batches: usize,
+    pub(crate) tasks: Vec<PlannedTask>,
+}
+
+impl AckPlan {
+    /// Create a new [AckPlan] response.
+    pub(crate) fn new(cid: Cid, tasks: Vec<PlannedTask>) -> Self {
+        let num_batches = tasks.iter().map(|task| task.batch + 1).max().unwrap_or(0);
+        Self {
+            cid: cid.to_string(),
+            num_batches,
+            tasks,
+        }
+    }
+
+    /// Number of tasks planned with a receipt to replay.
+    pub(crate) fn num_replayed(&self) -> usize {
+        self.tasks
+            .iter()
+            .filter(|task| task.receipt.is_some())
+            .count()
+    }
+}
+
+impl show::ConsoleTable for AckPlan {
+    fn table(&self) -> show::Output {
+        let mut summary_builder = Builder::default();
+        summary_builder.push_record([
+            "cid".to_string(),
+            "num_tasks".to_string(),
+            "num_batches".to_string(),
+            "num_replayed".to_string(),
+        ]);
+        summary_builder.push_record([
+            self.cid.clone(),
+            self.tasks.len().to_string(),
+            self.num_batches.to_string(),
+            self.num_replayed().to_string(),
+        ]);
+
+        let tasks_table = if self.tasks.is_empty() {
+            let mut builder = Builder::default();
+            builder.push_record(["Tasks".to_string()]);
+            builder.push_record(["<none>".to_string()]);
+            builder.build()
+        } else {
+            Table::new(&self.tasks)
+        };
+
+        col![summary_builder.build(), tasks_table].default_with_title("plan")
+    }
+
+    fn echo_table(&self) -> Result<(), std::io::Error> {
+        self.table().echo()
+    }
+}
+
+/// Where a [Receipt] was found when handling a client request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ReceiptSource {
+    /// Found in the local database.
+    Local,
+    /// Found on the DHT.
+    Dht,
+}
+
+impl fmt::Display for ReceiptSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReceiptSource::Local => write!(f, "local"),
+            ReceiptSource::Dht => write!(f, "dht"),
+        }
+    }
+}
+
+/// Receipt information for response / display, including the receipt
+/// itself as DAG-JSON and, if requested, the raw DAG-CBOR bytes that it's
+/// content-addressed by.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Tabled, JsonSchema)]
+#[schemars(rename = "receipt_info")]
+pub struct AckReceipt {
+    #[schemars(description = "Receipt CID")]
+    pub(crate) cid: String,
+    #[schemars(description = "Instruction CID the receipt was issued for")]
+    pub(crate) instruction: String,
+    #[schemars(description = "Invocation CID the receipt was issued for")]
+    pub(crate) ran: String,
+    #[schemars(description = "Receipt output status: ok, error, or just")]
+    pub(crate) status: String,
+    #[schemars(description = "Where the receipt was found: local or dht")]
+    pub(crate) source: String,
+    #[tabled(skip)]
+    #[schemars(description = "Receipt as DAG-JSON")]
+    pub(crate) receipt: serde_json::Value,
+    #[tabled(skip)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Receipt as raw DAG-CBOR bytes, in DAG-JSON bytes form")]
+    pub(crate) dag_cbor: Option<serde_json::Value>,
+}
+
+impl AckReceipt {
+    /// Create a new [AckReceipt] response, optionally including the raw
+    /// DAG-CBOR encoding of the receipt.
+    pub(crate) fn new(receipt: Receipt, source: ReceiptSource, cbor: bool) -> anyhow::Result<Self> {
+        let dag_cbor = if cbor {
+            let bytes: Vec<u8> = InvocationReceipt::from(&receipt).try_into()?;
+            Some(dag_cbor_to_json(bytes)?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            cid: receipt.cid().to_string(),
+            instruction: receipt.instruction().to_string(),
+            ran: receipt.ran(),
+            status: output_status(receipt.output()).to_string(),
+            source: source.to_string(),
+            receipt: serde_json::from_slice(&receipt.to_json()?)?,
+            dag_cbor,
+        })
+    }
+
+    /// Return the raw DAG-CBOR bytes of the receipt, if included in the
+    /// response.
+    pub(crate) fn dag_cbor_bytes(&self) -> anyhow::Result<Option<Vec<u8>>> {
+        self.dag_cbor.as_ref().map(dag_cbor_from_json).transpose()
+    }
+}
+
+impl show::ConsoleTable for AckReceipt {
+    fn table(&self) -> show::Output {
+        Table::new(vec![&self]).default_with_title("receipt")
+    }
+
+    fn echo_table(&self) -> Result<(), std::io::Error> {
+        self.table().echo()
+    }
+}
+
+/// Stored workflow document for response / display, including the workflow
+/// itself as DAG-JSON and, if requested, the raw DAG-CBOR bytes that it's
+/// content-addressed by.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Tabled, JsonSchema)]
+#[schemars(rename = "workflow_document")]
+pub struct AckWorkflowDocument {
+    #[schemars(description = "Workflow CID")]
+    pub(crate) cid: String,
+    #[tabled(display_with = "display_option")]
+    #[schemars(description = "Local name of the workflow")]
+    pub(crate) name: Option<String>,
+    #[schemars(description = "Number of tasks in the workflow")]
+    pub(crate) num_tasks: u32,
+    #[tabled(skip)]
+    #[schemars(description = "Workflow as DAG-JSON")]
+    pub(crate) workflow: serde_json::Value,
+    #[tabled(skip)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Workflow as raw DAG-CBOR bytes, in DAG-JSON bytes form")]
+    pub(crate) dag_cbor: Option<serde_json::Value>,
+}
+
+impl AckWorkflowDocument {
+    /// Create a new [AckWorkflowDocument] response from a stored workflow and
+    /// its DAG-CBOR encoded document, optionally including the raw bytes.
+    ///
+    /// The document must hash to the stored workflow's Cid.
+    pub(crate) fn new(
+        stored: workflow::Stored,
+        document: Vec<u8>,
+        cbor: bool,
+    ) -> anyhow::Result<Self> {
+        let cid = stored.cid.cid();
+        let workflow: Workflow<'_, Arg> = DagCbor::from_cbor(&document)?;
+        if workflow.clone().to_cid()? != cid {
+            return Err(anyhow::anyhow!(
+                "stored document does not match workflow Cid: {cid}"
+            ));
+        }
+
+        Ok(Self {
+            cid: cid.to_string(),
+            name: stored.name,
+            num_tasks: workflow.len(),
+            workflow: serde_json::from_slice(&workflow.to_json()?)?,
+            dag_cbor: cbor.then(|| dag_cbor_to_json(document)).transpose()?,
+        })
+    }
+
+    /// Return the raw DAG-CBOR bytes of the workflow, if included in the
+    /// response.
+    pub(crate) fn dag_cbor_bytes(&self) -> anyhow::Result<Option<Vec<u8>>> {
+        self.dag_cbor.as_ref().map(dag_cbor_from_json).transpose()
+    }
+}
+
+impl show::ConsoleTable for AckWorkflowDocument {
+    fn table(&self) -> show::Output {
+        Table::new(vec![&self]).default_with_title("workflow")
+    }
+
+    fn echo_table(&self) -> Result<(), std::io::Error> {
+        self.table().echo()
+    }
+}
+
+/// Receipt verification result for response / display, from re-executing
+/// the instruction the receipt was issued for.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Tabled)]
+pub struct AckVerify {
+    pub(crate) cid: String,
+    pub(crate) instruction: String,
+    pub(crate) verdict: String,
+    #[tabled(display_with = "display_option")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) reason: Option<String>,
+    #[tabled(skip)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) expected: Option<serde_json::Value>,
+    #[tabled(skip)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) actual: Option<serde_json::Value>,
+}
+
+impl AckVerify {
+    /// Create a new [AckVerify] response from a [Receipt] and its
+    /// [Verdict], including both outputs upon a mismatch.
+    pub(crate) fn new(receipt: &Receipt, verdict: Verdict) -> anyhow::Result<Self> {
+        let (reason, expected, actual) = match &verdict {
+            Verdict::Match => (None, None, None),
+            Verdict::Mismatch { expected, actual } => (
+                None,
+                Some(output_to_json(expected)?),
+                Some(output_to_json(actual)?),
+            ),
+            Verdict::Unverifiable(reason) => (Some(reason.to_owned()), None, None),
+        };
+
+        Ok(Self {
+            cid: receipt.cid().to_string(),
+            instruction: receipt.instruction().to_string(),
+            verdict: verdict.to_string(),
+            reason,
+            expected,
+            actual,
+        })
+    }
+
+    /// Whether the receipt's output didn't match its re-executed output.
+    pub(crate) fn is_mismatch(&self) -> bool {
+        self.expected.is_some()
+    }
+}
+
+impl show::ConsoleTable for AckVerify {
+    fn table(&self) -> show::Output {
+        Table::new(vec![&self]).default_with_title("verify")
+    }
+
+    fn echo_table(&self) -> Result<(), std::io::Error> {
+        self.table().echo()
+    }
+}
+
+/// Encode a task output as DAG-JSON.
+fn output_to_json(output: &task::Result<Ipld>) -> anyhow::Result<serde_json::Value> {
+    let encoded = DagJsonCodec.encode(&Ipld::from(output.to_owned()))?;
+    Ok(serde_json::from_slice(&encoded)?)
+}
+
+/// Encode raw DAG-CBOR bytes as DAG-JSON bytes form, e.g.
+/// `{"/": {"bytes": "..."}}`.
+fn dag_cbor_to_json(bytes: Vec<u8>) -> anyhow::Result<serde_json::Value> {
+    let encoded = DagJsonCodec.encode(&Ipld::Bytes(bytes))?;
+    Ok(serde_json::from_slice(&encoded)?)
+}
+
+/// Decode raw DAG-CBOR bytes from their DAG-JSON bytes form.
+fn dag_cbor_from_json(value: &serde_json::Value) -> anyhow::Result<Vec<u8>> {
+    match DagJsonCodec.decode(&serde_json::to_vec(value)?)? {
+        Ipld::Bytes(bytes) => Ok(bytes),
+        _ => Err(anyhow::anyhow!("DAG-CBOR is not encoded as bytes")),
+    }
+}
+
+fn output_status(output: &task::Result<Ipld>) -> &'static str {
+    match output {
+        task::Result::Ok(_) => "ok",
+        task::Result::Error(_) => "error",
+        task::Result::Just(_) => "just",
+    }
+}
+
+fn format_timestamp(timestamp: NaiveDateTime) -> String {
+    timestamp.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+fn display_option<T: ToString>(value: &Option<T>) -> String {
+    value.as_ref().map(T::to_string).unwrap_or_default()
+}
+
+/// Status of a submitted workflow, which is pending if it was queued.
+fn queued_status(queue_position: Option<usize>) -> workflow::Status {
+    if queue_position.is_some() {
+        workflow::Status::Pending
+    } else {
+        workflow::Status::Running
+    }
+}
+
 /// Ping response for display.
 #[derive(Debug, Tabled)]
 pub(crate) struct Ping {
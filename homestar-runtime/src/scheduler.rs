@@ -6,21 +6,23 @@
 
 use crate::{
     db::{Connection, Database},
+    receipt::trust::Decision,
+    settings::ReceiptTrust,
     workflow::{self, IndexedResources, Resource, Vertex},
-    Db,
+    Db, Receipt,
 };
 use anyhow::{anyhow, Result};
 use dagga::Node;
-use fnv::FnvHashSet;
+use fnv::{FnvHashMap, FnvHashSet};
 use futures::future::BoxFuture;
 use homestar_invocation::{task, Pointer};
 use homestar_wasm::io::Arg;
 use homestar_workflow::LinkMap;
 use indexmap::IndexMap;
-use libipld::Cid;
-use std::{str::FromStr, sync::Arc};
+use libipld::{Cid, Ipld};
+use std::{collections::VecDeque, str::FromStr, sync::Arc};
 use tokio::sync::RwLock;
-use tracing::debug;
+use tracing::{debug, info, warn};
 
 /// Type alias for a [Dag] set of batched nodes.
 ///
@@ -82,6 +84,13 @@ pub(crate) struct TaskScheduler<'a> {
     ///
     /// [Workflow]: homestar_workflow::Workflow
     pub(crate) resources: Arc<RwLock<IndexMap<Resource, Vec<u8>>>>,
+
+    /// Decisions on [Receipts] received from peers, given the node's
+    /// [ReceiptTrust] policy, keyed by instruction [Cid] and recorded as
+    /// receipt metadata.
+    ///
+    /// [Receipts]: crate::Receipt
+    pub(crate) trust_decisions: FnvHashMap<Cid, Ipld>,
 }
 
 /// Scheduler context containing the a schedule for executing tasks.
@@ -95,7 +104,8 @@ impl<'a> TaskScheduler<'a> {
     ///
     /// The scheduler will attempt to find already-executed tasks (via [Receipts])
     /// either in the database or through a [Swarm]/DHT query on a short(er)
-    /// timeout.
+    /// timeout. Receipts received from peers are only reused if trusted
+    /// under the given [ReceiptTrust] policy.
     ///
     /// [Receipts]: crate::Receipt
     /// [Swarm]: crate::network::swarm
@@ -103,6 +113,7 @@ impl<'a> TaskScheduler<'a> {
     pub(crate) async fn init<F>(
         mut graph: Arc<ExecutionGraph<'a>>,
         conn: &mut Connection,
+        trust: &ReceiptTrust,
         fetch_fn: F,
     ) -> Result<SchedulerContext<'a>>
     where
@@ -117,6 +128,7 @@ impl<'a> TaskScheduler<'a> {
         // Gather all resources to fetch
         let mut resources_to_fetch = Vec::new();
         let mut linkmap = LinkMap::<task::Result<Arg>>::default();
+        let mut trust_decisions = FnvHashMap::default();
 
         let mut last_idx = 0;
         for (idx, vec) in schedule.iter().enumerate().rev() {
@@ -137,6 +149,7 @@ impl<'a> TaskScheduler<'a> {
 
             if let Ok(pointers) = pointers {
                 if let Ok(found) = Db::find_instruction_pointers(&pointers, conn) {
+                    let found = trusted(found, trust, &mut trust_decisions, conn);
                     for receipt in found.iter() {
                         resources_to_fetch.retain(|(cid, _)| *cid != receipt.instruction().cid());
                         linkmap.insert(receipt.instruction().cid(), receipt.output_as_arg());
@@ -178,6 +191,7 @@ impl<'a> TaskScheduler<'a> {
                     },
                 );
         if let Ok(found) = Db::find_instruction_pointers(&promises_as_pointers, conn) {
+            let found = trusted(found, trust, &mut trust_decisions, conn);
             for receipt in found.iter() {
                 cids_to_resolve.retain(|cid| *cid != receipt.instruction().cid());
                 linkmap.insert(receipt.instruction().cid(), receipt.output_as_arg());
@@ -206,6 +220,7 @@ impl<'a> TaskScheduler<'a> {
                 run,
                 resume_step,
                 resources: Arc::new(fetched_resources.into()),
+                trust_decisions,
             },
         })
     }
@@ -230,6 +245,109 @@ impl<'a> TaskScheduler<'a> {
     }
 }
 
+/// Keep only the [Receipt]s that can be reused under the given [ReceiptTrust]
+/// policy, recording decisions on receipts received from peers.
+///
+/// Receipts whose peers can't be looked up aren't reused.
+fn trusted(
+    found: Vec<Receipt>,
+    trust: &ReceiptTrust,
+    decisions: &mut FnvHashMap<Cid, Ipld>,
+    conn: &mut Connection,
+) -> Vec<Receipt> {
+    found
+        .into_iter()
+        .filter(|receipt| {
+            let decision = match Decision::for_stored(trust, receipt, conn) {
+                Ok(decision) => decision,
+                Err(err) => {
+                    warn!(
+                        subject = "scheduler.trust.err",
+                        category = "scheduler.init",
+                        cid = receipt.cid().to_string(),
+                        err = ?err,
+                        "failed to look up peers for receipt, not reusing it"
+                    );
+                    return false;
+                }
+            };
+
+            if let Some(meta) = decision.to_meta(trust, receipt) {
+                info!(
+                    subject = "scheduler.trust",
+                    category = "scheduler.init",
+                    cid = receipt.cid().to_string(),
+                    instruction_cid = receipt.instruction().cid().to_string(),
+                    policy = trust.to_string(),
+                    trusted = decision.is_trusted(),
+                    "decided on reusing receipt received from peers"
+                );
+                decisions.insert(receipt.instruction().cid(), meta);
+            }
+
+            decision.is_trusted()
+        })
+        .collect()
+}
+
+/// Queue of tasks left to run for a [Workflow], handing out each task as
+/// soon as the instructions it awaits within the [Workflow] have resolved,
+/// rather than batch by batch.
+///
+/// Awaited instructions outside of the [Workflow] don't hold up a task, and
+/// are resolved when the task is run.
+///
+/// [Workflow]: homestar_workflow::Workflow
+#[derive(Debug, Default)]
+pub(crate) struct ReadyQueue<'a> {
+    pending: VecDeque<(Vertex<'a>, FnvHashSet<Cid>)>,
+}
+
+impl<'a> ReadyQueue<'a> {
+    /// Create a [ReadyQueue] from a [Schedule] of batches, given the set of
+    /// instruction [Cid]s awaited within the [Workflow].
+    ///
+    /// Tasks are handed out in [Schedule] order when several are ready.
+    ///
+    /// [Workflow]: homestar_workflow::Workflow
+    pub(crate) fn new(schedule: Schedule<'a>, in_flow: &FnvHashSet<Cid>) -> Self {
+        let pending = schedule
+            .into_iter()
+            .flatten()
+            .map(|node| {
+                let vertex = node.into_inner();
+                let awaits = vertex
+                    .awaits()
+                    .filter(|cid| in_flow.contains(cid))
+                    .collect();
+                (vertex, awaits)
+            })
+            .collect();
+
+        Self { pending }
+    }
+
+    /// Remove and return the first task whose awaited instructions are all
+    /// resolved within the [LinkMap], if any.
+    pub(crate) fn next_ready<T>(&mut self, linkmap: &LinkMap<T>) -> Option<Vertex<'a>> {
+        let idx = self
+            .pending
+            .iter()
+            .position(|(_, awaits)| awaits.iter().all(|cid| linkmap.get(cid).is_some()))?;
+        self.pending.remove(idx).map(|(vertex, _)| vertex)
+    }
+
+    /// Number of tasks left in the queue.
+    pub(crate) fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Whether the queue has no tasks left.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -279,9 +397,10 @@ mod test {
         let builder = workflow::Builder::new(workflow);
         let graph = builder.graph().unwrap();
 
-        let scheduler_ctx = TaskScheduler::init(graph.into(), &mut conn, fetch_fn)
-            .await
-            .unwrap();
+        let scheduler_ctx =
+            TaskScheduler::init(graph.into(), &mut conn, &ReceiptTrust::Any, fetch_fn)
+                .await
+                .unwrap();
 
         let ctx = scheduler_ctx.scheduler;
 
@@ -343,9 +462,10 @@ mod test {
         let builder = workflow::Builder::new(workflow);
         let graph = builder.graph().unwrap();
 
-        let scheduler_ctx = TaskScheduler::init(graph.into(), &mut conn, fetch_fn)
-            .await
-            .unwrap();
+        let scheduler_ctx =
+            TaskScheduler::init(graph.into(), &mut conn, &ReceiptTrust::Any, fetch_fn)
+                .await
+                .unwrap();
 
         let ctx = scheduler_ctx.scheduler;
         let ran = ctx.ran.as_ref().unwrap();
@@ -361,6 +481,95 @@ mod test {
         assert_eq!(ctx.resume_step, Some(1));
     }
 
+    #[homestar_runtime_proc_macro::db_async_test]
+    fn initialize_task_scheduler_with_untrusted_receipted_instruction() {
+        let settings = TestSettings::load();
+        let config = Resources::default();
+        let (instruction1, instruction2, _) = test_utils::related_wasm_instructions::<Arg>();
+        let task1 = Task::new(
+            RunInstruction::Expanded(instruction1.clone()),
+            config.clone().into(),
+            UcanPrf::default(),
+        );
+        let task2 = Task::new(
+            RunInstruction::Expanded(instruction2.clone()),
+            config.into(),
+            UcanPrf::default(),
+        );
+
+        let invocation_receipt = InvocationReceipt::new(
+            Invocation::new(task1.clone()).try_into().unwrap(),
+            task::Result::Ok(Ipld::Integer(4)),
+            Ipld::Null,
+            None,
+            UcanPrf::default(),
+        );
+        let receipt = Receipt::try_with(
+            instruction1.clone().try_into().unwrap(),
+            &invocation_receipt,
+        )
+        .unwrap();
+
+        let db = MemoryDb::setup_connection_pool(&settings.node, None).unwrap();
+        let mut conn = db.conn().unwrap();
+        let _ = MemoryDb::store_receipt(receipt.clone(), &mut conn).unwrap();
+        MemoryDb::store_receipt_peer(receipt.cid(), "peer-a", &mut conn).unwrap();
+
+        let workflow = Workflow::new(vec![task1.clone(), task2.clone()]);
+        let builder = workflow::Builder::new(workflow);
+        let graph = builder.graph().unwrap();
+        let fetch_fn = |_rscs: FnvHashSet<Resource>| {
+            {
+                async {
+                    let mut index_map = IndexMap::new();
+                    index_map.insert(Resource::Url(instruction1.resource().to_owned()), vec![]);
+                    index_map.insert(Resource::Url(instruction2.resource().to_owned()), vec![]);
+
+                    Ok(index_map)
+                }
+            }
+            .boxed()
+        };
+
+        let trust = ReceiptTrust::Quorum { quorum: 2 };
+        let scheduler_ctx = TaskScheduler::init(graph.into(), &mut conn, &trust, fetch_fn)
+            .await
+            .unwrap();
+
+        let ctx = scheduler_ctx.scheduler;
+        let instruction1_cid = instruction1.clone().to_cid().unwrap();
+
+        assert!(ctx.linkmap.read().await.is_empty());
+        assert!(ctx.ran.is_none());
+        assert_eq!(ctx.run.len(), 2);
+        assert_eq!(
+            ctx.trust_decisions
+                .get(&instruction1_cid)
+                .and_then(|meta| meta.get("trusted").ok()),
+            Some(&Ipld::Bool(false))
+        );
+
+        // A second, distinct peer reaches the quorum.
+        MemoryDb::store_receipt_peer(receipt.cid(), "peer-b", &mut conn).unwrap();
+        let graph = workflow::Builder::new(Workflow::new(vec![task1, task2]))
+            .graph()
+            .unwrap();
+        let fetch_fn = |_rscs: FnvHashSet<Resource>| async { Ok(IndexMap::new()) }.boxed();
+        let ctx = TaskScheduler::init(graph.into(), &mut conn, &trust, fetch_fn)
+            .await
+            .unwrap()
+            .scheduler;
+
+        assert!(ctx.linkmap.read().await.contains_key(&instruction1_cid));
+        assert_eq!(ctx.resume_step, Some(1));
+        assert_eq!(
+            ctx.trust_decisions
+                .get(&instruction1_cid)
+                .and_then(|meta| meta.get("trusted").ok()),
+            Some(&Ipld::Bool(true))
+        );
+    }
+
     #[homestar_runtime_proc_macro::db_async_test]
     fn initialize_task_scheduler_with_all_receipted_instruction() {
         let settings = TestSettings::load();
@@ -424,9 +633,10 @@ mod test {
         let builder = workflow::Builder::new(workflow);
         let graph = builder.graph().unwrap();
 
-        let scheduler_ctx = TaskScheduler::init(graph.into(), &mut conn, fetch_fn)
-            .await
-            .unwrap();
+        let scheduler_ctx =
+            TaskScheduler::init(graph.into(), &mut conn, &ReceiptTrust::Any, fetch_fn)
+                .await
+                .unwrap();
 
         let ctx = scheduler_ctx.scheduler;
         let ran = ctx.ran.as_ref().unwrap();
@@ -497,4 +707,43 @@ mod test {
         let graph = builder.graph();
         assert!(graph.is_ok());
     }
+
+    #[test]
+    fn ready_queue_hands_out_tasks_as_awaits_resolve() {
+        let config = Resources::default();
+        let (instruction1, instruction2, _) = test_utils::related_wasm_instructions::<Arg>();
+        let (independent, _) = test_utils::wasm_instruction_with_nonce::<Arg>();
+        let tasks = [&instruction1, &instruction2, &independent].map(|instruction| {
+            Task::new(
+                RunInstruction::Expanded(instruction.clone()),
+                config.clone().into(),
+                UcanPrf::default(),
+            )
+        });
+
+        let workflow = Workflow::new(tasks.to_vec());
+        let graph = workflow::Builder::new(workflow).graph().unwrap();
+        let in_flow = graph.awaiting.in_flow.iter().copied().collect();
+        let mut queue = ReadyQueue::new(graph.schedule, &in_flow);
+        let mut linkmap = LinkMap::<task::Result<Arg>>::default();
+
+        let mut ready = vec![];
+        while let Some(vertex) = queue.next_ready(&linkmap) {
+            ready.push(vertex.instruction);
+        }
+        assert_eq!(ready.len(), 2);
+        assert!(ready.contains(&instruction1));
+        assert!(ready.contains(&independent));
+        assert_eq!(queue.len(), 1);
+
+        linkmap.insert(
+            instruction1.clone().to_cid().unwrap(),
+            task::Result::Ok(Arg::Ipld(Ipld::Integer(2))),
+        );
+        assert_eq!(
+            queue.next_ready(&linkmap).unwrap().instruction,
+            instruction2
+        );
+        assert!(queue.is_empty());
+    }
 }
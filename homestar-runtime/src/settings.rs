@@ -16,7 +16,7 @@ use std::{
 
 mod libp2p_config;
 mod pubkey_config;
-pub use libp2p_config::{Autonat, Dht, Libp2p, Mdns, Pubsub, Rendezvous};
+pub use libp2p_config::{Autonat, Dht, Libp2p, Mdns, Pubsub, ReceiptTrust, Rendezvous};
 pub use pubkey_config::{ExistingKeyPath, KeyType, PubkeyConfig, RNGSeed};
 
 #[cfg(target_os = "windows")]
@@ -55,6 +55,9 @@ pub struct Node {
     /// Database settings.
     #[serde(default)]
     pub(crate) db: Database,
+    /// Task scheduling settings.
+    #[serde(default)]
+    pub(crate) scheduler: Scheduler,
     /// Garbage collection interval.
     #[serde_as(as = "DurationSeconds<u64>")]
     pub(crate) gc_interval: Duration,
@@ -81,6 +84,21 @@ pub struct Database {
     pub(crate) max_pool_size: u32,
 }
 
+/// Task scheduling settings for a homestar node.
+#[derive(Builder, Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[builder(default)]
+#[serde(default)]
+pub struct Scheduler {
+    /// Maximum number of workflows run concurrently, with any others
+    /// queued as pending, by priority.
+    pub(crate) max_concurrent_workflows: usize,
+    /// Maximum number of tasks run concurrently across all workflows.
+    pub(crate) max_concurrent_tasks: usize,
+    /// Resume workflows left `running` or `pending` when the node starts,
+    /// e.g. after a crash or restart, from their last completed step.
+    pub(crate) resume_on_startup: bool,
+}
+
 /// Monitoring settings.
 #[serde_as]
 #[derive(Builder, Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -121,6 +139,8 @@ pub struct Network {
     pub(crate) ipfs: Ipfs,
     /// Webserver settings
     pub(crate) webserver: Webserver,
+    /// Receipt verification settings.
+    pub(crate) verification: Verification,
 }
 
 /// IPFS Settings
@@ -137,6 +157,33 @@ pub struct Ipfs {
     pub(crate) port: u16,
 }
 
+/// Receipt verification settings, for re-executing a sampled fraction of
+/// the receipts received from peers and comparing their outputs.
+#[derive(Builder, Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[builder(default)]
+#[serde(default)]
+pub struct Verification {
+    /// Fraction of receipts received over gossipsub or found on the DHT to
+    /// re-execute, from `0.0` (none) to `1.0` (all).
+    pub(crate) sample_rate: f64,
+    /// What to do with a sampled receipt whose output doesn't match the
+    /// re-executed output.
+    pub(crate) on_mismatch: OnMismatch,
+}
+
+/// Policy for receipts that fail verification.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum OnMismatch {
+    /// Log a warning and store the receipt flagged, so that it's served but
+    /// never reused in place of running its instruction.
+    #[default]
+    #[serde(rename = "flag")]
+    Flag,
+    /// Log a warning and drop the receipt.
+    #[serde(rename = "reject")]
+    Reject,
+}
+
 /// Metrics settings.
 #[serde_as]
 #[derive(Builder, Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -197,6 +244,7 @@ impl Default for Node {
             monitoring: Default::default(),
             network: Default::default(),
             db: Default::default(),
+            scheduler: Default::default(),
         }
     }
 }
@@ -212,6 +260,11 @@ impl Node {
         &self.network
     }
 
+    /// Task scheduling settings.
+    pub fn scheduler(&self) -> &Scheduler {
+        &self.scheduler
+    }
+
     /// Node shutdown timeout.
     pub fn shutdown_timeout(&self) -> Duration {
         self.shutdown_timeout
@@ -227,6 +280,16 @@ impl Default for Database {
     }
 }
 
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self {
+            max_concurrent_workflows: 8,
+            max_concurrent_tasks: 16,
+            resume_on_startup: false,
+        }
+    }
+}
+
 #[cfg(feature = "monitoring")]
 #[cfg_attr(docsrs, doc(cfg(feature = "monitoring")))]
 impl Default for Monitoring {
@@ -260,6 +323,7 @@ impl Default for Network {
             #[cfg_attr(docsrs, doc(cfg(feature = "ipfs")))]
             ipfs: Default::default(),
             webserver: Webserver::default(),
+            verification: Verification::default(),
         }
     }
 }
@@ -281,6 +345,11 @@ impl Network {
     pub(crate) fn webserver(&self) -> &Webserver {
         &self.webserver
     }
+
+    /// Receipt verification settings.
+    pub(crate) fn verification(&self) -> &Verification {
+        &self.verification
+    }
 }
 
 #[cfg(feature = "ipfs")]
@@ -294,6 +363,15 @@ impl Default for Ipfs {
     }
 }
 
+impl Default for Verification {
+    fn default() -> Self {
+        Self {
+            sample_rate: 0.0,
+            on_mismatch: OnMismatch::Flag,
+        }
+    }
+}
+
 impl Default for Metrics {
     fn default() -> Self {
         Self { port: 4000 }
@@ -424,6 +502,8 @@ mod test {
         default_modded_settings.shutdown_timeout = Duration::from_secs(20);
         default_modded_settings.network.libp2p.node_addresses =
             vec!["/ip4/127.0.0.1/tcp/9998/ws".to_string().try_into().unwrap()];
+        default_modded_settings.network.libp2p.dht.receipt_trust =
+            ReceiptTrust::Quorum { quorum: 2 };
         assert_eq!(settings.node(), &default_modded_settings);
     }
 
@@ -4,7 +4,7 @@ use derive_builder::Builder;
 use http::Uri;
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DurationMilliSeconds, DurationSeconds};
-use std::time::Duration;
+use std::{fmt, time::Duration};
 
 /// libp2p settings.
 #[serde_as]
@@ -95,6 +95,53 @@ pub struct Dht {
     ///
     /// [workflow::Info]: crate::workflow::Info
     pub(crate) workflow_quorum: usize,
+    /// Policy for reusing receipts found on the network in place of running
+    /// the instructions they were issued for.
+    pub(crate) receipt_trust: ReceiptTrust,
+}
+
+/// Policy for reusing receipts received from peers, over gossipsub or the
+/// DHT.
+///
+/// Receipts issued by this node are always reused.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "policy", rename_all = "snake_case")]
+pub enum ReceiptTrust {
+    /// Reuse any receipt, whoever published it.
+    #[default]
+    Any,
+    /// Reuse only receipts published by one of the given peers, as
+    /// authenticated by the signature on gossiped receipts.
+    ///
+    /// Receipts found on the DHT don't authenticate their publisher, so are
+    /// never reused under this policy.
+    Allowlist {
+        /// Trusted peers, given as peer IDs or as `did:key` DIDs of their
+        /// Ed25519 or secp256k1 public keys.
+        peers: Vec<String>,
+    },
+    /// Reuse only receipts published by at least `quorum` distinct,
+    /// authenticated peers.
+    ///
+    /// Receipts are content-addressed, so peers publishing the same receipt
+    /// agree on its output.
+    Quorum {
+        /// Minimum number of distinct peers.
+        quorum: usize,
+    },
+    /// Never reuse receipts from peers.
+    Never,
+}
+
+impl fmt::Display for ReceiptTrust {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReceiptTrust::Any => write!(f, "any"),
+            ReceiptTrust::Allowlist { .. } => write!(f, "allowlist"),
+            ReceiptTrust::Quorum { .. } => write!(f, "quorum"),
+            ReceiptTrust::Never => write!(f, "never"),
+        }
+    }
 }
 
 /// mDNS settings.
@@ -227,6 +274,7 @@ impl Default for Dht {
             p2p_provider_timeout: Duration::from_millis(10000),
             receipt_quorum: 2,
             workflow_quorum: 3,
+            receipt_trust: ReceiptTrust::Any,
         }
     }
 }
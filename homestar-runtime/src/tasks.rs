@@ -1,24 +1,128 @@
 //! Module for working with task-types and task-specific functionality.
 
+use crate::worker::TaskError;
 use anyhow::{anyhow, Result};
-use enum_assoc::Assoc;
+use async_trait::async_trait;
+use homestar_invocation::task::{
+    instruction::{Args, Input},
+    Resources,
+};
+use homestar_wasm::io::Arg;
+use libipld::Ipld;
 use std::path::PathBuf;
+use url::Url;
 
 mod fetch;
+mod noop;
+mod registry;
+mod transform;
 mod wasm;
+mod workflow;
 
 pub(crate) use fetch::*;
+pub use registry::Registry;
 pub(crate) use wasm::*;
 
-const WASM_OP: &str = "wasm/run";
+pub(crate) const WASM_OP: &str = "wasm/run";
+pub(crate) const WORKFLOW_OP: &str = "workflow/run";
+pub(crate) const NOOP_OP: &str = "noop";
+pub(crate) const TRANSFORM_OP: &str = "ipld/transform";
 
-/// First-class registered task-types.
-#[derive(Debug, Clone, Assoc)]
-#[func(pub fn ability(s: &str) -> Option<Self>)]
-pub(crate) enum RegisteredTasks {
-    /// Basic `wasm/run` task-type.
-    #[assoc(ability = WASM_OP)]
-    WasmRun,
+/// Executor for a task-type, registered by the [Ability] of the tasks it
+/// runs in a [Registry].
+///
+/// Executors are only given tasks whose awaited inputs have resolved, and
+/// their outputs are receipted, and notified of, by the worker running the
+/// workflow, which also retries failed tasks as per their [RetryPolicy].
+///
+/// [Ability]: homestar_invocation::task::instruction::Ability
+/// [RetryPolicy]: homestar_invocation::task::RetryPolicy
+#[async_trait]
+pub trait Executor: Send + Sync {
+    /// Whether the task's resource, e.g. a Wasm module, is fetched and given
+    /// to the executor. Data-only tasks never load their resource.
+    fn loads_resource(&self) -> bool {
+        true
+    }
+
+    /// Whether tasks must name a function to run, e.g. one exported by a
+    /// Wasm component. Tasks without one fail as invalid input, without
+    /// being run.
+    fn requires_fun(&self) -> bool {
+        true
+    }
+
+    /// Whether tasks run the [Workflow] given as their resource, nested
+    /// within the workflow they're part of, rather than being [executed].
+    ///
+    /// [executed]: Executor::execute
+    /// [Workflow]: homestar_workflow::Workflow
+    fn runs_workflow(&self) -> bool {
+        false
+    }
+
+    /// Run a task on its resolved [TaskInput], returning its output.
+    #[allow(clippy::double_must_use)]
+    async fn execute(&self, input: TaskInput) -> Result<Ipld, TaskError>;
+}
+
+/// Input of a task run by an [Executor], with its arguments resolved.
+#[derive(Debug, Clone)]
+pub struct TaskInput {
+    pub(crate) resource: Url,
+    pub(crate) fun: Option<String>,
+    pub(crate) args: Args<Arg>,
+    pub(crate) resources: Resources,
+    pub(crate) bytes: Option<Vec<u8>>,
+}
+
+impl TaskInput {
+    /// Resource of the task's [Instruction].
+    ///
+    /// [Instruction]: homestar_invocation::task::Instruction
+    pub fn resource(&self) -> &Url {
+        &self.resource
+    }
+
+    /// Function to run, if the task names one.
+    ///
+    /// Only tasks run by [Executor]s that [require] a function are sure to
+    /// name one.
+    ///
+    /// [require]: Executor::requires_fun
+    pub fn fun(&self) -> Option<&str> {
+        self.fun.as_deref()
+    }
+
+    /// Resolved arguments of the function.
+    pub fn args(&self) -> &Args<Arg> {
+        &self.args
+    }
+
+    /// Resolved arguments of the function, as [Ipld].
+    pub fn ipld_args(&self) -> Vec<Ipld> {
+        self.args
+            .inner()
+            .iter()
+            .map(|input| match input {
+                Input::Ipld(ipld) => ipld.to_owned(),
+                Input::Deferred(promise) => promise.to_owned().into(),
+                Input::Arg(arg) => arg.inner().to_owned().into(),
+            })
+            .collect()
+    }
+
+    /// [Resources] configured for the task, e.g. its fuel and memory limits.
+    pub fn resources(&self) -> &Resources {
+        &self.resources
+    }
+
+    /// Contents of the task's resource, if [loaded].
+    ///
+    /// [loaded]: Executor::loads_resource
+    pub fn bytes(&self) -> Option<&[u8]> {
+        self.bytes.as_deref()
+    }
 }
 
 /// Trait for loading files for different task-types directly.
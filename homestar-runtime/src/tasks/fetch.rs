@@ -5,7 +5,12 @@
 
 #[cfg(feature = "ipfs")]
 use crate::network::IpfsCli;
-use crate::workflow::{self, Resource};
+use crate::{
+    db::{Connection, Database},
+    runner::file::Bundled,
+    workflow::{self, Resource},
+    Db,
+};
 use anyhow::Result;
 use fnv::FnvHashSet;
 use indexmap::IndexMap;
@@ -24,6 +29,72 @@ const WASM_CID: &str = "bafybeibk42jwhq7w2zcpe6q3wgtleugp3ymfs3pa5gerjmnakqihhqx
 const CAT_CID: &str = "bafybeiejevluvtoevgk66plh5t6xiy3ikyuuxg3vgofuvpeckb6eadresm";
 
 impl Fetch {
+    /// Split out [Resource]s already bundled with a workflow, e.g. Wasm
+    /// modules within a CAR file, returning the [Resource]s still left to
+    /// fetch alongside the bundled ones.
+    ///
+    /// A [Resource] matches a bundled block by [Cid] or by `ipfs://` URL.
+    ///
+    /// [Cid]: libipld::Cid
+    pub(crate) fn bundled(
+        resources: FnvHashSet<Resource>,
+        bundled: &Bundled,
+    ) -> (FnvHashSet<Resource>, IndexMap<Resource, Vec<u8>>) {
+        resources.into_iter().fold(
+            (FnvHashSet::default(), IndexMap::default()),
+            |(mut to_fetch, mut found), rsc| {
+                let cid = match &rsc {
+                    Resource::Cid(cid) => Some(*cid),
+                    Resource::Url(url) if url.scheme() == "ipfs" => url
+                        .domain()
+                        .and_then(|cid| libipld::Cid::try_from(cid).ok()),
+                    Resource::Url(_) => None,
+                };
+
+                match cid.and_then(|cid| bundled.get(&cid)) {
+                    Some(bytes) => {
+                        found.insert(rsc, bytes.clone());
+                    }
+                    None => {
+                        to_fetch.insert(rsc);
+                    }
+                }
+
+                (to_fetch, found)
+            },
+        )
+    }
+
+    /// Split out [Resource]s for workflow documents already stored by this
+    /// node, e.g. nested workflows run by `workflow/run` tasks, returning the
+    /// [Resource]s still left to fetch alongside the stored ones.
+    pub(crate) fn stored(
+        resources: FnvHashSet<Resource>,
+        conn: &mut Connection,
+    ) -> (FnvHashSet<Resource>, IndexMap<Resource, Vec<u8>>) {
+        resources.into_iter().fold(
+            (FnvHashSet::default(), IndexMap::default()),
+            |(mut to_fetch, mut found), rsc| {
+                let document = match &rsc {
+                    Resource::Url(url) => workflow::resource_cid(url)
+                        .and_then(|cid| Db::select_workflow_document(cid, conn).ok().flatten()),
+                    Resource::Cid(_) => None,
+                };
+
+                match document {
+                    Some(bytes) => {
+                        found.insert(rsc, bytes);
+                    }
+                    None => {
+                        to_fetch.insert(rsc);
+                    }
+                }
+
+                (to_fetch, found)
+            },
+        )
+    }
+
     /// Gather resources from IPFS or elsewhere, leveraging an exponential backoff.
     #[cfg(all(feature = "ipfs", not(test), not(feature = "test-utils")))]
     #[cfg_attr(docsrs, doc(cfg(feature = "ipfs")))]
@@ -201,3 +272,29 @@ impl Fetch {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use libipld::{
+        multihash::{Code, MultihashDigest},
+        Cid,
+    };
+
+    #[test]
+    fn split_bundled_resources() {
+        let wasm = b"\0asm".to_vec();
+        let cid = Cid::new_v1(0x55, Code::Sha2_256.digest(&wasm));
+        let bundled = Bundled::from([(cid, wasm.clone())]);
+
+        let by_url = Resource::Url(url::Url::parse(&format!("ipfs://{cid}")).unwrap());
+        let other = Resource::Url(url::Url::parse(&format!("ipfs://{CAT_CID}")).unwrap());
+        let resources = FnvHashSet::from_iter([by_url.clone(), Resource::Cid(cid), other.clone()]);
+
+        let (to_fetch, found) = Fetch::bundled(resources, &bundled);
+        assert_eq!(to_fetch, FnvHashSet::from_iter([other]));
+        assert_eq!(found.len(), 2);
+        assert_eq!(found.get(&by_url), Some(&wasm));
+        assert_eq!(found.get(&Resource::Cid(cid)), Some(&wasm));
+    }
+}
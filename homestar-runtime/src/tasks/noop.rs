@@ -0,0 +1,35 @@
+//! Identity [tasks], mostly useful for testing workflows.
+//!
+//! [tasks]: homestar_invocation::Task
+
+use super::{Executor, TaskInput};
+use crate::worker::TaskError;
+use async_trait::async_trait;
+use libipld::Ipld;
+
+/// [Executor] for `noop` tasks, outputting their resolved arguments as-is:
+/// a single argument by itself, several as a list, and none as null.
+///
+/// The task's function and resource are ignored.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct NoopExecutor;
+
+#[async_trait]
+impl Executor for NoopExecutor {
+    fn loads_resource(&self) -> bool {
+        false
+    }
+
+    fn requires_fun(&self) -> bool {
+        false
+    }
+
+    async fn execute(&self, input: TaskInput) -> Result<Ipld, TaskError> {
+        let mut args = input.ipld_args();
+        match args.len() {
+            0 => Ok(Ipld::Null),
+            1 => Ok(args.remove(0)),
+            _ => Ok(Ipld::List(args)),
+        }
+    }
+}
@@ -0,0 +1,172 @@
+//! [Registry] of [Executor]s, keyed by the [Ability] of the tasks they run.
+//!
+//! [Ability]: homestar_invocation::task::instruction::Ability
+
+use super::{
+    noop::NoopExecutor, transform::TransformExecutor, workflow::WorkflowExecutor, Executor,
+    WasmExecutor, NOOP_OP, TRANSFORM_OP, WASM_OP, WORKFLOW_OP,
+};
+use anyhow::Result;
+use fnv::FnvHashMap;
+use homestar_invocation::task::instruction::Ability;
+use std::{fmt, sync::Arc};
+
+/// Registry of [Executor]s, keyed by the [Ability] of the tasks they run,
+/// which embedders can extend with task-types of their own.
+///
+/// The default registry runs the built-in task-types:
+///
+/// * `wasm/run`: run a function of a Wasm component;
+/// * `noop`: output the task's arguments as-is;
+/// * `ipld/transform`: transform [Ipld] data, e.g. selecting by path;
+/// * `workflow/run`: run a nested workflow.
+///
+/// [Ability]: homestar_invocation::task::instruction::Ability
+/// [Ipld]: libipld::Ipld
+#[derive(Clone)]
+pub struct Registry {
+    executors: FnvHashMap<String, Arc<dyn Executor>>,
+}
+
+impl Registry {
+    /// Create a [Registry] without any [Executor]s, not even the built-in
+    /// ones.
+    pub fn empty() -> Self {
+        Self {
+            executors: FnvHashMap::default(),
+        }
+    }
+
+    /// Register an [Executor] for tasks of the given [Ability], replacing
+    /// any registered before.
+    ///
+    /// [Ability]: homestar_invocation::task::instruction::Ability
+    pub fn register<E>(&mut self, ability: impl Into<Ability>, executor: E) -> Result<()>
+    where
+        E: Executor + 'static,
+    {
+        self.executors
+            .insert(ability.into().to_string(), Arc::new(executor));
+        Ok(())
+    }
+
+    /// Get the [Executor] registered for tasks of the given [Ability].
+    ///
+    /// [Ability]: homestar_invocation::task::instruction::Ability
+    pub fn get(&self, ability: &Ability) -> Option<Arc<dyn Executor>> {
+        self.executors.get(&ability.to_string()).cloned()
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry
+            .executors
+            .insert(WASM_OP.to_string(), Arc::new(WasmExecutor));
+        registry
+            .executors
+            .insert(NOOP_OP.to_string(), Arc::new(NoopExecutor));
+        registry
+            .executors
+            .insert(TRANSFORM_OP.to_string(), Arc::new(TransformExecutor));
+        registry
+            .executors
+            .insert(WORKFLOW_OP.to_string(), Arc::new(WorkflowExecutor));
+        registry
+    }
+}
+
+impl fmt::Debug for Registry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.executors.keys()).finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{tasks::TaskInput, worker::TaskError};
+    use async_trait::async_trait;
+    use homestar_invocation::task::{
+        instruction::{Args, Input},
+        Resources,
+    };
+    use libipld::Ipld;
+    use url::Url;
+
+    #[derive(Debug)]
+    struct Constant(i64);
+
+    #[async_trait]
+    impl Executor for Constant {
+        async fn execute(&self, _input: TaskInput) -> Result<Ipld, TaskError> {
+            Ok(Ipld::Integer(self.0.into()))
+        }
+    }
+
+    fn input(args: Vec<Ipld>) -> TaskInput {
+        TaskInput {
+            resource: Url::parse("data:,").unwrap(),
+            fun: Some("identity".to_string()),
+            args: Args::new(args.into_iter().map(Input::Ipld).collect()),
+            resources: Resources::default(),
+            bytes: None,
+        }
+    }
+
+    #[test]
+    fn default_registry_runs_built_ins() {
+        let registry = Registry::default();
+
+        assert!(registry.get(&Ability::from(WASM_OP)).is_some());
+        assert!(registry.get(&Ability::from(NOOP_OP)).is_some());
+        assert!(registry.get(&Ability::from(TRANSFORM_OP)).is_some());
+        assert!(registry
+            .get(&Ability::from(WORKFLOW_OP))
+            .is_some_and(|executor| executor.runs_workflow()));
+        assert!(Registry::empty().get(&Ability::from(WASM_OP)).is_none());
+    }
+
+    #[tokio::test]
+    async fn register_executors() {
+        let mut registry = Registry::default();
+        registry.register("test/constant", Constant(1)).unwrap();
+        registry.register(NOOP_OP, Constant(2)).unwrap();
+
+        let output = registry
+            .get(&Ability::from("test/constant"))
+            .unwrap()
+            .execute(input(vec![]))
+            .await
+            .unwrap();
+        assert_eq!(output, Ipld::Integer(1));
+
+        // Built-ins can be replaced.
+        let output = registry
+            .get(&Ability::from(NOOP_OP))
+            .unwrap()
+            .execute(input(vec![]))
+            .await
+            .unwrap();
+        assert_eq!(output, Ipld::Integer(2));
+    }
+
+    #[tokio::test]
+    async fn noop_outputs_its_arguments() {
+        let noop = Registry::default().get(&Ability::from(NOOP_OP)).unwrap();
+        assert!(!noop.loads_resource());
+
+        assert_eq!(noop.execute(input(vec![])).await.unwrap(), Ipld::Null);
+        assert_eq!(
+            noop.execute(input(vec![Ipld::Integer(1)])).await.unwrap(),
+            Ipld::Integer(1)
+        );
+        assert_eq!(
+            noop.execute(input(vec![Ipld::Integer(1), "a".into()]))
+                .await
+                .unwrap(),
+            Ipld::List(vec![Ipld::Integer(1), "a".into()])
+        );
+    }
+}
@@ -0,0 +1,185 @@
+//! Data-only [tasks], transforming [Ipld] outputs of other tasks without
+//! running any module.
+//!
+//! [tasks]: homestar_invocation::Task
+
+use super::{Executor, TaskInput};
+use crate::worker::{TaskError, TaskErrorKind};
+use async_trait::async_trait;
+use homestar_invocation::pointer::Path;
+use libipld::Ipld;
+use std::collections::BTreeMap;
+
+const GET_FN: &str = "get";
+const MERGE_FN: &str = "merge";
+const CONCAT_FN: &str = "concat";
+
+/// [Executor] for `ipld/transform` tasks, whose function is one of:
+///
+/// * `get`: select a value by a [Path] of map keys and list indices, e.g.
+///   `.rows[1]`, given the value and the path;
+/// * `merge`: merge maps, with later keys taking precedence;
+/// * `concat`: concatenate lists.
+///
+/// The task's resource is ignored.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TransformExecutor;
+
+#[async_trait]
+impl Executor for TransformExecutor {
+    fn loads_resource(&self) -> bool {
+        false
+    }
+
+    async fn execute(&self, input: TaskInput) -> Result<Ipld, TaskError> {
+        let args = input.ipld_args();
+        match input.fun() {
+            Some(GET_FN) => match args.as_slice() {
+                [value, Ipld::String(path)] => get(value, path),
+                _ => Err(invalid_input(GET_FN, "expected a value and a path")),
+            },
+            Some(MERGE_FN) => args
+                .into_iter()
+                .try_fold(BTreeMap::new(), |mut merged, arg| match arg {
+                    Ipld::Map(map) => {
+                        merged.extend(map);
+                        Ok(merged)
+                    }
+                    _ => Err(invalid_input(MERGE_FN, "expected maps")),
+                })
+                .map(Ipld::Map),
+            Some(CONCAT_FN) => args
+                .into_iter()
+                .try_fold(vec![], |mut concatenated, arg| match arg {
+                    Ipld::List(list) => {
+                        concatenated.extend(list);
+                        Ok(concatenated)
+                    }
+                    _ => Err(invalid_input(CONCAT_FN, "expected lists")),
+                })
+                .map(Ipld::List),
+            Some(fun) => Err(TaskError::new(
+                TaskErrorKind::FunctionNotFound,
+                format!("no ipld/transform function: {fun}"),
+            )),
+            None => Err(TaskError::new(
+                TaskErrorKind::InvalidInput,
+                "no function defined",
+            )),
+        }
+    }
+}
+
+fn get(value: &Ipld, path: &str) -> Result<Ipld, TaskError> {
+    let selector = path
+        .parse::<Path>()
+        .map_err(|err| invalid_input(GET_FN, err.to_string()))?;
+
+    selector
+        .select(value.to_owned())
+        .ok_or_else(|| invalid_input(GET_FN, format!("no value at path: {path}")))
+}
+
+fn invalid_input(fun: &str, message: impl AsRef<str>) -> TaskError {
+    TaskError::new(
+        TaskErrorKind::InvalidInput,
+        format!("{fun}: {}", message.as_ref()),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use homestar_invocation::task::{
+        instruction::{Args, Input},
+        Resources,
+    };
+    use url::Url;
+
+    fn input(fun: &str, args: Vec<Ipld>) -> TaskInput {
+        TaskInput {
+            resource: Url::parse("data:,").unwrap(),
+            fun: Some(fun.to_string()),
+            args: Args::new(args.into_iter().map(Input::Ipld).collect()),
+            resources: Resources::default(),
+            bytes: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn get_by_path() {
+        let value = Ipld::Map(BTreeMap::from([(
+            "rows".into(),
+            Ipld::List(vec![Ipld::Integer(1), Ipld::Integer(2)]),
+        )]));
+
+        let output = TransformExecutor
+            .execute(input(GET_FN, vec![value.clone(), ".rows[1]".into()]))
+            .await
+            .unwrap();
+        assert_eq!(output, Ipld::Integer(2));
+
+        let err = TransformExecutor
+            .execute(input(GET_FN, vec![value.clone(), "rows/1".into()]))
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), TaskErrorKind::InvalidInput);
+
+        let err = TransformExecutor
+            .execute(input(GET_FN, vec![value, ".rows[2]".into()]))
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), TaskErrorKind::InvalidInput);
+    }
+
+    #[tokio::test]
+    async fn merge_and_concat() {
+        let output = TransformExecutor
+            .execute(input(
+                MERGE_FN,
+                vec![
+                    Ipld::Map(BTreeMap::from([
+                        ("a".into(), Ipld::Integer(1)),
+                        ("b".into(), Ipld::Integer(2)),
+                    ])),
+                    Ipld::Map(BTreeMap::from([("b".into(), Ipld::Integer(3))])),
+                ],
+            ))
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            Ipld::Map(BTreeMap::from([
+                ("a".into(), Ipld::Integer(1)),
+                ("b".into(), Ipld::Integer(3)),
+            ]))
+        );
+
+        let output = TransformExecutor
+            .execute(input(
+                CONCAT_FN,
+                vec![
+                    Ipld::List(vec![Ipld::Integer(1)]),
+                    Ipld::List(vec![Ipld::Integer(2), Ipld::Integer(3)]),
+                ],
+            ))
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            Ipld::List(vec![Ipld::Integer(1), Ipld::Integer(2), Ipld::Integer(3)])
+        );
+
+        let err = TransformExecutor
+            .execute(input(CONCAT_FN, vec![Ipld::Integer(1)]))
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), TaskErrorKind::InvalidInput);
+
+        let err = TransformExecutor
+            .execute(input("reverse", vec![]))
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), TaskErrorKind::FunctionNotFound);
+    }
+}
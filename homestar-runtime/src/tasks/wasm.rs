@@ -2,35 +2,113 @@
 //!
 //! [tasks]: homestar_invocation::Task
 
-use super::FileLoad;
-use homestar_invocation::task::instruction::Args;
+use super::{Executor, FileLoad, TaskInput};
+use crate::worker::{TaskError, TaskErrorKind};
+use async_trait::async_trait;
+use homestar_invocation::task::{instruction::Args, Resources};
 use homestar_wasm::{
     io::{Arg, Output},
     wasmtime::{world::Env, Error as WasmRuntimeError, State, World},
 };
-use tracing::Instrument;
+use libipld::Ipld;
+use std::time::Duration;
+use tracing::{debug_span, Instrument};
+
+/// [Executor] for `wasm/run` tasks, running a function of the Wasm
+/// component given as the task's resource.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct WasmExecutor;
+
+#[async_trait]
+impl Executor for WasmExecutor {
+    async fn execute(&self, input: TaskInput) -> Result<Ipld, TaskError> {
+        let wasm = input.bytes.ok_or_else(|| {
+            TaskError::new(
+                TaskErrorKind::ResourceNotAvailable,
+                format!("resource not available: {}", input.resource),
+            )
+        })?;
+
+        let fun = input
+            .fun
+            .ok_or_else(|| TaskError::new(TaskErrorKind::InvalidInput, "no function defined"))?;
+
+        let output = WasmContext::new(input.resources)?
+            .run(wasm, &fun, input.args)
+            .instrument(debug_span!("wasm_run").or_current())
+            .await?;
+
+        // Outputs that can't be represented as Ipld would fail the same way
+        // on every attempt.
+        Ipld::try_from(output)
+            .map_err(|err| TaskError::new(TaskErrorKind::InvalidOutput, err.to_string()))
+    }
+}
 
-#[allow(dead_code)]
 #[allow(missing_debug_implementations)]
 pub(crate) struct WasmContext {
     env: Env<State>,
+    timeout: Option<Duration>,
 }
 
 impl WasmContext {
-    #[allow(dead_code)]
-    pub(crate) fn new(data: State) -> Result<Self, WasmRuntimeError> {
+    /// Create a new [WasmContext], bounding execution by the fuel quota,
+    /// memory ceiling, and timeout of the given task [Resources].
+    pub(crate) fn new(resources: Resources) -> Result<Self, WasmRuntimeError> {
+        let timeout = resources.time();
         Ok(Self {
-            env: World::default(data)?,
+            env: World::default(State::from(resources))?,
+            timeout,
         })
     }
 
     /// Instantiate environment via [World] and execute on [Args].
-    #[allow(dead_code)]
+    ///
+    /// Resource limits that are hit during instantiation or execution are
+    /// surfaced as [WasmRuntimeError::OutOfFuel],
+    /// [WasmRuntimeError::MemoryLimitExceeded], or
+    /// [WasmRuntimeError::Timeout].
     pub(crate) async fn run<'a>(
         &mut self,
         bytes: Vec<u8>,
         fun_name: &'a str,
         args: Args<Arg>,
+    ) -> Result<Output, WasmRuntimeError> {
+        let result = match self.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, self.execute(bytes, fun_name, args))
+                .await
+                .unwrap_or(Err(WasmRuntimeError::Timeout(timeout))),
+            None => self.execute(bytes, fun_name, args).await,
+        };
+
+        result.map_err(|err| {
+            let limits = self.env.store().data().limits();
+            match (limits.memory_limit_exceeded(), limits.max_memory_size()) {
+                (true, Some(max_memory)) => {
+                    WasmRuntimeError::MemoryLimitExceeded(max_memory as u64)
+                }
+                _ => err,
+            }
+        })
+    }
+
+    /// Instantiate environment via [World] and type-check [Args] against the
+    /// function's parameter types, without executing it.
+    pub(crate) async fn typecheck(
+        &mut self,
+        bytes: Vec<u8>,
+        fun_name: &str,
+        args: &Args<Arg>,
+    ) -> Result<(), WasmRuntimeError> {
+        let env = World::instantiate_with_current_env(bytes, fun_name, &mut self.env).await?;
+        env.typecheck(args)
+    }
+
+    async fn execute(
+        &mut self,
+        bytes: Vec<u8>,
+        fun_name: &str,
+        args: Args<Arg>,
     ) -> Result<Output, WasmRuntimeError> {
         let env = World::instantiate_with_current_env(bytes, fun_name, &mut self.env).await?;
         env.execute(args).in_current_span().await
@@ -42,7 +120,8 @@ impl FileLoad for WasmContext {}
 #[cfg(test)]
 mod test {
     use super::*;
-    use std::path::PathBuf;
+    use homestar_invocation::task::instruction::{Input, Parse};
+    use std::{collections::BTreeMap, path::PathBuf};
 
     fn fixtures(file: &str) -> PathBuf {
         PathBuf::from(format!(
@@ -59,4 +138,78 @@ mod test {
 
         assert!(!wat.is_empty());
     }
+
+    #[tokio::test]
+    async fn run_bounded_by_fuel() {
+        let wasm = WasmContext::load(fixtures("example_test.wasm"))
+            .await
+            .unwrap();
+        let args = Input::<Arg>::Ipld(Ipld::Map(BTreeMap::from([
+            ("func".into(), Ipld::String("add_one".to_string())),
+            ("args".into(), Ipld::List(vec![Ipld::Integer(1)])),
+        ])))
+        .parse()
+        .unwrap()
+        .into_args();
+
+        let mut resources = Resources::default();
+        resources.set_fuel(1);
+        let mut ctx = WasmContext::new(resources).unwrap();
+        let result = ctx.run(wasm, "add_one", args).await;
+
+        assert!(matches!(result, Err(WasmRuntimeError::OutOfFuel)));
+    }
+
+    #[tokio::test]
+    async fn run_bounded_by_memory() {
+        let wasm = WasmContext::load(fixtures("example_test.wasm"))
+            .await
+            .unwrap();
+        let args = Input::<Arg>::Ipld(Ipld::Map(BTreeMap::from([
+            ("func".into(), Ipld::String("add_one".to_string())),
+            ("args".into(), Ipld::List(vec![Ipld::Integer(1)])),
+        ])))
+        .parse()
+        .unwrap()
+        .into_args();
+
+        let mut resources = Resources::default();
+        resources.set_memory(10);
+        let mut ctx = WasmContext::new(resources).unwrap();
+        let result = ctx.run(wasm, "add_one", args).await;
+
+        assert!(matches!(
+            result,
+            Err(WasmRuntimeError::MemoryLimitExceeded(10))
+        ));
+    }
+
+    #[tokio::test]
+    async fn run_bounded_by_time() {
+        let wasm = WasmContext::load(fixtures("example_test.wasm"))
+            .await
+            .unwrap();
+        // Sorting a large, reversed list runs for well over a millisecond.
+        let list = (0..200_000).rev().map(Ipld::Integer).collect();
+        let args = Input::<Arg>::Ipld(Ipld::Map(BTreeMap::from([
+            ("func".into(), Ipld::String("binary_search".to_string())),
+            (
+                "args".into(),
+                Ipld::List(vec![Ipld::List(list), Ipld::Integer(1)]),
+            ),
+        ])))
+        .parse()
+        .unwrap()
+        .into_args();
+
+        let mut resources = Resources::default();
+        resources.set_time(Duration::from_millis(1));
+        let mut ctx = WasmContext::new(resources).unwrap();
+        let result = ctx.run(wasm, "binary_search", args).await;
+
+        assert!(matches!(
+            result,
+            Err(WasmRuntimeError::Timeout(timeout)) if timeout == Duration::from_millis(1)
+        ));
+    }
 }
@@ -0,0 +1,38 @@
+//! [Tasks] running nested [Workflow]s.
+//!
+//! [Tasks]: homestar_invocation::Task
+//! [Workflow]: homestar_workflow::Workflow
+
+use super::{Executor, TaskInput};
+use crate::worker::{TaskError, TaskErrorKind};
+use async_trait::async_trait;
+use libipld::Ipld;
+
+/// [Executor] for `workflow/run` tasks, whose resource is a nested
+/// [Workflow], given inline or as an `ipfs://<cid>` resource, run by the
+/// worker running the parent workflow.
+///
+/// The task's arguments only order the nested workflow after the
+/// instructions they await.
+///
+/// [Workflow]: homestar_workflow::Workflow
+#[derive(Debug, Clone, Default)]
+pub(crate) struct WorkflowExecutor;
+
+#[async_trait]
+impl Executor for WorkflowExecutor {
+    fn requires_fun(&self) -> bool {
+        false
+    }
+
+    fn runs_workflow(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, _input: TaskInput) -> Result<Ipld, TaskError> {
+        Err(TaskError::new(
+            TaskErrorKind::Workflow,
+            "workflow/run tasks are run as nested workflows",
+        ))
+    }
+}
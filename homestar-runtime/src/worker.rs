@@ -7,47 +7,157 @@
 #[cfg(feature = "websocket-notify")]
 use crate::event_handler::event::Replay;
 use crate::{
-    channel::AsyncChannelSender,
+    channel::{AsyncChannel, AsyncChannelReceiver, AsyncChannelSender},
     db::Database,
     event_handler::{event::Captured, Event},
-    receipt::metadata::{REPLAYED_KEY, WORKFLOW_KEY, WORKFLOW_NAME_KEY},
+    receipt::{
+        metadata::{
+            ATTEMPTS_KEY, MAPPED_FROM_KEY, NESTED_WORKFLOW_KEY, PARENT_WORKFLOW_KEY, REPLAYED_KEY,
+            SKIPPED_KEY, TRUST_KEY, WORKFLOW_KEY, WORKFLOW_NAME_KEY,
+        },
+        trust,
+    },
     runner::{ModifiedSet, RunningTaskSet},
-    scheduler::ExecutionGraph,
+    scheduler::{ExecutionGraph, ReadyQueue},
     settings,
-    tasks::{RegisteredTasks, WasmContext},
-    workflow::{self, Resource},
+    tasks::{Executor, Fetch, Registry, TaskInput},
+    workflow::{self, Resource, Vertex},
     Db, Receipt, TaskScheduler,
 };
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, Result};
 use chrono::NaiveDateTime;
 use faststr::FastStr;
-use fnv::FnvHashSet;
+use fnv::{FnvHashMap, FnvHashSet};
 use futures::{future::BoxFuture, FutureExt};
 use homestar_invocation::{
-    authority::UcanPrf, ipld::DagCbor, receipt::metadata::OP_KEY, task, Pointer,
-    Receipt as InvocationReceipt,
-};
-use homestar_wasm::{
-    io::{Arg, Output},
-    wasmtime::State,
+    authority::UcanPrf,
+    error::ResolveError,
+    ipld::DagCbor,
+    pointer::AwaitResult,
+    receipt::metadata::OP_KEY,
+    task::{
+        self,
+        instruction::{Parse, RunInstruction},
+        Instruction, Resources, RetryPolicy,
+    },
+    Invocation, Pointer, Receipt as InvocationReceipt, Task,
 };
+use homestar_wasm::io::Arg;
 use homestar_workflow::Workflow;
 use indexmap::IndexMap;
 use libipld::{Cid, Ipld};
 use std::{collections::BTreeMap, sync::Arc};
-use tokio::task::JoinSet;
-use tracing::{debug, debug_span, error, info, info_span, instrument, Instrument};
+use tokio::{
+    sync::{OwnedSemaphorePermit, Semaphore},
+    task::{AbortHandle, JoinSet},
+};
+use tracing::{debug, error, info, info_span, instrument, warn, Instrument};
 
+mod error;
+mod nested;
 mod poller;
 mod resolver;
+pub(crate) use error::KIND_KEY;
+pub use error::{TaskError, TaskErrorKind};
+use nested::Nested;
 use poller::Poll;
-use resolver::Resolver;
+pub(crate) use resolver::Resolver;
 
 use self::resolver::DHTResolver;
 
+/// Function fetching [Resource]s for a [Worker], shared with the nested
+/// [Worker]s running the workflows of `workflow/run` tasks.
+pub(crate) type FetchFn = Arc<
+    dyn Fn(FnvHashSet<Resource>) -> BoxFuture<'static, Result<IndexMap<Resource, Vec<u8>>>>
+        + Send
+        + Sync,
+>;
+
+/// Output of a task run on a [TaskSet], along with its instruction and
+/// invocation [Pointer]s, and the receipt's metadata.
+pub(crate) type TaskOutput = (
+    std::result::Result<TaskOutcome, TaskError>,
+    Pointer,
+    Pointer,
+    Ipld,
+    Ipld,
+);
+
 /// [JoinSet] of tasks run by a [Worker].
 #[allow(dead_code)]
-pub(crate) type TaskSet = JoinSet<anyhow::Result<(Output, Pointer, Pointer, Ipld, Ipld)>>;
+pub(crate) type TaskSet = JoinSet<TaskOutput>;
+
+/// Guard reporting the failure of an expanded instruction's task if it's
+/// dropped before finishing, i.e. if the task panicked or was aborted, as
+/// [JoinError]s don't say which task they're for.
+///
+/// [JoinError]: tokio::task::JoinError
+struct Unfinished {
+    sender: AsyncChannelSender<TaskOutput>,
+    output: Option<TaskOutput>,
+}
+
+impl Unfinished {
+    fn new(sender: AsyncChannelSender<TaskOutput>, output: TaskOutput) -> Self {
+        Self {
+            sender,
+            output: Some(output),
+        }
+    }
+
+    /// Mark the task as finished, without reporting a failure.
+    fn finish(mut self) {
+        self.output = None;
+    }
+}
+
+impl Drop for Unfinished {
+    fn drop(&mut self) {
+        if let Some(output) = self.output.take() {
+            let _ = self.sender.send(output);
+        }
+    }
+}
+
+/// Outcome of a task run on a [TaskSet].
+#[derive(Debug)]
+pub(crate) enum TaskOutcome {
+    /// Task ran, with its output.
+    Ran(Ipld),
+    /// Task was skipped, as its [Condition] didn't hold, or as it awaited a
+    /// skipped task.
+    ///
+    /// [Condition]: homestar_invocation::task::Condition
+    Skipped,
+    /// Task was mapped over the elements of an awaited list, expanding into
+    /// an instruction per element, each yet to run as a task of its own.
+    Expanded(Expansion),
+    /// Task ran a nested [Workflow], with its final result(s).
+    Nested(Ipld),
+}
+
+/// Instructions expanded from a mapped task, one per element of the awaited
+/// list, in order, run under the mapped task's [Resources] and
+/// [RetryPolicy].
+#[derive(Debug)]
+pub(crate) struct Expansion {
+    instructions: Vec<Instruction<'static, Arg>>,
+    resources: Resources,
+    retry: RetryPolicy,
+}
+
+/// Mapped task awaiting the tasks of its expanded instructions, whose
+/// outputs are gathered, in order, into the mapped task's output.
+struct Mapping {
+    instruction_ptr: Pointer,
+    invocation_ptr: Pointer,
+    receipt_meta: Ipld,
+    add_meta: Ipld,
+    expanded: Vec<Cid>,
+    outputs: FnvHashMap<Cid, Ipld>,
+    pending: usize,
+    failure: Option<TaskError>,
+}
 
 /// Messages sent to [Worker] from [Runner].
 ///
@@ -84,6 +194,17 @@ pub(crate) struct Worker<'a, DB: Database> {
     pub(crate) network_settings: Arc<settings::Dht>,
     /// [NaiveDateTime] of when the [Workflow] was started.
     pub(crate) workflow_started: NaiveDateTime,
+    /// Permits for running tasks, which can be shared across [Worker]s to
+    /// limit the number of tasks run concurrently on a node.
+    pub(crate) task_permits: Arc<Semaphore>,
+    /// Cid of the parent [Workflow], if run by a `workflow/run` task.
+    pub(crate) parent: Option<Cid>,
+    /// Function fetching [Resource]s, shared with nested [Worker]s.
+    pub(crate) fetch: Option<FetchFn>,
+    /// [Executor]s for the tasks run, by the [Ability] of their tasks.
+    ///
+    /// [Ability]: homestar_invocation::task::instruction::Ability
+    pub(crate) executors: Arc<Registry>,
 }
 
 impl<'a, DB> Worker<'a, DB>
@@ -91,8 +212,6 @@ where
     DB: Database + 'static,
 {
     /// Instantiate a new [Worker] for a [Workflow].
-    ///
-    /// TODO: integrate settings within workflow
     #[allow(dead_code)]
     pub(crate) async fn new<S: Into<FastStr>>(
         workflow: Workflow<'a, Arg>,
@@ -107,6 +226,14 @@ where
         let workflow_len = workflow.len();
         // Need to take ownership here to get the cid.
         let workflow_cid = workflow.to_owned().to_cid()?;
+        // Keep a copy of the workflow to persist, for resuming it after a
+        // node restart.
+        let document = workflow.to_owned().to_cbor()?;
+        let instructions = workflow
+            .tasks_ref()
+            .iter()
+            .map(|task| task.instruction_cid())
+            .collect::<Result<Vec<_>, _>>()?;
 
         let builder = workflow::Builder::new(workflow);
         let graph = builder.graph()?;
@@ -124,6 +251,11 @@ where
             db.conn()?,
         )
         .await?;
+        let mut conn = db.conn()?;
+        Db::store_workflow_document(workflow_cid, &document, &mut conn)?;
+        Db::store_workflow_instructions(workflow_cid, &instructions, &mut conn)?;
+
+        let task_permits = Arc::new(Semaphore::new(settings.max_concurrent_tasks.max(1)));
 
         Ok(Self {
             graph: graph.into(),
@@ -135,6 +267,10 @@ where
             workflow_settings: settings.into(),
             workflow_started: timestamp,
             network_settings: network_settings.into(),
+            task_permits,
+            parent: None,
+            fetch: None,
+            executors: Arc::new(Registry::default()),
         })
     }
 
@@ -162,9 +298,31 @@ where
     where
         F: FnOnce(FnvHashSet<Resource>) -> BoxFuture<'a, Result<IndexMap<Resource, Vec<u8>>>>,
     {
+        // Workflow documents stored by this node, i.e. nested workflows, and
+        // resources that no task's executor loads, are never fetched.
+        let db = self.db.clone();
+        let unloaded = self.unloaded_resources();
+        let fetch_fn = move |rscs: FnvHashSet<Resource>| {
+            let rscs = rscs
+                .into_iter()
+                .filter(|rsc| !unloaded.contains(rsc))
+                .collect();
+            let (rscs, mut found) = match db.conn() {
+                Ok(mut conn) => Fetch::stored(rscs, &mut conn),
+                Err(_) => (rscs, IndexMap::default()),
+            };
+            let fetched = fetch_fn(rscs);
+            async move {
+                found.extend(fetched.await?);
+                Ok(found)
+            }
+            .boxed()
+        };
+
         match TaskScheduler::init(
             self.graph.clone(), // Arc'ed
             &mut self.db.conn()?,
+            &self.network_settings.receipt_trust,
             fetch_fn,
         )
         .await
@@ -184,6 +342,7 @@ where
                     promises_to_resolve,
                     self.network_settings.p2p_receipt_timeout,
                     self.workflow_info.cid,
+                    self.network_settings.receipt_trust.clone(),
                 );
                 if self.network_settings.enable_resolve_receipts_in_background
                     && self.network_settings.p2p_receipt_timeout.as_millis() > 0
@@ -204,7 +363,7 @@ where
                 }
 
                 // Set the workflow status to running.
-                let conn = &mut self.db.conn()?;
+                let mut conn = self.db.conn()?;
                 if ctx.scheduler.run_length() > 0 {
                     info!(
                         subject = "worker.start_workflow",
@@ -216,7 +375,7 @@ where
                     Db::set_workflow_status(
                         self.workflow_info.cid,
                         workflow::Status::Running,
-                        conn,
+                        &mut conn,
                     )?;
                 } else {
                     info!(
@@ -229,18 +388,43 @@ where
                     Db::set_workflow_status(
                         self.workflow_info.cid,
                         workflow::Status::Completed,
-                        conn,
+                        &mut conn,
                     )?;
                 }
+                // Release the connection, as nested workflows run within
+                // the queue.
+                drop(conn);
 
                 // Run the queue of tasks.
-                self.run_queue(ctx.scheduler, running_tasks).await
+                let db = self.db.clone();
+                let workflow_cid = self.workflow_info.cid;
+                let result = self.run_queue(ctx.scheduler, running_tasks).await;
+                if let (Err(err), Ok(mut conn)) = (&result, db.conn()) {
+                    let _ = Db::set_workflow_failure(
+                        workflow_cid,
+                        workflow::Status::Failed,
+                        &err.to_string(),
+                        None,
+                        &mut conn,
+                    );
+                }
+
+                result
             }
             Err(err) => {
                 error!(subject = "worker.init.err",
                        category = "worker.run",
                        err=?err,
                        "error initializing scheduler");
+                if let Ok(mut conn) = self.db.conn() {
+                    let _ = Db::set_workflow_failure(
+                        self.workflow_info.cid,
+                        workflow::Status::Failed,
+                        &format!("error initializing scheduler: {err}"),
+                        None,
+                        &mut conn,
+                    );
+                }
                 Err(anyhow!("error initializing scheduler"))
             }
         }
@@ -265,6 +449,9 @@ where
                     scheduler.run_length()
                 );
                 let mut pointers = Vec::new();
+                // Receipts reused from peers are replayed on their own, with
+                // the decision to trust them.
+                let mut trusted = Vec::new();
                 for batch in scheduler
                     .ran
                     .as_mut()
@@ -273,223 +460,967 @@ where
                 {
                     for node in batch.into_iter() {
                         let vertice = node.into_inner();
-                        pointers.push(Pointer::new(vertice.instruction.to_cid()?));
+                        let cid = vertice.instruction.to_cid()?;
+                        match scheduler.trust_decisions.get(&cid) {
+                            Some(decision) => trusted.push((Pointer::new(cid), decision.clone())),
+                            None => pointers.push(Pointer::new(cid)),
+                        }
                     }
                 }
 
-                let additional_meta = Ipld::Map(BTreeMap::from([
+                let mut additional_meta = BTreeMap::from([
                     (REPLAYED_KEY.into(), Ipld::Bool(true)),
                     (WORKFLOW_KEY.into(), self.workflow_info.cid().into()),
                     (
                         WORKFLOW_NAME_KEY.into(),
                         self.workflow_name.to_string().into(),
                     ),
-                ]));
-
-                let _ = self
-                    .event_sender
-                    .send_async(Event::ReplayReceipts(Replay::with(
-                        pointers,
-                        Some(additional_meta.clone()),
-                    )))
-                    .await;
+                ]);
+                if let Some(parent) = self.parent {
+                    additional_meta.insert(PARENT_WORKFLOW_KEY.into(), parent.into());
+                }
+
+                if !pointers.is_empty() {
+                    let _ = self
+                        .event_sender
+                        .send_async(Event::ReplayReceipts(Replay::with(
+                            pointers,
+                            Some(Ipld::Map(additional_meta.clone())),
+                        )))
+                        .await;
+                }
+
+                for (pointer, decision) in trusted {
+                    let mut meta = additional_meta.clone();
+                    meta.insert(TRUST_KEY.into(), decision);
+
+                    let _ = self
+                        .event_sender
+                        .send_async(Event::ReplayReceipts(Replay::with(
+                            vec![pointer],
+                            Some(Ipld::Map(meta)),
+                        )))
+                        .await;
+                }
             }
         }
 
-        for batch in scheduler.run.into_iter() {
-            let mut task_set = TaskSet::new();
-            let mut handles = Vec::new();
+        // First task failure (if any), which fails the workflow as a whole.
+        let mut failure: Option<(String, Option<Cid>)> = None;
+        // Failed tasks, which fail the workflow unless a task awaiting their
+        // `await/error` branch runs, handling the failure.
+        let mut unhandled: IndexMap<Cid, String> = IndexMap::new();
+
+        // Start each task as soon as the instructions it awaits within the
+        // workflow have resolved and a task permit is available, instead of
+        // waiting on whole batches.
+        let in_flow = self.graph.awaiting.in_flow.iter().copied().collect();
+        let mut queue = ReadyQueue::new(std::mem::take(&mut scheduler.run), &in_flow);
+        let mut task_set = TaskSet::new();
+        // Mapped tasks awaiting the tasks of their expanded instructions, and
+        // the mapped task of each expanded instruction.
+        let mut mappings: FnvHashMap<Cid, Mapping> = FnvHashMap::default();
+        let mut expanded_from: FnvHashMap<Cid, Cid> = FnvHashMap::default();
+        let (unfinished_tx, unfinished_rx): (_, AsyncChannelReceiver<TaskOutput>) =
+            AsyncChannel::unbounded();
+        // Instructions of skipped tasks, whose dependents are skipped too.
+        //
+        // Receipts of skipped tasks are local to the workflow, so aren't
+        // found by the scheduler, and are instead recovered from the
+        // workflow's own receipts if it's resumed.
+        let mut skipped = FnvHashSet::default();
+        for receipt in Db::find_workflow_receipts(self.workflow_info.cid, &mut self.db.conn()?)?
+            .into_iter()
+            .filter(|receipt| receipt.skipped())
+        {
+            let cid = receipt.instruction().cid();
+            scheduler
+                .linkmap
+                .write()
+                .await
+                .insert(cid, receipt.output_as_arg());
+            skipped.insert(cid);
+        }
 
-            for node in batch.into_iter() {
-                let vertice = node.into_inner();
-                let invocation_ptr = vertice.invocation;
-                let instruction = vertice.instruction;
-                let rsc = instruction.resource();
-                let parsed = vertice.parsed;
-                let fun = parsed.fun().ok_or_else(|| anyhow!("no function defined"))?;
+        loop {
+            let mut handles = Vec::new();
+            while let Some(permit) = self.task_permit(&task_set, &queue).await? {
+                let Some(vertice) = queue.next_ready(&*scheduler.linkmap.read().await) else {
+                    break;
+                };
 
-                let args = parsed.into_args();
-                let receipt_meta =
-                    Ipld::Map(BTreeMap::from([(OP_KEY.into(), fun.to_string().into())]));
+                // Tasks awaiting a branch their awaited instruction didn't
+                // take, e.g. `await/ok` on a failed instruction, are skipped.
+                let cid = vertice.instruction.clone().to_cid()?;
+                let skip = {
+                    let linkmap = scheduler.linkmap.read().await;
+                    skipped.contains(&cid)
+                        || vertice.promises().any(|promise| {
+                            skipped.contains(&promise.instruction_cid())
+                                || linkmap
+                                    .get(&promise.instruction_cid())
+                                    .is_some_and(|result| !promise.result().matches(result))
+                        })
+                };
+                if !skip {
+                    for promise in vertice.promises() {
+                        if *promise.result() == AwaitResult::Error {
+                            unhandled.shift_remove(&promise.instruction_cid());
+                        }
+                    }
+                }
+                let handle = self
+                    .spawn_task(
+                        vertice,
+                        skip,
+                        permit,
+                        &scheduler,
+                        &running_tasks,
+                        &mut task_set,
+                    )
+                    .await?;
+                handles.push(handle);
+            }
 
-                let additional_meta = Ipld::Map(BTreeMap::from([
-                    (REPLAYED_KEY.into(), Ipld::Bool(false)),
-                    (WORKFLOW_KEY.into(), self.workflow_info.cid().into()),
-                    (
-                        WORKFLOW_NAME_KEY.into(),
-                        self.workflow_name.to_string().into(),
-                    ),
-                ]));
+            // Concurrently add handles to Runner's running set.
+            if !handles.is_empty() {
+                running_tasks.append_or_insert(self.workflow_info.cid(), handles);
+            }
 
-                match RegisteredTasks::ability(&instruction.op().to_string()) {
-                    Some(RegisteredTasks::WasmRun) => {
-                        let wasm = scheduler
-                            .resources
-                            .read()
-                            .await
-                            .get(&Resource::Url(rsc.to_owned()))
-                            .ok_or_else(|| anyhow!("resource not available"))?
-                            .to_owned();
-
-                        let instruction_ptr = Pointer::try_from(instruction)?;
-                        let state = State::default();
-                        let mut wasm_ctx = WasmContext::new(state)?;
-
-                        let db = self.db.clone();
-                        let linkmap = scheduler.linkmap.clone();
-                        let resources = scheduler.resources.clone();
-                        let workflow_cid = self.workflow_info.cid();
-
-                        let resolved = args.resolve(move |cid| {
-                            info!(
-                                subject = "worker.resolve_cid",
-                                category = "worker.run",
-                                workflow_cid = workflow_cid.to_string(),
-                                cid = cid.to_string(),
-                                "attempting to resolve workflow args by cid"
-                            );
-
-                            cid.resolve(linkmap.clone(), resources.clone(), db.clone())
-                                .boxed()
-                        });
-
-                        let handle = task_set.spawn(async move {
-                             match resolved.await {
-                                Ok(inst_result) => {
-                                    match wasm_ctx.run(wasm, &fun, inst_result).instrument({
-                                        debug_span!("wasm_run").or_current()
-                                    }).await {
-                                        Ok(output) => Ok((
-                                            output,
-                                            instruction_ptr,
-                                            invocation_ptr,
-                                            receipt_meta,
-                                            additional_meta)),
-                                        Err(err) => Err(
-                                            anyhow!("cannot execute wasm module: {:#?}", err))
-                                            .with_context(|| {
-                                                format!("not able to run fn {fun} for cid: {instruction_ptr}, in workflow {workflow_cid}")
-                                            }),
-                                    }
-                                },
-                                Err(err) => {
-                                    Err(anyhow!("error resolving cid: {:#?}", err))
-                                        .with_context(|| {
-                                            format!("not able to resolve instruction: {instruction_ptr}, in workflow {workflow_cid}")
-                                        })
-                                }
-                            }
-                        }
-                        .instrument({
-                            info_span!("spawn_workflow_tasks").or_current()
-                        }));
+            let Some(res) = task_set.join_next().await else {
+                // Nothing is running, so any tasks left are awaiting
+                // instructions that will never resolve.
+                if !queue.is_empty() {
+                    failure.get_or_insert((
+                        format!(
+                            "{} task(s) awaiting instructions that never resolved",
+                            queue.len()
+                        ),
+                        None,
+                    ));
+                }
+                break;
+            };
 
-                        handles.push(handle);
-                    }
-                    None => error!(
+            let (output, instruction_ptr, invocation_ptr, receipt_meta, add_meta) = match res {
+                Ok(data) => data,
+                Err(err) => {
+                    error!(
                         subject = "worker.run.task.err",
                         category = "worker.run",
-                        "no valid task/instruction-type referenced by operation: {}",
-                        instruction.op()
-                    ),
+                        err = format!("{:#?}", err),
+                        "error in running task"
+                    );
+                    // Expanded instructions' tasks that panicked or were
+                    // aborted report their own failure, which is receipted
+                    // and gathered into their mapped task's receipt.
+                    match unfinished_rx.try_recv() {
+                        Ok(data) => data,
+                        Err(_) => {
+                            failure.get_or_insert((err.to_string(), None));
+                            continue;
+                        }
+                    }
                 }
-            }
+            };
+
+            // Tasks of expanded instructions get receipts of their own, and
+            // are gathered into their mapped task's receipt once all of them
+            // have run.
+            if let Some(mapped_cid) = expanded_from.remove(&instruction_ptr.cid()) {
+                let Some(mapping) = mappings.get_mut(&mapped_cid) else {
+                    continue;
+                };
 
-            // Concurrently add handles to Runner's running set.
-            running_tasks.append_or_insert(self.workflow_info.cid(), handles);
-            while let Some(res) = task_set.join_next().await {
-                let (executed, instruction_ptr, invocation_ptr, receipt_meta, add_meta) = match res
-                {
-                    Ok(Ok(data)) => data,
-                    Ok(Err(err)) => {
-                        error!(
-                            subject = "worker.run.task.err",
-                            category = "worker.run",
-                            err = format!("{:#?}", err),
-                            "error in running task"
+                let expanded_output = match output {
+                    Ok(TaskOutcome::Ran(executed)) => {
+                        mapping
+                            .outputs
+                            .insert(instruction_ptr.cid(), executed.clone());
+                        task::Result::Ok(executed)
+                    }
+                    Ok(outcome) => {
+                        let err = TaskError::new(
+                            TaskErrorKind::Execution,
+                            format!("unexpected outcome of expanded instruction: {outcome:?}"),
                         );
-                        break;
+                        mapping.failure.get_or_insert(err.clone());
+                        task::Result::Error(Ipld::from(err))
                     }
                     Err(err) => {
                         error!(
                             subject = "worker.run.task.err",
                             category = "worker.run",
+                            workflow_cid = self.workflow_info.cid.to_string(),
+                            instruction_cid = instruction_ptr.cid().to_string(),
                             err = format!("{:#?}", err),
-                            "error in running task"
+                            "error in running mapped task"
                         );
-                        break;
+                        mapping.failure.get_or_insert(err.clone());
+                        task::Result::Error(Ipld::from(err))
                     }
                 };
+                mapping.pending -= 1;
 
-                let output_to_store = Ipld::try_from(executed)?;
-                let invocation_receipt = InvocationReceipt::new(
+                self.commit_task_receipt(
+                    &scheduler,
+                    instruction_ptr,
                     invocation_ptr,
-                    task::Result::Ok(output_to_store),
+                    expanded_output,
                     receipt_meta,
-                    None,
-                    UcanPrf::default(),
-                );
+                    add_meta,
+                )
+                .await?;
 
-                let receipt = Receipt::try_with(instruction_ptr, &invocation_receipt)?;
+                if mapping.pending > 0 {
+                    continue;
+                }
 
-                scheduler
-                    .linkmap
-                    .write()
-                    .await
-                    .insert(receipt.instruction().cid(), receipt.output_as_arg());
-
-                // modify workflow info before progress update, in case
-                // that we time out getting info from the network, but later
-                // recovered where we last started from.
-                if let Some(step) = scheduler.resume_step {
-                    let current_progress_count = self.workflow_info.progress_count;
-                    Arc::make_mut(&mut self.workflow_info)
-                        .set_progress_count(std::cmp::max(current_progress_count, step as u32))
+                let Some(mut mapping) = mappings.remove(&mapped_cid) else {
+                    continue;
+                };
+                let mapped_output = match mapping.failure {
+                    None => task::Result::Ok(Ipld::List(
+                        mapping
+                            .expanded
+                            .iter()
+                            .filter_map(|cid| mapping.outputs.remove(cid))
+                            .collect(),
+                    )),
+                    Some(err) => {
+                        unhandled.entry(mapped_cid).or_insert(err.to_string());
+                        task::Result::Error(Ipld::from(err))
+                    }
                 };
 
-                let instruction_cid = receipt.instruction().cid();
-                let stored_receipt =
-                    Db::commit_receipt(self.workflow_info.cid, receipt, &mut self.db.conn()?)?;
+                self.commit_task_receipt(
+                    &scheduler,
+                    mapping.instruction_ptr,
+                    mapping.invocation_ptr,
+                    mapped_output,
+                    mapping.receipt_meta,
+                    mapping.add_meta,
+                )
+                .await?;
+                continue;
+            }
 
-                debug!(
-                    subject = "db.commit_receipt",
-                    category = "worker.run",
-                    workflow_cid = self.workflow_info.cid.to_string(),
-                    instruction_cid = instruction_cid.to_string(),
-                    "committed to database"
-                );
+            // Failed tasks still produce a receipt, with their error
+            // captured on the `error` branch of the output.
+            let output_to_store = match output {
+                Ok(TaskOutcome::Ran(executed)) => task::Result::Ok(executed),
+                Ok(TaskOutcome::Nested(output)) => task::Result::Ok(output),
+                Ok(TaskOutcome::Skipped) => {
+                    info!(
+                        subject = "worker.run.task.skip",
+                        category = "worker.run",
+                        workflow_cid = self.workflow_info.cid.to_string(),
+                        instruction_cid = instruction_ptr.cid().to_string(),
+                        "skipped task"
+                    );
+                    skipped.insert(instruction_ptr.cid());
+                    task::Result::Just(Ipld::Null)
+                }
+                Ok(TaskOutcome::Expanded(expansion)) => {
+                    let mut expanded = Vec::with_capacity(expansion.instructions.len());
+                    let mut expanded_handles = Vec::with_capacity(expansion.instructions.len());
+                    for instruction in expansion.instructions.iter().cloned() {
+                        let (cid, handle) = self
+                            .spawn_expanded(
+                                instruction,
+                                &expansion,
+                                &instruction_ptr,
+                                &receipt_meta,
+                                &add_meta,
+                                &scheduler,
+                                &mut task_set,
+                                unfinished_tx.clone(),
+                            )
+                            .await?;
+                        expanded_from.insert(cid, instruction_ptr.cid());
+                        expanded.push(cid);
+                        expanded_handles.push(handle);
+                    }
 
-                info!(
-                    subject = "worker.receipt",
-                    category = "worker.run",
-                    receipt_cid = stored_receipt.cid().to_string(),
-                    "computed receipt"
-                );
+                    if expanded.is_empty() {
+                        task::Result::Ok(Ipld::List(vec![]))
+                    } else {
+                        running_tasks.append_or_insert(self.workflow_info.cid(), expanded_handles);
+                        mappings.insert(
+                            instruction_ptr.cid(),
+                            Mapping {
+                                instruction_ptr,
+                                invocation_ptr,
+                                receipt_meta,
+                                add_meta,
+                                pending: expanded.len(),
+                                expanded,
+                                outputs: FnvHashMap::default(),
+                                failure: None,
+                            },
+                        );
+                        continue;
+                    }
+                }
+                Err(err) => {
+                    error!(
+                        subject = "worker.run.task.err",
+                        category = "worker.run",
+                        workflow_cid = self.workflow_info.cid.to_string(),
+                        instruction_cid = instruction_ptr.cid().to_string(),
+                        err = format!("{:#?}", err),
+                        "error in running task"
+                    );
+                    unhandled
+                        .entry(instruction_ptr.cid())
+                        .or_insert(err.to_string());
+                    task::Result::Error(Ipld::from(err))
+                }
+            };
+
+            // modify workflow info before progress update, in case
+            // that we time out getting info from the network, but later
+            // recovered where we last started from.
+            if let Some(step) = scheduler.resume_step {
+                let current_progress_count = self.workflow_info.progress_count;
+                Arc::make_mut(&mut self.workflow_info)
+                    .set_progress_count(std::cmp::max(current_progress_count, step as u32))
+            };
+
+            self.commit_task_receipt(
+                &scheduler,
+                instruction_ptr,
+                invocation_ptr,
+                output_to_store,
+                receipt_meta,
+                add_meta,
+            )
+            .await?;
+        }
 
-                let _ = self
-                    .event_sender
-                    .send_async(Event::CapturedReceipt(Captured::with(
-                        stored_receipt.cid(),
-                        self.workflow_info.clone(),
-                        Some(add_meta),
-                    )))
-                    .await;
-            }
+        let failure = unhandled
+            .into_iter()
+            .next()
+            .map(|(cid, reason)| (reason, Some(cid)))
+            .or(failure);
+
+        let conn = &mut self.db.conn()?;
+        if let Some((reason, failed_instruction)) = failure {
+            // Set the workflow status to `failed`
+            Db::set_workflow_failure(
+                self.workflow_info.cid,
+                workflow::Status::Failed,
+                &reason,
+                failed_instruction,
+                conn,
+            )?;
+
+            info!(
+                subject = "worker.end_workflow",
+                category = "worker.run",
+                workflow_cid = self.workflow_info.cid.to_string(),
+                reason,
+                "workflow failed"
+            );
+        } else {
+            // Set the workflow status to `completed`
+            Db::set_workflow_status(self.workflow_info.cid, workflow::Status::Completed, conn)?;
+
+            info!(
+                subject = "worker.end_workflow",
+                category = "worker.run",
+                workflow_cid = self.workflow_info.cid.to_string(),
+                "workflow completed"
+            );
         }
 
-        // Set the workflow status to `completed`
+        Ok(())
+    }
+
+    /// Commit a [Receipt] for a task's output, making the output available
+    /// to tasks awaiting its instruction, and capture the [Receipt] as an
+    /// [Event].
+    async fn commit_task_receipt(
+        &self,
+        scheduler: &TaskScheduler<'a>,
+        instruction_ptr: Pointer,
+        invocation_ptr: Pointer,
+        output: task::Result<Ipld>,
+        receipt_meta: Ipld,
+        add_meta: Ipld,
+    ) -> Result<()> {
+        let invocation_receipt = InvocationReceipt::new(
+            invocation_ptr,
+            output,
+            receipt_meta,
+            None,
+            UcanPrf::default(),
+        );
+
+        let receipt = Receipt::try_with(instruction_ptr, &invocation_receipt)?;
+
+        scheduler
+            .linkmap
+            .write()
+            .await
+            .insert(receipt.instruction().cid(), receipt.output_as_arg());
+
+        let instruction_cid = receipt.instruction().cid();
         let conn = &mut self.db.conn()?;
-        Db::set_workflow_status(self.workflow_info.cid, workflow::Status::Completed, conn)?;
+        let stored_receipt = Db::commit_receipt(self.workflow_info.cid, receipt, conn)?;
+        // This node issued the receipt itself, so it's no longer one only
+        // received from peers.
+        trust::mark_local(stored_receipt.cid(), conn)?;
 
-        info!(
-            subject = "worker.end_workflow",
+        debug!(
+            subject = "db.commit_receipt",
             category = "worker.run",
             workflow_cid = self.workflow_info.cid.to_string(),
-            "workflow completed"
+            instruction_cid = instruction_cid.to_string(),
+            "committed to database"
+        );
+
+        info!(
+            subject = "worker.receipt",
+            category = "worker.run",
+            receipt_cid = stored_receipt.cid().to_string(),
+            "computed receipt"
         );
 
+        let _ = self
+            .event_sender
+            .send_async(Event::CapturedReceipt(Captured::with(
+                stored_receipt.cid(),
+                self.workflow_info.clone(),
+                Some(add_meta),
+            )))
+            .await;
+
         Ok(())
     }
+
+    /// [Resource]s only referenced by tasks whose [Executor] doesn't load
+    /// them, e.g. data-only tasks.
+    fn unloaded_resources(&self) -> FnvHashSet<Resource> {
+        let mut unloaded = FnvHashSet::default();
+        let mut loaded = FnvHashSet::default();
+        for vertice in self
+            .graph
+            .schedule
+            .iter()
+            .flatten()
+            .map(|node| node.inner())
+        {
+            let Some(rscs) = vertice
+                .instruction
+                .clone()
+                .to_cid()
+                .ok()
+                .and_then(|cid| self.graph.indexed_resources.get(&cid))
+            else {
+                continue;
+            };
+
+            let loads = self
+                .executors
+                .get(vertice.instruction.op())
+                .map_or(true, |executor| executor.loads_resource());
+            if loads {
+                loaded.extend(rscs.iter().cloned());
+            } else {
+                unloaded.extend(rscs.iter().cloned());
+            }
+        }
+
+        unloaded.retain(|rsc| !loaded.contains(rsc));
+        unloaded
+    }
+
+    /// Get a permit to run a task, waiting on one only if none of the
+    /// [Worker]'s tasks are running, as otherwise the [Worker] waits on
+    /// those to finish instead.
+    async fn task_permit(
+        &self,
+        task_set: &TaskSet,
+        queue: &ReadyQueue<'a>,
+    ) -> Result<Option<OwnedSemaphorePermit>> {
+        if task_set.is_empty() && !queue.is_empty() {
+            Ok(Some(self.task_permits.clone().acquire_owned().await?))
+        } else {
+            Ok(self.task_permits.clone().try_acquire_owned().ok())
+        }
+    }
+
+    /// Spawn a task to run on the [TaskSet], holding the given permit until
+    /// the task finishes, and returning its [AbortHandle].
+    ///
+    /// The task is skipped, rather than run, if `skip` is set or if its
+    /// [Condition] doesn't hold. Otherwise, it fails if no [Executor] is
+    /// registered for its operation.
+    ///
+    /// [AbortHandle]: tokio::task::AbortHandle
+    /// [Condition]: homestar_invocation::task::Condition
+    async fn spawn_task(
+        &self,
+        vertice: Vertex<'a>,
+        skip: bool,
+        permit: OwnedSemaphorePermit,
+        scheduler: &TaskScheduler<'a>,
+        running_tasks: &Arc<RunningTaskSet>,
+        task_set: &mut TaskSet,
+    ) -> Result<AbortHandle> {
+        let invocation_ptr = vertice.invocation;
+        let instruction = vertice.instruction;
+        let rsc = instruction.resource().to_owned();
+        let parsed = vertice.parsed;
+        let task_resources = vertice.resources;
+        let task_retry = vertice.retry;
+        let task_condition = vertice.condition;
+        let task_map = vertice.map;
+        let task_nested = vertice.nested;
+        // Optional, as `workflow/run` tasks run a whole workflow instead.
+        let fun = parsed.fun();
+
+        let args = parsed.into_args();
+        let mut receipt_meta = BTreeMap::new();
+        if let Some(ref fun) = fun {
+            receipt_meta.insert(OP_KEY.into(), fun.to_string().into());
+        }
+
+        let mut additional_meta = BTreeMap::from([
+            (REPLAYED_KEY.into(), Ipld::Bool(false)),
+            (WORKFLOW_KEY.into(), self.workflow_info.cid().into()),
+            (
+                WORKFLOW_NAME_KEY.into(),
+                self.workflow_name.to_string().into(),
+            ),
+        ]);
+        let instruction_ptr = Pointer::try_from(instruction.clone())?;
+        // Record why a receipt received from peers wasn't reused.
+        if let Some(decision) = scheduler.trust_decisions.get(&instruction_ptr.cid()) {
+            additional_meta.insert(TRUST_KEY.into(), decision.clone());
+        }
+        if let Some(parent) = self.parent {
+            additional_meta.insert(PARENT_WORKFLOW_KEY.into(), parent.into());
+        }
+        let additional_meta = Ipld::Map(additional_meta);
+
+        let lookup_fn = self.lookup_fn(scheduler);
+
+        let condition = task_condition.map(|condition| {
+            let resolved = lookup_fn(condition.promise().instruction_cid());
+            async move { resolved.await.map(|result| condition.holds(&result)) }
+        });
+
+        let op = instruction.op().to_owned();
+        match self.executors.get(&op) {
+            Some(executor) if executor.runs_workflow() => {
+                let nested_cid = workflow::resource_cid(&rsc);
+                let nested = Nested {
+                    parent_cid: self.workflow_info.cid(),
+                    instruction_cid: instruction_ptr.cid(),
+                    resource: rsc,
+                    inline: task_nested,
+                    name: format!("{}/{}", self.workflow_name, instruction_ptr.cid()),
+                    workflow_settings: (*self.workflow_settings).clone(),
+                    network_settings: (*self.network_settings).clone(),
+                    event_sender: self.event_sender.clone(),
+                    runner_sender: self.runner_sender.clone(),
+                    db: self.db.clone(),
+                    task_permits: self.task_permits.clone(),
+                    fetch: self.fetch.clone(),
+                    executors: self.executors.clone(),
+                    resources: scheduler.resources.clone(),
+                    running_tasks: running_tasks.clone(),
+                };
+                let resolved = args.resolve(lookup_fn);
+
+                let handle = task_set.spawn(
+                    async move {
+                        // The nested workflow's tasks run on the shared
+                        // permits, so this task gives its own up.
+                        drop(permit);
+                        let output = async {
+                            let holds = match condition {
+                                Some(condition) if !skip => condition.await.map_err(|err| {
+                                    TaskError::new(TaskErrorKind::Resolve, err.to_string())
+                                })?,
+                                _ => !skip,
+                            };
+
+                            if !holds {
+                                return Ok(TaskOutcome::Skipped);
+                            }
+
+                            if task_map.is_some() {
+                                return Err(TaskError::new(
+                                    TaskErrorKind::Workflow,
+                                    "workflow/run tasks cannot be mapped",
+                                ));
+                            }
+
+                            // Arguments only order the nested workflow after
+                            // the instructions they await.
+                            resolved.await.map_err(|err| {
+                                TaskError::new(TaskErrorKind::Resolve, err.to_string())
+                            })?;
+
+                            nested.run().await.map(TaskOutcome::Nested)
+                        }
+                        .await;
+
+                        if let Ok(TaskOutcome::Skipped) = output {
+                            receipt_meta.insert(SKIPPED_KEY.into(), true.into());
+                        } else if let Some(nested_cid) = nested_cid {
+                            receipt_meta.insert(NESTED_WORKFLOW_KEY.into(), nested_cid.into());
+                        }
+
+                        (
+                            output,
+                            instruction_ptr,
+                            invocation_ptr,
+                            Ipld::Map(receipt_meta),
+                            additional_meta,
+                        )
+                    }
+                    .instrument(info_span!("spawn_workflow_tasks").or_current()),
+                );
+
+                Ok(handle)
+            }
+            executor => {
+                let bytes = scheduler
+                    .resources
+                    .read()
+                    .await
+                    .get(&Resource::Url(rsc.to_owned()))
+                    .cloned();
+
+                let mapped = task_map.map(|map| {
+                    let resolved = lookup_fn(map.promise().instruction_cid());
+                    // Owned copy of the instruction, expanded once the
+                    // mapped list resolves.
+                    let instruction: Instruction<'static, Arg> = Instruction::new_with_nonce(
+                        instruction.resource().to_owned(),
+                        instruction.op().to_owned(),
+                        instruction.input().to_owned(),
+                        instruction.nonce().to_owned(),
+                    );
+                    (map, instruction, resolved)
+                });
+                let resolved = args.resolve(lookup_fn);
+
+                let handle = task_set.spawn(
+                    async move {
+                        let _permit = permit;
+                        let mut attempts: u32 = 0;
+                        let output = async {
+                            let holds = match condition {
+                                Some(condition) if !skip => condition.await.map_err(|err| {
+                                    TaskError::new(TaskErrorKind::Resolve, err.to_string())
+                                })?,
+                                _ => !skip,
+                            };
+
+                            if !holds {
+                                return Ok(TaskOutcome::Skipped);
+                            }
+
+                            let Some(executor) = executor else {
+                                return Err(TaskError::new(
+                                    TaskErrorKind::UnsupportedOperation,
+                                    format!("unsupported operation: {op}"),
+                                ));
+                            };
+
+                            if executor.loads_resource() && bytes.is_none() {
+                                return Err(TaskError::new(
+                                    TaskErrorKind::ResourceNotAvailable,
+                                    format!("resource not available: {rsc}"),
+                                ));
+                            }
+
+                            if executor.requires_fun() && fun.is_none() {
+                                return Err(TaskError::new(
+                                    TaskErrorKind::InvalidInput,
+                                    "no function defined",
+                                ));
+                            }
+
+                            let input = |args| TaskInput {
+                                resource: rsc.clone(),
+                                fun: fun.clone(),
+                                args,
+                                resources: task_resources.clone(),
+                                bytes: bytes.clone(),
+                            };
+
+                            let Some((map, instruction, list)) = mapped else {
+                                let inst_result = resolved.await.map_err(|err| {
+                                    TaskError::new(TaskErrorKind::Resolve, err.to_string())
+                                })?;
+
+                                return run_task(
+                                    &executor,
+                                    input(inst_result),
+                                    &task_retry,
+                                    &mut attempts,
+                                )
+                                .await
+                                .map(TaskOutcome::Ran);
+                            };
+
+                            let list = list.await.map_err(|err| {
+                                TaskError::new(TaskErrorKind::Resolve, err.to_string())
+                            })?;
+                            let elements = map.elements(&list).ok_or_else(|| {
+                                TaskError::new(
+                                    TaskErrorKind::Resolve,
+                                    format!(
+                                        "mapped instruction output is not a list: {}",
+                                        map.promise().instruction_cid()
+                                    ),
+                                )
+                            })?;
+                            let instructions =
+                                map.expand(&instruction, elements).map_err(|err| {
+                                    TaskError::new(TaskErrorKind::Resolve, err.to_string())
+                                })?;
+
+                            // Expanded instructions are spawned as tasks of
+                            // their own, so this task gives its permit up.
+                            Ok(TaskOutcome::Expanded(Expansion {
+                                instructions,
+                                resources: task_resources.clone(),
+                                retry: task_retry.clone(),
+                            }))
+                        }
+                        .await;
+
+                        match output {
+                            Ok(TaskOutcome::Skipped) => {
+                                receipt_meta.insert(SKIPPED_KEY.into(), true.into());
+                            }
+                            Ok(TaskOutcome::Expanded(_)) => {}
+                            _ if task_retry.retries() > 0 => {
+                                receipt_meta.insert(ATTEMPTS_KEY.into(), attempts.into());
+                            }
+                            _ => {}
+                        }
+
+                        (
+                            output,
+                            instruction_ptr,
+                            invocation_ptr,
+                            Ipld::Map(receipt_meta),
+                            additional_meta,
+                        )
+                    }
+                    .instrument(info_span!("spawn_workflow_tasks").or_current()),
+                );
+
+                Ok(handle)
+            }
+        }
+    }
+
+    /// Spawn an instruction expanded from a mapped task to run on the
+    /// [TaskSet] as a task of its own, under its own task permit and retry
+    /// attempts, returning the expanded instruction's Cid and the task's
+    /// [AbortHandle].
+    ///
+    /// The task's invocation is derived from the expanded instruction and
+    /// the mapped task's [Resources].
+    ///
+    /// If the task panics or is aborted, its failure is sent on `unfinished`.
+    #[allow(clippy::too_many_arguments)]
+    async fn spawn_expanded(
+        &self,
+        instruction: Instruction<'static, Arg>,
+        expansion: &Expansion,
+        mapped_ptr: &Pointer,
+        receipt_meta: &Ipld,
+        add_meta: &Ipld,
+        scheduler: &TaskScheduler<'a>,
+        task_set: &mut TaskSet,
+        unfinished: AsyncChannelSender<TaskOutput>,
+    ) -> Result<(Cid, AbortHandle)> {
+        let instruction_ptr = Pointer::try_from(instruction.clone())?;
+        let invocation_ptr: Pointer = Invocation::<Arg>::from(Task::new(
+            RunInstruction::Expanded(instruction.clone()),
+            expansion.resources.clone().into(),
+            UcanPrf::default(),
+        ))
+        .try_into()?;
+
+        let executor = self
+            .executors
+            .get(instruction.op())
+            .ok_or_else(|| anyhow!("no executor for operation: {}", instruction.op()))?;
+        let rsc = instruction.resource().to_owned();
+        let bytes = scheduler
+            .resources
+            .read()
+            .await
+            .get(&Resource::Url(rsc.to_owned()))
+            .cloned();
+        let lookup_fn = self.lookup_fn(scheduler);
+        let task_permits = self.task_permits.clone();
+        let task_resources = expansion.resources.clone();
+        let task_retry = expansion.retry.clone();
+
+        let mut receipt_meta = match receipt_meta {
+            Ipld::Map(meta) => meta.to_owned(),
+            _ => BTreeMap::new(),
+        };
+        receipt_meta.insert(MAPPED_FROM_KEY.into(), mapped_ptr.cid().into());
+        let additional_meta = add_meta.to_owned();
+        let expanded_ptr = instruction_ptr.clone();
+        // Moved into the task up front, so that it's dropped along with the
+        // task even if it's aborted before being polled.
+        let unfinished = Unfinished::new(
+            unfinished,
+            (
+                Err(TaskError::new(
+                    TaskErrorKind::Execution,
+                    "task panicked or was aborted",
+                )),
+                expanded_ptr.clone(),
+                invocation_ptr.clone(),
+                Ipld::Map(receipt_meta.clone()),
+                additional_meta.clone(),
+            ),
+        );
+
+        let handle = task_set.spawn(
+            async move {
+                let unfinished = unfinished;
+                let mut attempts: u32 = 0;
+                let output =
+                    async {
+                        let _permit = task_permits.acquire_owned().await.map_err(|err| {
+                            TaskError::new(TaskErrorKind::Execution, err.to_string())
+                        })?;
+
+                        let parsed = instruction.input().parse().map_err(|err| {
+                            TaskError::new(TaskErrorKind::Resolve, err.to_string())
+                        })?;
+                        let fun = parsed.fun();
+                        if executor.requires_fun() && fun.is_none() {
+                            return Err(TaskError::new(
+                                TaskErrorKind::InvalidInput,
+                                "no function defined",
+                            ));
+                        }
+                        let args = parsed.into_args().resolve(lookup_fn).await.map_err(|err| {
+                            TaskError::new(TaskErrorKind::Resolve, err.to_string())
+                        })?;
+
+                        let input = TaskInput {
+                            resource: rsc,
+                            fun,
+                            args,
+                            resources: task_resources,
+                            bytes,
+                        };
+                        run_task(&executor, input, &task_retry, &mut attempts)
+                            .await
+                            .map(TaskOutcome::Ran)
+                    }
+                    .await;
+
+                if task_retry.retries() > 0 {
+                    receipt_meta.insert(ATTEMPTS_KEY.into(), attempts.into());
+                }
+                unfinished.finish();
+
+                (
+                    output,
+                    expanded_ptr,
+                    invocation_ptr,
+                    Ipld::Map(receipt_meta),
+                    additional_meta,
+                )
+            }
+            .instrument(info_span!("spawn_workflow_tasks").or_current()),
+        );
+
+        Ok((instruction_ptr.cid(), handle))
+    }
+
+    /// Function resolving the instruction Cids awaited by a task, from the
+    /// outputs of tasks run so far, fetched resources, or stored receipts.
+    fn lookup_fn(
+        &self,
+        scheduler: &TaskScheduler<'a>,
+    ) -> impl Fn(Cid) -> BoxFuture<'static, std::result::Result<task::Result<Arg>, ResolveError>>
+           + Clone
+           + Send
+           + Sync
+           + 'static {
+        let db = self.db.clone();
+        let linkmap = scheduler.linkmap.clone();
+        let resources = scheduler.resources.clone();
+        let workflow_cid = self.workflow_info.cid();
+        let trust = Arc::new(self.network_settings.receipt_trust.clone());
+
+        move |cid: Cid| {
+            info!(
+                subject = "worker.resolve_cid",
+                category = "worker.run",
+                workflow_cid = workflow_cid.to_string(),
+                cid = cid.to_string(),
+                "attempting to resolve workflow args by cid"
+            );
+
+            cid.resolve(
+                linkmap.clone(),
+                resources.clone(),
+                trust.clone(),
+                db.clone(),
+            )
+            .boxed()
+        }
+    }
+}
+
+/// Run a task on its [Executor], retrying failed runs as per the task's
+/// [RetryPolicy], only breaking early on non-retryable errors, and counting
+/// every attempt made.
+async fn run_task(
+    executor: &Arc<dyn Executor>,
+    input: TaskInput,
+    retry: &RetryPolicy,
+    attempts: &mut u32,
+) -> std::result::Result<Ipld, TaskError> {
+    tryhard::retry_fn(|| {
+        *attempts += 1;
+        let input = input.clone();
+        async move {
+            match executor.execute(input).await {
+                Err(err) if err.kind().is_retryable() => Err(err),
+                result => Ok(result),
+            }
+        }
+    })
+    .retries(retry.retries())
+    .exponential_backoff(retry.initial_delay())
+    .max_delay(retry.max_delay())
+    .on_retry(|attempts, next_delay, error| {
+        let err = error.to_string();
+        async move {
+            warn!(
+                subject = "worker.run.task.retry",
+                category = "worker.run",
+                err = err,
+                attempts = attempts,
+                "retrying task after error @ {}ms",
+                next_delay.map(|d| d.as_millis()).unwrap_or(0)
+            );
+        }
+    })
+    .await
+    .and_then(|output| output)
 }
 
 impl<'a, DB> Drop for Worker<'a, DB>
@@ -512,9 +1443,14 @@ mod test {
         workflow::{IndexedResources, Status},
     };
     use homestar_invocation::{
-        task::{instruction::RunInstruction, Resources},
+        pointer::{Await, AwaitResult},
+        task::{
+            instruction::{Ability, Input, RunInstruction},
+            Condition, Instruction, Map, Predicate, Resources, RetryPolicy,
+        },
         Invocation, Task,
     };
+    use std::time::Duration;
 
     #[homestar_runtime_proc_macro::db_async_test]
     fn initialize_worker() {
@@ -745,6 +1681,1010 @@ mod test {
         assert_eq!(workflow_stored.status, Status::Completed);
     }
 
+    #[homestar_runtime_proc_macro::db_async_test]
+    async fn run_worker_rerunning_failed_awaited_instruction() {
+        let settings = TestSettings::load();
+
+        let config = Resources::default();
+        let (instruction1, instruction2, _) =
+            homestar_invocation::test_utils::related_wasm_instructions::<Arg>();
+
+        let task1 = Task::new(
+            RunInstruction::Expanded(instruction1.clone()),
+            config.clone().into(),
+            UcanPrf::default(),
+        );
+
+        let task2 = Task::new(
+            RunInstruction::Expanded(instruction2.clone()),
+            config.into(),
+            UcanPrf::default(),
+        );
+
+        // First instruction has already failed, but failures aren't reused,
+        // so it's run again, and the second instruction, awaiting its `ok`
+        // branch, runs after it.
+        let invocation_receipt = InvocationReceipt::new(
+            Invocation::new(task1.clone()).try_into().unwrap(),
+            task::Result::Error(TaskError::new(TaskErrorKind::OutOfFuel, "out of fuel").into()),
+            Ipld::Null,
+            None,
+            UcanPrf::default(),
+        );
+        let receipt = Receipt::try_with(
+            instruction1.clone().try_into().unwrap(),
+            &invocation_receipt,
+        )
+        .unwrap();
+
+        let (tx, rx) = test_utils::event::setup_event_channel(settings.node.clone());
+
+        let builder = WorkerBuilder::new(settings.node)
+            .with_event_sender(tx)
+            .with_tasks(vec![task1, task2]);
+        let fetch_fn = builder.fetch_fn();
+        let db = builder.db();
+        let workflow_cid = builder.workflow_cid();
+
+        let mut index_map = IndexMap::new();
+        index_map.insert(
+            instruction1.clone().to_cid().unwrap(),
+            vec![Resource::Url(instruction1.resource().to_owned())],
+        );
+        index_map.insert(
+            instruction2.clone().to_cid().unwrap(),
+            vec![Resource::Url(instruction2.resource().to_owned())],
+        );
+
+        let mut conn = db.conn().unwrap();
+        let _ = MemoryDb::store_workflow(
+            workflow::Stored::new_with_resources(
+                Pointer::new(workflow_cid),
+                None,
+                builder.workflow_len() as i32,
+                IndexedResources::new(index_map),
+            ),
+            &mut conn,
+        );
+        let _ = MemoryDb::commit_receipt(workflow_cid, receipt.clone(), &mut conn).unwrap();
+        assert!(MemoryDb::find_instruction_by_cid(
+            instruction1.clone().to_cid().unwrap(),
+            &mut conn
+        )
+        .is_err());
+
+        let worker = builder.build().await;
+        let running_tasks = Arc::new(RunningTaskSet::new());
+        worker.run(running_tasks.clone(), fetch_fn).await.unwrap();
+
+        let mut ran = vec![];
+        while let Ok(event) = rx.recv_async().await {
+            if let Event::CapturedReceipt(Captured { receipt, .. }) = event {
+                let receipt = MemoryDb::find_receipt_by_cid(receipt, &mut conn).unwrap();
+                assert!(matches!(receipt.output(), task::Result::Ok(_)));
+                ran.push(receipt.instruction().cid());
+            }
+        }
+
+        assert_eq!(
+            ran,
+            vec![
+                instruction1.to_cid().unwrap(),
+                instruction2.to_cid().unwrap()
+            ]
+        );
+
+        let workflow_stored = MemoryDb::select_workflow(workflow_cid, &mut conn).unwrap();
+        assert_eq!(workflow_stored.status, Status::Completed);
+        assert!(workflow_stored.failed_instruction.is_none());
+    }
+
+    #[homestar_runtime_proc_macro::db_async_test]
+    async fn run_worker_with_handled_failure() {
+        let settings = TestSettings::load();
+
+        let (instruction, _, _) =
+            homestar_invocation::test_utils::related_wasm_instructions::<Arg>();
+        let failing_instruction = Instruction::new(
+            instruction.resource().to_owned(),
+            Ability::from("wasm/run"),
+            Input::Ipld(Ipld::Map(BTreeMap::from([
+                ("func".into(), Ipld::String("add_one".to_string())),
+                ("args".into(), Ipld::List(vec![Ipld::String("one".into())])),
+            ]))),
+        );
+        let failing_ptr = Pointer::new(failing_instruction.clone().to_cid().unwrap());
+
+        // Handles the failure, given the kind of error it failed with.
+        let handler = Instruction::new(
+            instruction.resource().to_owned(),
+            Ability::from("wasm/run"),
+            Input::Ipld(Ipld::Map(BTreeMap::from([
+                ("func".into(), Ipld::String("append_string".to_string())),
+                (
+                    "args".into(),
+                    Ipld::List(vec![Await::new(failing_ptr.clone(), AwaitResult::Error)
+                        .with_path(".kind".parse().unwrap())
+                        .into()]),
+                ),
+            ]))),
+        );
+        // Only runs if the failing instruction succeeds.
+        let dependent = Instruction::new(
+            instruction.resource().to_owned(),
+            Ability::from("wasm/run"),
+            Input::Ipld(Ipld::Map(BTreeMap::from([
+                ("func".into(), Ipld::String("add_one".to_string())),
+                (
+                    "args".into(),
+                    Ipld::List(vec![Await::new(failing_ptr.clone(), AwaitResult::Ok).into()]),
+                ),
+            ]))),
+        );
+        let handler_cid = handler.clone().to_cid().unwrap();
+        let dependent_cid = dependent.clone().to_cid().unwrap();
+
+        let tasks = [failing_instruction, handler, dependent]
+            .into_iter()
+            .map(|instruction| {
+                Task::new(
+                    RunInstruction::Expanded(instruction),
+                    Resources::default().into(),
+                    UcanPrf::default(),
+                )
+            })
+            .collect();
+
+        let (tx, rx) = test_utils::event::setup_event_channel(settings.node.clone());
+        let builder = WorkerBuilder::new(settings.node)
+            .with_event_sender(tx)
+            .with_tasks(tasks);
+        let fetch_fn = builder.fetch_fn();
+        let db = builder.db();
+        let workflow_cid = builder.workflow_cid();
+
+        let worker = builder.build().await;
+        let running_tasks = Arc::new(RunningTaskSet::new());
+        worker.run(running_tasks.clone(), fetch_fn).await.unwrap();
+
+        let mut conn = db.conn().unwrap();
+        let mut receipts = BTreeMap::new();
+        while let Ok(event) = rx.recv_async().await {
+            if let Event::CapturedReceipt(Captured { receipt, .. }) = event {
+                let receipt = MemoryDb::find_receipt_by_cid(receipt, &mut conn).unwrap();
+                receipts.insert(receipt.instruction().cid(), receipt);
+            }
+        }
+
+        assert_eq!(receipts.len(), 3);
+        assert!(receipts[&failing_ptr.cid()].failed());
+        assert!(matches!(
+            receipts[&handler_cid].output(),
+            task::Result::Ok(Ipld::String(kind)) if kind.starts_with(TaskErrorKind::InvalidInput.as_str())
+        ));
+        assert!(receipts[&dependent_cid].skipped());
+
+        let workflow_stored = MemoryDb::select_workflow(workflow_cid, &mut conn).unwrap();
+        assert_eq!(workflow_stored.status, Status::Completed);
+    }
+
+    #[homestar_runtime_proc_macro::db_async_test]
+    async fn run_worker_without_retrying_mistyped_instruction() {
+        let settings = TestSettings::load();
+
+        let (instruction, _, _) =
+            homestar_invocation::test_utils::related_wasm_instructions::<Arg>();
+        let failing_instruction = Instruction::new(
+            instruction.resource().to_owned(),
+            Ability::from("wasm/run"),
+            Input::Ipld(Ipld::Map(BTreeMap::from([
+                ("func".into(), Ipld::String("add_one".to_string())),
+                ("args".into(), Ipld::List(vec![Ipld::String("one".into())])),
+            ]))),
+        );
+
+        let failing_instruction_cid = failing_instruction.clone().to_cid().unwrap();
+        let retry = RetryPolicy::new(2, Duration::from_millis(10), Duration::from_millis(50));
+        let task = Task::new(
+            RunInstruction::Expanded(failing_instruction),
+            retry.add_to_meta(Resources::default().into()),
+            UcanPrf::default(),
+        );
+
+        let (tx, rx) = test_utils::event::setup_event_channel(settings.node.clone());
+
+        let builder = WorkerBuilder::new(settings.node)
+            .with_event_sender(tx)
+            .with_tasks(vec![task]);
+        let fetch_fn = builder.fetch_fn();
+        let db = builder.db();
+        let workflow_cid = builder.workflow_cid();
+
+        let worker = builder.build().await;
+        let running_tasks = Arc::new(RunningTaskSet::new());
+        worker.run(running_tasks.clone(), fetch_fn).await.unwrap();
+
+        let mut conn = db.conn().unwrap();
+        let mut captured_receipt = false;
+        while let Ok(event) = rx.recv_async().await {
+            if let Event::CapturedReceipt(Captured { receipt, .. }) = event {
+                let receipt = MemoryDb::find_receipt_by_cid(receipt, &mut conn).unwrap();
+                assert_eq!(receipt.instruction().cid(), failing_instruction_cid);
+                assert!(matches!(receipt.output(), task::Result::Error(_)));
+
+                let Ipld::Map(meta) = receipt.meta() else {
+                    panic!("expected receipt metadata map")
+                };
+                // Mistyped arguments fail the same way on every attempt.
+                assert_eq!(meta.get(ATTEMPTS_KEY), Some(&Ipld::Integer(1)));
+                captured_receipt = true;
+            }
+        }
+
+        assert!(captured_receipt);
+
+        let workflow_stored = MemoryDb::select_workflow(workflow_cid, &mut conn).unwrap();
+        assert_eq!(workflow_stored.status, Status::Failed);
+    }
+
+    #[homestar_runtime_proc_macro::db_async_test]
+    async fn run_worker_failing_unsupported_operation() {
+        let settings = TestSettings::load();
+
+        let (instruction, _, _) =
+            homestar_invocation::test_utils::related_wasm_instructions::<Arg>();
+        let unsupported_instruction = Instruction::new(
+            instruction.resource().to_owned(),
+            Ability::from("test/unsupported"),
+            instruction.input().to_owned(),
+        );
+        let unsupported_instruction_cid = unsupported_instruction.clone().to_cid().unwrap();
+        let task = Task::new(
+            RunInstruction::Expanded(unsupported_instruction),
+            Resources::default().into(),
+            UcanPrf::default(),
+        );
+
+        let (tx, rx) = test_utils::event::setup_event_channel(settings.node.clone());
+
+        let builder = WorkerBuilder::new(settings.node)
+            .with_event_sender(tx)
+            .with_tasks(vec![task]);
+        let fetch_fn = builder.fetch_fn();
+        let db = builder.db();
+        let workflow_cid = builder.workflow_cid();
+
+        let worker = builder.build().await;
+        let running_tasks = Arc::new(RunningTaskSet::new());
+        worker.run(running_tasks.clone(), fetch_fn).await.unwrap();
+
+        let mut conn = db.conn().unwrap();
+        let mut captured_receipt = false;
+        while let Ok(event) = rx.recv_async().await {
+            if let Event::CapturedReceipt(Captured { receipt, .. }) = event {
+                let receipt = MemoryDb::find_receipt_by_cid(receipt, &mut conn).unwrap();
+                assert_eq!(receipt.instruction().cid(), unsupported_instruction_cid);
+                let task::Result::Error(Ipld::Map(error)) = receipt.output() else {
+                    panic!("expected an error output")
+                };
+                assert_eq!(
+                    error.get(KIND_KEY),
+                    Some(&Ipld::String("unsupported_operation".into()))
+                );
+                captured_receipt = true;
+            }
+        }
+
+        assert!(captured_receipt);
+
+        let workflow_stored = MemoryDb::select_workflow(workflow_cid, &mut conn).unwrap();
+        assert_eq!(workflow_stored.status, Status::Failed);
+        assert_eq!(
+            workflow_stored.failed_instruction,
+            Some(Pointer::new(unsupported_instruction_cid))
+        );
+    }
+
+    #[homestar_runtime_proc_macro::db_async_test]
+    async fn run_worker_with_concurrency_limit() {
+        let settings = TestSettings::load();
+
+        let (instruction1, instruction2, _) =
+            homestar_invocation::test_utils::related_wasm_instructions::<Arg>();
+        let (independent, _) =
+            homestar_invocation::test_utils::wasm_instruction_with_nonce::<Arg>();
+        let instruction1_cid = instruction1.clone().to_cid().unwrap();
+        let instruction2_cid = instruction2.clone().to_cid().unwrap();
+        let tasks = [instruction2, independent, instruction1]
+            .into_iter()
+            .map(|instruction| {
+                Task::new(
+                    RunInstruction::Expanded(instruction),
+                    Resources::default().into(),
+                    UcanPrf::default(),
+                )
+            })
+            .collect();
+
+        let workflow_settings = workflow::Settings {
+            max_concurrent_tasks: 1,
+            ..Default::default()
+        };
+
+        let (tx, rx) = test_utils::event::setup_event_channel(settings.node.clone());
+        let builder = WorkerBuilder::new(settings.node)
+            .with_event_sender(tx)
+            .with_tasks(tasks)
+            .with_workflow_settings(workflow_settings);
+        let fetch_fn = builder.fetch_fn();
+        let db = builder.db();
+        let workflow_cid = builder.workflow_cid();
+
+        let worker = builder.build().await;
+        let running_tasks = Arc::new(RunningTaskSet::new());
+        worker.run(running_tasks.clone(), fetch_fn).await.unwrap();
+        assert_eq!(running_tasks.get(&workflow_cid).unwrap().len(), 3);
+
+        let mut conn = db.conn().unwrap();
+        let mut receipts = vec![];
+        while let Ok(event) = rx.recv_async().await {
+            if let Event::CapturedReceipt(Captured { receipt, .. }) = event {
+                receipts.push(MemoryDb::find_receipt_by_cid(receipt, &mut conn).unwrap());
+            }
+        }
+
+        assert_eq!(receipts.len(), 3);
+        // The awaiting task only runs once the task it awaits has resolved.
+        let position = |cid| {
+            receipts
+                .iter()
+                .position(|receipt| receipt.instruction().cid() == cid)
+                .unwrap()
+        };
+        assert!(position(instruction1_cid) < position(instruction2_cid));
+        assert_eq!(
+            receipts[position(instruction2_cid)].output(),
+            &task::Result::Ok(Ipld::Integer(3))
+        );
+
+        let workflow_stored = MemoryDb::select_workflow(workflow_cid, &mut conn).unwrap();
+        assert_eq!(workflow_stored.status, Status::Completed);
+    }
+
+    #[homestar_runtime_proc_macro::db_async_test]
+    async fn run_worker_with_conditional_branches() {
+        let settings = TestSettings::load();
+
+        let (instruction, _, _) =
+            homestar_invocation::test_utils::related_wasm_instructions::<Arg>();
+        let (taken, _) = homestar_invocation::test_utils::wasm_instruction_with_nonce::<Arg>();
+        let (not_taken, _) = homestar_invocation::test_utils::wasm_instruction_with_nonce::<Arg>();
+        let instruction_cid = instruction.clone().to_cid().unwrap();
+        let taken_cid = taken.clone().to_cid().unwrap();
+        let not_taken_cid = not_taken.clone().to_cid().unwrap();
+
+        // Awaits the branch that's not taken, so is skipped along with it.
+        let dependent = Instruction::new(
+            instruction.resource().to_owned(),
+            Ability::from("wasm/run"),
+            Input::Ipld(Ipld::Map(BTreeMap::from([
+                ("func".into(), Ipld::String("add_one".to_string())),
+                (
+                    "args".into(),
+                    Ipld::List(vec![Await::new(
+                        Pointer::new(not_taken_cid),
+                        AwaitResult::Ok,
+                    )
+                    .into()]),
+                ),
+            ]))),
+        );
+        let dependent_cid = dependent.clone().to_cid().unwrap();
+
+        // `instruction` outputs 2.
+        let branch = |predicate| {
+            Condition::new(
+                Await::new(Pointer::new(instruction_cid), AwaitResult::Ok),
+                predicate,
+            )
+            .add_to_meta(Resources::default().into())
+        };
+
+        let tasks = vec![
+            Task::new(
+                RunInstruction::Expanded(instruction),
+                Resources::default().into(),
+                UcanPrf::default(),
+            ),
+            Task::new(
+                RunInstruction::Expanded(taken),
+                branch(Predicate::Lte(Ipld::Integer(2))),
+                UcanPrf::default(),
+            ),
+            Task::new(
+                RunInstruction::Expanded(not_taken),
+                branch(Predicate::Gt(Ipld::Integer(2))),
+                UcanPrf::default(),
+            ),
+            Task::new(
+                RunInstruction::Expanded(dependent),
+                Resources::default().into(),
+                UcanPrf::default(),
+            ),
+        ];
+
+        let (tx, rx) = test_utils::event::setup_event_channel(settings.node.clone());
+        let builder = WorkerBuilder::new(settings.node)
+            .with_event_sender(tx)
+            .with_tasks(tasks);
+        let fetch_fn = builder.fetch_fn();
+        let db = builder.db();
+        let workflow_cid = builder.workflow_cid();
+
+        let worker = builder.build().await;
+        let running_tasks = Arc::new(RunningTaskSet::new());
+        worker.run(running_tasks.clone(), fetch_fn).await.unwrap();
+
+        let mut conn = db.conn().unwrap();
+        let mut receipts = BTreeMap::new();
+        while let Ok(event) = rx.recv_async().await {
+            if let Event::CapturedReceipt(Captured { receipt, .. }) = event {
+                let receipt = MemoryDb::find_receipt_by_cid(receipt, &mut conn).unwrap();
+                receipts.insert(receipt.instruction().cid(), receipt);
+            }
+        }
+
+        assert_eq!(receipts.len(), 4);
+        let skipped = |cid| {
+            let Ipld::Map(meta) = receipts[&cid].meta() else {
+                panic!("expected receipt metadata map")
+            };
+            meta.get(SKIPPED_KEY) == Some(&Ipld::Bool(true))
+        };
+
+        assert!(!skipped(taken_cid));
+        assert_eq!(
+            receipts[&taken_cid].output(),
+            &task::Result::Ok(Ipld::Integer(2))
+        );
+        assert!(skipped(not_taken_cid));
+        assert_eq!(
+            receipts[&not_taken_cid].output(),
+            &task::Result::Just(Ipld::Null)
+        );
+        assert!(skipped(dependent_cid));
+
+        // Skipped receipts aren't reused for their instructions elsewhere.
+        assert!(MemoryDb::find_instruction_by_cid(not_taken_cid, &mut conn).is_err());
+        assert_eq!(
+            MemoryDb::find_instruction_pointers(
+                &vec![Pointer::new(taken_cid), Pointer::new(not_taken_cid)],
+                &mut conn
+            )
+            .unwrap()
+            .len(),
+            1
+        );
+
+        let workflow_stored = MemoryDb::select_workflow(workflow_cid, &mut conn).unwrap();
+        assert_eq!(workflow_stored.status, Status::Completed);
+    }
+
+    #[homestar_runtime_proc_macro::db_async_test]
+    async fn run_worker_with_mapped_instruction() {
+        let settings = TestSettings::load();
+
+        let (instruction, _, _) =
+            homestar_invocation::test_utils::related_wasm_instructions::<Arg>();
+        let list_instruction = Instruction::new(
+            instruction.resource().to_owned(),
+            Ability::from("wasm/run"),
+            Input::Ipld(Ipld::Map(BTreeMap::from([
+                ("func".into(), Ipld::String("transpose".to_string())),
+                (
+                    "args".into(),
+                    Ipld::List(vec![Ipld::List(vec![Ipld::List(vec![
+                        Ipld::Integer(1),
+                        Ipld::Integer(2),
+                        Ipld::Integer(3),
+                    ])])]),
+                ),
+            ]))),
+        );
+        let promise = Await::new(
+            Pointer::try_from(list_instruction.clone()).unwrap(),
+            AwaitResult::Ok,
+        );
+
+        // Pops the single value off of each row of the transposed matrix.
+        let mapped_instruction = Instruction::new(
+            instruction.resource().to_owned(),
+            Ability::from("wasm/run"),
+            Input::Ipld(Ipld::Map(BTreeMap::from([
+                ("func".into(), Ipld::String("pop".to_string())),
+                ("args".into(), Ipld::List(vec![promise.clone().into()])),
+            ]))),
+        );
+        let mapped_cid = mapped_instruction.clone().to_cid().unwrap();
+        let map = Map::new(promise);
+
+        let tasks = vec![
+            Task::new(
+                RunInstruction::Expanded(list_instruction),
+                Resources::default().into(),
+                UcanPrf::default(),
+            ),
+            Task::new(
+                RunInstruction::Expanded(mapped_instruction),
+                map.add_to_meta(Resources::default().into()),
+                UcanPrf::default(),
+            ),
+        ];
+
+        let (tx, rx) = test_utils::event::setup_event_channel(settings.node.clone());
+        let builder = WorkerBuilder::new(settings.node)
+            .with_event_sender(tx)
+            .with_tasks(tasks);
+        let fetch_fn = builder.fetch_fn();
+        let db = builder.db();
+        let workflow_cid = builder.workflow_cid();
+
+        let worker = builder.build().await;
+        let running_tasks = Arc::new(RunningTaskSet::new());
+        worker.run(running_tasks.clone(), fetch_fn).await.unwrap();
+
+        let mut conn = db.conn().unwrap();
+        let mut receipts = vec![];
+        while let Ok(event) = rx.recv_async().await {
+            if let Event::CapturedReceipt(Captured { receipt, .. }) = event {
+                receipts.push(MemoryDb::find_receipt_by_cid(receipt, &mut conn).unwrap());
+            }
+        }
+
+        // The list, an expanded instruction per element, and the gather.
+        assert_eq!(receipts.len(), 5);
+
+        let expanded: Vec<_> = receipts
+            .iter()
+            .filter(|receipt| {
+                let Ipld::Map(meta) = receipt.meta() else {
+                    return false;
+                };
+                meta.get(MAPPED_FROM_KEY) == Some(&Ipld::Link(mapped_cid))
+            })
+            .collect();
+        // Expanded instructions run as tasks of their own, finishing in any
+        // order.
+        let mut outputs = expanded
+            .iter()
+            .map(|receipt| match receipt.output() {
+                task::Result::Ok(Ipld::Integer(i)) => *i,
+                output => panic!("unexpected output: {output:?}"),
+            })
+            .collect::<Vec<_>>();
+        outputs.sort();
+        assert_eq!(outputs, vec![1, 2, 3]);
+
+        let gathered = receipts
+            .iter()
+            .find(|receipt| receipt.instruction().cid() == mapped_cid)
+            .unwrap();
+
+        // Each with an invocation of its own, derived from its instruction.
+        let ran = expanded
+            .iter()
+            .map(|receipt| receipt.ran())
+            .collect::<FnvHashSet<_>>();
+        assert_eq!(ran.len(), 3);
+        assert!(!ran.contains(&gathered.ran()));
+        assert_eq!(
+            gathered.output(),
+            &task::Result::Ok(Ipld::List(vec![
+                Ipld::Integer(1),
+                Ipld::Integer(2),
+                Ipld::Integer(3),
+            ]))
+        );
+
+        let workflow_stored = MemoryDb::select_workflow(workflow_cid, &mut conn).unwrap();
+        assert_eq!(workflow_stored.status, Status::Completed);
+    }
+
+    #[homestar_runtime_proc_macro::db_async_test]
+    async fn run_worker_with_nested_workflow() {
+        let settings = TestSettings::load();
+
+        let (instruction1, instruction2, _) =
+            homestar_invocation::test_utils::related_wasm_instructions::<Arg>();
+        let nested = Workflow::new(vec![
+            Task::new(
+                RunInstruction::Expanded(instruction1),
+                Resources::default().into(),
+                UcanPrf::default(),
+            ),
+            Task::new(
+                RunInstruction::Expanded(instruction2.clone()),
+                Resources::default().into(),
+                UcanPrf::default(),
+            ),
+        ]);
+        let nested_cid = nested.clone().to_cid().unwrap();
+
+        let Ipld::Map(mut meta) = Resources::default().into() else {
+            panic!("resources are not a map")
+        };
+        meta.insert(workflow::INLINE_WORKFLOW_KEY.into(), nested.into());
+        let run_instruction = Instruction::new(
+            url::Url::parse(&format!("ipfs://{nested_cid}")).unwrap(),
+            Ability::from("workflow/run"),
+            Input::Ipld(Ipld::Map(BTreeMap::from([(
+                "args".into(),
+                Ipld::List(vec![]),
+            )]))),
+        );
+        let run_cid = run_instruction.clone().to_cid().unwrap();
+
+        let tasks = vec![Task::new(
+            RunInstruction::Expanded(run_instruction),
+            Ipld::Map(meta),
+            UcanPrf::default(),
+        )];
+
+        let (tx, rx) = test_utils::event::setup_event_channel(settings.node.clone());
+        let builder = WorkerBuilder::new(settings.node)
+            .with_event_sender(tx)
+            .with_tasks(tasks);
+        let fetch_fn = builder.fetch_fn();
+        let db = builder.db();
+        let workflow_cid = builder.workflow_cid();
+
+        let worker = builder.build().await;
+        let workflow_name = worker.workflow_name.to_string();
+        let running_tasks = Arc::new(RunningTaskSet::new());
+        worker.run(running_tasks.clone(), fetch_fn).await.unwrap();
+
+        let mut conn = db.conn().unwrap();
+        let mut receipts = vec![];
+        while let Ok(event) = rx.recv_async().await {
+            if let Event::CapturedReceipt(Captured { receipt, .. }) = event {
+                receipts.push(MemoryDb::find_receipt_by_cid(receipt, &mut conn).unwrap());
+            }
+        }
+
+        // The nested workflow's two receipts, and the parent's.
+        assert_eq!(receipts.len(), 3);
+
+        // The nested workflow's final result is the parent task's output.
+        let nested_output =
+            MemoryDb::find_instruction_by_cid(instruction2.to_cid().unwrap(), &mut conn)
+                .unwrap()
+                .output()
+                .to_owned();
+        let run_receipt = MemoryDb::find_instruction_by_cid(run_cid, &mut conn).unwrap();
+        assert_eq!(run_receipt.output(), &nested_output);
+        let Ipld::Map(run_meta) = run_receipt.meta() else {
+            panic!("receipt metadata is not a map")
+        };
+        assert_eq!(
+            run_meta.get(NESTED_WORKFLOW_KEY),
+            Some(&Ipld::Link(nested_cid))
+        );
+
+        assert_eq!(
+            MemoryDb::find_nested_workflows(workflow_cid, &mut conn).unwrap(),
+            vec![nested_cid]
+        );
+        let nested_stored = MemoryDb::select_workflow(nested_cid, &mut conn).unwrap();
+        assert_eq!(nested_stored.status, Status::Completed);
+        assert_eq!(
+            nested_stored.name,
+            Some(format!("{workflow_name}/{run_cid}"))
+        );
+        assert_eq!(
+            MemoryDb::select_workflow(workflow_cid, &mut conn)
+                .unwrap()
+                .status,
+            Status::Completed
+        );
+    }
+
+    /// Embedder-defined executor doubling its integer argument.
+    struct Double;
+
+    #[async_trait::async_trait]
+    impl Executor for Double {
+        fn loads_resource(&self) -> bool {
+            false
+        }
+
+        async fn execute(&self, input: TaskInput) -> std::result::Result<Ipld, TaskError> {
+            match input.ipld_args().as_slice() {
+                [Ipld::Integer(i)] => Ok(Ipld::Integer(i * 2)),
+                _ => Err(TaskError::new(
+                    TaskErrorKind::InvalidInput,
+                    "expected an integer",
+                )),
+            }
+        }
+    }
+
+    /// Embedder-defined executor failing as if a host call did.
+    struct Failing;
+
+    #[async_trait::async_trait]
+    impl Executor for Failing {
+        fn loads_resource(&self) -> bool {
+            false
+        }
+
+        async fn execute(&self, _input: TaskInput) -> std::result::Result<Ipld, TaskError> {
+            Err(TaskError::new(TaskErrorKind::Execution, "host call failed"))
+        }
+    }
+
+    /// Embedder-defined executor panicking on the integer 2, and doubling
+    /// other integers.
+    struct Panicking;
+
+    #[async_trait::async_trait]
+    impl Executor for Panicking {
+        fn loads_resource(&self) -> bool {
+            false
+        }
+
+        async fn execute(&self, input: TaskInput) -> std::result::Result<Ipld, TaskError> {
+            match input.ipld_args().as_slice() {
+                [Ipld::Integer(2)] => panic!("executor panicked"),
+                [Ipld::Integer(i)] => Ok(Ipld::Integer(i * 2)),
+                _ => Err(TaskError::new(
+                    TaskErrorKind::InvalidInput,
+                    "expected an integer",
+                )),
+            }
+        }
+    }
+
+    #[homestar_runtime_proc_macro::db_async_test]
+    async fn run_worker_with_panicking_mapped_instruction() {
+        let settings = TestSettings::load();
+
+        let resource = url::Url::parse("data:,").unwrap();
+        let list_instruction = Instruction::<Arg>::new(
+            resource.clone(),
+            Ability::from("noop"),
+            Input::Ipld(Ipld::Map(BTreeMap::from([(
+                "args".into(),
+                Ipld::List(vec![Ipld::List(vec![
+                    Ipld::Integer(1),
+                    Ipld::Integer(2),
+                    Ipld::Integer(3),
+                ])]),
+            )]))),
+        );
+        let promise = Await::new(
+            Pointer::try_from(list_instruction.clone()).unwrap(),
+            AwaitResult::Ok,
+        );
+        let mapped_instruction = Instruction::<Arg>::new(
+            resource,
+            Ability::from("test/panic"),
+            Input::Ipld(Ipld::Map(BTreeMap::from([
+                ("func".into(), Ipld::String("double".to_string())),
+                ("args".into(), Ipld::List(vec![promise.clone().into()])),
+            ]))),
+        );
+        let mapped_cid = mapped_instruction.clone().to_cid().unwrap();
+
+        let tasks = vec![
+            Task::new(
+                RunInstruction::Expanded(list_instruction),
+                Resources::default().into(),
+                UcanPrf::default(),
+            ),
+            Task::new(
+                RunInstruction::Expanded(mapped_instruction),
+                Map::new(promise).add_to_meta(Resources::default().into()),
+                UcanPrf::default(),
+            ),
+        ];
+
+        let (tx, rx) = test_utils::event::setup_event_channel(settings.node.clone());
+        let builder = WorkerBuilder::new(settings.node)
+            .with_event_sender(tx)
+            .with_tasks(tasks);
+        let fetch_fn = builder.fetch_fn();
+        let db = builder.db();
+        let workflow_cid = builder.workflow_cid();
+
+        let mut executors = Registry::default();
+        executors.register("test/panic", Panicking).unwrap();
+        let mut worker = builder.build().await;
+        worker.executors = Arc::new(executors);
+
+        let running_tasks = Arc::new(RunningTaskSet::new());
+        worker.run(running_tasks.clone(), fetch_fn).await.unwrap();
+
+        let mut conn = db.conn().unwrap();
+        let mut receipts = vec![];
+        while let Ok(event) = rx.recv_async().await {
+            if let Event::CapturedReceipt(Captured { receipt, .. }) = event {
+                receipts.push(MemoryDb::find_receipt_by_cid(receipt, &mut conn).unwrap());
+            }
+        }
+
+        // The list, an expanded instruction per element, including the one
+        // that panicked, and the gather.
+        assert_eq!(receipts.len(), 5);
+        let failed: Vec<_> = receipts.iter().filter(|receipt| receipt.failed()).collect();
+        assert_eq!(failed.len(), 2);
+        assert!(failed.iter().any(|receipt| {
+            let Ipld::Map(meta) = receipt.meta() else {
+                return false;
+            };
+            meta.get(MAPPED_FROM_KEY) == Some(&Ipld::Link(mapped_cid))
+        }));
+        assert!(failed
+            .iter()
+            .any(|receipt| receipt.instruction().cid() == mapped_cid));
+
+        let workflow_stored = MemoryDb::select_workflow(workflow_cid, &mut conn).unwrap();
+        assert_eq!(workflow_stored.status, Status::Failed);
+        assert_eq!(
+            workflow_stored.failed_instruction,
+            Some(Pointer::new(mapped_cid))
+        );
+    }
+
+    #[homestar_runtime_proc_macro::db_async_test]
+    async fn run_worker_with_retried_failing_instruction() {
+        let settings = TestSettings::load();
+
+        let failing_instruction = Instruction::<Arg>::new(
+            url::Url::parse("data:,").unwrap(),
+            Ability::from("test/fail"),
+            Input::Ipld(Ipld::Map(BTreeMap::from([
+                ("func".into(), Ipld::String("fail".to_string())),
+                ("args".into(), Ipld::List(vec![])),
+            ]))),
+        );
+
+        let failing_instruction_cid = failing_instruction.clone().to_cid().unwrap();
+        let retry = RetryPolicy::new(2, Duration::from_millis(10), Duration::from_millis(50));
+        let task = Task::new(
+            RunInstruction::Expanded(failing_instruction),
+            retry.add_to_meta(Resources::default().into()),
+            UcanPrf::default(),
+        );
+
+        let (tx, rx) = test_utils::event::setup_event_channel(settings.node.clone());
+        let builder = WorkerBuilder::new(settings.node)
+            .with_event_sender(tx)
+            .with_tasks(vec![task]);
+        let fetch_fn = builder.fetch_fn();
+        let db = builder.db();
+        let workflow_cid = builder.workflow_cid();
+
+        let mut executors = Registry::default();
+        executors.register("test/fail", Failing).unwrap();
+        let mut worker = builder.build().await;
+        worker.executors = Arc::new(executors);
+
+        let running_tasks = Arc::new(RunningTaskSet::new());
+        worker.run(running_tasks.clone(), fetch_fn).await.unwrap();
+
+        let mut conn = db.conn().unwrap();
+        let mut captured_receipt = false;
+        while let Ok(event) = rx.recv_async().await {
+            if let Event::CapturedReceipt(Captured { receipt, .. }) = event {
+                let receipt = MemoryDb::find_receipt_by_cid(receipt, &mut conn).unwrap();
+                assert_eq!(receipt.instruction().cid(), failing_instruction_cid);
+                assert!(matches!(receipt.output(), task::Result::Error(_)));
+
+                let Ipld::Map(meta) = receipt.meta() else {
+                    panic!("expected receipt metadata map")
+                };
+                assert_eq!(meta.get(ATTEMPTS_KEY), Some(&Ipld::Integer(3)));
+                captured_receipt = true;
+            }
+        }
+
+        assert!(captured_receipt);
+
+        let workflow_stored = MemoryDb::select_workflow(workflow_cid, &mut conn).unwrap();
+        assert_eq!(workflow_stored.status, Status::Failed);
+    }
+
+    #[homestar_runtime_proc_macro::db_async_test]
+    async fn run_worker_with_registered_executors() {
+        let settings = TestSettings::load();
+
+        let resource = url::Url::parse("data:,").unwrap();
+        let instruction = |ability: &str, fun: Option<&str>, args: Vec<Ipld>| {
+            let mut input = BTreeMap::from([("args".into(), Ipld::List(args))]);
+            if let Some(fun) = fun {
+                input.insert("func".into(), Ipld::String(fun.to_string()));
+            }
+            Instruction::<Arg>::new(
+                resource.clone(),
+                Ability::from(ability),
+                Input::Ipld(Ipld::Map(input)),
+            )
+        };
+        let promise = |instruction: &Instruction<'_, Arg>| {
+            Ipld::from(Await::new(
+                Pointer::try_from(instruction.clone()).unwrap(),
+                AwaitResult::Ok,
+            ))
+        };
+
+        // `noop` tasks don't need a function.
+        let noop_instruction = instruction(
+            "noop",
+            None,
+            vec![Ipld::Map(BTreeMap::from([(
+                "rows".into(),
+                Ipld::List(vec![Ipld::Integer(1), Ipld::Integer(2)]),
+            )]))],
+        );
+        let get_instruction = instruction(
+            "ipld/transform",
+            Some("get"),
+            vec![promise(&noop_instruction), ".rows[1]".into()],
+        );
+        let double_instruction = instruction(
+            "test/double",
+            Some("double"),
+            vec![promise(&get_instruction)],
+        );
+        let double_cid = double_instruction.clone().to_cid().unwrap();
+
+        let tasks = [noop_instruction, get_instruction, double_instruction]
+            .into_iter()
+            .map(|instruction| {
+                Task::new(
+                    RunInstruction::Expanded(instruction),
+                    Resources::default().into(),
+                    UcanPrf::default(),
+                )
+            })
+            .collect();
+
+        let (tx, rx) = test_utils::event::setup_event_channel(settings.node.clone());
+        let builder = WorkerBuilder::new(settings.node)
+            .with_event_sender(tx)
+            .with_tasks(tasks);
+        let fetch_fn = builder.fetch_fn();
+        let db = builder.db();
+        let workflow_cid = builder.workflow_cid();
+
+        let mut executors = Registry::default();
+        executors.register("test/double", Double).unwrap();
+        let mut worker = builder.build().await;
+        worker.executors = Arc::new(executors);
+        assert_eq!(
+            worker.unloaded_resources(),
+            FnvHashSet::from_iter([Resource::Url(resource)])
+        );
+
+        let running_tasks = Arc::new(RunningTaskSet::new());
+        worker.run(running_tasks.clone(), fetch_fn).await.unwrap();
+
+        let mut conn = db.conn().unwrap();
+        let mut receipts = vec![];
+        while let Ok(event) = rx.recv_async().await {
+            if let Event::CapturedReceipt(Captured { receipt, .. }) = event {
+                receipts.push(MemoryDb::find_receipt_by_cid(receipt, &mut conn).unwrap());
+            }
+        }
+        assert_eq!(receipts.len(), 3);
+
+        let doubled = MemoryDb::find_instruction_by_cid(double_cid, &mut conn).unwrap();
+        assert_eq!(doubled.output(), &task::Result::Ok(Ipld::Integer(4)));
+
+        let workflow_stored = MemoryDb::select_workflow(workflow_cid, &mut conn).unwrap();
+        assert_eq!(workflow_stored.status, Status::Completed);
+    }
+
     #[homestar_runtime_proc_macro::db_async_test]
     fn initialize_worker_with_all_receipted_instruction() {
         let mut settings = TestSettings::load();
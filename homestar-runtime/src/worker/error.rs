@@ -0,0 +1,206 @@
+//! Structured errors for tasks that fail during a [Worker] run, captured as
+//! the `error` branch of a task's [Receipt] output.
+//!
+//! [Worker]: crate::Worker
+//! [Receipt]: crate::Receipt
+
+use enum_assoc::Assoc;
+use homestar_wasm::wasmtime::Error as WasmRuntimeError;
+use libipld::Ipld;
+use std::{collections::BTreeMap, fmt};
+
+/// Key for the kind of failure in an error payload.
+pub(crate) const KIND_KEY: &str = "kind";
+
+/// Key for the human-readable message in an error payload.
+pub(crate) const MESSAGE_KEY: &str = "message";
+
+/// Kinds of task failures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Assoc)]
+#[func(pub(crate) const fn as_str(&self) -> &'static str)]
+pub enum TaskErrorKind {
+    /// Function was not found, e.g. in the given Wasm component.
+    #[assoc(as_str = "function_not_found")]
+    FunctionNotFound,
+    /// Wasm execution exceeded its memory limit.
+    #[assoc(as_str = "memory_limit_exceeded")]
+    MemoryLimitExceeded,
+    /// Wasm execution ran out of fuel.
+    #[assoc(as_str = "out_of_fuel")]
+    OutOfFuel,
+    /// Awaited inputs could not be resolved.
+    #[assoc(as_str = "resolve")]
+    Resolve,
+    /// Resource (e.g. Wasm module) was not available to run.
+    #[assoc(as_str = "resource_not_available")]
+    ResourceNotAvailable,
+    /// Wasm execution timed out.
+    #[assoc(as_str = "timeout")]
+    Timeout,
+    /// Wasm execution trapped, e.g. on reaching unreachable code.
+    #[assoc(as_str = "trap")]
+    Trap,
+    /// Resource, e.g. a Wasm module, could not be parsed or instantiated.
+    #[assoc(as_str = "invalid_resource")]
+    InvalidResource,
+    /// Generic failure during execution, e.g. a failing host call.
+    #[assoc(as_str = "execution")]
+    Execution,
+    /// Inputs don't fit the task's function, e.g. mistyped arguments to a
+    /// data-only task.
+    #[assoc(as_str = "invalid_input")]
+    InvalidInput,
+    /// Output of the task's function can't be represented as [Ipld].
+    #[assoc(as_str = "invalid_output")]
+    InvalidOutput,
+    /// Nested workflow, run by a `workflow/run` task, failed or couldn't be
+    /// run.
+    #[assoc(as_str = "workflow")]
+    Workflow,
+    /// No [Executor] is registered for the task's operation.
+    ///
+    /// [Executor]: crate::Executor
+    #[assoc(as_str = "unsupported_operation")]
+    UnsupportedOperation,
+}
+
+impl TaskErrorKind {
+    /// Whether a failure of this kind may succeed if the task is re-run,
+    /// e.g. a failing host call or a timeout.
+    ///
+    /// Failures that re-running the same task with the same inputs would
+    /// deterministically reproduce, e.g. traps and mistyped inputs, are not
+    /// retried.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, TaskErrorKind::Execution | TaskErrorKind::Timeout)
+    }
+}
+
+impl fmt::Display for TaskErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Error raised when running a task, which is converted into the
+/// [Ipld] error payload of a receipt:
+///
+/// `{"kind": "<kind>", "message": "<message>"}`
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+#[error("{kind}: {message}")]
+pub struct TaskError {
+    kind: TaskErrorKind,
+    message: String,
+}
+
+impl TaskError {
+    /// Create a new [TaskError] of a given [TaskErrorKind].
+    pub fn new(kind: TaskErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+
+    /// Return the [TaskErrorKind] of the error.
+    pub fn kind(&self) -> TaskErrorKind {
+        self.kind
+    }
+}
+
+impl From<WasmRuntimeError> for TaskError {
+    fn from(err: WasmRuntimeError) -> Self {
+        let kind = match err {
+            WasmRuntimeError::WasmFunctionNotFound(_) => TaskErrorKind::FunctionNotFound,
+            WasmRuntimeError::MemoryLimitExceeded(_) => TaskErrorKind::MemoryLimitExceeded,
+            WasmRuntimeError::OutOfFuel => TaskErrorKind::OutOfFuel,
+            WasmRuntimeError::ResolvePromise(_) => TaskErrorKind::Resolve,
+            WasmRuntimeError::Timeout(_) => TaskErrorKind::Timeout,
+            WasmRuntimeError::Trap(_) => TaskErrorKind::Trap,
+            WasmRuntimeError::InterpreterError(_) | WasmRuntimeError::WasmArgumentCount { .. } => {
+                TaskErrorKind::InvalidInput
+            }
+            WasmRuntimeError::IntoWasmComponent(_)
+            | WasmRuntimeError::WasmParser(_)
+            | WasmRuntimeError::Wat(_)
+            | WasmRuntimeError::WatComponent(_) => TaskErrorKind::InvalidResource,
+            _ => TaskErrorKind::Execution,
+        };
+
+        Self::new(kind, err.to_string())
+    }
+}
+
+impl From<TaskError> for Ipld {
+    fn from(err: TaskError) -> Self {
+        Ipld::Map(BTreeMap::from([
+            (KIND_KEY.into(), err.kind.as_str().into()),
+            (MESSAGE_KEY.into(), err.message.into()),
+        ]))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn wasm_errors_to_kinds() {
+        assert_eq!(
+            TaskError::from(WasmRuntimeError::OutOfFuel).kind(),
+            TaskErrorKind::OutOfFuel
+        );
+        assert_eq!(
+            TaskError::from(WasmRuntimeError::Timeout(Duration::from_secs(1))).kind(),
+            TaskErrorKind::Timeout
+        );
+        assert_eq!(
+            TaskError::from(WasmRuntimeError::WasmFunctionNotFound("nope".into())).kind(),
+            TaskErrorKind::FunctionNotFound
+        );
+        assert_eq!(
+            TaskError::from(WasmRuntimeError::WasmArgumentCount {
+                expected: 1,
+                given: 2
+            })
+            .kind(),
+            TaskErrorKind::InvalidInput
+        );
+        assert_eq!(
+            TaskError::from(WasmRuntimeError::WatComponent("nope".into())).kind(),
+            TaskErrorKind::InvalidResource
+        );
+        assert_eq!(
+            TaskError::from(WasmRuntimeError::Unknown).kind(),
+            TaskErrorKind::Execution
+        );
+    }
+
+    #[test]
+    fn only_transient_kinds_are_retryable() {
+        assert!(TaskErrorKind::Execution.is_retryable());
+        assert!(TaskErrorKind::Timeout.is_retryable());
+        assert!(!TaskErrorKind::Trap.is_retryable());
+        assert!(!TaskErrorKind::InvalidInput.is_retryable());
+        assert!(!TaskErrorKind::InvalidOutput.is_retryable());
+        assert!(!TaskErrorKind::InvalidResource.is_retryable());
+        assert!(!TaskErrorKind::OutOfFuel.is_retryable());
+    }
+
+    #[test]
+    fn task_error_to_ipld() {
+        let err = TaskError::new(TaskErrorKind::OutOfFuel, "Wasm execution ran out of fuel");
+
+        assert_eq!(
+            Ipld::from(err),
+            Ipld::Map(BTreeMap::from([
+                (KIND_KEY.into(), Ipld::String("out_of_fuel".into())),
+                (
+                    MESSAGE_KEY.into(),
+                    Ipld::String("Wasm execution ran out of fuel".into())
+                ),
+            ]))
+        );
+    }
+}
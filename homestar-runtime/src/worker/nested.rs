@@ -0,0 +1,266 @@
+//! Nested [Workflow]s run by `workflow/run` tasks, on a nested [Worker]
+//! sharing its parent's channels, settings, and task permits.
+//!
+//! [Workflow]: homestar_workflow::Workflow
+
+use super::{FetchFn, TaskError, TaskErrorKind, Worker, WorkerMessage};
+use crate::{
+    channel::AsyncChannelSender,
+    db::Database,
+    event_handler::Event,
+    runner::RunningTaskSet,
+    settings,
+    tasks::Registry,
+    workflow::{self, Resource},
+    Db,
+};
+use fnv::FnvHashSet;
+use futures::{future::BoxFuture, FutureExt};
+use homestar_invocation::{ipld::DagCbor, task};
+use homestar_wasm::io::Arg;
+use homestar_workflow::Workflow;
+use indexmap::IndexMap;
+use libipld::{Cid, Ipld};
+use std::sync::Arc;
+use tokio::sync::{RwLock, Semaphore};
+use tracing::info;
+use url::Url;
+
+/// Context for running the nested [Workflow] of a `workflow/run` task.
+///
+/// [Workflow]: homestar_workflow::Workflow
+pub(crate) struct Nested<DB: Database> {
+    /// Cid of the parent [Workflow].
+    ///
+    /// [Workflow]: homestar_workflow::Workflow
+    pub(crate) parent_cid: Cid,
+    /// Cid of the `workflow/run` [Instruction].
+    ///
+    /// [Instruction]: homestar_invocation::task::Instruction
+    pub(crate) instruction_cid: Cid,
+    /// `ipfs://<cid>` resource referencing the nested [Workflow].
+    ///
+    /// [Workflow]: homestar_workflow::Workflow
+    pub(crate) resource: Url,
+    /// Nested [Workflow] given inline, if any.
+    ///
+    /// [Workflow]: homestar_workflow::Workflow
+    pub(crate) inline: Option<Workflow<'static, Arg>>,
+    /// Local name of the nested [Workflow], given by the parent's name and
+    /// the `workflow/run` [Instruction] Cid.
+    ///
+    /// [Instruction]: homestar_invocation::task::Instruction
+    /// [Workflow]: homestar_workflow::Workflow
+    pub(crate) name: String,
+    pub(crate) workflow_settings: workflow::Settings,
+    pub(crate) network_settings: settings::Dht,
+    pub(crate) event_sender: Arc<AsyncChannelSender<Event>>,
+    pub(crate) runner_sender: AsyncChannelSender<WorkerMessage>,
+    pub(crate) db: DB,
+    pub(crate) task_permits: Arc<Semaphore>,
+    pub(crate) fetch: Option<FetchFn>,
+    pub(crate) executors: Arc<Registry>,
+    /// Resources already fetched for the parent [Workflow].
+    ///
+    /// [Workflow]: homestar_workflow::Workflow
+    pub(crate) resources: Arc<RwLock<IndexMap<Resource, Vec<u8>>>>,
+    pub(crate) running_tasks: Arc<RunningTaskSet>,
+}
+
+impl<DB> Nested<DB>
+where
+    DB: Database + 'static,
+{
+    /// Run the nested [Workflow] to completion, returning its final
+    /// result(s), i.e. the outputs of its tasks not awaited by other tasks,
+    /// as a single output, or a list of outputs in task order.
+    ///
+    /// Skipped final tasks output `null`, while a failed one fails the run.
+    ///
+    /// The run is boxed, as the nested [Worker] spawns tasks of its own.
+    ///
+    /// [Workflow]: homestar_workflow::Workflow
+    pub(crate) fn run(self) -> BoxFuture<'static, Result<Ipld, TaskError>> {
+        self.run_workflow().boxed()
+    }
+
+    async fn run_workflow(self) -> Result<Ipld, TaskError> {
+        let workflow = self.workflow().await?;
+        let workflow_cid = workflow.clone().to_cid().map_err(workflow_err)?;
+        let instruction_cids = workflow
+            .tasks_ref()
+            .iter()
+            .map(|task| task.instruction_cid())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(workflow_err)?;
+
+        info!(
+            subject = "worker.nested",
+            category = "worker.run",
+            workflow_cid = self.parent_cid.to_string(),
+            nested_workflow_cid = workflow_cid.to_string(),
+            "running nested workflow"
+        );
+
+        let mut worker = Worker::new(
+            workflow,
+            self.workflow_settings,
+            self.network_settings,
+            Some(self.name),
+            self.event_sender,
+            self.runner_sender,
+            self.db.clone(),
+        )
+        .await
+        .map_err(workflow_err)?;
+        worker.task_permits = self.task_permits;
+        worker.parent = Some(self.parent_cid);
+        worker.fetch = self.fetch.clone();
+        worker.executors = self.executors;
+
+        Db::store_nested_workflow(
+            self.parent_cid,
+            workflow_cid,
+            self.instruction_cid,
+            &mut self.db.conn().map_err(workflow_err)?,
+        )
+        .map_err(workflow_err)?;
+
+        let in_flow = worker.graph.awaiting.in_flow.clone();
+        let resources = self.resources;
+        let fetch = self.fetch;
+        let fetch_fn = move |rscs: FnvHashSet<Resource>| {
+            async move {
+                let mut found = IndexMap::default();
+                let mut to_fetch = FnvHashSet::default();
+                for rsc in rscs {
+                    match resources.read().await.get(&rsc) {
+                        Some(bytes) => {
+                            found.insert(rsc, bytes.to_owned());
+                        }
+                        None => {
+                            to_fetch.insert(rsc);
+                        }
+                    }
+                }
+
+                if let (Some(fetch), false) = (fetch, to_fetch.is_empty()) {
+                    found.extend(fetch(to_fetch).await?);
+                }
+
+                Ok(found)
+            }
+            .boxed()
+        };
+
+        worker
+            .run(self.running_tasks, fetch_fn)
+            .await
+            .map_err(workflow_err)?;
+
+        let conn = &mut self.db.conn().map_err(workflow_err)?;
+        let stored = Db::select_workflow(workflow_cid, conn).map_err(workflow_err)?;
+        if stored.status != workflow::Status::Completed {
+            return Err(TaskError::new(
+                TaskErrorKind::Workflow,
+                format!(
+                    "nested workflow {workflow_cid} {}{}",
+                    stored.status,
+                    stored
+                        .failure_reason
+                        .map(|reason| format!(": {reason}"))
+                        .unwrap_or_default()
+                ),
+            ));
+        }
+
+        // Receipts of skipped tasks are local to the nested workflow, so
+        // aren't found by instruction.
+        let skipped: FnvHashSet<Cid> = Db::find_workflow_receipts(workflow_cid, conn)
+            .map_err(workflow_err)?
+            .into_iter()
+            .filter(|receipt| receipt.skipped())
+            .map(|receipt| receipt.instruction().cid())
+            .collect();
+
+        let mut outputs = Vec::new();
+        for cid in instruction_cids
+            .into_iter()
+            .filter(|cid| !in_flow.contains(cid))
+        {
+            if skipped.contains(&cid) {
+                outputs.push(Ipld::Null);
+                continue;
+            }
+
+            let receipt = Db::find_instruction_by_cid(cid, conn).map_err(workflow_err)?;
+            match receipt.output() {
+                task::Result::Ok(ipld) | task::Result::Just(ipld) => outputs.push(ipld.to_owned()),
+                task::Result::Error(err) => {
+                    return Err(TaskError::new(
+                        TaskErrorKind::Workflow,
+                        format!("nested workflow {workflow_cid} task {cid} failed: {err:?}"),
+                    ))
+                }
+            }
+        }
+
+        if outputs.len() == 1 {
+            Ok(outputs.remove(0))
+        } else {
+            Ok(Ipld::List(outputs))
+        }
+    }
+
+    /// Find the nested [Workflow], given inline, or by the Cid of its
+    /// resource, in stored workflow documents or in fetched resources.
+    ///
+    /// [Workflow]: homestar_workflow::Workflow
+    async fn workflow(&self) -> Result<Workflow<'static, Arg>, TaskError> {
+        if let Some(ref workflow) = self.inline {
+            return Ok(workflow.to_owned());
+        }
+
+        let cid = workflow::resource_cid(&self.resource).ok_or_else(|| {
+            TaskError::new(
+                TaskErrorKind::ResourceNotAvailable,
+                format!("not an ipfs://<cid> workflow resource: {}", self.resource),
+            )
+        })?;
+
+        let stored = self
+            .db
+            .conn()
+            .ok()
+            .and_then(|mut conn| Db::select_workflow_document(cid, &mut conn).ok().flatten());
+        let document = match stored {
+            Some(document) => document,
+            None => self
+                .resources
+                .read()
+                .await
+                .get(&Resource::Url(self.resource.to_owned()))
+                .cloned()
+                .ok_or_else(|| {
+                    TaskError::new(
+                        TaskErrorKind::ResourceNotAvailable,
+                        format!("workflow not available: {}", self.resource),
+                    )
+                })?,
+        };
+
+        let workflow = Workflow::<'static, Arg>::from_cbor(&document).map_err(workflow_err)?;
+        if workflow.clone().to_cid().map_err(workflow_err)? != cid {
+            return Err(TaskError::new(
+                TaskErrorKind::Workflow,
+                format!("workflow does not match resource: {}", self.resource),
+            ));
+        }
+
+        Ok(workflow)
+    }
+}
+
+fn workflow_err(err: impl ToString) -> TaskError {
+    TaskError::new(TaskErrorKind::Workflow, err.to_string())
+}
@@ -8,6 +8,8 @@ use crate::{
         Event,
     },
     network::swarm::CapsuleTag,
+    receipt::trust::{self, Decision, UNKNOWN_PEER},
+    settings::ReceiptTrust,
     workflow::Resource,
     Db,
 };
@@ -23,13 +25,14 @@ use tokio::{
     sync::RwLock,
     time::{timeout_at, Instant},
 };
-use tracing::{debug, instrument};
+use tracing::{debug, info, instrument};
 
 pub(crate) trait Resolver {
     async fn resolve(
         self,
         linkmap: Arc<RwLock<LinkMap<task::Result<Arg>>>>,
         resources: Arc<RwLock<IndexMap<Resource, Vec<u8>>>>,
+        trust: Arc<ReceiptTrust>,
         db: impl Database,
     ) -> Result<task::Result<Arg>, ResolveError>;
 }
@@ -40,6 +43,7 @@ impl Resolver for Cid {
         self,
         linkmap: Arc<RwLock<LinkMap<task::Result<Arg>>>>,
         resources: Arc<RwLock<IndexMap<Resource, Vec<u8>>>>,
+        trust: Arc<ReceiptTrust>,
         db: impl Database,
     ) -> Result<task::Result<Arg>, ResolveError> {
         if let Some(result) = linkmap.read().await.get(&self) {
@@ -63,7 +67,23 @@ impl Resolver for Cid {
         } else {
             let conn = &mut db.conn()?;
             match Db::find_instruction_by_cid(self, conn) {
-                Ok(found) => Ok(found.output_as_arg()),
+                Ok(found)
+                    if Decision::for_stored(&trust, &found, conn)
+                        .is_ok_and(|decision| decision.is_trusted()) =>
+                {
+                    Ok(found.output_as_arg())
+                }
+                Ok(found) => {
+                    debug!(
+                        subject = "worker.resolve_cid",
+                        category = "worker.run",
+                        cid = self.to_string(),
+                        receipt_cid = found.cid().to_string(),
+                        policy = trust.to_string(),
+                        "related instruction receipt in the DB is not trusted"
+                    );
+                    Err(ResolveError::UnresolvedCid((self).to_string()))
+                }
                 Err(_) => {
                     debug!(
                         subject = "worker.resolve_cid",
@@ -83,6 +103,7 @@ pub(crate) struct DHTResolver {
     cids: Arc<FnvHashSet<Cid>>,
     p2p_receipt_timeout: Duration,
     workflow_cid: Cid,
+    trust: ReceiptTrust,
 }
 
 impl DHTResolver {
@@ -91,11 +112,13 @@ impl DHTResolver {
         cids: Arc<FnvHashSet<Cid>>,
         p2p_receipt_timeout: Duration,
         workflow_cid: Cid,
+        trust: ReceiptTrust,
     ) -> Self {
         Self {
             cids,
             p2p_receipt_timeout,
             workflow_cid,
+            trust,
         }
     }
 }
@@ -139,8 +162,43 @@ where
 
             let conn = &mut ctx.db.conn()?;
 
-            let receipt = Db::commit_receipt(self.workflow_cid, found.clone().receipt, conn)
-                .unwrap_or(found.clone().receipt);
+            // Evaluate the receipt against the peers it was already received
+            // from. DHT records don't authenticate their publisher, so the
+            // record counts as coming from an unknown peer.
+            let peer = UNKNOWN_PEER.to_string();
+            let peers = trust::remote_peers(found.receipt.cid(), conn).unwrap_or(Some(vec![]));
+            let decision = peers.clone().map_or(Decision::Local, |mut peers| {
+                peers.push(peer.clone());
+                Decision::new(&self.trust, peers)
+            });
+            // Receipts flagged on verification are kept, but never reused.
+            let reuse = decision.is_trusted()
+                && !Db::is_receipt_flagged(found.receipt.cid(), conn).unwrap_or(false);
+
+            let receipt = if reuse {
+                Db::commit_receipt(self.workflow_cid, found.clone().receipt, conn)
+                    .unwrap_or(found.clone().receipt)
+            } else {
+                // Keep untrusted or flagged receipts, unattached to the
+                // workflow, e.g. toward a quorum of peers.
+                let _ = Db::store_receipt(found.clone().receipt, conn);
+                found.clone().receipt
+            };
+            if peers.is_some() {
+                let _ = Db::store_receipt_peer(receipt.cid(), &peer, conn);
+            }
+
+            if !reuse {
+                info!(
+                    subject = "dht.resolver.untrusted",
+                    category = "dht.resolver",
+                    cid_resolved = cid.to_string(),
+                    receipt_cid = receipt.cid().to_string(),
+                    policy = self.trust.to_string(),
+                    "not reusing untrusted or flagged receipt found on the DHT"
+                );
+                continue;
+            }
 
             debug!(
                 subject = "db.commit_receipt",
@@ -168,3 +226,82 @@ where
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        channel::AsyncChannelSender,
+        event_handler::swarm_event::ReceiptEvent,
+        test_utils::{self, db::MemoryDb},
+        Receipt,
+    };
+    use homestar_invocation::test_utils::cid::generate_cid;
+    use rand::thread_rng;
+
+    /// Answer every DHT query for a receipt with the given receipt.
+    fn dht_with(receipt: Receipt) -> Arc<AsyncChannelSender<Event>> {
+        let (tx, rx) = AsyncChannel::with(1);
+        tokio::spawn(async move {
+            while let Ok(Event::FindRecord(QueryRecord {
+                sender: Some(sender),
+                ..
+            })) = rx.recv_async().await
+            {
+                let _ = sender
+                    .send_async(ResponseEvent::Found(Ok(FoundEvent::Receipt(
+                        ReceiptEvent {
+                            peer_id: None,
+                            receipt: receipt.clone(),
+                        },
+                    ))))
+                    .await;
+            }
+        });
+
+        Arc::new(tx)
+    }
+
+    #[homestar_runtime_proc_macro::db_async_test]
+    async fn dht_receipts_never_count_toward_allowlist_or_quorum() {
+        let settings = TestSettings::load();
+        let (_, receipt) = test_utils::receipt::receipts();
+        let instruction_cid = receipt.instruction().cid();
+
+        for trust in [
+            ReceiptTrust::Allowlist {
+                peers: vec![UNKNOWN_PEER.to_string()],
+            },
+            ReceiptTrust::Quorum { quorum: 1 },
+        ] {
+            let db = MemoryDb::setup_connection_pool(settings.node(), None).unwrap();
+            let linkmap = Arc::new(RwLock::new(LinkMap::default()));
+            let poller = Poller {
+                db: db.clone(),
+                event_sender: dht_with(receipt.clone()),
+                linkmap: Some(linkmap.clone()),
+            };
+            let resolver = DHTResolver::new(
+                Arc::new(FnvHashSet::from_iter([instruction_cid])),
+                Duration::from_secs(1),
+                generate_cid(&mut thread_rng()),
+                trust.clone(),
+            );
+
+            // Found on the DHT twice, from what may be different peers, yet
+            // recorded as a single unknown one.
+            resolver.poll(&poller).await.unwrap();
+            resolver.poll(&poller).await.unwrap();
+
+            let mut conn = db.conn().unwrap();
+            assert!(linkmap.read().await.get(&instruction_cid).is_none());
+            assert_eq!(
+                MemoryDb::find_receipt_peers(receipt.cid(), &mut conn).unwrap(),
+                vec![UNKNOWN_PEER.to_string()]
+            );
+            assert!(!Decision::for_stored(&trust, &receipt, &mut conn)
+                .unwrap()
+                .is_trusted());
+        }
+    }
+}
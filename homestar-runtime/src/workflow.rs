@@ -11,14 +11,16 @@ use diesel::{
     backend::Backend,
     deserialize::{self, FromSql},
     serialize::{self, IsNull, Output, ToSql},
-    sql_types::Binary,
+    sql_types::{Binary, Text},
     sqlite::Sqlite,
     AsExpression, FromSqlRow,
 };
 use homestar_invocation::{
+    ipld::DagCbor,
+    pointer::Await,
     task::{
         instruction::{Parse, Parsed, RunInstruction},
-        Instruction,
+        Condition, Instruction, Map, Resources, RetryPolicy,
     },
     Invocation, Pointer,
 };
@@ -44,6 +46,11 @@ pub use settings::Settings;
 
 type Dag<'a> = dagga::Dag<Vertex<'a>, usize>;
 
+/// Task metadata key for a nested [Workflow] given inline, to be run by a
+/// `workflow/run` task instead of looking the nested [Workflow] up by its
+/// resource.
+pub(crate) const INLINE_WORKFLOW_KEY: &str = "workflow";
+
 /// A [Workflow] [Builder] wrapper for the runtime.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Builder<'a>(Workflow<'a, Arg>);
@@ -89,7 +96,6 @@ impl AOTContext<'static> {
     ///
     /// [Dag]: dagga::Dag
     /// [dot]: <https://graphviz.org/doc/info/lang.html>
-    #[allow(dead_code)]
     pub(crate) fn dot(&self, name: &str, path: &Path) -> anyhow::Result<()> {
         DagLegend::new(self.dag.nodes())
             .with_name(name)
@@ -99,6 +105,34 @@ impl AOTContext<'static> {
             )
             .map_err(|e| anyhow!(e))
     }
+
+    /// Convert [Dag] to a [Mermaid] flowchart, with an edge from each awaited
+    /// instruction to the instructions awaiting it.
+    ///
+    /// [Dag]: dagga::Dag
+    /// [Mermaid]: <https://mermaid.js.org/syntax/flowchart.html>
+    pub(crate) fn mermaid(&self) -> String {
+        let nodes = self.dag.nodes().collect::<Vec<_>>();
+        let mut chart = String::from("flowchart TD\n");
+
+        for (i, node) in nodes.iter().enumerate() {
+            let fun = node.inner().parsed.fun().unwrap_or_default();
+            chart.push_str(&format!("    n{i}[\"{fun}<br/>{}\"]\n", node.name()));
+        }
+
+        for (i, node) in nodes.iter().enumerate() {
+            for read in node.get_reads() {
+                if let Some(j) = nodes
+                    .iter()
+                    .position(|awaited| awaited.get_results().any(|result| result == read))
+                {
+                    chart.push_str(&format!("    n{j} --> n{i}\n"));
+                }
+            }
+        }
+
+        chart
+    }
 }
 
 /// Vertex information for [Dag] [Node].
@@ -109,6 +143,16 @@ pub(crate) struct Vertex<'a> {
     pub(crate) instruction: Instruction<'a, Arg>,
     pub(crate) parsed: Parsed<Arg>,
     pub(crate) invocation: Pointer,
+    /// [Resources] (fuel, memory, timeout) to bound the task's execution by.
+    pub(crate) resources: Resources,
+    /// [RetryPolicy] for re-running the task's execution on failure.
+    pub(crate) retry: RetryPolicy,
+    /// [Condition] on the task running, which is skipped otherwise.
+    pub(crate) condition: Option<Condition>,
+    /// [Map] of the task over an awaited list output.
+    pub(crate) map: Option<Map>,
+    /// Nested [Workflow] given inline, run by `workflow/run` tasks.
+    pub(crate) nested: Option<Workflow<'static, Arg>>,
 }
 
 /// [Origin] of a [Cid] being in/not-in a [Workflow] itself.
@@ -147,13 +191,58 @@ impl<'a> Vertex<'a> {
         instruction: Instruction<'a, Arg>,
         parsed: Parsed<Arg>,
         invocation: Pointer,
+        resources: Resources,
+        retry: RetryPolicy,
+        condition: Option<Condition>,
+        map: Option<Map>,
+        nested: Option<Workflow<'static, Arg>>,
     ) -> Vertex<'a> {
         Vertex {
             instruction,
             parsed,
             invocation,
+            resources,
+            retry,
+            condition,
+            map,
+            nested,
         }
     }
+
+    /// Return the [Instruction] Cids awaited by the task, through its
+    /// arguments, its [Condition], or its [Map].
+    pub(crate) fn awaits(&self) -> impl Iterator<Item = Cid> + '_ {
+        awaits(&self.parsed, self.condition.as_ref(), self.map.as_ref())
+    }
+
+    /// Return the [Await]'ed promises of the task, through its arguments,
+    /// its [Condition], or its [Map].
+    pub(crate) fn promises(&self) -> impl Iterator<Item = Await> + '_ {
+        promises(&self.parsed, self.condition.as_ref(), self.map.as_ref())
+    }
+}
+
+/// [Instruction] Cids awaited through a task's arguments, [Condition], and
+/// [Map].
+pub(crate) fn awaits<'b>(
+    parsed: &'b Parsed<Arg>,
+    condition: Option<&'b Condition>,
+    map: Option<&'b Map>,
+) -> impl Iterator<Item = Cid> + 'b {
+    promises(parsed, condition, map).map(|promise| promise.instruction_cid())
+}
+
+/// [Await]'ed promises of a task's arguments, [Condition], and [Map].
+fn promises<'b>(
+    parsed: &'b Parsed<Arg>,
+    condition: Option<&'b Condition>,
+    map: Option<&'b Map>,
+) -> impl Iterator<Item = Await> + 'b {
+    parsed
+        .args()
+        .promises()
+        .chain(condition.map(|condition| condition.promise().to_owned()))
+        .chain(map.map(|map| map.promise().to_owned()))
 }
 
 impl<'a> Builder<'a> {
@@ -189,7 +278,11 @@ impl<'a> Builder<'a> {
         }
     }
 
-    fn aot(self) -> anyhow::Result<AOTContext<'a>> {
+    /// Build the [AOTContext] for the [Workflow], i.e. its [Dag], promises,
+    /// and indexed resources.
+    ///
+    /// [Dag]: dagga::Dag
+    pub(crate) fn aot(self) -> anyhow::Result<AOTContext<'a>> {
         let lookup_table = self.lookup_table()?;
         let (mut dag, unawaits, awaited, promised_cids, resources) =
             self.into_inner().tasks().into_iter().enumerate().try_fold(
@@ -219,15 +312,44 @@ impl<'a> Builder<'a> {
                     // Clone as we're owning the struct going backward.
                     let ptr: Pointer = Invocation::<Arg>::from(task.clone()).try_into()?;
 
+                    let task_resources = Resources::try_from(task.meta())?;
+                    let task_retry = RetryPolicy::try_from(task.meta())?;
+                    let task_condition = Condition::from_meta(task.meta())?;
+                    let task_map = Map::from_meta(task.meta())?;
+                    let task_nested = inline_workflow(task.meta())?;
+
                     let RunInstruction::Expanded(instr) = task.into_instruction() else {
                         bail!("workflow tasks/instructions must be expanded / inlined")
                     };
 
+                    // Nested workflows given inline rely on their own tasks'
+                    // resources, rather than on being fetched.
+                    let instr_resources = match task_nested {
+                        Some(ref nested) => {
+                            if resource_cid(instr.resource()) != Some(nested.clone().to_cid()?) {
+                                bail!(
+                                    "inline workflow does not match resource: {}",
+                                    instr.resource()
+                                )
+                            }
+                            Builder::new(nested.clone())
+                                .aot()?
+                                .indexed_resources
+                                .into_iter()
+                                .fold(vec![], |mut rscs, rsc| {
+                                    if !rscs.contains(&rsc) {
+                                        rscs.push(rsc);
+                                    }
+                                    rscs
+                                })
+                        }
+                        None => vec![Resource::Url(instr.resource().to_owned())],
+                    };
                     resources
                         .entry(instr_cid)
-                        .or_insert_with(|| vec![Resource::Url(instr.resource().to_owned())]);
+                        .or_insert_with(|| instr_resources);
                     let parsed = instr.input().parse()?;
-                    let deferred = parsed.args().deferreds();
+                    let deferred = awaits(&parsed, task_condition.as_ref(), task_map.as_ref());
                     let reads = deferred.fold(vec![], |mut in_flow_reads, cid| {
                         if let Some(v) = lookup_table.get(&cid) {
                             in_flows.push(cid);
@@ -249,9 +371,18 @@ impl<'a> Builder<'a> {
                             .or_insert_with(|| vec![Resource::Cid(cid.to_owned())]);
                     });
 
-                    let node = Node::new(Vertex::new(instr.to_owned(), parsed, ptr))
-                        .with_name(instr_cid.to_string())
-                        .with_result(i);
+                    let node = Node::new(Vertex::new(
+                        instr.to_owned(),
+                        parsed,
+                        ptr,
+                        task_resources,
+                        task_retry,
+                        task_condition,
+                        task_map,
+                        task_nested,
+                    ))
+                    .with_name(instr_cid.to_string())
+                    .with_result(i);
 
                     if !reads.is_empty() {
                         dag.add_node(node.with_reads(reads.clone()));
@@ -301,6 +432,27 @@ impl<'a> Builder<'a> {
     }
 }
 
+/// Nested [Workflow] given inline in a task's metadata, if any.
+fn inline_workflow(meta: &Ipld) -> anyhow::Result<Option<Workflow<'static, Arg>>> {
+    match meta {
+        Ipld::Map(map) => map
+            .get(INLINE_WORKFLOW_KEY)
+            .map(|ipld| Workflow::try_from(ipld.to_owned()))
+            .transpose()
+            .map_err(|err| anyhow!("invalid inline workflow: {err}")),
+        _ => Ok(None),
+    }
+}
+
+/// [Cid] referenced by an `ipfs://<cid>` resource, if any.
+pub(crate) fn resource_cid(url: &Url) -> Option<Cid> {
+    if url.scheme() != "ipfs" {
+        return None;
+    }
+
+    url.host_str().and_then(|cid| Cid::try_from(cid).ok())
+}
+
 /// A container for [IndexMap]s from Cid => resource.
 #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, AsExpression, FromSqlRow)]
 #[diesel(sql_type = Binary)]
@@ -444,6 +596,50 @@ where
     }
 }
 
+/// Cids of [Instruction]s, e.g. those left unfinished by a timed out
+/// workflow, stored as a JSON array of strings.
+#[derive(Debug, Default, Clone, PartialEq, AsExpression, FromSqlRow)]
+#[diesel(sql_type = Text)]
+pub struct InstructionCids(Vec<Cid>);
+
+impl InstructionCids {
+    /// Create a new [InstructionCids] container from [Instruction] Cids.
+    pub(crate) fn new(cids: Vec<Cid>) -> InstructionCids {
+        InstructionCids(cids)
+    }
+
+    /// Return a referenced [Vec] of [Instruction] Cids.
+    pub(crate) fn inner(&self) -> &Vec<Cid> {
+        &self.0
+    }
+}
+
+impl ToSql<Text, Sqlite> for InstructionCids
+where
+    String: ToSql<Text, Sqlite>,
+{
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Sqlite>) -> serialize::Result {
+        let cids = self.0.iter().map(Cid::to_string).collect::<Vec<String>>();
+        out.set_value(serde_json::to_string(&cids)?);
+        Ok(IsNull::No)
+    }
+}
+
+impl<DB> FromSql<Text, DB> for InstructionCids
+where
+    DB: Backend,
+    String: FromSql<Text, DB>,
+{
+    fn from_sql(bytes: DB::RawValue<'_>) -> deserialize::Result<Self> {
+        let json = <String as FromSql<Text, DB>>::from_sql(bytes)?;
+        let cids = serde_json::from_str::<Vec<String>>(&json)?
+            .into_iter()
+            .map(Cid::try_from)
+            .collect::<Result<Vec<Cid>, _>>()?;
+        Ok(InstructionCids(cids))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -502,6 +698,48 @@ mod test {
         assert!(Path::new("test.dot").exists());
     }
 
+    #[test]
+    fn dag_to_mermaid() {
+        let config = Resources::default();
+        let (instruction1, instruction2, _) = test_utils::related_wasm_instructions::<Arg>();
+        let task1 = Task::new(
+            RunInstruction::Expanded(instruction1.clone()),
+            config.clone().into(),
+            UcanPrf::default(),
+        );
+        let task2 = Task::new(
+            RunInstruction::Expanded(instruction2.clone()),
+            config.into(),
+            UcanPrf::default(),
+        );
+
+        let workflow = Workflow::new(vec![task1, task2]);
+        let aot = Builder::new(workflow).aot().unwrap();
+        let chart = aot.mermaid();
+
+        assert!(chart.starts_with("flowchart TD\n"));
+        assert!(chart.contains(&instruction1.to_cid().unwrap().to_string()));
+        assert!(chart.contains(&instruction2.to_cid().unwrap().to_string()));
+        assert_eq!(chart.matches("-->").count(), 1);
+    }
+
+    #[test]
+    fn build_with_malformed_meta_fails() {
+        let instruction = test_utils::wasm_instruction::<Arg>();
+        let meta = Ipld::Map(BTreeMap::from([(
+            "fuel".into(),
+            Ipld::String("lots".into()),
+        )]));
+        let task = Task::new(
+            RunInstruction::Expanded(instruction),
+            meta,
+            UcanPrf::default(),
+        );
+
+        let workflow = Workflow::new(vec![task]);
+        assert!(Builder::new(workflow).aot().is_err());
+    }
+
     #[test]
     fn build_parallel_schedule() {
         let config = Resources::default();
@@ -558,6 +796,61 @@ mod test {
         dagga::assert_batches(&[&instr1, &instr2], dag);
     }
 
+    #[test]
+    fn build_with_inline_nested_workflow() {
+        let config = Resources::default();
+        let (instruction1, instruction2, _) = test_utils::related_wasm_instructions::<Arg>();
+        let nested = Workflow::new(vec![
+            Task::new(
+                RunInstruction::Expanded(instruction1.clone()),
+                config.clone().into(),
+                UcanPrf::default(),
+            ),
+            Task::new(
+                RunInstruction::Expanded(instruction2),
+                config.clone().into(),
+                UcanPrf::default(),
+            ),
+        ]);
+
+        let Ipld::Map(mut meta) = config.into() else {
+            panic!("resources are not a map")
+        };
+        meta.insert(INLINE_WORKFLOW_KEY.into(), nested.clone().into());
+
+        let run_instruction = |cid: Cid| {
+            Instruction::<Arg>::new(
+                Url::parse(&format!("ipfs://{cid}")).unwrap(),
+                Ability::from("workflow/run"),
+                Input::Ipld(Ipld::Map(BTreeMap::from([
+                    ("func".into(), Ipld::String("nested".to_string())),
+                    ("args".into(), Ipld::List(vec![])),
+                ]))),
+            )
+        };
+
+        let task = Task::new(
+            RunInstruction::Expanded(run_instruction(nested.clone().to_cid().unwrap())),
+            Ipld::Map(meta.clone()),
+            UcanPrf::default(),
+        );
+        let instr = task.instruction_cid().unwrap();
+
+        let aot = Builder::new(Workflow::new(vec![task])).aot().unwrap();
+        assert_eq!(
+            aot.indexed_resources.get(&instr),
+            Some(&vec![Resource::Url(instruction1.resource().to_owned())])
+        );
+
+        // An inline workflow has to match the workflow Cid of its resource.
+        let mismatched = Task::new(
+            RunInstruction::Expanded(run_instruction(instruction1.to_cid().unwrap())),
+            Ipld::Map(meta),
+            UcanPrf::default(),
+        );
+        assert!(Builder::new(Workflow::new(vec![mismatched])).aot().is_err());
+    }
+
     #[test]
     fn build_mixed_graph() {
         let config = Resources::default();
@@ -1,5 +1,5 @@
 #![allow(missing_docs)]
-use super::IndexedResources;
+use super::{IndexedResources, InstructionCids};
 use crate::{
     channel::{AsyncChannel, AsyncChannelSender},
     db::{Connection, Database},
@@ -40,7 +40,8 @@ const RESOURCES_KEY: &str = "resources";
 /// Status of a [Workflow].
 ///
 /// [Workflow]: homestar_workflow::Workflow
-#[derive(Debug, Clone, PartialEq, diesel_derive_enum::DbEnum)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, diesel_derive_enum::DbEnum)]
+#[serde(rename_all = "snake_case")]
 pub enum Status {
     /// Workflow is pending - default case.
     Pending,
@@ -50,6 +51,54 @@ pub enum Status {
     Completed,
     /// Workflow is stuck, awaiting CIDs we can't find on the network.
     Stuck,
+    /// Workflow has failed from a failed task.
+    Failed,
+    /// Workflow has been cancelled.
+    Cancelled,
+    /// Workflow has timed out, with its unfinished tasks aborted.
+    TimedOut,
+}
+
+impl Status {
+    /// Whether the workflow has finished, one way or another, and won't run
+    /// any further.
+    pub(crate) fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            Status::Completed | Status::Failed | Status::Cancelled | Status::TimedOut
+        )
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::Pending => write!(f, "pending"),
+            Status::Running => write!(f, "running"),
+            Status::Completed => write!(f, "completed"),
+            Status::Stuck => write!(f, "stuck"),
+            Status::Failed => write!(f, "failed"),
+            Status::Cancelled => write!(f, "cancelled"),
+            Status::TimedOut => write!(f, "timed_out"),
+        }
+    }
+}
+
+impl std::str::FromStr for Status {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "pending" => Ok(Status::Pending),
+            "running" => Ok(Status::Running),
+            "completed" => Ok(Status::Completed),
+            "stuck" => Ok(Status::Stuck),
+            "failed" => Ok(Status::Failed),
+            "cancelled" => Ok(Status::Cancelled),
+            "timed_out" => Ok(Status::TimedOut),
+            other => Err(anyhow!("unknown workflow status: {other}")),
+        }
+    }
 }
 
 /// [Workflow] information stored in the database.
@@ -90,6 +139,25 @@ pub struct Stored {
     ///
     /// [Workflow]: homestar_workflow::Workflow
     pub(crate) retries: i32,
+    /// Reason the [Workflow] failed, was cancelled, or timed out.
+    ///
+    /// [Workflow]: homestar_workflow::Workflow
+    pub(crate) failure_reason: Option<String>,
+    /// Wrapped-Cid of the [Instruction] that caused the [Workflow] to fail.
+    ///
+    /// [Instruction]: homestar_invocation::task::Instruction
+    /// [Workflow]: homestar_workflow::Workflow
+    pub(crate) failed_instruction: Option<Pointer>,
+    /// Local timestamp of [Workflow] failure or cancellation.
+    ///
+    /// [Workflow]: homestar_workflow::Workflow
+    pub(crate) failed_at: Option<NaiveDateTime>,
+    /// Cids of the [Instruction]s left unfinished when the [Workflow] timed
+    /// out.
+    ///
+    /// [Instruction]: homestar_invocation::task::Instruction
+    /// [Workflow]: homestar_workflow::Workflow
+    pub(crate) unfinished_instructions: Option<InstructionCids>,
 }
 
 impl Stored {
@@ -112,6 +180,10 @@ impl Stored {
             completed_at: None,
             status: Status::Pending,
             retries: 0,
+            failure_reason: None,
+            failed_instruction: None,
+            failed_at: None,
+            unfinished_instructions: None,
         }
     }
 
@@ -133,6 +205,10 @@ impl Stored {
             completed_at: None,
             status: Status::Pending,
             retries: 0,
+            failure_reason: None,
+            failed_instruction: None,
+            failed_at: None,
+            unfinished_instructions: None,
         }
     }
 
@@ -150,6 +226,10 @@ impl Stored {
             completed_at: None,
             status: Status::Pending,
             retries: 0,
+            failure_reason: None,
+            failed_instruction: None,
+            failed_at: None,
+            unfinished_instructions: None,
         }
     }
 }
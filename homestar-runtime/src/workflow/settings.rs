@@ -2,6 +2,8 @@
 //!
 //! [Workflow]: homestar_workflow::Workflow
 
+use anyhow::ensure;
+use homestar_workflow::workflow::Meta;
 use std::time::Duration;
 
 /// Workflow settings.
@@ -15,6 +17,14 @@ pub struct Settings {
     pub(crate) retry_initial_delay: Duration,
     /// Timeout for a given workflow.
     pub(crate) timeout: Duration,
+    /// Priority of a given workflow, where higher runs first.
+    pub(crate) priority: i32,
+    /// Maximum number of tasks run concurrently for a given workflow,
+    /// unless its worker shares the node-wide limit set by the [Scheduler]
+    /// settings.
+    ///
+    /// [Scheduler]: crate::settings::Scheduler
+    pub(crate) max_concurrent_tasks: usize,
 }
 
 #[cfg(all(not(test), not(feature = "test-utils")))]
@@ -25,6 +35,8 @@ impl Default for Settings {
             retry_max_delay: Duration::new(60, 0),
             retry_initial_delay: Duration::from_millis(500),
             timeout: Duration::new(3600, 0),
+            priority: 0,
+            max_concurrent_tasks: 16,
         }
     }
 }
@@ -38,6 +50,75 @@ impl Default for Settings {
             retry_max_delay: Duration::new(1, 0),
             retry_initial_delay: Duration::from_millis(50),
             timeout: Duration::from_secs(3600),
+            priority: 0,
+            max_concurrent_tasks: 16,
         }
     }
 }
+
+impl TryFrom<&Meta> for Settings {
+    type Error = anyhow::Error;
+
+    /// Map a workflow's [Meta] section onto [Settings], falling back to
+    /// defaults for anything unset.
+    fn try_from(meta: &Meta) -> Result<Self, Self::Error> {
+        let default = Settings::default();
+        let settings = Settings {
+            retries: meta.retries().unwrap_or(default.retries),
+            retry_max_delay: meta.retry_max_delay().unwrap_or(default.retry_max_delay),
+            retry_initial_delay: meta
+                .retry_initial_delay()
+                .unwrap_or(default.retry_initial_delay),
+            timeout: meta.timeout().unwrap_or(default.timeout),
+            priority: meta.priority().unwrap_or(default.priority),
+            max_concurrent_tasks: default.max_concurrent_tasks,
+        };
+
+        ensure!(
+            !settings.timeout.is_zero(),
+            "workflow timeout must be greater than zero"
+        );
+        ensure!(
+            settings.retry_initial_delay <= settings.retry_max_delay,
+            "workflow retry_initial_delay ({}ms) must not exceed retry_max_delay ({}ms)",
+            settings.retry_initial_delay.as_millis(),
+            settings.retry_max_delay.as_millis()
+        );
+
+        Ok(settings)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn settings_from_meta() {
+        assert_eq!(
+            Settings::try_from(&Meta::default()).unwrap(),
+            Settings::default()
+        );
+
+        let mut meta = Meta::default();
+        meta.set_retries(5);
+        meta.set_timeout(Duration::from_secs(30));
+        meta.set_priority(10);
+        let settings = Settings::try_from(&meta).unwrap();
+        assert_eq!(settings.retries, 5);
+        assert_eq!(settings.timeout, Duration::from_secs(30));
+        assert_eq!(settings.priority, 10);
+        assert_eq!(
+            settings.retry_max_delay,
+            Settings::default().retry_max_delay
+        );
+
+        meta.set_timeout(Duration::ZERO);
+        assert!(Settings::try_from(&meta).is_err());
+
+        let mut meta = Meta::default();
+        meta.set_retry_initial_delay(Duration::from_secs(10));
+        meta.set_retry_max_delay(Duration::from_secs(1));
+        assert!(Settings::try_from(&meta).is_err());
+    }
+}
@@ -40,7 +40,9 @@ fn test_help_integration() -> Result<()> {
         .stdout(predicate::str::contains("run"))
         .stdout(predicate::str::contains("help"))
         .stdout(predicate::str::contains("version"))
-        .stdout(predicate::str::contains("init"));
+        .stdout(predicate::str::contains("init"))
+        .stdout(predicate::str::contains("validate"))
+        .stdout(predicate::str::contains("plan"));
 
     Command::new(BIN.as_os_str())
         .arg("-h")
@@ -52,7 +54,9 @@ fn test_help_integration() -> Result<()> {
         .stdout(predicate::str::contains("run"))
         .stdout(predicate::str::contains("help"))
         .stdout(predicate::str::contains("version"))
-        .stdout(predicate::str::contains("init"));
+        .stdout(predicate::str::contains("init"))
+        .stdout(predicate::str::contains("validate"))
+        .stdout(predicate::str::contains("plan"));
 
     Ok(())
 }
@@ -109,6 +113,60 @@ fn test_server_not_running_integration() -> Result<()> {
     Ok(())
 }
 
+#[test]
+#[serial_test::parallel]
+fn test_workflow_validate_integration() -> Result<()> {
+    Command::new(BIN.as_os_str())
+        .arg("validate")
+        .arg("tests/fixtures/test-workflow-add-one.json")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("is valid"))
+        .stderr(predicate::str::contains("was not type-checked"));
+
+    let modules = std::env::temp_dir().join("homestar_validate_modules");
+    std::fs::create_dir_all(&modules)?;
+    std::fs::copy(
+        "../homestar-wasm/fixtures/example_test.wasm",
+        modules.join("bafybeibk42jwhq7w2zcpe6q3wgtleugp3ymfs3pa5gerjmnakqihhqx4zq.wasm"),
+    )?;
+
+    Command::new(BIN.as_os_str())
+        .arg("validate")
+        .arg("tests/fixtures/test-workflow-add-one.json")
+        .arg("--modules")
+        .arg(&modules)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("is valid"))
+        .stderr(predicate::str::contains("was not type-checked").not());
+
+    Ok(())
+}
+
+#[test]
+#[serial_test::parallel]
+fn test_workflow_plan_integration() -> Result<()> {
+    let db = std::env::temp_dir().join("homestar_plan_integration.db");
+    let mermaid = std::env::temp_dir().join("homestar_plan_integration.mmd");
+
+    Command::new(BIN.as_os_str())
+        .arg("plan")
+        .arg("tests/fixtures/test-workflow-add-one.json")
+        .arg("--db")
+        .arg(&db)
+        .arg("--mermaid")
+        .arg(&mermaid)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("plan"))
+        .stdout(predicate::str::contains("add_one"));
+
+    assert!(std::fs::read_to_string(&mermaid)?.starts_with("flowchart TD"));
+
+    Ok(())
+}
+
 #[test]
 #[serial_test::parallel]
 fn test_server_integration() -> Result<()> {
@@ -262,6 +320,77 @@ fn test_workflow_run_integration() -> Result<()> {
         .stdout(predicate::str::contains("num_tasks"))
         .stdout(predicate::str::contains("progress_count"));
 
+    // cancel a workflow that was never run
+    Command::new(BIN.as_os_str())
+        .arg("cancel")
+        .arg("-p")
+        .arg(rpc_port.to_string())
+        .arg("not-a-workflow")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "no workflow found with Cid or name: not-a-workflow",
+        ));
+
+    // list the workflows that were run
+    Command::new(BIN.as_os_str())
+        .arg("workflows")
+        .arg("list")
+        .arg("-p")
+        .arg(rpc_port.to_string())
+        .arg("--json")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"workflows\""))
+        .stdout(predicate::str::contains("\"num_tasks\": 2"));
+
+    // show a workflow that was never run
+    Command::new(BIN.as_os_str())
+        .arg("workflows")
+        .arg("show")
+        .arg("-p")
+        .arg(rpc_port.to_string())
+        .arg("not-a-workflow")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "no workflow found with Cid or name: not-a-workflow",
+        ));
+
+    // get a workflow that was never run
+    Command::new(BIN.as_os_str())
+        .arg("workflows")
+        .arg("get")
+        .arg("-p")
+        .arg(rpc_port.to_string())
+        .arg("not-a-workflow")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "no workflow found with Cid or name: not-a-workflow",
+        ));
+
+    // get a receipt that was never issued
+    Command::new(BIN.as_os_str())
+        .arg("receipt")
+        .arg("get")
+        .arg("-p")
+        .arg(rpc_port.to_string())
+        .arg("bafybeibk42jwhq7w2zcpe6q3wgtleugp3ymfs3pa5gerjmnakqihhqx4zq")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no receipt found for receipt Cid"));
+
+    // verify a receipt that was never issued
+    Command::new(BIN.as_os_str())
+        .arg("verify")
+        .arg("-p")
+        .arg(rpc_port.to_string())
+        .arg("bafybeibk42jwhq7w2zcpe6q3wgtleugp3ymfs3pa5gerjmnakqihhqx4zq")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no receipt found for receipt Cid"));
+
     Ok(())
 }
 
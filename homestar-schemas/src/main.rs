@@ -2,6 +2,7 @@
 
 use homestar_invocation::Receipt;
 use homestar_runtime::{
+    runner::response::{AckCancel, AckReceipt, AckRunWorkflow},
     Health, NetworkNotification, NodeInfo, PrometheusData, ReceiptNotification,
 };
 use homestar_workflow::Workflow;
@@ -61,6 +62,21 @@ fn main() {
         .unwrap()
         .write_all(&serde_json::to_vec_pretty(&receipt_notification_schema).unwrap());
 
+    let cancel_workflow_schema = schema_for!(AckCancel);
+    let _ = fs::File::create(schema_path("cancel_workflow.json"))
+        .unwrap()
+        .write_all(&serde_json::to_vec_pretty(&cancel_workflow_schema).unwrap());
+
+    let receipt_info_schema = schema_for!(AckReceipt);
+    let _ = fs::File::create(schema_path("receipt_info.json"))
+        .unwrap()
+        .write_all(&serde_json::to_vec_pretty(&receipt_info_schema).unwrap());
+
+    let run_workflow_schema = schema_for!(AckRunWorkflow);
+    let _ = fs::File::create(schema_path("run_workflow.json"))
+        .unwrap()
+        .write_all(&serde_json::to_vec_pretty(&run_workflow_schema).unwrap());
+
     let api_doc = generate_api_doc(
         health_schema,
         metrics_schema,
@@ -68,6 +84,9 @@ fn main() {
         network_schema,
         workflow_schema,
         receipt_notification_schema,
+        cancel_workflow_schema,
+        receipt_info_schema,
+        run_workflow_schema,
     );
     let _ = fs::File::create(schema_path("api.json"))
         .unwrap()
@@ -75,6 +94,7 @@ fn main() {
 }
 
 // Spec: https://github.com/open-rpc/spec/blob/1.2.6/spec.md
+#[allow(clippy::too_many_arguments)]
 fn generate_api_doc(
     health_schema: RootSchema,
     metrics_schema: RootSchema,
@@ -82,6 +102,9 @@ fn generate_api_doc(
     network_schema: RootSchema,
     workflow_schema: RootSchema,
     receipt_notification_schema: RootSchema,
+    cancel_workflow_schema: RootSchema,
+    receipt_info_schema: RootSchema,
+    run_workflow_schema: RootSchema,
 ) -> OpenrpcDocument {
     let discover: MethodObject = MethodObject {
         name: "rpc.discover".to_string(),
@@ -253,7 +276,7 @@ fn generate_api_doc(
                 summary: None,
                 description: None,
                 required: Some(true),
-                schema: JSONSchema::JsonSchemaObject(workflow_schema),
+                schema: JSONSchema::JsonSchemaObject(workflow_schema.clone()),
                 deprecated: Some(false),
             },
         )],
@@ -304,6 +327,183 @@ fn generate_api_doc(
         x_messages: None,
     };
 
+    let cancel_workflow: MethodObject = MethodObject {
+        name: "cancel_workflow".to_string(),
+        description: Some("Cancel a running workflow, given its CID or local name".to_string()),
+        summary: None,
+        servers: None,
+        tags: None,
+        param_structure: Some(MethodObjectParamStructure::ByPosition),
+        params: vec![ContentDescriptorOrReference::ContentDescriptorObject(
+            ContentDescriptorObject {
+                name: "workflow".to_string(),
+                summary: None,
+                description: Some("Workflow CID or local name".to_string()),
+                required: Some(true),
+                schema: JSONSchema::JsonSchemaObject(schema_for!(String)),
+                deprecated: Some(false),
+            },
+        )],
+        result: ContentDescriptorOrReference::ContentDescriptorObject(ContentDescriptorObject {
+            name: "cancel_workflow".to_string(),
+            summary: None,
+            description: None,
+            required: Some(true),
+            schema: JSONSchema::JsonSchemaObject(cancel_workflow_schema),
+            deprecated: Some(false),
+        }),
+        external_docs: None,
+        errors: None,
+        links: None,
+        examples: None,
+        deprecated: Some(false),
+        x_messages: None,
+    };
+
+    let run_workflow: MethodObject = MethodObject {
+        name: "run_workflow".to_string(),
+        description: Some(
+            "Run a workflow given inline, returning its CID without subscribing to its events"
+                .to_string(),
+        ),
+        summary: None,
+        servers: None,
+        tags: None,
+        param_structure: Some(MethodObjectParamStructure::Either),
+        params: vec![
+            ContentDescriptorOrReference::ContentDescriptorObject(ContentDescriptorObject {
+                name: "name".to_string(),
+                summary: None,
+                description: Some("Local workflow name".to_string()),
+                required: Some(false),
+                schema: JSONSchema::JsonSchemaObject(schema_for!(String)),
+                deprecated: Some(false),
+            }),
+            ContentDescriptorOrReference::ContentDescriptorObject(ContentDescriptorObject {
+                name: "workflow".to_string(),
+                summary: None,
+                description: Some(
+                    "Workflow as a DAG-JSON object, or as a base64-encoded DAG-CBOR string"
+                        .to_string(),
+                ),
+                required: Some(true),
+                schema: JSONSchema::JsonSchemaObject(workflow_schema),
+                deprecated: Some(false),
+            }),
+        ],
+        result: ContentDescriptorOrReference::ContentDescriptorObject(ContentDescriptorObject {
+            name: "run_workflow".to_string(),
+            summary: None,
+            description: None,
+            required: Some(true),
+            schema: JSONSchema::JsonSchemaObject(run_workflow_schema),
+            deprecated: Some(false),
+        }),
+        external_docs: None,
+        errors: None,
+        links: None,
+        examples: None,
+        deprecated: Some(false),
+        x_messages: None,
+    };
+
+    let get_receipt: MethodObject = MethodObject {
+        name: "get_receipt".to_string(),
+        description: Some(
+            "Get a receipt, given its CID or the CID of the instruction it was issued for"
+                .to_string(),
+        ),
+        summary: None,
+        servers: None,
+        tags: None,
+        param_structure: Some(MethodObjectParamStructure::Either),
+        params: vec![
+            ContentDescriptorOrReference::ContentDescriptorObject(ContentDescriptorObject {
+                name: "cid".to_string(),
+                summary: None,
+                description: Some("Receipt CID, or instruction CID".to_string()),
+                required: Some(true),
+                schema: JSONSchema::JsonSchemaObject(schema_for!(String)),
+                deprecated: Some(false),
+            }),
+            ContentDescriptorOrReference::ContentDescriptorObject(ContentDescriptorObject {
+                name: "instruction".to_string(),
+                summary: None,
+                description: Some(
+                    "Look up the receipt by instruction CID, falling back to the DHT".to_string(),
+                ),
+                required: Some(false),
+                schema: JSONSchema::JsonSchemaObject(schema_for!(bool)),
+                deprecated: Some(false),
+            }),
+            ContentDescriptorOrReference::ContentDescriptorObject(ContentDescriptorObject {
+                name: "cbor".to_string(),
+                summary: None,
+                description: Some("Include the receipt's raw DAG-CBOR bytes".to_string()),
+                required: Some(false),
+                schema: JSONSchema::JsonSchemaObject(schema_for!(bool)),
+                deprecated: Some(false),
+            }),
+        ],
+        result: ContentDescriptorOrReference::ContentDescriptorObject(ContentDescriptorObject {
+            name: "receipt_info".to_string(),
+            summary: None,
+            description: None,
+            required: Some(true),
+            schema: JSONSchema::JsonSchemaObject(receipt_info_schema.clone()),
+            deprecated: Some(false),
+        }),
+        external_docs: None,
+        errors: None,
+        links: None,
+        examples: None,
+        deprecated: Some(false),
+        x_messages: None,
+    };
+
+    let get_receipts_by_workflow: MethodObject = MethodObject {
+        name: "get_receipts_by_workflow".to_string(),
+        description: Some(
+            "Get the receipts for a workflow, given its CID or local name".to_string(),
+        ),
+        summary: None,
+        servers: None,
+        tags: None,
+        param_structure: Some(MethodObjectParamStructure::Either),
+        params: vec![
+            ContentDescriptorOrReference::ContentDescriptorObject(ContentDescriptorObject {
+                name: "workflow".to_string(),
+                summary: None,
+                description: Some("Workflow CID or local name".to_string()),
+                required: Some(true),
+                schema: JSONSchema::JsonSchemaObject(schema_for!(String)),
+                deprecated: Some(false),
+            }),
+            ContentDescriptorOrReference::ContentDescriptorObject(ContentDescriptorObject {
+                name: "cbor".to_string(),
+                summary: None,
+                description: Some("Include each receipt's raw DAG-CBOR bytes".to_string()),
+                required: Some(false),
+                schema: JSONSchema::JsonSchemaObject(schema_for!(bool)),
+                deprecated: Some(false),
+            }),
+        ],
+        result: ContentDescriptorOrReference::ContentDescriptorObject(ContentDescriptorObject {
+            name: "receipts".to_string(),
+            summary: None,
+            description: None,
+            required: Some(true),
+            schema: JSONSchema::JsonSchemaObject(schema_for!(Vec<AckReceipt>)),
+            deprecated: Some(false),
+        }),
+        external_docs: None,
+        errors: None,
+        links: None,
+        examples: None,
+        deprecated: Some(false),
+        x_messages: None,
+    };
+
     OpenrpcDocument {
         openrpc: Openrpc::V26,
         info: InfoObject {
@@ -337,6 +537,10 @@ fn generate_api_doc(
             network_unsubscribe,
             workflow,
             workflow_unsubscribe,
+            cancel_workflow,
+            run_workflow,
+            get_receipt,
+            get_receipts_by_workflow,
         ],
         components: None,
     }
@@ -57,20 +57,22 @@ impl From<Arg> for Ipld {
 }
 
 impl Parse<Arg> for Input<Arg> {
+    /// Parse `args`, along with the `func` to apply them to, which is
+    /// optional, as not every task runs a function, e.g. `workflow/run`
+    /// tasks.
     fn parse(&self) -> Result<Parsed<Arg>, InputParseError<Arg>> {
         if let Input::Ipld(ref ipld) = self {
             let map = from_ipld::<BTreeMap<String, Ipld>>(ipld.to_owned())?;
 
-            let func = map.get("func").ok_or_else(|| {
-                InputParseError::Invocation(InvocationError::MissingField("func".to_string()))
-            })?;
-
             let wasm_args = map.get("args").ok_or_else(|| {
                 InputParseError::Invocation(InvocationError::MissingField("args".to_string()))
             })?;
 
             let args: Args<Arg> = wasm_args.to_owned().try_into()?;
-            Ok(Parsed::with_fn(from_ipld::<String>(func.to_owned())?, args))
+            match map.get("func") {
+                Some(func) => Ok(Parsed::with_fn(from_ipld::<String>(func.to_owned())?, args)),
+                None => Ok(Parsed::with(args)),
+            }
         } else {
             Err(InputParseError::UnexpectedTaskInput(self.clone()))
         }
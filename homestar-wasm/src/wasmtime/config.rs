@@ -7,7 +7,16 @@ impl From<Resources> for wasmtime::State {
     fn from(resources: Resources) -> wasmtime::State {
         wasmtime::State::new(
             resources.fuel().unwrap_or(u64::MAX),
-            StoreLimitsAsync::new(Some(consts::WASM_MAX_MEMORY as usize), None),
+            StoreLimitsAsync::new(
+                Some(
+                    resources
+                        .memory()
+                        .map_or(consts::WASM_MAX_MEMORY, |memory| {
+                            memory.min(consts::WASM_MAX_MEMORY)
+                        }) as usize,
+                ),
+                None,
+            ),
         )
     }
 }
@@ -3,6 +3,8 @@
 //!
 //! [Wasmtime]: <https://docs.rs/wasmtime/latest/wasmtime>
 
+use std::time::Duration;
+
 /// Generic error type for Wasm execution, conversions, instantiations, etc.
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -14,11 +16,28 @@ pub enum Error {
     /// Failure to convert from Wasm binary into Wasm component.
     #[error("cannot convert from binary structure to Wasm component")]
     IntoWasmComponent(#[source] anyhow::Error),
+    /// Failure to grow memory beyond the maximum memory size (in bytes)
+    /// allotted for execution.
+    #[error("Wasm execution exceeded memory limit of {0} bytes")]
+    MemoryLimitExceeded(u64),
+    /// Failure to complete execution within the fuel allotted.
+    #[error("Wasm execution ran out of fuel")]
+    OutOfFuel,
     /// Bubble-up [ResolveError]s for Cids still awaiting resolution.
     ///
     /// [ResolveError]: homestar_invocation::error::ResolveError
     #[error(transparent)]
     ResolvePromise(#[from] homestar_invocation::error::ResolveError),
+    /// Failure to complete execution within the time allotted.
+    #[error("Wasm execution timed out after {0:?}")]
+    Timeout(Duration),
+    /// Wasm execution trapped, e.g. on reaching unreachable code or
+    /// accessing memory out of bounds.
+    ///
+    /// Transparently forwards from [anyhow::Error]'s `source` and
+    /// `Display` methods through to an underlying [wasmtime::Trap].
+    #[error(transparent)]
+    Trap(anyhow::Error),
     /// Generic unknown error.
     #[error("unknown error")]
     Unknown,
@@ -40,6 +59,15 @@ pub enum Error {
     /// Failure to find Wasm function for execution.
     #[error("Wasm function {0} not found in given Wasm component/resource")]
     WasmFunctionNotFound(String),
+    /// Mismatch between the number of arguments given and the number of
+    /// parameters a Wasm function expects.
+    #[error("Wasm function expects {expected} argument(s), but was given {given}")]
+    WasmArgumentCount {
+        /// Number of parameters expected.
+        expected: usize,
+        /// Number of arguments given.
+        given: usize,
+    },
     /// [Wat] as Wasm component error.
     ///
     /// [Wat]: wat
@@ -52,3 +80,16 @@ pub enum Error {
     #[error(transparent)]
     Wat(#[from] wat::Error),
 }
+
+impl Error {
+    /// Convert a generic [wasmtime] runtime error into an [Error], surfacing
+    /// resource-limit traps, e.g. running out of fuel, and other traps as
+    /// their own variants.
+    pub(crate) fn from_runtime(err: anyhow::Error) -> Self {
+        match err.downcast_ref::<wasmtime::Trap>() {
+            Some(wasmtime::Trap::OutOfFuel) => Error::OutOfFuel,
+            Some(_) => Error::Trap(err),
+            None => Error::WasmRuntime(err),
+        }
+    }
+}
@@ -6,6 +6,7 @@
 //! [Wasmtime]: <https://docs.rs/wasmtime/latest/wasmtime/>
 
 use crate::{
+    error::InterpreterError,
     io::{Arg, Output},
     wasmtime::{
         ipld::{InterfaceType, RuntimeVal},
@@ -19,6 +20,7 @@ use homestar_invocation::{
     error::ResolveError,
     task::instruction::{Args, Input},
 };
+use libipld::Ipld;
 use std::{iter, time::Instant};
 use tracing::{instrument, Instrument};
 use wasmtime::{
@@ -105,6 +107,16 @@ impl State {
         self.fuel = fuel
     }
 
+    /// Get fuel allotted.
+    pub fn fuel(&self) -> u64 {
+        self.fuel
+    }
+
+    /// Get [StoreLimitsAsync] applied to the store.
+    pub fn limits(&self) -> &StoreLimitsAsync {
+        &self.limits
+    }
+
     /// Initial time from instantiation.
     pub fn start_time(&self) -> Instant {
         self.start_time
@@ -184,6 +196,14 @@ impl<T> Env<T> {
                         ))))
                     }
                 };
+                // Mistyped arguments would otherwise only surface as a
+                // runtime error once called.
+                if v.ty() != *typ {
+                    bail!(Error::InterpreterError(InterpreterError::TypeMismatch {
+                        expected: format!("{typ:?}"),
+                        given: Some(format!("{:?}", v.ty())),
+                    }));
+                }
                 acc.push(v);
                 Ok::<_, Error>(acc)
             })?;
@@ -199,7 +219,8 @@ impl<T> Env<T> {
             .func()
             .call_async(&mut self.store, &params, &mut results_alloc)
             .in_current_span()
-            .await?;
+            .await
+            .map_err(Error::from_runtime)?;
 
         self.bindings
             .as_mut()
@@ -207,7 +228,8 @@ impl<T> Env<T> {
             .func()
             .post_return_async(&mut self.store)
             .in_current_span()
-            .await?;
+            .await
+            .map_err(Error::from_runtime)?;
 
         let results = match &results_alloc[..] {
             [v] => Output::Value(v.to_owned()),
@@ -218,6 +240,54 @@ impl<T> Env<T> {
         Ok(results)
     }
 
+    /// Type-check [Args] against the instantiated function's parameter
+    /// types, without executing it.
+    ///
+    /// Inputs only known at execution time, i.e. [awaited promises] and
+    /// links to content resolved later on, are left unchecked.
+    ///
+    /// [awaited promises]: Input::Deferred
+    pub fn typecheck(&self, args: &Args<Arg>) -> Result<(), Error> {
+        let param_types = self
+            .bindings
+            .as_ref()
+            .ok_or(Error::WasmInstantiation)?
+            .func()
+            .params(&self.store);
+
+        if param_types.len() != args.inner().len() {
+            bail!(Error::WasmArgumentCount {
+                expected: param_types.len(),
+                given: args.inner().len(),
+            });
+        }
+
+        iter::zip(param_types.iter(), args.inner()).try_for_each(|(typ, arg)| {
+            let value = match arg {
+                Input::Deferred(_) | Input::Ipld(Ipld::Link(_)) => return Ok(()),
+                input if input.is_deferred() => return Ok(()),
+                Input::Ipld(ipld) => {
+                    RuntimeVal::try_from(ipld.to_owned(), &InterfaceType::from(typ))?.value()
+                }
+                Input::Arg(val) => match val.inner() {
+                    Arg::Ipld(ipld) => {
+                        RuntimeVal::try_from(ipld.to_owned(), &InterfaceType::from(typ))?.value()
+                    }
+                    Arg::Value(v) => v.to_owned(),
+                },
+            };
+
+            if value.ty() != *typ {
+                bail!(Error::InterpreterError(InterpreterError::TypeMismatch {
+                    expected: format!("{typ:?}"),
+                    given: Some(format!("{:?}", value.ty())),
+                }));
+            }
+
+            Ok(())
+        })
+    }
+
     /// Return `wasmtime` bindings.
     pub fn bindings(&self) -> &Option<World> {
         &self.bindings
@@ -262,6 +332,7 @@ impl World {
         Imports::add_to_linker(&mut linker, |state: &mut State| state)?;
 
         let mut store = Store::new(&engine, data);
+        store.limiter_async(|s| &mut s.limits);
         store.set_fuel(store.data().fuel)?;
 
         // Configures a `Store` to yield execution of async WebAssembly code
@@ -304,8 +375,9 @@ impl World {
         // engine clones are shallow (not deep).
         let component = component_from_bytes(&bytes, engine.clone())?;
 
-        let (_bindings, instance) =
-            Imports::instantiate_async(&mut store, &component, &linker).await?;
+        let (_bindings, instance) = Imports::instantiate_async(&mut store, &component, &linker)
+            .await
+            .map_err(Error::from_runtime)?;
 
         let bindings = Self::new(&mut store, &instance, fun_name)?;
 
@@ -334,7 +406,9 @@ impl World {
         let component = component_from_bytes(&bytes, env.engine.clone())?;
 
         let (_bindings, instance) =
-            Imports::instantiate_async(&mut env.store, &component, &env.linker).await?;
+            Imports::instantiate_async(&mut env.store, &component, &env.linker)
+                .await
+                .map_err(Error::from_runtime)?;
 
         let bindings = Self::new(&mut env.store, &instance, fun_name)?;
         env.set_instance(instance);
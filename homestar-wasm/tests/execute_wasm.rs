@@ -40,6 +40,24 @@ async fn test_wasm_exceeds_max_memory() {
     }
 }
 
+#[tokio::test]
+async fn test_wasm_runs_out_of_fuel() {
+    let ipld = Input::Ipld(Ipld::Map(BTreeMap::from([
+        ("func".into(), Ipld::String("add_one".to_string())),
+        ("args".into(), Ipld::List(vec![Ipld::Integer(1)])),
+    ])));
+
+    let wasm = fs::read(fixtures("example_test.wasm")).unwrap();
+    let result =
+        match World::instantiate(wasm, "add_one", State::new(1, StoreLimitsAsync::default())).await
+        {
+            Ok(mut env) => env.execute(ipld.parse().unwrap().into()).await,
+            Err(err) => Err(err),
+        };
+
+    assert!(matches!(result, Err(Error::OutOfFuel)));
+}
+
 #[tokio::test]
 async fn test_execute_wat() {
     let ipld = Input::Ipld(Ipld::Map(BTreeMap::from([
@@ -642,3 +660,43 @@ async fn test_subtract_float_to_int() {
     let res = env.execute(ipld.parse().unwrap().into()).await.unwrap();
     assert_eq!(res, Output::Value(wasmtime::component::Val::S8(-1)));
 }
+
+#[tokio::test]
+async fn test_typecheck_args() {
+    let wasm = fs::read(fixtures("example_test.wasm")).unwrap();
+    let env = World::instantiate(wasm, "add_one", State::default())
+        .await
+        .unwrap();
+
+    let args = |args: Vec<Ipld>| {
+        Input::<Arg>::Ipld(Ipld::Map(BTreeMap::from([
+            ("func".into(), Ipld::String("add_one".to_string())),
+            ("args".into(), Ipld::List(args)),
+        ])))
+        .parse()
+        .unwrap()
+        .into_args()
+    };
+
+    assert!(env.typecheck(&args(vec![Ipld::Integer(1)])).is_ok());
+    assert!(matches!(
+        env.typecheck(&args(vec![])),
+        Err(Error::WasmArgumentCount {
+            expected: 1,
+            given: 0
+        })
+    ));
+    assert!(matches!(
+        env.typecheck(&args(vec![Ipld::String("one".to_string())])),
+        Err(Error::InterpreterError(_))
+    ));
+
+    let promise = Await::new(
+        Pointer::new(Cid::new_v1(0x55, Code::Sha3_256.digest(b"add_one"))),
+        AwaitResult::Ok,
+    );
+    assert!(
+        env.typecheck(&args(vec![promise.into()])).is_ok(),
+        "awaited promises are left unchecked"
+    );
+}
@@ -13,21 +13,43 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
+mod meta;
+
+pub use meta::Meta;
+
 const TASKS_KEY: &str = "tasks";
+const META_KEY: &str = "meta";
 
-/// Workflow composed of [tasks].
+/// Workflow composed of [tasks], with optional workflow-level settings
+/// given as [Meta].
 ///
 /// [tasks]: Task
 #[derive(Debug, Clone, JsonSchema, PartialEq, Serialize, Deserialize)]
 #[schemars(title = "Workflow", description = "Workflow composed of tasks")]
 pub struct Workflow<'a, T> {
     tasks: Vec<Task<'a, T>>,
+    #[serde(default, skip_serializing_if = "Meta::is_empty")]
+    meta: Meta,
 }
 
 impl<'a, T> Workflow<'a, T> {
     /// Create a new [Workflow] given a set of tasks.
     pub fn new(tasks: Vec<Task<'a, T>>) -> Self {
-        Self { tasks }
+        Self {
+            tasks,
+            meta: Meta::default(),
+        }
+    }
+
+    /// Set workflow-level settings, as [Meta], on a [Workflow].
+    pub fn with_meta(mut self, meta: Meta) -> Self {
+        self.meta = meta;
+        self
+    }
+
+    /// Return a reference to [Workflow]'s [Meta] settings.
+    pub fn meta(&self) -> &Meta {
+        &self.meta
     }
 
     /// Return a [Workflow]'s [tasks] vector.
@@ -64,7 +86,7 @@ where
     Ipld: From<Task<'a, T>>,
 {
     fn from(workflow: Workflow<'a, T>) -> Self {
-        Ipld::Map(BTreeMap::from([(
+        let mut map = BTreeMap::from([(
             TASKS_KEY.into(),
             Ipld::List(
                 workflow
@@ -73,7 +95,15 @@ where
                     .map(Ipld::from)
                     .collect::<Vec<Ipld>>(),
             ),
-        )]))
+        )]);
+
+        // Only include settings when given, keeping the Cid of workflows
+        // without settings stable.
+        if !workflow.meta.is_empty() {
+            map.insert(META_KEY.into(), workflow.meta.into());
+        }
+
+        Ipld::Map(map)
     }
 }
 
@@ -98,7 +128,12 @@ where
             bail!(Error::not_an_ipld_list());
         };
 
-        Ok(Self { tasks })
+        let meta = match map.get(META_KEY) {
+            None | Some(Ipld::Null) => Meta::default(),
+            Some(ipld) => Meta::try_from(ipld.to_owned())?,
+        };
+
+        Ok(Self { tasks, meta })
     }
 }
 
@@ -230,4 +265,32 @@ mod test {
 
         assert_eq!(workflow, de);
     }
+
+    #[test]
+    fn meta_roundtrip_and_cid() {
+        let config = Resources::default();
+        let instruction = test_utils::instruction::<Unit>();
+        let task = Task::new(
+            RunInstruction::Expanded(instruction),
+            config.into(),
+            UcanPrf::default(),
+        );
+
+        let workflow = Workflow::new(vec![task]);
+        let mut meta = Meta::default();
+        meta.set_retries(1);
+        meta.set_timeout(std::time::Duration::from_secs(30));
+        let with_meta = workflow.clone().with_meta(meta.clone());
+
+        let json_string = with_meta.clone().to_json_string().unwrap();
+        let parsed: Workflow<'_, Unit> = DagJson::from_json_string(json_string).unwrap();
+        assert_eq!(parsed.meta(), &meta);
+        assert_eq!(parsed, with_meta);
+
+        assert!(Ipld::from(workflow.clone()).get(META_KEY).is_err());
+        assert_ne!(
+            workflow.clone().to_cid().unwrap(),
+            with_meta.to_cid().unwrap()
+        );
+    }
 }
@@ -0,0 +1,190 @@
+//! Optional, workflow-level metadata for configuring a [Workflow]'s run.
+//!
+//! [Workflow]: crate::Workflow
+
+use homestar_invocation::{Error, Unit};
+use libipld::{serde::from_ipld, Ipld};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, time::Duration};
+
+const RETRIES_KEY: &str = "retries";
+const RETRY_INITIAL_DELAY_KEY: &str = "retry_initial_delay";
+const RETRY_MAX_DELAY_KEY: &str = "retry_max_delay";
+const TIMEOUT_KEY: &str = "timeout";
+const PRIORITY_KEY: &str = "priority";
+
+/// Workflow-level settings, expressed under a workflow's `meta` key:
+///
+/// `{"tasks": [..], "meta": {"retries": 3, "retry_initial_delay": 500, "retry_max_delay": 60000, "timeout": 3600000, "priority": 0}}`
+///
+/// Every setting is optional, falling back to the runtime's default
+/// when unset.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[schemars(
+    rename = "meta",
+    description = "Workflow settings for retries, timeout, and priority"
+)]
+pub struct Meta {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Number of retries when fetching resources")]
+    retries: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Initial delay between retries in milliseconds")]
+    retry_initial_delay: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Maximum delay between retries in milliseconds")]
+    retry_max_delay: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        range(min = 1),
+        description = "Timeout for the whole workflow in milliseconds"
+    )]
+    timeout: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Priority of the workflow, higher runs first")]
+    priority: Option<i32>,
+}
+
+impl Meta {
+    /// Whether any setting is given.
+    pub fn is_empty(&self) -> bool {
+        self == &Meta::default()
+    }
+
+    /// Get number of retries.
+    pub fn retries(&self) -> Option<u32> {
+        self.retries
+    }
+
+    /// Set number of retries.
+    pub fn set_retries(&mut self, retries: u32) {
+        self.retries = Some(retries)
+    }
+
+    /// Get initial delay between retries.
+    pub fn retry_initial_delay(&self) -> Option<Duration> {
+        self.retry_initial_delay.map(Duration::from_millis)
+    }
+
+    /// Set initial delay between retries.
+    pub fn set_retry_initial_delay(&mut self, delay: Duration) {
+        self.retry_initial_delay = Some(delay.as_millis() as u64)
+    }
+
+    /// Get maximum delay between retries.
+    pub fn retry_max_delay(&self) -> Option<Duration> {
+        self.retry_max_delay.map(Duration::from_millis)
+    }
+
+    /// Set maximum delay between retries.
+    pub fn set_retry_max_delay(&mut self, delay: Duration) {
+        self.retry_max_delay = Some(delay.as_millis() as u64)
+    }
+
+    /// Get workflow timeout.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout.map(Duration::from_millis)
+    }
+
+    /// Set workflow timeout.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = Some(timeout.as_millis() as u64)
+    }
+
+    /// Get workflow priority.
+    pub fn priority(&self) -> Option<i32> {
+        self.priority
+    }
+
+    /// Set workflow priority.
+    pub fn set_priority(&mut self, priority: i32) {
+        self.priority = Some(priority)
+    }
+}
+
+impl From<Meta> for Ipld {
+    fn from(meta: Meta) -> Ipld {
+        Ipld::Map(
+            [
+                (RETRIES_KEY, meta.retries.map(Ipld::from)),
+                (
+                    RETRY_INITIAL_DELAY_KEY,
+                    meta.retry_initial_delay.map(Ipld::from),
+                ),
+                (RETRY_MAX_DELAY_KEY, meta.retry_max_delay.map(Ipld::from)),
+                (TIMEOUT_KEY, meta.timeout.map(Ipld::from)),
+                (PRIORITY_KEY, meta.priority.map(Ipld::from)),
+            ]
+            .into_iter()
+            .filter_map(|(key, value)| value.map(|value| (key.to_string(), value)))
+            .collect(),
+        )
+    }
+}
+
+impl TryFrom<Ipld> for Meta {
+    type Error = Error<Unit>;
+
+    /// Parse [Meta] from Ipld, erroring on settings of the wrong type.
+    fn try_from(ipld: Ipld) -> Result<Self, Self::Error> {
+        let map = from_ipld::<BTreeMap<String, Ipld>>(ipld)?;
+
+        fn get<T: serde::de::DeserializeOwned>(
+            map: &BTreeMap<String, Ipld>,
+            key: &str,
+        ) -> Result<Option<T>, Error<Unit>> {
+            match map.get(key) {
+                None | Some(Ipld::Null) => Ok(None),
+                Some(ipld) => Ok(Some(from_ipld(ipld.to_owned())?)),
+            }
+        }
+
+        Ok(Meta {
+            retries: get(&map, RETRIES_KEY)?,
+            retry_initial_delay: get(&map, RETRY_INITIAL_DELAY_KEY)?,
+            retry_max_delay: get(&map, RETRY_MAX_DELAY_KEY)?,
+            timeout: get(&map, TIMEOUT_KEY)?,
+            priority: get(&map, PRIORITY_KEY)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ipld_roundtrip() {
+        let mut meta = Meta::default();
+        meta.set_retries(1);
+        meta.set_timeout(Duration::from_secs(10));
+        meta.set_priority(-1);
+
+        let ipld = Ipld::from(meta.clone());
+        assert_eq!(
+            ipld,
+            Ipld::Map(BTreeMap::from([
+                (RETRIES_KEY.into(), Ipld::Integer(1)),
+                (TIMEOUT_KEY.into(), Ipld::Integer(10_000)),
+                (PRIORITY_KEY.into(), Ipld::Integer(-1)),
+            ]))
+        );
+        assert_eq!(Meta::try_from(ipld).unwrap(), meta);
+        assert!(Meta::try_from(Ipld::Map(BTreeMap::new()))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn wrong_types_error() {
+        let ipld = Ipld::Map(BTreeMap::from([(
+            TIMEOUT_KEY.into(),
+            Ipld::String("1h".into()),
+        )]));
+        assert!(Meta::try_from(ipld).is_err());
+
+        let ipld = Ipld::Map(BTreeMap::from([(RETRIES_KEY.into(), Ipld::Integer(-1))]));
+        assert!(Meta::try_from(ipld).is_err());
+    }
+}